@@ -0,0 +1,20 @@
+//! Compiles the gRPC definitions in `proto/` when the `grpc` feature is on
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    compile_protos();
+}
+
+/// Uses `PROTOC` when it is set, otherwise the vendored protoc, so building
+/// the service doesn't need protobuf installed
+#[cfg(feature = "grpc")]
+fn compile_protos() {
+    println!("cargo:rerun-if-changed=proto/know_ball.proto");
+    if std::env::var_os("PROTOC").is_none() {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is available");
+        std::env::set_var("PROTOC", protoc);
+    }
+    tonic_prost_build::compile_protos("proto/know_ball.proto")
+        .unwrap_or_else(|e| panic!("couldn't compile proto/know_ball.proto: {e}"));
+}
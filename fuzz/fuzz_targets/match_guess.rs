@@ -0,0 +1,19 @@
+//! Guesses against player names, through the normalizer and every matcher
+#![no_main]
+
+use know_ball::answer_match::{normalize_name, tokenize_name, MatcherKind};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&str, &str)| {
+    let (guess, answer) = input;
+    let normalized = normalize_name(guess);
+    assert_eq!(normalize_name(&normalized), normalized, "{guess:?}");
+    tokenize_name(guess);
+    for kind in [
+        MatcherKind::Exact,
+        MatcherKind::Substring,
+        MatcherKind::Fuzzy,
+    ] {
+        kind.matcher().matches(guess, answer);
+    }
+});
@@ -0,0 +1,29 @@
+//! Whole REPL lines, including the explicit parameters of
+//! `custom <question | stat> [TEAM] [YEAR | START END] [POSITION] [min=N]`,
+//! followed through to the question they would generate
+#![no_main]
+
+use know_ball::command::{find_custom_question, parse_command, Command};
+use know_ball::questions::{
+    builtin_registry, find_stat, generate_question, validate_params, CustomQuestion,
+};
+use libfuzzer_sys::fuzz_target;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fuzz_target!(|input: &str| {
+    let Ok(Command::Custom { question, params }) = parse_command(input) else {
+        return;
+    };
+    let registry = builtin_registry();
+    if let Some(found) = find_custom_question(registry, &question, &params) {
+        if validate_params(found.as_ref(), &params).is_ok() {
+            let mut rng = StdRng::seed_from_u64(0);
+            generate_question(found.as_ref(), &params, &mut rng);
+        }
+    } else if find_stat(&question).is_some() {
+        if let Ok(custom) = CustomQuestion::from_params(&question, &params) {
+            let _ = custom.spec();
+        }
+    }
+});
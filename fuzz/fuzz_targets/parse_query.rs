@@ -0,0 +1,15 @@
+//! Question codes as typed at the prompt, e.g. `last10rushers_PIT` or a
+//! renamed code like `recyds_PIT_yearrange`
+#![no_main]
+
+use know_ball::questions::{builtin_registry, parse_query, TEAMS};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let Some(parsed) = parse_query(input, builtin_registry()) else {
+        return;
+    };
+    if let Some(team) = &parsed.team {
+        assert!(TEAMS.contains(&team.as_str()), "{input:?} gave team {team}");
+    }
+});
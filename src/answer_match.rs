@@ -0,0 +1,479 @@
+//! Guess normalization and answer matching
+//!
+//! Both the user's guess and the stored player name go through the same
+//! normalization before they are compared, so punctuation differences like
+//! "St. Brown" vs "st brown" don't cost the player a strike. How forgiving
+//! the comparison is comes from a [`Matcher`], so each frontend can pick.
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalizes a name for comparison.
+///
+/// Lowercases, strips accents, drops periods and apostrophes, turns hyphens
+/// and other punctuation into spaces, and collapses runs of whitespace.
+/// "Amon-Ra St. Brown" becomes "amon ra st brown" and "Zoë Ruíz" "zoe ruiz".
+pub fn normalize_name(name: &str) -> String {
+    let mut cleaned = String::with_capacity(name.len());
+    for c in name.nfd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            '.' | '\'' | '’' | '‘' | '`' | 'ʼ' => {}
+            c if c.is_ascii_punctuation() || c.is_whitespace() => cleaned.push(' '),
+            '–' | '—' => cleaned.push(' '),
+            _ => cleaned.extend(c.to_lowercase()),
+        }
+    }
+
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Generational suffixes recognized at the end of a name
+const SUFFIXES: [&str; 5] = ["jr", "sr", "ii", "iii", "iv"];
+
+/// A normalized name split into its base and optional generational suffix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameTokens {
+    pub base: String,
+    pub suffix: Option<String>,
+}
+
+/// Normalizes a name and splits off a trailing Jr./Sr./II/III/IV suffix.
+///
+/// "Marvin Harrison Jr." becomes base "marvin harrison" with suffix "jr".
+/// A lone suffix-looking word is kept as the base rather than stripped.
+pub fn tokenize_name(name: &str) -> NameTokens {
+    let normalized = normalize_name(name);
+    let mut words: Vec<&str> = normalized.split(' ').collect();
+
+    let suffix = match words.last() {
+        Some(last) if words.len() > 1 && SUFFIXES.contains(last) => {
+            let suffix = last.to_string();
+            words.pop();
+            Some(suffix)
+        }
+        _ => None,
+    };
+
+    NameTokens {
+        base: words.join(" "),
+        suffix,
+    }
+}
+
+/// Guesses shorter than this many letters must be a whole word of the name
+/// rather than any part of it, so "son" doesn't find every Johnson
+pub const MIN_PARTIAL: usize = 4;
+
+/// Returns true if the guess matches the answer once both are normalized.
+///
+/// Either side containing the other counts as a match, so a last name alone
+/// ("Rudolph") or a name with extra words works, though a guess shorter than
+/// [`MIN_PARTIAL`] letters has to be one of the name's words. Suffixes are
+/// optional, but when both sides carry one they must agree, which separates
+/// a Sr. from a Jr.
+pub fn is_match(guess: &str, answer: &str) -> bool {
+    let guess = tokenize_name(guess);
+    let answer = tokenize_name(answer);
+    comparable(&guess, &answer) && overlaps(&guess, &answer)
+}
+
+/// Returns true if the guess is one word that is the answer's last name, not
+/// counting a suffix: "Johnson" for "Calvin Johnson" but not "Johnson
+/// Bademosi"
+pub fn is_last_name(guess: &str, answer: &str) -> bool {
+    let guess = tokenize_name(guess);
+    let answer = tokenize_name(answer);
+    !guess.base.contains(' ') && answer.base.rsplit(' ').next() == Some(guess.base.as_str())
+}
+
+/// One normalized name containing the other, or for a short guess, the
+/// guess being one of the answer's words
+fn overlaps(guess: &NameTokens, answer: &NameTokens) -> bool {
+    if guess.base.chars().count() < MIN_PARTIAL {
+        return answer.base.split(' ').any(|word| word == guess.base);
+    }
+    answer.base.contains(&guess.base) || guess.base.contains(&answer.base)
+}
+
+/// Both names are non-empty and any suffixes they both carry agree
+fn comparable(guess: &NameTokens, answer: &NameTokens) -> bool {
+    if guess.base.is_empty() || answer.base.is_empty() {
+        return false;
+    }
+    match (&guess.suffix, &answer.suffix) {
+        (Some(g), Some(a)) => g == a,
+        _ => true,
+    }
+}
+
+/// Decides whether a guess names a board answer.
+///
+/// The game asks its matcher about every row, so frontends choose how
+/// forgiving a board is: the CLI takes partial names, a bot may want full ones.
+pub trait Matcher: Send + Sync + fmt::Debug {
+    fn matches(&self, guess: &str, answer: &str) -> bool;
+}
+
+/// Only the full name matches, though suffixes stay optional
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExactMatcher;
+
+impl Matcher for ExactMatcher {
+    fn matches(&self, guess: &str, answer: &str) -> bool {
+        let (guess, answer) = (tokenize_name(guess), tokenize_name(answer));
+        comparable(&guess, &answer) && guess.base == answer.base
+    }
+}
+
+/// Either name containing the other, as in [`is_match`]; the default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SubstringMatcher;
+
+impl Matcher for SubstringMatcher {
+    fn matches(&self, guess: &str, answer: &str) -> bool {
+        is_match(guess, answer)
+    }
+}
+
+/// Substring matching that also forgives a few typos against the full name
+/// or the last name. Like [`is_match`], guesses shorter than [`MIN_PARTIAL`]
+/// letters must be a whole word of the name, and they aren't forgiven typos.
+#[derive(Debug, Clone, Copy)]
+pub struct FuzzyMatcher {
+    pub max_edits: usize,
+}
+
+impl Default for FuzzyMatcher {
+    fn default() -> Self {
+        Self { max_edits: 1 }
+    }
+}
+
+impl Matcher for FuzzyMatcher {
+    fn matches(&self, guess: &str, answer: &str) -> bool {
+        let (guess, answer) = (tokenize_name(guess), tokenize_name(answer));
+        if !comparable(&guess, &answer) {
+            return false;
+        }
+        if overlaps(&guess, &answer) {
+            return true;
+        }
+        if guess.base.chars().count() < MIN_PARTIAL {
+            return false;
+        }
+        let last = answer.base.rsplit(' ').next().unwrap_or(&answer.base);
+        edit_distance(&guess.base, &answer.base) <= self.max_edits
+            || edit_distance(&guess.base, last) <= self.max_edits
+    }
+}
+
+/// A shared matcher, so one can be wrapped without giving up the caller's
+/// handle
+impl<M: Matcher + ?Sized> Matcher for Arc<M> {
+    fn matches(&self, guess: &str, answer: &str) -> bool {
+        (**self).matches(guess, answer)
+    }
+}
+
+/// Nicknames fans type instead of the name on the board, nickname → full
+/// name. A player's own config file can add more.
+pub const NICKNAMES: [(&str, &str); 22] = [
+    ("AP", "Adrian Peterson"),
+    ("ARSB", "Amon-Ra St. Brown"),
+    ("Beast Mode", "Marshawn Lynch"),
+    ("Big Ben", "Ben Roethlisberger"),
+    ("Breesus", "Drew Brees"),
+    ("CJ2K", "Chris Johnson"),
+    ("CMC", "Christian McCaffrey"),
+    ("Cheetah", "Tyreek Hill"),
+    ("Fitzmagic", "Ryan Fitzpatrick"),
+    ("Gronk", "Rob Gronkowski"),
+    ("Hollywood", "Marquise Brown"),
+    ("King Henry", "Derrick Henry"),
+    ("LT", "LaDainian Tomlinson"),
+    ("Matty Ice", "Matt Ryan"),
+    ("Megatron", "Calvin Johnson"),
+    ("MJD", "Maurice Jones-Drew"),
+    ("OBJ", "Odell Beckham"),
+    ("Ochocinco", "Chad Johnson"),
+    ("Sun God", "Amon-Ra St. Brown"),
+    ("T.O.", "Terrell Owens"),
+    ("TB12", "Tom Brady"),
+    ("The Sheriff", "Peyton Manning"),
+];
+
+/// Accepts nicknames ("TB12" for Tom Brady) on top of another matcher
+#[derive(Debug, Clone, Default)]
+pub struct AliasMatcher<M> {
+    inner: M,
+    /// Normalized nickname → full name
+    aliases: HashMap<String, String>,
+}
+
+impl<M: Matcher> AliasMatcher<M> {
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner,
+            aliases: HashMap::new(),
+        }
+    }
+
+    /// `inner` accepting the bundled [`NICKNAMES`]
+    pub fn with_nicknames(inner: M) -> Self {
+        Self::new(inner).aliases(NICKNAMES)
+    }
+
+    /// Adds a nickname, replacing any earlier one spelled the same
+    pub fn alias(mut self, nickname: &str, name: &str) -> Self {
+        self.aliases
+            .insert(normalize_name(nickname), name.to_string());
+        self
+    }
+
+    /// Adds every nickname → name pair, in order
+    pub fn aliases<N: AsRef<str>, F: AsRef<str>>(
+        self,
+        pairs: impl IntoIterator<Item = (N, F)>,
+    ) -> Self {
+        pairs.into_iter().fold(self, |matcher, (nickname, name)| {
+            matcher.alias(nickname.as_ref(), name.as_ref())
+        })
+    }
+}
+
+impl<M: Matcher> Matcher for AliasMatcher<M> {
+    fn matches(&self, guess: &str, answer: &str) -> bool {
+        match self.aliases.get(&normalize_name(guess)) {
+            Some(name) => self.inner.matches(name, answer),
+            None => self.inner.matches(guess, answer),
+        }
+    }
+}
+
+/// The built-in matchers, by the name used to pick one in options
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatcherKind {
+    Exact,
+    #[default]
+    Substring,
+    Fuzzy,
+}
+
+impl MatcherKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatcherKind::Exact => "exact",
+            MatcherKind::Substring => "substring",
+            MatcherKind::Fuzzy => "fuzzy",
+        }
+    }
+
+    pub fn matcher(&self) -> Arc<dyn Matcher> {
+        match self {
+            MatcherKind::Exact => Arc::new(ExactMatcher),
+            MatcherKind::Substring => Arc::new(SubstringMatcher),
+            MatcherKind::Fuzzy => Arc::new(FuzzyMatcher::default()),
+        }
+    }
+}
+
+impl FromStr for MatcherKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "exact" => Ok(MatcherKind::Exact),
+            "substring" => Ok(MatcherKind::Substring),
+            "fuzzy" => Ok(MatcherKind::Fuzzy),
+            _ => Err(format!(
+                "unknown matcher '{s}', expected exact, substring, or fuzzy"
+            )),
+        }
+    }
+}
+
+/// Number of single-character edits needed to turn `a` into `b`.
+///
+/// Inserts, deletes, substitutions, and swaps of two neighbouring characters
+/// each count as one edit, so the common "lsit" typo is one away from "list"
+/// (optimal string alignment distance).
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(d[i - 2][j - 2] + 1);
+            }
+            d[i][j] = best;
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_strips_punctuation() {
+        assert_eq!(normalize_name("Amon-Ra St. Brown"), "amon ra st brown");
+        assert_eq!(normalize_name("Ja'Marr Chase"), "jamarr chase");
+        assert_eq!(normalize_name("  A.J.   Brown "), "aj brown");
+        assert_eq!(normalize_name("Ja’Marr Chase"), "jamarr chase");
+        assert_eq!(normalize_name("Odell Beckham, Jr."), "odell beckham jr");
+    }
+
+    #[test]
+    fn test_normalize_strips_diacritics() {
+        assert_eq!(normalize_name("Zoë Ruíz"), "zoe ruiz");
+        assert_eq!(normalize_name("ÉMILE CÔTÉ"), "emile cote");
+        assert!(is_match("Ruiz", "Zoë Ruíz"));
+        assert!(ExactMatcher.matches("Zoë Ruíz", "zoe ruiz"));
+    }
+
+    #[test]
+    fn test_is_match_ignores_punctuation() {
+        assert!(is_match("st brown", "Amon-Ra St. Brown"));
+        assert!(is_match("St. Brown", "Amon-Ra St. Brown"));
+        assert!(is_match("amon ra", "Amon-Ra St. Brown"));
+        assert!(is_match("AJ Brown", "A.J. Brown"));
+        assert!(is_match("jamarr", "Ja'Marr Chase"));
+    }
+
+    #[test]
+    fn test_short_guess_must_be_a_whole_word() {
+        assert!(!is_match("son", "Calvin Johnson"));
+        assert!(!is_match("a", "Tom Brady"));
+        assert!(is_match("AJ", "A.J. Brown"));
+        assert!(is_match("Tom", "Tom Brady"));
+        assert!(is_match("john", "Calvin Johnson"));
+        assert!(is_match("Bo Jackson", "Bo"));
+    }
+
+    #[test]
+    fn test_is_last_name() {
+        assert!(is_last_name("johnson", "Calvin Johnson"));
+        assert!(is_last_name("Harrison", "Marvin Harrison Jr."));
+        assert!(!is_last_name("johnson", "Johnson Bademosi"));
+        assert!(!is_last_name("calvin johnson", "Calvin Johnson"));
+    }
+
+    #[test]
+    fn test_is_match_guess_with_extra_words() {
+        assert!(is_match("odell beckham jr", "Odell Beckham"));
+    }
+
+    #[test]
+    fn test_tokenize_splits_suffix() {
+        let tokens = tokenize_name("Marvin Harrison Jr.");
+        assert_eq!(tokens.base, "marvin harrison");
+        assert_eq!(tokens.suffix.as_deref(), Some("jr"));
+
+        let tokens = tokenize_name("Robert Griffin III");
+        assert_eq!(tokens.base, "robert griffin");
+        assert_eq!(tokens.suffix.as_deref(), Some("iii"));
+
+        let tokens = tokenize_name("Tom Brady");
+        assert_eq!(tokens.suffix, None);
+    }
+
+    #[test]
+    fn test_suffix_is_optional() {
+        assert!(is_match("Marvin Harrison", "Marvin Harrison Jr."));
+        assert!(is_match("Odell Beckham Jr.", "Odell Beckham"));
+        assert!(is_match("griffin", "Robert Griffin III"));
+    }
+
+    #[test]
+    fn test_suffix_disambiguates() {
+        assert!(is_match("marvin harrison jr", "Marvin Harrison Jr."));
+        assert!(!is_match("marvin harrison jr", "Marvin Harrison Sr."));
+        assert!(!is_match("Marvin Harrison Sr", "Marvin Harrison Jr."));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("brady", "brady"), 0);
+        assert_eq!(edit_distance("lsit", "list"), 1);
+    }
+
+    #[test]
+    fn test_matchers_differ_in_strictness() {
+        let answer = "Mason Rudolph";
+        assert!(!ExactMatcher.matches("Rudolph", answer));
+        assert!(ExactMatcher.matches("mason rudolph", answer));
+        assert!(SubstringMatcher.matches("Rudolph", answer));
+        assert!(!SubstringMatcher.matches("Rudoph", answer));
+        assert!(FuzzyMatcher::default().matches("Rudoph", answer));
+        assert!(FuzzyMatcher::default().matches("mason rudoph", answer));
+        assert!(!FuzzyMatcher::default().matches("Hill", "Tyreek Bell"));
+        assert!(!ExactMatcher.matches("Marvin Harrison Sr", "Marvin Harrison Jr."));
+    }
+
+    #[test]
+    fn test_fuzzy_forgives_typos_not_fragments() {
+        let fuzzy = FuzzyMatcher::default();
+        assert!(fuzzy.matches("Rothlisberger", "Ben Roethlisberger"));
+        assert!(!fuzzy.matches("Rothlsberger", "Ben Roethlisberger"));
+        assert!(FuzzyMatcher { max_edits: 2 }.matches("Rothlsberger", "Ben Roethlisberger"));
+        assert!(!fuzzy.matches("a", "Tom Brady"));
+        assert!(!fuzzy.matches("bra", "Tom Brady"));
+        assert!(fuzzy.matches("Tom", "Tom Brady"));
+        assert!(fuzzy.matches("AJ", "A.J. Brown"));
+    }
+
+    #[test]
+    fn test_alias_matcher() {
+        let matcher = AliasMatcher::new(ExactMatcher).alias("TB12", "Tom Brady");
+        assert!(matcher.matches("tb12", "Tom Brady"));
+        assert!(matcher.matches("Tom Brady", "Tom Brady"));
+        assert!(!matcher.matches("tb12", "Mason Rudolph"));
+    }
+
+    #[test]
+    fn test_bundled_and_added_nicknames() {
+        let inner: Arc<dyn Matcher> = MatcherKind::Substring.matcher();
+        let matcher = AliasMatcher::with_nicknames(inner).aliases([("Megatron", "Mason Rudolph")]);
+        assert!(matcher.matches("gronk", "Rob Gronkowski"));
+        assert!(matcher.matches("CMC", "Christian McCaffrey"));
+        assert!(matcher.matches("t.o.", "Terrell Owens"));
+        // A player's own nickname replaces the bundled one
+        assert!(matcher.matches("Megatron", "Mason Rudolph"));
+        assert!(!matcher.matches("Megatron", "Calvin Johnson"));
+        // Anything else still goes to the inner matcher
+        assert!(matcher.matches("johnson", "Calvin Johnson"));
+    }
+
+    #[test]
+    fn test_matcher_kind_from_str() {
+        assert_eq!("Fuzzy".parse::<MatcherKind>(), Ok(MatcherKind::Fuzzy));
+        assert!("loose".parse::<MatcherKind>().is_err());
+        assert!(MatcherKind::default()
+            .matcher()
+            .matches("brady", "Tom Brady"));
+    }
+
+    #[test]
+    fn test_is_match_rejects_empty() {
+        assert!(!is_match("...", "Tom Brady"));
+        assert!(!is_match("Tom Brady", ""));
+    }
+}
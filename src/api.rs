@@ -0,0 +1,213 @@
+//! Request and response bodies of the HTTP API
+//!
+//! These are the JSON shapes the `http` server speaks and the `http-client`
+//! client decodes, kept apart from the engine's own types so the contract in
+//! `openapi/know_ball.json` only changes on purpose. They mirror the messages
+//! in `proto/know_ball.proto`.
+use crate::game::{GameEnd, GuessOutcome, TriviaGame};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "http")]
+use utoipa::ToSchema;
+
+/// A registered question code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+pub struct QuestionInfo {
+    pub code: String,
+    pub description: String,
+}
+
+/// Picks a question by code, e.g. `last10passers_PIT`; empty for a random one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+pub struct GenerateRequest {
+    #[serde(default)]
+    pub code: String,
+}
+
+/// The parameters a question was generated with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+pub struct QuestionParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_year: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_year: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<String>,
+}
+
+/// A generated question: its text and the SQL for its board, with the
+/// parameter values written in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+pub struct QuestionSpec {
+    pub code: String,
+    pub params: QuestionParams,
+    pub text: String,
+    pub sql: String,
+}
+
+/// A board's columns and rows; the player name is always the first column
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+pub struct Board {
+    pub column_names: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Why a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum End {
+    Perfect,
+    StruckOut,
+    GaveUp,
+    TimeUp,
+    Skipped,
+}
+
+/// A game in progress or just finished. Unguessed names are blank until it
+/// ends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+pub struct Game {
+    pub id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<QuestionSpec>,
+    pub board: Board,
+    /// Points for each row, in board order
+    pub point_values: Vec<u32>,
+    pub score: u32,
+    pub strikes: u32,
+    /// Set once the game is over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<End>,
+}
+
+/// One guess at a game's hidden names
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+pub struct GuessRequest {
+    pub text: String,
+}
+
+/// What a guess did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Correct,
+    AlreadyGuessed,
+    /// The guess fits more than one player; nothing was revealed
+    Ambiguous,
+    Strike,
+    Empty,
+}
+
+/// A guess's outcome and the game after it. `row`, `name`, and `points` are
+/// only set for a correct guess.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+pub struct GuessResponse {
+    pub outcome: Outcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<u32>,
+    pub game: Game,
+}
+
+/// The body of every error response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "http", derive(ToSchema))]
+pub struct ApiError {
+    pub error: String,
+}
+
+impl From<&crate::questions::QuestionSpec> for QuestionSpec {
+    fn from(spec: &crate::questions::QuestionSpec) -> Self {
+        let params = &spec.params;
+        Self {
+            code: spec.code.clone(),
+            params: QuestionParams {
+                team: params.team.clone(),
+                year: params.year,
+                first_year: params.year_range.map(|(first, _)| first),
+                last_year: params.year_range.map(|(_, last)| last),
+                threshold: params.threshold,
+                position: params.position.clone(),
+            },
+            text: spec.text.clone(),
+            sql: spec.query.inline(),
+        }
+    }
+}
+
+impl From<GameEnd> for End {
+    fn from(end: GameEnd) -> Self {
+        match end {
+            GameEnd::Perfect => End::Perfect,
+            GameEnd::StruckOut => End::StruckOut,
+            GameEnd::GaveUp => End::GaveUp,
+            GameEnd::TimeUp => End::TimeUp,
+            GameEnd::Skipped => End::Skipped,
+        }
+    }
+}
+
+impl Game {
+    /// The API view of `game`, with unguessed names blanked
+    pub fn new(id: u64, game: &TriviaGame) -> Self {
+        let board = game.visible_board();
+        Self {
+            id,
+            spec: game.spec().map(QuestionSpec::from),
+            board: Board {
+                column_names: board.column_names,
+                rows: board.rows,
+            },
+            point_values: game.point_values().to_vec(),
+            score: game.score(),
+            strikes: game.strikes() as u32,
+            end: game.end().map(End::from),
+        }
+    }
+}
+
+impl GuessResponse {
+    /// The response to `outcome`, or None for a guess at a finished or
+    /// paused game
+    pub fn new(outcome: GuessOutcome, game: Game) -> Option<Self> {
+        let mut response = Self {
+            outcome: Outcome::Empty,
+            row: None,
+            name: None,
+            points: None,
+            game,
+        };
+        response.outcome = match outcome {
+            GuessOutcome::Correct { row, name, points } => {
+                response.row = Some(row as u32);
+                response.name = Some(name);
+                response.points = Some(points);
+                Outcome::Correct
+            }
+            GuessOutcome::AlreadyGuessed => Outcome::AlreadyGuessed,
+            GuessOutcome::Ambiguous { .. } => Outcome::Ambiguous,
+            GuessOutcome::Strike { .. } => Outcome::Strike,
+            GuessOutcome::Empty => Outcome::Empty,
+            GuessOutcome::GameOver | GuessOutcome::Paused => return None,
+        };
+        Some(response)
+    }
+}
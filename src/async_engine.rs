@@ -0,0 +1,148 @@
+//! Async wrappers around the engine and game (`async` feature)
+//!
+//! rusqlite is blocking, so board queries run on tokio's blocking pool while
+//! question generation and guess checks, which are quick and in-memory, run
+//! behind async mutexes. Servers and bots can share these handles across tasks
+//! without stalling the executor.
+use crate::engine::{keep_board, worth_fetching, TriviaEngine};
+use crate::error::Result;
+use crate::game::{GuessOutcome, TriviaGame, TriviaResult};
+use crate::questions::{validate_params, ParsedRequest, Question, QuestionParams, QuestionSpec};
+use crate::sql_runner::Board;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::task::spawn_blocking;
+
+/// Cloneable async handle to a shared `TriviaEngine`
+#[derive(Clone)]
+pub struct AsyncTriviaEngine {
+    inner: Arc<Mutex<TriviaEngine>>,
+}
+
+impl AsyncTriviaEngine {
+    pub fn new(engine: TriviaEngine) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(engine)),
+        }
+    }
+
+    pub async fn random_question(&self) -> Option<Arc<dyn Question>> {
+        self.inner.lock().await.random_question()
+    }
+
+    pub async fn lookup(&self, input: &str) -> Option<ParsedRequest> {
+        self.inner.lock().await.lookup(input)
+    }
+
+    pub async fn generate(&self, question: &dyn Question, fixed: &QuestionParams) -> QuestionSpec {
+        self.inner.lock().await.generate(question, fixed)
+    }
+
+    /// Generates a question and loads its board, rerolling like
+    /// [`TriviaEngine::load_question`].
+    ///
+    /// The engine lock is only held while generating, so other tasks can
+    /// keep using the engine while the query runs.
+    pub async fn load_question(
+        &self,
+        question: Arc<dyn Question>,
+        fixed: &QuestionParams,
+    ) -> Result<(QuestionSpec, Board)> {
+        validate_params(question.as_ref(), fixed)?;
+        let store = self.inner.lock().await.store();
+        let mut attempt = 1;
+        let mut last_query = None;
+        loop {
+            let spec = self.generate(question.as_ref(), fixed).await;
+            let repeated = last_query.as_ref() == Some(&spec.query);
+            let (store, query) = (store.clone(), spec.query.clone());
+            let board = spawn_blocking(move || {
+                if !repeated && !worth_fetching(store.as_ref(), &query, attempt)? {
+                    return Ok(None);
+                }
+                store.query_board(&query).map(Some)
+            })
+            .await??;
+            match board {
+                Some(board) if repeated || keep_board(question.as_ref(), &board, attempt) => {
+                    return Ok((spec, board));
+                }
+                _ => {
+                    attempt += 1;
+                    last_query = Some(spec.query);
+                }
+            }
+        }
+    }
+}
+
+/// Cloneable async handle to one board being played, for frontends where
+/// guesses arrive on different tasks
+#[derive(Clone)]
+pub struct SharedGame {
+    inner: Arc<Mutex<TriviaGame>>,
+}
+
+impl SharedGame {
+    pub fn new(game: TriviaGame) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(game)),
+        }
+    }
+
+    pub async fn submit_guess(&self, guess: &str) -> GuessOutcome {
+        self.inner.lock().await.submit_guess(guess)
+    }
+
+    pub async fn reveal(&self) {
+        self.inner.lock().await.reveal()
+    }
+
+    pub async fn is_over(&self) -> bool {
+        self.inner.lock().await.is_over()
+    }
+
+    pub async fn result(&self) -> TriviaResult {
+        self.inner.lock().await.result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_runner::Board;
+
+    #[tokio::test]
+    async fn test_async_generate_matches_sync() {
+        let engine = AsyncTriviaEngine::new(TriviaEngine::with_seed(7));
+        let mut sync = TriviaEngine::with_seed(7);
+
+        let a = engine.random_question().await.unwrap();
+        let b = sync.random_question().unwrap();
+        assert_eq!(a.meta().code, b.meta().code);
+        assert_eq!(
+            engine
+                .generate(a.as_ref(), &QuestionParams::default())
+                .await
+                .query,
+            sync.generate(b.as_ref(), &QuestionParams::default()).query
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shared_game_across_tasks() {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![vec!["Tom Brady".into(), "5000".into()]],
+        };
+        let game = SharedGame::new(TriviaGame::new("q", board));
+
+        let handle = game.clone();
+        let outcome = tokio::spawn(async move { handle.submit_guess("Brady").await })
+            .await
+            .unwrap();
+
+        assert!(matches!(outcome, GuessOutcome::Correct { .. }));
+        assert!(game.is_over().await);
+    }
+}
@@ -0,0 +1,88 @@
+//! Discord bot: each channel plays a shared Know Ball board
+//!
+//! Set `DISCORD_TOKEN` to the bot token and run from the directory that
+//! contains `nfl.sqlite`. `KNOWBALL_PREFIX` (default `!kb`),
+//! `KNOWBALL_COOLDOWN_SECS` (default 3), `KNOWBALL_BOARD_COOLDOWN_SECS`
+//! (default 0), and `KNOWBALL_ADMINS` tune how the bot listens. The bot needs
+//! the Message Content intent enabled in the developer portal.
+use know_ball::bot::{BotConfig, ChannelGames};
+use know_ball::TriviaEngine;
+use serenity::async_trait;
+use serenity::model::channel::Message;
+use serenity::model::gateway::Ready;
+use serenity::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+struct Handler {
+    games: Arc<Mutex<ChannelGames>>,
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn message(&self, ctx: Context, msg: Message) {
+        if msg.author.bot {
+            return;
+        }
+        let games = Arc::clone(&self.games);
+        let (channel, user) = (msg.channel_id.get(), msg.author.id.get());
+        let name = msg.author.name.clone();
+        let text = msg.content.clone();
+        // Starting a board queries SQLite, so keep it off the async workers
+        let reply = tokio::task::spawn_blocking(move || {
+            let mut games = games.lock().unwrap_or_else(|e| e.into_inner());
+            games.handle_message(channel, user, &name, &text, Instant::now())
+        })
+        .await;
+
+        match reply {
+            Ok(Some(reply)) => {
+                if let Err(e) = msg.channel_id.say(&ctx.http, reply).await {
+                    eprintln!("Error sending reply: {e}");
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Error handling message: {e}"),
+        }
+    }
+
+    async fn ready(&self, _: Context, ready: Ready) {
+        println!("{} is connected", ready.user.name);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let Ok(token) = std::env::var("DISCORD_TOKEN") else {
+        eprintln!("Error: set DISCORD_TOKEN to the bot's token");
+        std::process::exit(2);
+    };
+    let config = match BotConfig::default().with_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    let handler = Handler {
+        games: Arc::new(Mutex::new(ChannelGames::new(TriviaEngine::new(), config))),
+    };
+    let intents = GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::DIRECT_MESSAGES
+        | GatewayIntents::MESSAGE_CONTENT;
+    let mut client = match Client::builder(&token, intents)
+        .event_handler(handler)
+        .await
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Error creating client: {e}");
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = client.start().await {
+        eprintln!("Client error: {e}");
+        std::process::exit(1);
+    }
+}
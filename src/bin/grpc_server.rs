@@ -0,0 +1,33 @@
+//! gRPC server for question generation and guess evaluation
+//!
+//! Run from the directory that contains `nfl.sqlite`. It listens on
+//! `KNOWBALL_GRPC_ADDR` (default `127.0.0.1:50051`) and serves the
+//! `know_ball.v1.KnowBall` service from `proto/know_ball.proto`. See
+//! `know_ball::grpc`.
+use know_ball::grpc::KnowBallService;
+use know_ball::TriviaEngine;
+use std::net::SocketAddr;
+
+#[tokio::main]
+async fn main() {
+    let addr =
+        std::env::var("KNOWBALL_GRPC_ADDR").unwrap_or_else(|_| "127.0.0.1:50051".to_string());
+    let addr: SocketAddr = match addr.parse() {
+        Ok(addr) => addr,
+        Err(e) => {
+            eprintln!("Error: bad address '{addr}': {e}");
+            std::process::exit(2);
+        }
+    };
+    println!("Know Ball gRPC listening on {addr}");
+
+    let service = KnowBallService::new(TriviaEngine::new()).into_server();
+    if let Err(e) = tonic::transport::Server::builder()
+        .add_service(service)
+        .serve(addr)
+        .await
+    {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
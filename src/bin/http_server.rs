@@ -0,0 +1,34 @@
+//! HTTP server for question generation and guess evaluation
+//!
+//! Run from the directory that contains `nfl.sqlite`. It listens on
+//! `KNOWBALL_HTTP_ADDR` (default `127.0.0.1:8080`) and serves the JSON API
+//! described at `/openapi.json`. With `--openapi` it prints that document and
+//! exits instead. See `know_ball::http`.
+use know_ball::http::{openapi, router};
+use know_ball::TriviaEngine;
+
+#[tokio::main]
+async fn main() {
+    if std::env::args().nth(1).as_deref() == Some("--openapi") {
+        let document = openapi()
+            .to_pretty_json()
+            .expect("the OpenAPI document serializes to JSON");
+        println!("{document}");
+        return;
+    }
+
+    let addr = std::env::var("KNOWBALL_HTTP_ADDR").unwrap_or_else(|_| "127.0.0.1:8080".to_string());
+    let listener = match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error: couldn't listen on {addr}: {e}");
+            std::process::exit(2);
+        }
+    };
+    println!("Know Ball HTTP API listening on http://{addr}");
+
+    if let Err(e) = axum::serve(listener, router(TriviaEngine::new())).await {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
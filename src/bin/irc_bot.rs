@@ -0,0 +1,189 @@
+//! IRC bot: each channel plays a shared Know Ball board
+//!
+//! Run from the directory that contains `nfl.sqlite`. `IRC_SERVER` is the
+//! `host:port` to connect to over plain TCP (default `irc.libera.chat:6667`),
+//! `IRC_NICK` the bot's nick (default `knowball`), `IRC_CHANNELS` the
+//! comma-separated channels to join, and `IRC_PASSWORD` an optional server
+//! password. A private message plays a board of its own. The `KNOWBALL_*`
+//! settings are the Discord bot's, except that a channel gets at most one
+//! board every 30 seconds unless `KNOWBALL_BOARD_COOLDOWN_SECS` says
+//! otherwise. Admins are matched by nick, so only list registered nicks.
+use know_ball::bot::{BotConfig, ChannelGames};
+use know_ball::TriviaEngine;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::unbounded_channel;
+
+/// Gap between lines sent to the server, to stay under flood limits
+const SEND_INTERVAL: Duration = Duration::from_millis(500);
+
+/// One line from the server: `[:prefix] COMMAND params... [:trailing]`
+struct Line<'a> {
+    prefix: Option<&'a str>,
+    command: &'a str,
+    params: Vec<&'a str>,
+}
+
+fn parse_line(line: &str) -> Option<Line<'_>> {
+    let (prefix, rest) = match line.strip_prefix(':') {
+        Some(rest) => {
+            let (prefix, rest) = rest.split_once(' ')?;
+            (Some(prefix), rest)
+        }
+        None => (None, line),
+    };
+    let (rest, trailing) = match rest.split_once(" :") {
+        Some((rest, trailing)) => (rest, Some(trailing)),
+        None => (rest, None),
+    };
+    let mut words = rest.split_whitespace();
+    let command = words.next()?;
+    let mut params: Vec<&str> = words.collect();
+    params.extend(trailing);
+    Some(Line {
+        prefix,
+        command,
+        params,
+    })
+}
+
+/// A chat reply as IRC lines, without the code fences, bold, and backticks
+/// that only mean something in Markdown
+fn irc_lines(reply: &str) -> Vec<String> {
+    reply
+        .lines()
+        .filter(|line| line.trim() != "```")
+        .map(|line| line.replace("**", "").replace('`', ""))
+        .filter(|line| !line.trim().is_empty())
+        .collect()
+}
+
+/// Stable numeric ids for channel names and nicks, which `ChannelGames` keys on
+#[derive(Default)]
+struct Ids(HashMap<String, u64>);
+
+impl Ids {
+    fn get(&mut self, name: &str) -> u64 {
+        let next = self.0.len() as u64 + 1;
+        *self.0.entry(name.to_lowercase()).or_insert(next)
+    }
+}
+
+fn env_or(var: &str, default: &str) -> String {
+    std::env::var(var).unwrap_or_else(|_| default.to_string())
+}
+
+#[tokio::main]
+async fn main() {
+    let server = env_or("IRC_SERVER", "irc.libera.chat:6667");
+    let mut nick = env_or("IRC_NICK", "knowball");
+    let channels = env_or("IRC_CHANNELS", "");
+    if channels.trim().is_empty() {
+        eprintln!("Error: set IRC_CHANNELS to the channels to join, e.g. #knowball");
+        std::process::exit(2);
+    }
+    let config = BotConfig {
+        board_cooldown: Duration::from_secs(30),
+        ..BotConfig::default()
+    };
+    let config = match config.with_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    let stream = match TcpStream::connect(&server).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            eprintln!("Error: couldn't connect to {server}: {e}");
+            std::process::exit(1);
+        }
+    };
+    let (reader, mut writer) = stream.into_split();
+    let (tx, mut rx) = unbounded_channel::<String>();
+    tokio::spawn(async move {
+        while let Some(line) = rx.recv().await {
+            if writer
+                .write_all(format!("{line}\r\n").as_bytes())
+                .await
+                .is_err()
+            {
+                break;
+            }
+            tokio::time::sleep(SEND_INTERVAL).await;
+        }
+    });
+    let send = |line: String| {
+        // A closed channel means the connection is going away
+        let _ = tx.send(line);
+    };
+
+    if let Ok(password) = std::env::var("IRC_PASSWORD") {
+        send(format!("PASS {password}"));
+    }
+    send(format!("NICK {nick}"));
+    send(format!("USER {nick} 0 * :Know Ball"));
+
+    let games = Arc::new(Mutex::new(ChannelGames::new(TriviaEngine::new(), config)));
+    let mut ids = Ids::default();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(raw)) = lines.next_line().await {
+        let Some(line) = parse_line(&raw) else {
+            continue;
+        };
+        match (line.command, &line.params[..]) {
+            ("PING", params) => send(format!("PONG :{}", params.join(" "))),
+            // Welcome: registration is done
+            ("001", _) => {
+                println!("Connected to {server} as {nick}");
+                send(format!("JOIN {channels}"));
+            }
+            // Nick in use
+            ("433", _) => {
+                nick.push('_');
+                send(format!("NICK {nick}"));
+            }
+            ("PRIVMSG", [target, text]) => {
+                let Some(sender) = line.prefix.and_then(|p| p.split('!').next()) else {
+                    continue;
+                };
+                // CTCP requests like VERSION and ACTION
+                if text.starts_with('\u{1}') {
+                    continue;
+                }
+                let reply_to = if target.starts_with(['#', '&']) {
+                    target.to_string()
+                } else {
+                    sender.to_string()
+                };
+                let (channel, user) = (ids.get(&reply_to), ids.get(sender));
+                let (games, sender, text) =
+                    (Arc::clone(&games), sender.to_string(), text.to_string());
+                // Starting a board queries SQLite, so keep it off the async workers
+                let reply = tokio::task::spawn_blocking(move || {
+                    let mut games = games.lock().unwrap_or_else(|e| e.into_inner());
+                    games.handle_message(channel, user, &sender, &text, Instant::now())
+                })
+                .await;
+                match reply {
+                    Ok(Some(reply)) => {
+                        for line in irc_lines(&reply) {
+                            send(format!("PRIVMSG {reply_to} :{line}"));
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Error handling message: {e}"),
+                }
+            }
+            ("ERROR", params) => eprintln!("Server error: {}", params.join(" ")),
+            _ => {}
+        }
+    }
+    eprintln!("Disconnected from {server}");
+    std::process::exit(1);
+}
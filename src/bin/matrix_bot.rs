@@ -0,0 +1,287 @@
+//! Matrix bot: each room plays a shared Know Ball board
+//!
+//! Run from the directory that contains `nfl.sqlite`. `MATRIX_HOMESERVER` is
+//! the homeserver's base URL (default `https://matrix.org`), `MATRIX_TOKEN` an
+//! access token for the bot's account, and `MATRIX_ROOMS` an optional
+//! comma-separated list of room ids or aliases to join at startup; the bot
+//! also accepts invites. The `KNOWBALL_*` settings are the Discord bot's, and
+//! admins are full Matrix ids like `@ann:example.org`.
+//!
+//! The bot talks plain client-server HTTP and can't decrypt messages itself.
+//! To play in encrypted rooms, run it behind an E2EE proxy such as pantalaimon
+//! and point `MATRIX_HOMESERVER` at the proxy.
+use know_ball::bot::{BotConfig, ChannelGames};
+use know_ball::TriviaEngine;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How long the homeserver may hold a sync request open waiting for events
+const SYNC_TIMEOUT_MS: u64 = 30_000;
+
+/// Pause after a failed sync before trying again
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Only the recent timeline is needed; skip presence and account data
+const SYNC_FILTER: &str = r#"{"presence":{"types":[]},"account_data":{"types":[]},"room":{"timeline":{"limit":50},"state":{"lazy_load_members":true}}}"#;
+
+struct Matrix {
+    homeserver: String,
+    token: String,
+    agent: ureq::Agent,
+    next_txn: u64,
+}
+
+impl Matrix {
+    fn url(&self, path: &str) -> String {
+        format!("{}/_matrix/client/v3{path}", self.homeserver)
+    }
+
+    fn auth(&self, request: ureq::Request) -> ureq::Request {
+        request.set("Authorization", &format!("Bearer {}", self.token))
+    }
+
+    fn whoami(&self) -> Result<String, String> {
+        let request = self.auth(self.agent.get(&self.url("/account/whoami")));
+        let body = read(request.call())?;
+        body["user_id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "whoami returned no user_id".to_string())
+    }
+
+    fn join(&self, room: &str) -> Result<(), String> {
+        let path = format!("/join/{}", encode(room));
+        let request = self.auth(self.agent.post(&self.url(&path)));
+        read(request.send_json(json!({}))).map(|_| ())
+    }
+
+    fn sync(&self, since: Option<&str>) -> Result<Value, String> {
+        let mut request = self
+            .auth(self.agent.get(&self.url("/sync")))
+            .query("filter", SYNC_FILTER);
+        if let Some(since) = since {
+            request = request
+                .query("since", since)
+                .query("timeout", &SYNC_TIMEOUT_MS.to_string());
+        }
+        read(request.call())
+    }
+
+    /// Sends a reply as plain text plus the same text as HTML, waiting out
+    /// the homeserver's rate limit once if it asks
+    fn send(&mut self, room: &str, reply: &str) -> Result<(), String> {
+        self.next_txn += 1;
+        let path = format!(
+            "/rooms/{}/send/m.room.message/kb{}-{}",
+            encode(room),
+            std::process::id(),
+            self.next_txn
+        );
+        let content = json!({
+            "msgtype": "m.notice",
+            "body": reply,
+            "format": "org.matrix.custom.html",
+            "formatted_body": html(reply),
+        });
+        for _ in 0..2 {
+            let request = self.auth(self.agent.put(&self.url(&path)));
+            match request.send_json(&content) {
+                Err(ureq::Error::Status(429, response)) => {
+                    let body: Value = response.into_json().unwrap_or_default();
+                    let wait = body["retry_after_ms"].as_u64().unwrap_or(1000);
+                    std::thread::sleep(Duration::from_millis(wait));
+                }
+                response => return read(response).map(|_| ()),
+            }
+        }
+        Err(format!("still rate limited sending to {room}"))
+    }
+}
+
+/// The JSON body of a response, or a readable error
+fn read(response: Result<ureq::Response, ureq::Error>) -> Result<Value, String> {
+    match response {
+        Ok(response) => response.into_json().map_err(|e| e.to_string()),
+        Err(ureq::Error::Status(status, response)) => {
+            let body: Value = response.into_json().unwrap_or_default();
+            let error = body["error"].as_str().unwrap_or("no details");
+            Err(format!("HTTP {status}: {error}"))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Percent-encodes a room id or alias for a URL path
+fn encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// A chat reply as Matrix HTML: code fences become `<pre>`, and bold and
+/// inline code keep their meaning
+fn html(reply: &str) -> String {
+    let mut out = String::new();
+    let mut in_code = false;
+    for line in reply.lines() {
+        if line.trim() == "```" {
+            out.push_str(if in_code {
+                "</code></pre>"
+            } else {
+                "<pre><code>"
+            });
+            in_code = !in_code;
+            continue;
+        }
+        let line = line
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        if in_code {
+            out.push_str(&line);
+            out.push('\n');
+            continue;
+        }
+        out.push_str(&inline(&inline(&line, "**", "strong"), "`", "code"));
+        out.push_str("<br>");
+    }
+    out.trim_end_matches("<br>").to_string()
+}
+
+/// Wraps each pair of `marker`s in `<tag>`, leaving an unpaired one as is
+fn inline(line: &str, marker: &str, tag: &str) -> String {
+    let parts: Vec<&str> = line.split(marker).collect();
+    if parts.len().is_multiple_of(2) {
+        return line.to_string();
+    }
+    let mut out = String::new();
+    for (i, part) in parts.iter().enumerate() {
+        if i % 2 == 1 {
+            out.push_str(&format!("<{tag}>{part}</{tag}>"));
+        } else {
+            out.push_str(part);
+        }
+    }
+    out
+}
+
+/// Stable numeric ids for room and user ids, which `ChannelGames` keys on
+#[derive(Default)]
+struct Ids(HashMap<String, u64>);
+
+impl Ids {
+    fn get(&mut self, name: &str) -> u64 {
+        let next = self.0.len() as u64 + 1;
+        *self.0.entry(name.to_string()).or_insert(next)
+    }
+}
+
+fn main() {
+    let homeserver =
+        std::env::var("MATRIX_HOMESERVER").unwrap_or_else(|_| "https://matrix.org".to_string());
+    let Ok(token) = std::env::var("MATRIX_TOKEN") else {
+        eprintln!("Error: set MATRIX_TOKEN to the bot account's access token");
+        std::process::exit(2);
+    };
+    let config = match BotConfig::default().with_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    let timeout = Duration::from_millis(SYNC_TIMEOUT_MS) + Duration::from_secs(30);
+    let mut matrix = Matrix {
+        homeserver: homeserver.trim_end_matches('/').to_string(),
+        token,
+        agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        next_txn: 0,
+    };
+    let me = match matrix.whoami() {
+        Ok(me) => me,
+        Err(e) => {
+            eprintln!("Error: couldn't log in to {homeserver}: {e}");
+            std::process::exit(1);
+        }
+    };
+    println!("Connected to {homeserver} as {me}");
+    let rooms = std::env::var("MATRIX_ROOMS").unwrap_or_default();
+    for room in rooms.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+        if let Err(e) = matrix.join(room) {
+            eprintln!("Error joining {room}: {e}");
+        }
+    }
+
+    let mut games = ChannelGames::new(TriviaEngine::new(), config);
+    let mut ids = Ids::default();
+    let mut warned = HashSet::new();
+    // The first sync only finds where "now" is, so old messages aren't replayed
+    let mut since: Option<String> = None;
+    loop {
+        let sync = match matrix.sync(since.as_deref()) {
+            Ok(sync) => sync,
+            Err(e) => {
+                eprintln!("Sync failed: {e}");
+                std::thread::sleep(RETRY_DELAY);
+                continue;
+            }
+        };
+        let caught_up = since.is_some();
+        since = sync["next_batch"].as_str().map(str::to_string);
+
+        if let Some(invites) = sync["rooms"]["invite"].as_object() {
+            for room in invites.keys() {
+                match matrix.join(room) {
+                    Ok(()) => println!("Joined {room}"),
+                    Err(e) => eprintln!("Error joining {room}: {e}"),
+                }
+            }
+        }
+        if !caught_up {
+            continue;
+        }
+
+        let Some(joined) = sync["rooms"]["join"].as_object() else {
+            continue;
+        };
+        for (room, state) in joined {
+            let events = state["timeline"]["events"].as_array();
+            for event in events.into_iter().flatten() {
+                let sender = event["sender"].as_str().unwrap_or_default();
+                if sender == me {
+                    continue;
+                }
+                if event["type"] == "m.room.encrypted" {
+                    if warned.insert(room.clone()) {
+                        eprintln!(
+                            "{room} is encrypted; run the bot behind an E2EE proxy such as pantalaimon to play there"
+                        );
+                    }
+                    continue;
+                }
+                let content = &event["content"];
+                if event["type"] != "m.room.message" || content["msgtype"] != "m.text" {
+                    continue;
+                }
+                let Some(text) = content["body"].as_str() else {
+                    continue;
+                };
+                let (channel, user) = (ids.get(room), ids.get(sender));
+                let reply = games.handle_message(channel, user, sender, text, Instant::now());
+                if let Some(reply) = reply {
+                    if let Err(e) = matrix.send(room, &reply) {
+                        eprintln!("Error replying in {room}: {e}");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! WebSocket server: clients join rooms and race on a shared board
+//!
+//! Run from the directory that contains `nfl.sqlite`. It listens on
+//! `KNOWBALL_ADDR` (default `127.0.0.1:9001`). Every WebSocket text frame is a
+//! JSON `ClientMessage`, such as `{"type":"join","room":"bar","name":"Ann"}`,
+//! and the server answers with JSON `ServerMessage`s. See `know_ball::rooms`.
+use futures_util::{SinkExt, StreamExt};
+use know_ball::rooms::{ClientMessage, Delivery, Rooms, ServerMessage};
+use know_ball::TriviaEngine;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Outgoing frames for each connected client
+type Clients = Arc<Mutex<HashMap<u64, UnboundedSender<String>>>>;
+
+struct Server {
+    rooms: Arc<Mutex<Rooms>>,
+    clients: Clients,
+}
+
+impl Server {
+    /// Applies a message to the rooms and sends out the replies. Starting a
+    /// board queries SQLite, so it runs off the async workers; the rooms lock
+    /// applies guesses in the order they arrive.
+    async fn dispatch(&self, client: u64, message: Option<ClientMessage>) {
+        let rooms = Arc::clone(&self.rooms);
+        let deliveries = tokio::task::spawn_blocking(move || {
+            let mut rooms = rooms.lock().unwrap_or_else(|e| e.into_inner());
+            match message {
+                Some(message) => rooms.handle(client, message),
+                None => rooms.leave(client),
+            }
+        })
+        .await;
+        match deliveries {
+            Ok(deliveries) => self.deliver(deliveries),
+            Err(e) => eprintln!("Error handling message: {e}"),
+        }
+    }
+
+    fn deliver(&self, deliveries: Vec<Delivery>) {
+        let clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        for (client, message) in deliveries {
+            if let Some(tx) = clients.get(&client) {
+                let text = serde_json::to_string(&message).expect("server messages serialize");
+                // A closed channel means the client is disconnecting
+                let _ = tx.send(text);
+            }
+        }
+    }
+
+    async fn serve(self: Arc<Self>, stream: TcpStream, client: u64) {
+        let ws = match tokio_tungstenite::accept_async(stream).await {
+            Ok(ws) => ws,
+            Err(e) => {
+                eprintln!("Handshake failed: {e}");
+                return;
+            }
+        };
+        let (mut sink, mut incoming) = ws.split();
+        let (tx, mut rx) = unbounded_channel::<String>();
+        self.clients
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(client, tx);
+
+        let writer = tokio::spawn(async move {
+            while let Some(text) = rx.recv().await {
+                if sink.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        while let Some(Ok(frame)) = incoming.next().await {
+            match frame {
+                Message::Text(text) => match serde_json::from_str(&text) {
+                    Ok(message) => self.dispatch(client, Some(message)).await,
+                    Err(e) => self.deliver(vec![(
+                        client,
+                        ServerMessage::Error {
+                            message: format!("bad message: {e}"),
+                        },
+                    )]),
+                },
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+
+        self.dispatch(client, None).await;
+        self.clients
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&client);
+        writer.abort();
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let addr = std::env::var("KNOWBALL_ADDR").unwrap_or_else(|_| "127.0.0.1:9001".to_string());
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error: couldn't listen on {addr}: {e}");
+            std::process::exit(2);
+        }
+    };
+    println!("Know Ball rooms listening on ws://{addr}");
+
+    let server = Arc::new(Server {
+        rooms: Arc::new(Mutex::new(Rooms::new(TriviaEngine::new()))),
+        clients: Arc::new(Mutex::new(HashMap::new())),
+    });
+    let next_client = AtomicU64::new(1);
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let client = next_client.fetch_add(1, Ordering::Relaxed);
+                tokio::spawn(Arc::clone(&server).serve(stream, client));
+            }
+            Err(e) => eprintln!("Error accepting connection: {e}"),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Blitz runs on top of a [`GameSession`]
+//!
+//! One clock runs for the whole run, three minutes unless set otherwise, and
+//! random boards keep coming until it runs out. Each board gets whatever time
+//! is left, so a board can end the usual ways and the next one starts at
+//! once. The clock is the boards' own time added up, so pausing a board
+//! pauses the run. The blitz score is every point earned before the clock
+//! stops. Giving up a board ends the run early.
+use crate::error::Result;
+use crate::game::{TriviaGame, TriviaResult};
+use crate::ghost::clock;
+use crate::questions::QuestionSpec;
+use crate::rules::GameRules;
+use crate::run;
+use crate::session::GameSession;
+use crate::speech::plural;
+use std::time::Duration;
+
+/// How long a blitz lasts when no time is given
+pub const BLITZ_TIME: Duration = Duration::from_secs(180);
+
+/// One board of the run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlitzBoard {
+    pub code: String,
+    pub score: u32,
+    pub correct: usize,
+    pub total: usize,
+    /// Time spent on the board
+    pub elapsed: Duration,
+}
+
+/// A run of boards against one clock
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blitz {
+    limit: Duration,
+    boards: Vec<BlitzBoard>,
+    gave_up: bool,
+}
+
+impl Default for Blitz {
+    fn default() -> Self {
+        Self::new(BLITZ_TIME)
+    }
+}
+
+impl Blitz {
+    pub fn new(limit: Duration) -> Self {
+        Self {
+            limit,
+            boards: Vec::new(),
+            gave_up: false,
+        }
+    }
+
+    pub fn boards(&self) -> &[BlitzBoard] {
+        &self.boards
+    }
+
+    /// Time left on the run's clock
+    pub fn remaining(&self) -> Duration {
+        let elapsed: Duration = self.boards.iter().map(|board| board.elapsed).sum();
+        self.limit.saturating_sub(elapsed)
+    }
+
+    /// Over once the clock runs out or the player gives up a board
+    pub fn is_over(&self) -> bool {
+        self.gave_up || self.remaining().is_zero()
+    }
+
+    /// Every point earned in the run
+    pub fn score(&self) -> u32 {
+        self.boards.iter().map(|board| board.score).sum()
+    }
+
+    /// Loads a random board and plays it with the time the run has left. The
+    /// session's other rules still apply, and its time limit is put back
+    /// afterwards. An empty board doesn't count.
+    pub fn play_board<F>(&mut self, session: &mut GameSession, play: F) -> Result<TriviaResult>
+    where
+        F: FnOnce(&QuestionSpec, &mut TriviaGame),
+    {
+        let time_limit = Some(self.remaining());
+        let result = run::play_random_board(
+            session,
+            |rules| GameRules {
+                time_limit,
+                ..rules
+            },
+            play,
+        )?;
+        if result.total > 0 {
+            self.record(&result);
+        }
+        Ok(result)
+    }
+
+    /// Counts a finished board
+    pub fn record(&mut self, result: &TriviaResult) {
+        self.gave_up = run::gave_up(result);
+        self.boards.push(BlitzBoard {
+            code: run::code(result),
+            score: result.score,
+            correct: result.correct(),
+            total: result.total,
+            elapsed: result.elapsed,
+        });
+    }
+
+    /// The rules of the run, before the first board
+    pub fn intro_line(&self) -> String {
+        format!(
+            "Blitz: {} on one clock. Boards keep coming until it runs out, and every point counts.",
+            clock(self.limit)
+        )
+    }
+
+    /// Where the run stands between boards
+    pub fn status_line(&self) -> String {
+        format!(
+            "Blitz: {} after {}, {} left",
+            plural(self.score() as usize, "point"),
+            plural(self.boards.len(), "board"),
+            clock(self.remaining())
+        )
+    }
+
+    /// How the run went, once it's over
+    pub fn final_line(&self) -> String {
+        let ending = if self.gave_up {
+            "You stopped"
+        } else {
+            "Time's up"
+        };
+        format!(
+            "{ending} after {}. Blitz score: {}.",
+            plural(self.boards.len(), "board"),
+            plural(self.score() as usize, "point")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEnd, RowResult};
+
+    fn result(score: u32) -> TriviaResult {
+        TriviaResult {
+            score,
+            total: 10,
+            end: Some(GameEnd::Perfect),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_clock_waits_for_the_first_board() {
+        let run = Blitz::default();
+        assert_eq!(run.remaining(), BLITZ_TIME);
+        assert!(!run.is_over());
+        assert_eq!(
+            run.intro_line(),
+            "Blitz: 3:00 on one clock. Boards keep coming until it runs out, and every point counts."
+        );
+        assert!(Blitz::new(Duration::ZERO).is_over());
+    }
+
+    #[test]
+    fn test_score_adds_up_the_boards() {
+        let mut run = Blitz::default();
+        run.record(&result(400));
+        assert_eq!(
+            run.status_line(),
+            "Blitz: 400 points after 1 board, 3:00 left"
+        );
+        run.record(&TriviaResult {
+            elapsed: Duration::from_secs(75),
+            ..result(250)
+        });
+        assert_eq!(run.remaining(), Duration::from_secs(105));
+        assert_eq!(run.score(), 650);
+        assert_eq!(
+            run.final_line(),
+            "Time's up after 2 boards. Blitz score: 650 points."
+        );
+        run.record(&TriviaResult {
+            end: Some(GameEnd::GaveUp),
+            rows: vec![RowResult {
+                name: "Tom Brady".into(),
+                stat: "5000".into(),
+                points: 100,
+                guessed: false,
+                passed: false,
+                cells: Vec::new(),
+                found_at: None,
+            }],
+            ..result(0)
+        });
+        assert!(run.is_over());
+        assert!(run.final_line().starts_with("You stopped after 3 boards."));
+    }
+}
@@ -0,0 +1,559 @@
+//! Shared boards for chat bots (`bot` feature)
+//!
+//! `ChannelGames` runs one board per chat channel: anyone in the channel can
+//! guess, strikes are shared, and each member waits out a cooldown between
+//! guesses so one fast typist can't sweep the board. Points go to whoever
+//! named the player, and the channel keeps a running total per member. Admins
+//! can limit a channel's random boards to one category. It only turns
+//! messages into replies, so a chat transport like the Discord or
+//! IRC binary is a thin loop around [`ChannelGames::handle_message`].
+use crate::engine::TriviaEngine;
+use crate::game::{GameEnd, GuessOutcome, TriviaGame, ANSWER_COL, MAX_STRIKES};
+use crate::questions::{by_category, Category, QuestionParams};
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How the bot listens in a channel
+#[derive(Debug, Clone)]
+pub struct BotConfig {
+    /// Word that starts a bot command, e.g. "!kb start"
+    pub prefix: String,
+    /// Time each member waits between guesses
+    pub cooldown: Duration,
+    /// Time a channel waits after one board starts before the next can
+    pub board_cooldown: Duration,
+    /// Names allowed to run `stop`, `reset`, and `category <name>`, compared
+    /// case-insensitively
+    pub admins: Vec<String>,
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self {
+            prefix: "!kb".to_string(),
+            cooldown: Duration::from_secs(3),
+            board_cooldown: Duration::ZERO,
+            admins: Vec::new(),
+        }
+    }
+}
+
+impl BotConfig {
+    /// Overrides the settings with `KNOWBALL_PREFIX`, `KNOWBALL_COOLDOWN_SECS`,
+    /// `KNOWBALL_BOARD_COOLDOWN_SECS`, and `KNOWBALL_ADMINS` (comma-separated
+    /// names) where they are set
+    pub fn with_env(mut self) -> Result<Self, String> {
+        let secs = |var: &str| -> Result<Option<Duration>, String> {
+            match std::env::var(var) {
+                Ok(secs) => secs
+                    .parse::<u64>()
+                    .map(|s| Some(Duration::from_secs(s)))
+                    .map_err(|_| format!("invalid {var} '{secs}'")),
+                Err(_) => Ok(None),
+            }
+        };
+        if let Ok(prefix) = std::env::var("KNOWBALL_PREFIX") {
+            self.prefix = prefix;
+        }
+        if let Some(cooldown) = secs("KNOWBALL_COOLDOWN_SECS")? {
+            self.cooldown = cooldown;
+        }
+        if let Some(cooldown) = secs("KNOWBALL_BOARD_COOLDOWN_SECS")? {
+            self.board_cooldown = cooldown;
+        }
+        if let Ok(admins) = std::env::var("KNOWBALL_ADMINS") {
+            self.admins = admins
+                .split(',')
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        Ok(self)
+    }
+
+    fn is_admin(&self, name: &str) -> bool {
+        self.admins.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A board being played in one channel
+struct ChannelGame {
+    game: TriviaGame,
+    last_guess: HashMap<u64, Instant>,
+}
+
+/// A member's running total in one channel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberScore {
+    pub name: String,
+    pub score: u32,
+}
+
+/// Every channel's board, keyed by channel id
+pub struct ChannelGames {
+    engine: TriviaEngine,
+    config: BotConfig,
+    channels: HashMap<u64, ChannelGame>,
+    /// Running totals per channel, keyed by member id
+    scores: HashMap<u64, HashMap<u64, MemberScore>>,
+    last_board: HashMap<u64, Instant>,
+    /// The category each channel's random boards come from, if limited
+    categories: HashMap<u64, Category>,
+}
+
+impl ChannelGames {
+    pub fn new(engine: TriviaEngine, config: BotConfig) -> Self {
+        Self {
+            engine,
+            config,
+            channels: HashMap::new(),
+            scores: HashMap::new(),
+            last_board: HashMap::new(),
+            categories: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &BotConfig {
+        &self.config
+    }
+
+    /// Returns true while the channel has a board in play
+    pub fn is_playing(&self, channel: u64) -> bool {
+        self.channels.contains_key(&channel)
+    }
+
+    /// The channel's totals, highest first
+    pub fn scores(&self, channel: u64) -> Vec<MemberScore> {
+        let mut scores: Vec<MemberScore> = self
+            .scores
+            .get(&channel)
+            .map(|members| members.values().cloned().collect())
+            .unwrap_or_default();
+        scores.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+        scores
+    }
+
+    /// Handles one chat message from member `user`, shown as `name`, and
+    /// returns the bot's reply, if any.
+    ///
+    /// Messages starting with the prefix are commands (`start [code]`,
+    /// `board`, `reveal`, `scores`, `category`, `help`, and for admins `stop`,
+    /// `reset`, and `category <name | all>`); anything else in a channel with a board in play is a guess.
+    /// Other chatter gets no reply.
+    pub fn handle_message(
+        &mut self,
+        channel: u64,
+        user: u64,
+        name: &str,
+        text: &str,
+        now: Instant,
+    ) -> Option<String> {
+        let text = text.trim();
+        let mut words = text.split_whitespace();
+        if words
+            .next()
+            .is_some_and(|w| w.eq_ignore_ascii_case(&self.config.prefix))
+        {
+            let command = words.next().unwrap_or("help").to_ascii_lowercase();
+            let arg = words.next();
+            return Some(match command.as_str() {
+                "start" => self.start(channel, arg, now),
+                "board" => match self.channels.get(&channel) {
+                    Some(state) => render_board(&state.game),
+                    None => self.idle_message(),
+                },
+                "reveal" => match self.channels.remove(&channel) {
+                    Some(state) => render_final(&state.game),
+                    None => self.idle_message(),
+                },
+                "scores" => self.render_scores(channel),
+                "category" if arg.is_none() => self.category_message(channel),
+                "stop" | "reset" | "category" if !self.config.is_admin(name) => {
+                    "Only admins can do that.".to_string()
+                }
+                "stop" => match self.channels.remove(&channel) {
+                    Some(_) => "Board stopped.".to_string(),
+                    None => self.idle_message(),
+                },
+                "reset" => {
+                    self.scores.remove(&channel);
+                    "Scores reset.".to_string()
+                }
+                "category" => self.set_category(channel, arg.unwrap_or_default()),
+                _ => self.help(),
+            });
+        }
+
+        let cooldown = self.config.cooldown;
+        let state = self.channels.get_mut(&channel)?;
+        if let Some(last) = state.last_guess.get(&user) {
+            let waited = now.saturating_duration_since(*last);
+            if waited < cooldown {
+                let wait = (cooldown - waited).as_secs_f32().ceil();
+                return Some(format!("Slow down! Wait {wait}s before guessing again."));
+            }
+        }
+
+        let reply = match state.game.submit_guess(text) {
+            GuessOutcome::Correct {
+                name: answer,
+                points,
+                ..
+            } => {
+                let member = self
+                    .scores
+                    .entry(channel)
+                    .or_default()
+                    .entry(user)
+                    .or_insert_with(|| MemberScore {
+                        name: name.to_string(),
+                        score: 0,
+                    });
+                member.name = name.to_string();
+                member.score += points;
+                format!("✅ {answer} (+{points} points for {name})")
+            }
+            GuessOutcome::AlreadyGuessed => "Already on the board!".to_string(),
+            GuessOutcome::Ambiguous { rows } => {
+                format!("🤔 {} players match that — be more specific", rows.len())
+            }
+            GuessOutcome::Strike { strikes } => format!("❌ Strike {strikes}/{MAX_STRIKES}"),
+            GuessOutcome::Empty | GuessOutcome::GameOver | GuessOutcome::Paused => return None,
+        };
+        state.last_guess.insert(user, now);
+
+        if state.game.is_over() {
+            let state = self.channels.remove(&channel)?;
+            return Some(format!("{reply}\n{}", render_final(&state.game)));
+        }
+        Some(reply)
+    }
+
+    /// Loads a board for the channel, by code or at random
+    fn start(&mut self, channel: u64, code: Option<&str>, now: Instant) -> String {
+        if self.is_playing(channel) {
+            return format!(
+                "A board is already going here. Type `{} reveal` to end it.",
+                self.config.prefix
+            );
+        }
+        if let Some(last) = self.last_board.get(&channel) {
+            let waited = now.saturating_duration_since(*last);
+            if waited < self.config.board_cooldown {
+                let wait = (self.config.board_cooldown - waited).as_secs_f32().ceil();
+                return format!("Next board in {wait}s.");
+            }
+        }
+        let (question, fixed) = match code {
+            Some(code) => match self.engine.lookup(code) {
+                Some(parsed) => (
+                    parsed.question,
+                    QuestionParams::for_team(parsed.team.as_deref()),
+                ),
+                None => return format!("Unknown question code '{code}'."),
+            },
+            None => {
+                let question = match self.categories.get(&channel) {
+                    Some(&category) => {
+                        let questions = by_category(self.engine.registry(), category);
+                        questions.choose(self.engine.rng_mut()).cloned()
+                    }
+                    None => self.engine.random_question(),
+                };
+                match question {
+                    Some(question) => (question, QuestionParams::default()),
+                    None => return "No questions are registered.".to_string(),
+                }
+            }
+        };
+        match self.engine.load_question(question.as_ref(), &fixed) {
+            Ok((spec, board)) if !board.rows.is_empty() => {
+                let game = TriviaGame::new(spec.text.clone(), board).with_spec(spec);
+                let reply = render_board(&game);
+                self.last_board.insert(channel, now);
+                self.channels.insert(
+                    channel,
+                    ChannelGame {
+                        game,
+                        last_guess: HashMap::new(),
+                    },
+                );
+                reply
+            }
+            Ok(_) => "That question came back empty. Try another.".to_string(),
+            Err(e) => format!("Couldn't load a board: {e}"),
+        }
+    }
+
+    fn idle_message(&self) -> String {
+        format!(
+            "No board in play. Type `{} start` to begin.",
+            self.config.prefix
+        )
+    }
+
+    fn category_message(&self, channel: u64) -> String {
+        match self.categories.get(&channel) {
+            Some(category) => format!("Random boards here are {} questions.", category.as_str()),
+            None => "Random boards here can come from any category.".to_string(),
+        }
+    }
+
+    /// Limits the channel's random boards to `name`, or lifts the limit for "all"
+    fn set_category(&mut self, channel: u64, name: &str) -> String {
+        if name.eq_ignore_ascii_case("all") {
+            self.categories.remove(&channel);
+            return self.category_message(channel);
+        }
+        match name.parse::<Category>() {
+            Ok(category) => {
+                self.categories.insert(channel, category);
+                self.category_message(channel)
+            }
+            Err(_) => {
+                let names: Vec<&str> = Category::ALL.iter().map(Category::as_str).collect();
+                format!(
+                    "Unknown category '{name}'. Pick one of {}, or all.",
+                    names.join(", ")
+                )
+            }
+        }
+    }
+
+    fn render_scores(&self, channel: u64) -> String {
+        let scores = self.scores(channel);
+        if scores.is_empty() {
+            return "No scores yet.".to_string();
+        }
+        let scores: Vec<String> = scores
+            .iter()
+            .enumerate()
+            .map(|(i, s)| format!("{}. {} {}", i + 1, s.name, s.score))
+            .collect();
+        format!("Scores: {}", scores.join(", "))
+    }
+
+    fn help(&self) -> String {
+        let p = &self.config.prefix;
+        let mut help = format!(
+            "Know Ball commands:\n\
+             `{p} start [code]` - start a board (random if no code)\n\
+             `{p} board` - show the current board\n\
+             `{p} reveal` - give up and show the answers\n\
+             `{p} scores` - show everyone's points in this channel\n\
+             `{p} category` - show which category random boards come from\n\
+             While a board is up, type a player's name to guess."
+        );
+        if !self.config.admins.is_empty() {
+            help.push_str(&format!(
+                "\nAdmins: `{p} stop` ends the board without answers, `{p} reset` clears the scores, \
+                 `{p} category <name | all>` picks the category for random boards."
+            ));
+        }
+        help
+    }
+}
+
+/// The board with unguessed names hidden, as a chat code block
+pub fn render_board(game: &TriviaGame) -> String {
+    let mut out = format!("**{}**\n```\n", game.question());
+    for (i, row) in game.board().rows.iter().enumerate() {
+        let cols: Vec<&str> = row
+            .iter()
+            .enumerate()
+            .map(|(j, val)| {
+                if j == ANSWER_COL && !game.is_guessed(i) {
+                    "???"
+                } else {
+                    val.as_str()
+                }
+            })
+            .collect();
+        out.push_str(&format!("{:>2}. {}\n", i + 1, cols.join(" | ")));
+    }
+    out.push_str("```\n");
+    out.push_str(&format!(
+        "Correct: {}/{}  Strikes: {}/{MAX_STRIKES}  Score: {}",
+        game.correct(),
+        game.total(),
+        game.strikes(),
+        game.score()
+    ));
+    out
+}
+
+/// Every answer and how the round ended, as a chat code block
+pub fn render_final(game: &TriviaGame) -> String {
+    let heading = match game.end() {
+        Some(GameEnd::Perfect) => "Perfect board!",
+        Some(GameEnd::StruckOut) => "Three strikes, the board is over.",
+        Some(GameEnd::TimeUp) => "Time's up!",
+        Some(GameEnd::Skipped) => "Board skipped.",
+        _ => "Board revealed.",
+    };
+    let mut out = format!("{heading}\n```\n");
+    for (i, row) in game.board().rows.iter().enumerate() {
+        let status = if game.is_guessed(i) { "✓" } else { "✗" };
+        out.push_str(&format!(
+            "{:>2} {status} {} ({}pts)\n",
+            i + 1,
+            row.join(" | "),
+            game.point_values()[i]
+        ));
+    }
+    out.push_str("```\n");
+    out.push_str(&format!("Final score: {}/1000", game.score()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_runner::Board;
+    use crate::store::MockStore;
+
+    fn games() -> ChannelGames {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Mason Rudolph".into(), "1000".into()],
+            ],
+        };
+        let engine = TriviaEngine::with_seed(1).with_store(MockStore::new(board));
+        ChannelGames::new(engine, BotConfig::default())
+    }
+
+    #[test]
+    fn test_channel_plays_shared_board() {
+        let mut bot = games();
+        let now = Instant::now();
+
+        assert_eq!(bot.handle_message(1, 10, "Ann", "Brady", now), None);
+        let board = bot
+            .handle_message(1, 10, "Ann", "!kb start passyds_PIT", now)
+            .unwrap();
+        assert!(board.contains("???"));
+        assert!(!bot.is_playing(2));
+
+        let reply = bot.handle_message(1, 10, "Ann", "brady", now).unwrap();
+        assert!(reply.starts_with("✅ Tom Brady"));
+        let reply = bot.handle_message(1, 11, "Bo", "rudolph", now).unwrap();
+        assert!(reply.contains("Perfect board!"));
+        assert!(!bot.is_playing(1));
+    }
+
+    #[test]
+    fn test_cooldown_is_per_member() {
+        let mut bot = games();
+        let now = Instant::now();
+        bot.handle_message(1, 10, "Ann", "!kb start", now);
+
+        let reply = bot.handle_message(1, 10, "Ann", "Manning", now).unwrap();
+        assert_eq!(reply, "❌ Strike 1/3");
+        let reply = bot.handle_message(1, 10, "Ann", "Brady", now + Duration::from_secs(1));
+        assert!(reply.unwrap().starts_with("Slow down"));
+        let reply = bot.handle_message(1, 11, "Bo", "Brady", now + Duration::from_secs(1));
+        assert!(reply.unwrap().starts_with("✅"));
+        let reply = bot.handle_message(1, 10, "Ann", "Favre", now + Duration::from_secs(3));
+        assert_eq!(reply.unwrap(), "❌ Strike 2/3");
+    }
+
+    #[test]
+    fn test_commands_without_board() {
+        let mut bot = games();
+        let now = Instant::now();
+        assert!(bot
+            .handle_message(1, 10, "Ann", "!kb reveal", now)
+            .unwrap()
+            .starts_with("No board in play"));
+        assert!(bot
+            .handle_message(1, 10, "Ann", "!KB", now)
+            .unwrap()
+            .contains("`!kb start [code]`"));
+        assert!(bot
+            .handle_message(1, 10, "Ann", "!kb start nope", now)
+            .unwrap()
+            .starts_with("Unknown question code"));
+    }
+
+    #[test]
+    fn test_scores_boards_and_admins() {
+        let config = BotConfig {
+            board_cooldown: Duration::from_secs(30),
+            admins: vec!["ann".into()],
+            ..BotConfig::default()
+        };
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![vec!["Tom Brady".into(), "5000".into()]],
+        };
+        let engine = TriviaEngine::with_seed(1).with_store(MockStore::new(board));
+        let mut bot = ChannelGames::new(engine, config);
+        let now = Instant::now();
+
+        bot.handle_message(1, 11, "Bo", "!kb start", now);
+        let reply = bot.handle_message(1, 11, "Bo", "brady", now).unwrap();
+        assert!(reply.contains("(+1000 points for Bo)"));
+        assert_eq!(
+            bot.scores(1),
+            [MemberScore {
+                name: "Bo".into(),
+                score: 1000
+            }]
+        );
+        assert_eq!(
+            bot.handle_message(1, 11, "Bo", "!kb scores", now).unwrap(),
+            "Scores: 1. Bo 1000"
+        );
+
+        let later = now + Duration::from_secs(10);
+        let reply = bot.handle_message(1, 11, "Bo", "!kb start", later).unwrap();
+        assert_eq!(reply, "Next board in 20s.");
+        assert!(bot
+            .handle_message(2, 11, "Bo", "!kb start", later)
+            .is_some());
+        assert!(bot.is_playing(2));
+
+        let reply = bot.handle_message(2, 11, "Bo", "!kb stop", later).unwrap();
+        assert_eq!(reply, "Only admins can do that.");
+        let reply = bot.handle_message(2, 10, "ANN", "!kb stop", later).unwrap();
+        assert_eq!(reply, "Board stopped.");
+        assert!(!bot.is_playing(2));
+        bot.handle_message(1, 10, "Ann", "!kb reset", later);
+        assert!(bot.scores(1).is_empty());
+    }
+
+    #[test]
+    fn test_admins_pick_the_category() {
+        let config = BotConfig {
+            admins: vec!["ann".into()],
+            ..BotConfig::default()
+        };
+        let mut bot = games();
+        bot.config = config;
+        let now = Instant::now();
+
+        let reply = bot.handle_message(1, 11, "Bo", "!kb category rushing", now);
+        assert_eq!(reply.unwrap(), "Only admins can do that.");
+        let reply = bot.handle_message(1, 10, "Ann", "!kb category defense", now);
+        assert!(reply.unwrap().starts_with("Unknown category 'defense'"));
+        let reply = bot.handle_message(1, 10, "Ann", "!kb category Rushing", now);
+        assert_eq!(reply.unwrap(), "Random boards here are rushing questions.");
+        let reply = bot.handle_message(1, 11, "Bo", "!kb category", now);
+        assert_eq!(reply.unwrap(), "Random boards here are rushing questions.");
+
+        for _ in 0..5 {
+            bot.handle_message(1, 11, "Bo", "!kb start", now);
+            let code = &bot.channels[&1].game.spec().unwrap().code;
+            let meta = bot.engine.registry()[code].meta().clone();
+            assert!(meta.categories.contains(&Category::Rushing), "{code}");
+            bot.handle_message(1, 11, "Bo", "!kb reveal", now);
+        }
+        assert!(!bot.categories.contains_key(&2));
+        bot.handle_message(1, 10, "Ann", "!kb category all", now);
+        assert!(!bot.categories.contains_key(&1));
+    }
+}
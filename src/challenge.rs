@@ -0,0 +1,250 @@
+//! Challenge files (`.kbc`): play a friend's board and compare, offline
+//!
+//! A challenge is one finished board: the question's spec, so the friend's
+//! copy loads the same board, the score, and which rows were found. The
+//! answers themselves are only kept as a hash, the same one quiz packs use,
+//! so opening the file doesn't give the board away. After the friend plays it,
+//! [`Challenge::head_to_head`] lines the two results up row by row.
+//!
+//! ```json
+//! { "version": 1, "from": "Ann", "spec": { ... }, "answers": "9c1e...", "score": 450, "found": [true, false, ...] }
+//! ```
+use crate::game::TriviaResult;
+use crate::questions::pack::{check_select, PackError};
+use crate::questions::quiz_pack::answer_hash;
+use crate::questions::QuestionSpec;
+use crate::sql_runner::Board;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File extension for challenges
+pub const CHALLENGE_EXTENSION: &str = "kbc";
+
+/// Format version written to new challenges
+pub const CHALLENGE_VERSION: u32 = 1;
+
+/// A finished board for a friend to beat
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Challenge {
+    pub version: u32,
+    /// Who set the challenge
+    pub from: String,
+    pub spec: QuestionSpec,
+    /// Hash of the board's answers, to tell whether the friend's database
+    /// loads the same board
+    pub answers: String,
+    pub score: u32,
+    /// Whether each row was found, in board order
+    pub found: Vec<bool>,
+    pub strikes: usize,
+}
+
+/// Two results on the same board, side by side
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeadToHead {
+    pub opponent: String,
+    pub their_score: u32,
+    pub your_score: u32,
+    pub rows: Vec<RowComparison>,
+}
+
+/// One board row and who found it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowComparison {
+    pub name: String,
+    pub points: u32,
+    pub they_found: bool,
+    pub you_found: bool,
+}
+
+impl Challenge {
+    /// A challenge from `from`'s finished board, or None if the board wasn't
+    /// generated from a spec
+    pub fn from_result(from: impl Into<String>, result: &TriviaResult) -> Option<Self> {
+        let board = Board {
+            column_names: result.column_names.clone(),
+            rows: result
+                .rows
+                .iter()
+                .map(|row| vec![row.name.clone()])
+                .collect(),
+        };
+        Some(Self {
+            version: CHALLENGE_VERSION,
+            from: from.into(),
+            spec: result.spec.clone()?,
+            answers: answer_hash(&board),
+            score: result.score,
+            found: result.rows.iter().map(|row| row.guessed).collect(),
+            strikes: result.strikes,
+        })
+    }
+
+    /// Whether `board` has the answers the challenge was played on
+    pub fn matches_board(&self, board: &Board) -> bool {
+        self.answers == answer_hash(board)
+    }
+
+    /// Lines up the friend's result against the challenger's, row by row
+    pub fn head_to_head(&self, yours: &TriviaResult) -> HeadToHead {
+        let rows = yours
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| RowComparison {
+                name: row.name.clone(),
+                points: row.points,
+                they_found: self.found.get(i).copied().unwrap_or(false),
+                you_found: row.guessed,
+            })
+            .collect();
+        HeadToHead {
+            opponent: self.from.clone(),
+            their_score: self.score,
+            your_score: yours.score,
+            rows,
+        }
+    }
+
+    /// Parses and checks a challenge. Only a read-only query is accepted,
+    /// since the SQL comes from whoever sent the file.
+    pub fn from_json(contents: &str) -> Result<Self, PackError> {
+        let challenge: Challenge =
+            serde_json::from_str(contents).map_err(|e| PackError::Parse(e.to_string()))?;
+        if challenge.version > CHALLENGE_VERSION {
+            return Err(PackError::Parse(format!(
+                "challenge version {} is newer than this build supports ({CHALLENGE_VERSION})",
+                challenge.version
+            )));
+        }
+        check_select(&challenge.spec.query.sql).map_err(|reason| PackError::Invalid {
+            code: challenge.spec.code.clone(),
+            reason: reason.into(),
+        })?;
+        Ok(challenge)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("challenges serialize to JSON")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, PackError> {
+        let contents = std::fs::read_to_string(path).map_err(PackError::Io)?;
+        Self::from_json(&contents)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PackError> {
+        std::fs::write(path, self.to_json() + "\n").map_err(PackError::Io)
+    }
+}
+
+impl HeadToHead {
+    /// "Ann wins by 70.", "You win by 20.", or "It's a tie at 300."
+    pub fn verdict(&self) -> String {
+        let (them, you) = (self.their_score, self.your_score);
+        match them.cmp(&you) {
+            std::cmp::Ordering::Greater => format!("{} wins by {}.", self.opponent, them - you),
+            std::cmp::Ordering::Less => format!("You win by {}.", you - them),
+            std::cmp::Ordering::Equal => format!("It's a tie at {you}."),
+        }
+    }
+
+    /// A table of who found each row, the scores, and the verdict
+    pub fn to_text(&self) -> String {
+        let width = self.opponent.chars().count().max(3);
+        let mut out = format!("--- HEAD TO HEAD ---\n #  {:<width$}  You\n", self.opponent);
+        let mark = |found: bool| if found { "✓" } else { "✗" };
+        for (i, row) in self.rows.iter().enumerate() {
+            out.push_str(&format!(
+                "{:>2}  {:<width$}  {:<3}  {} ({}pts)\n",
+                i + 1,
+                mark(row.they_found),
+                mark(row.you_found),
+                row.name,
+                row.points
+            ));
+        }
+        out.push_str(&format!(
+            "{}: {}  You: {}\n{}\n",
+            self.opponent,
+            self.their_score,
+            self.your_score,
+            self.verdict()
+        ));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::TriviaGame;
+    use crate::questions::QuestionParams;
+
+    fn game() -> TriviaGame {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Drew Brees".into(), "4000".into()],
+            ],
+        };
+        let spec = QuestionSpec {
+            code: "passyds_TEAM".into(),
+            params: QuestionParams::default(),
+            text: "Most yards".into(),
+            query: "SELECT name, yards FROM passers".into(),
+        };
+        TriviaGame::new("Most yards", board).with_spec(spec)
+    }
+
+    #[test]
+    fn test_round_trip_hides_answers() {
+        let mut theirs = game();
+        theirs.submit_guess("Brees");
+        theirs.reveal();
+        let challenge = Challenge::from_result("Ann", &theirs.result()).unwrap();
+        assert_eq!(challenge.found, [false, true]);
+        assert!(challenge.matches_board(game().board()));
+
+        let json = challenge.to_json();
+        assert!(!json.contains("Brees"));
+        assert_eq!(Challenge::from_json(&json).unwrap(), challenge);
+
+        let mut bad = challenge.clone();
+        bad.spec.query = "DELETE FROM players".into();
+        assert!(Challenge::from_json(&bad.to_json()).is_err());
+        assert!(Challenge::from_result(
+            "Ann",
+            &TriviaGame::new(
+                "q",
+                Board {
+                    column_names: vec![],
+                    rows: vec![],
+                }
+            )
+            .result()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_head_to_head() {
+        let mut theirs = game();
+        theirs.submit_guess("Brees");
+        theirs.reveal();
+        let challenge = Challenge::from_result("Ann", &theirs.result()).unwrap();
+
+        let mut yours = game();
+        yours.submit_guess("Brady");
+        yours.submit_guess("Brees");
+        let comparison = challenge.head_to_head(&yours.result());
+        assert_eq!(
+            comparison.verdict(),
+            format!("You win by {}.", yours.score() - challenge.score)
+        );
+        let text = comparison.to_text();
+        assert!(text.contains(" 1  ✗    ✓    Tom Brady ("));
+        assert!(text.contains(" 2  ✓    ✓    Drew Brees ("));
+    }
+}
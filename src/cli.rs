@@ -0,0 +1,985 @@
+//! Terminal frontend that drives a `TriviaGame` from stdin
+//!
+//! Play is either human-readable text, one JSON object per line with `--json`
+//! so other tools can wrap the binary, or plain sentences with `--speech` for
+//! text-to-speech.
+#[cfg(feature = "packs")]
+use crate::challenge::HeadToHead;
+use crate::game::{
+    GameEnd, GuessOutcome, TriviaGame, TriviaResult, ANSWER_COL, MAX_STRIKES, STREAK_MULTIPLIER,
+};
+use crate::ghost::clock;
+use crate::layout::{Layout, SEPARATOR};
+#[cfg(feature = "leaderboard")]
+use crate::leaderboard::Standing;
+use crate::party::{Party, PartyOutcome};
+use crate::questions::{QuestionMeta, QuestionSpec};
+use crate::session::SessionSummary;
+use crate::speech;
+use crate::sql_runner::Board;
+use crate::theme::{self, Style, Theme};
+#[cfg(feature = "packs")]
+use crate::weekly::{IsoWeek, WeeklyEntry};
+use serde::Serialize;
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use unicode_width::UnicodeWidthStr;
+
+/// One line of `--json` output, tagged with a `type` field
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonMessage<'a> {
+    /// Printed once at startup
+    Ready {
+        seed: u64,
+    },
+    /// Every question code, sorted, from the `list` command
+    Codes {
+        questions: Vec<&'a QuestionMeta>,
+    },
+    /// A board is waiting for guesses; unguessed names are blank
+    Question {
+        spec: Option<&'a QuestionSpec>,
+        board: Board,
+    },
+    /// One guess and the round's totals after it
+    Guess {
+        guess: &'a str,
+        outcome: GuessOutcome,
+        correct: usize,
+        strikes: usize,
+        score: u32,
+    },
+    /// A finished round
+    Result {
+        result: &'a TriviaResult,
+    },
+    /// Session totals, from `score` and `quit`
+    Summary {
+        summary: SessionSummary,
+    },
+    /// A printable quiz from `export-quiz` without `--out`
+    Quiz {
+        document: String,
+    },
+    /// A playable quiz page from `export-html` without `--out`
+    Page {
+        html: String,
+    },
+    /// A flashcard deck from `export-anki` without `--out`
+    Deck {
+        deck: String,
+    },
+    /// A Markdown scoreboard from `report` without a file
+    Report {
+        markdown: String,
+    },
+    /// A played challenge next to the challenger's result
+    #[cfg(feature = "packs")]
+    HeadToHead {
+        comparison: HeadToHead,
+    },
+    /// A week's local standings, from `weekly board` and `weekly add`
+    #[cfg(feature = "packs")]
+    Weekly {
+        week: IsoWeek,
+        standings: Vec<WeeklyEntry>,
+    },
+    /// The day's global top scores, from `leaderboard`
+    #[cfg(feature = "leaderboard")]
+    Leaderboard {
+        day: u64,
+        standings: Vec<Standing>,
+    },
+    /// A timed round's clock stopped, with `remaining` left on it
+    Paused {
+        remaining: Duration,
+    },
+    /// A paused round's clock started again
+    Resumed {
+        remaining: Duration,
+    },
+    /// Something the caller should know that isn't an error, e.g. a renamed code
+    Notice {
+        message: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Prints one `--json` message on its own line
+pub fn emit_json(message: &JsonMessage) {
+    let line = serde_json::to_string(message).expect("JSON messages always serialize");
+    println!("{line}");
+}
+
+/// Plays a trivia game interactively at the terminal, where users guess
+/// hidden player names.
+///
+/// Players have 3 strikes unless the game's rules say otherwise. Scoring is out of 1000 points, with harder answers
+/// (lower stats) worth more points. The first column should be the player name,
+/// and the last column should be the numeric stat for scoring. When the game
+/// races a ghost, its progress is shown under the board after every guess.
+pub fn play_game(game: &mut TriviaGame) -> TriviaResult {
+    if game.total() == 0 {
+        println!("(No rows returned for this question.)");
+        return game.result();
+    }
+
+    println!("--- TRIVIA ---");
+    println!("{}", game.question());
+    let rules = *game.rules();
+    println!("Guess the hidden names! You have {}.", rules.strikes_text());
+    if rules.allow_reveal {
+        println!(
+            "(Type a player name, e.g. 'Rudolph' or 'Mason Rudolph'. Type 'reveal' to give up.)"
+        );
+        println!("(Stuck on one? 'pass 3' shows row 3 for no points and no strike, and 'skip' banks your points and moves on.)");
+    } else {
+        println!("(Type a player name, e.g. 'Rudolph' or 'Mason Rudolph'. No reveals, skips, or passes this time.)");
+    }
+
+    if rules.allow_hints {
+        println!("(Need a nudge? 'hint 3' shows the initials of row 3.)");
+    }
+    if let Some(limit) = game.time_limit() {
+        println!(
+            "The clock is running: {} for the board. ('pause' stops it.)",
+            clock(limit)
+        );
+    }
+    println!();
+
+    let input = LineInput::new(game.time_limit().is_some());
+    let theme = theme::current();
+    let mut renderer = BoardRenderer::new(game);
+
+    while !game.is_over() {
+        if game.is_paused() {
+            print!("Paused. Type 'resume' to continue: ");
+            io::stdout().flush().ok();
+            match input.next(None) {
+                Line::Typed(line) if line.trim().eq_ignore_ascii_case("resume") => {
+                    game.resume();
+                }
+                Line::Typed(_) | Line::Failed => {}
+                Line::Closed | Line::TimedOut => {
+                    game.reveal();
+                    break;
+                }
+            }
+            continue;
+        }
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(renderer.render(game).as_bytes()).ok();
+        if let Some(ghost) = game.ghost() {
+            writeln!(stdout, "{}", ghost.race_line(game.elapsed(), game.score())).ok();
+        }
+        drop(stdout);
+
+        print!("Enter guess: ");
+        io::stdout().flush().ok();
+
+        let guess = match input.next(game.remaining()) {
+            Line::Typed(guess) => guess,
+            Line::Closed => {
+                // Input closed; treat it like giving up rather than spinning
+                game.reveal();
+                break;
+            }
+            Line::Failed => {
+                println!("Error reading input, try again.");
+                continue;
+            }
+            Line::TimedOut => {
+                println!();
+                input.finish();
+                break;
+            }
+        };
+        if game.check_clock() {
+            break;
+        }
+        let guess = guess.trim();
+
+        let ends = ["reveal", "skip"]
+            .iter()
+            .any(|word| guess.eq_ignore_ascii_case(word));
+        if !rules.allow_reveal && (ends || parse_pass(guess).is_some()) {
+            println!("No reveals, skips, or passes under these rules. Keep guessing!\n");
+            continue;
+        }
+        if guess.eq_ignore_ascii_case("reveal") {
+            game.reveal();
+            break;
+        }
+        if guess.eq_ignore_ascii_case("skip") {
+            game.skip();
+            break;
+        }
+        if guess.eq_ignore_ascii_case("pause") {
+            match game.remaining().filter(|_| game.pause()) {
+                Some(left) => println!(
+                    "Clock stopped with {} left, and the board is hidden until you resume.\n",
+                    clock(Duration::from_secs(left.as_secs_f64().ceil() as u64))
+                ),
+                None => println!("There's no clock to pause on this board.\n"),
+            }
+            continue;
+        }
+        if guess.eq_ignore_ascii_case("resume") {
+            println!("The clock isn't paused.\n");
+            continue;
+        }
+        if let Some(row) = parse_pass(guess) {
+            match row.checked_sub(1).and_then(|row| game.pass(row)) {
+                Some(name) => println!("Row {row} was {name}. No points, no strike.\n"),
+                None => println!("There's no hidden row {row} to pass on.\n"),
+            }
+            continue;
+        }
+        if let Some(row) = parse_hint(guess) {
+            if !rules.allow_hints {
+                println!("No hints under these rules. Keep guessing!\n");
+                continue;
+            }
+            match row.checked_sub(1).and_then(|row| game.hint(row)) {
+                Some(initials) => println!("Row {row}'s initials are {initials}\n"),
+                None => println!("There's no hidden row {row} to give a hint for.\n"),
+            }
+            continue;
+        }
+
+        // A number answers the "which one?" after an ambiguous guess
+        let chosen = match guess.parse::<usize>() {
+            Ok(n) if n > 0 && !game.pending().is_empty() => game.choose(n - 1),
+            _ => None,
+        };
+        let bonus = game.streak_bonus();
+        match chosen.unwrap_or_else(|| game.submit_guess(guess)) {
+            GuessOutcome::Correct { name, points, .. } => {
+                print!(
+                    "{} {} (+{} points",
+                    theme.found.paint("Correct!"),
+                    name,
+                    points
+                );
+                match game.streak_bonus() - bonus {
+                    0 => println!(")"),
+                    extra => println!(", +{extra} for a {}-name streak)", game.streak()),
+                }
+            }
+            GuessOutcome::AlreadyGuessed => println!("You already got that one!"),
+            GuessOutcome::Ambiguous { rows } => print!("{}", which_one(game, guess, &rows)),
+            GuessOutcome::Strike { strikes } => {
+                println!("{}", theme.missed.paint(&format!("Strike {strikes}!")))
+            }
+            GuessOutcome::Empty | GuessOutcome::GameOver | GuessOutcome::Paused => continue,
+        }
+        println!();
+    }
+
+    game.check_clock();
+    print_final(game);
+    let result = game.result();
+    if let Some(ghost) = game.ghost() {
+        println!("{}\n", ghost.final_line(&result));
+    }
+    result
+}
+
+/// One read from [`LineInput`]
+enum Line {
+    Typed(String),
+    /// Stdin is closed
+    Closed,
+    Failed,
+    /// The wait ran out before a whole line came in
+    TimedOut,
+}
+
+/// Where [`play_game`] reads guesses. A timed board reads stdin on its own
+/// thread, one line per go-ahead, so waiting can stop when the clock runs
+/// out and no line meant for the REPL is read once the board is over.
+enum LineInput {
+    Stdin,
+    Thread {
+        more: mpsc::Sender<()>,
+        lines: mpsc::Receiver<io::Result<String>>,
+        asked: Cell<bool>,
+    },
+}
+
+impl LineInput {
+    fn new(timed: bool) -> Self {
+        if !timed {
+            return LineInput::Stdin;
+        }
+        let (more, go) = mpsc::channel();
+        let (send, lines) = mpsc::channel();
+        thread::spawn(move || {
+            while go.recv().is_ok() {
+                let mut line = String::new();
+                let read = io::stdin().read_line(&mut line).map(|_| line);
+                if send.send(read).is_err() {
+                    break;
+                }
+            }
+        });
+        LineInput::Thread {
+            more,
+            lines,
+            asked: Cell::new(false),
+        }
+    }
+
+    /// The next line, waiting at most `wait` when it is given
+    fn next(&self, wait: Option<Duration>) -> Line {
+        let read = match self {
+            LineInput::Stdin => {
+                let mut line = String::new();
+                io::stdin().read_line(&mut line).map(|_| line)
+            }
+            LineInput::Thread { more, lines, asked } => {
+                if !asked.replace(true) {
+                    more.send(()).ok();
+                }
+                let line = match wait {
+                    Some(wait) => lines
+                        .recv_timeout(wait)
+                        .map_err(|e| e == mpsc::RecvTimeoutError::Timeout),
+                    None => lines.recv().map_err(|_| false),
+                };
+                match line {
+                    Ok(read) => {
+                        asked.set(false);
+                        read
+                    }
+                    Err(true) => return Line::TimedOut,
+                    Err(false) => return Line::Closed,
+                }
+            }
+        };
+        match read {
+            Ok(line) if line.is_empty() => Line::Closed,
+            Ok(line) => Line::Typed(line),
+            Err(_) => Line::Failed,
+        }
+    }
+
+    /// Waits out a line the player started before the clock ran out, so it
+    /// isn't taken as the REPL's next command
+    fn finish(&self) {
+        if let LineInput::Thread { asked, .. } = self {
+            if asked.get() {
+                println!("Time's up! Press Enter to see the answers.");
+                self.next(None);
+            }
+        }
+    }
+}
+
+/// The row number in a `pass N` line
+fn parse_pass(line: &str) -> Option<usize> {
+    parse_row_command(line, "pass")
+}
+
+/// The row number in a `hint N` line
+fn parse_hint(line: &str) -> Option<usize> {
+    parse_row_command(line, "hint")
+}
+
+fn parse_row_command(line: &str, command: &str) -> Option<usize> {
+    let (word, row) = line.split_once(char::is_whitespace)?;
+    if !word.eq_ignore_ascii_case(command) {
+        return None;
+    }
+    row.trim().parse().ok()
+}
+
+/// Asks which of the board's `rows` an ambiguous guess meant, showing each
+/// one's other columns (team, seasons, stat) since its name is still hidden
+fn which_one(game: &TriviaGame, guess: &str, rows: &[usize]) -> String {
+    let board = game.board();
+    let mut out = format!("{} players match '{guess}'. Which one?\n", rows.len());
+    for (n, &row) in rows.iter().enumerate() {
+        let details: Vec<String> = board.column_names[1..]
+            .iter()
+            .zip(&board.rows[row][1..])
+            .map(|(column, value)| format!("{column} {value}"))
+            .collect();
+        out.push_str(&format!(
+            "  {}) #{}: {}\n",
+            n + 1,
+            row + 1,
+            details.join(", ")
+        ));
+    }
+    out.push_str("Type its number, or keep guessing.\n");
+    out
+}
+
+/// Plays a trivia game from stdin in `--json` mode: the board goes out as a
+/// `question` message, each input line is a guess answered by a `guess`
+/// message, and the round ends with a `result` message.
+///
+/// As in [`play_game`], `reveal` (or closing stdin) gives up and `skip` moves
+/// on. `pause` stops a timed round's clock, answered by a `paused` message,
+/// and guesses get a `paused` outcome until `resume`.
+pub fn play_game_json(game: &mut TriviaGame) -> TriviaResult {
+    emit_json(&JsonMessage::Question {
+        spec: game.spec(),
+        board: game.visible_board(),
+    });
+
+    let mut lines = io::stdin().lock().lines();
+    while !game.is_over() {
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(_)) => continue,
+            None => {
+                game.reveal();
+                break;
+            }
+        };
+        let guess = line.trim();
+        let skip = guess.eq_ignore_ascii_case("skip");
+        if skip || guess.eq_ignore_ascii_case("reveal") {
+            if !game.rules().allow_reveal {
+                emit_json(&JsonMessage::Error {
+                    message: "no reveals or skips under these rules".into(),
+                });
+                continue;
+            }
+            if skip {
+                game.skip();
+            } else {
+                game.reveal();
+            }
+            break;
+        }
+        if guess.eq_ignore_ascii_case("pause") || guess.eq_ignore_ascii_case("resume") {
+            let pausing = guess.eq_ignore_ascii_case("pause");
+            let changed = if pausing { game.pause() } else { game.resume() };
+            let remaining = game.remaining().unwrap_or_default();
+            emit_json(&match (changed, pausing) {
+                (true, true) => JsonMessage::Paused { remaining },
+                (true, false) => JsonMessage::Resumed { remaining },
+                (false, true) => JsonMessage::Error {
+                    message: "no clock to pause on this board".into(),
+                },
+                (false, false) => JsonMessage::Error {
+                    message: "the clock isn't paused".into(),
+                },
+            });
+            if game.check_clock() {
+                break;
+            }
+            continue;
+        }
+        let outcome = game.submit_guess(guess);
+        if outcome == GuessOutcome::Empty {
+            continue;
+        }
+        emit_json(&JsonMessage::Guess {
+            guess,
+            outcome,
+            correct: game.correct(),
+            strikes: game.strikes(),
+            score: game.score(),
+        });
+    }
+
+    let result = game.result();
+    emit_json(&JsonMessage::Result { result: &result });
+    result
+}
+
+/// Plays a trivia game from stdin in `--speech` mode, where the board and
+/// every reply are plain sentences with no tables or symbols.
+///
+/// `repeat` reads the board again, and `pause` and `resume` stop and start a
+/// timed round's clock; as in [`play_game`], `reveal` (or closing stdin) gives
+/// up and `skip` moves on.
+pub fn play_game_speech(game: &mut TriviaGame) -> TriviaResult {
+    let say = |sentences: Vec<String>| println!("{}", sentences.join(" "));
+    say(speech::board_sentences(game));
+    if game.total() == 0 {
+        return game.result();
+    }
+
+    let mut lines = io::stdin().lock().lines();
+    while !game.is_over() {
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(_)) => continue,
+            None => {
+                game.reveal();
+                break;
+            }
+        };
+        let guess = line.trim();
+        let skip = guess.eq_ignore_ascii_case("skip");
+        if skip || guess.eq_ignore_ascii_case("reveal") {
+            if !game.rules().allow_reveal {
+                println!("No reveals or skips under these rules. Keep guessing.");
+                continue;
+            }
+            if skip {
+                game.skip();
+            } else {
+                game.reveal();
+            }
+            break;
+        }
+        if guess.eq_ignore_ascii_case("repeat") {
+            if game.is_paused() {
+                println!("{}", speech::clock_sentence(game));
+            } else {
+                say(speech::board_sentences(game));
+            }
+            continue;
+        }
+        if guess.eq_ignore_ascii_case("pause") {
+            if game.pause() {
+                println!("{}", speech::clock_sentence(game));
+            } else {
+                println!("There's no clock to pause on this board.");
+            }
+            continue;
+        }
+        if guess.eq_ignore_ascii_case("resume") {
+            if game.resume() {
+                println!("{}", speech::clock_sentence(game));
+            } else {
+                println!("The clock isn't paused.");
+            }
+            continue;
+        }
+        let outcome = game.submit_guess(guess);
+        if let Some(reply) = speech::outcome_sentence(game, &outcome) {
+            println!("{reply}");
+        }
+    }
+
+    say(speech::result_sentences(game));
+    game.result()
+}
+
+/// Plays one board of a buzzer party at the terminal: each line is a player's
+/// number or name and a guess, e.g. `1:mahomes`, and the standings bar is
+/// redrawn with the board after every guess. `reveal` (or closing stdin)
+/// gives up.
+pub fn play_party(game: &mut TriviaGame, party: &mut Party) -> TriviaResult {
+    if game.total() == 0 {
+        println!("(No rows returned for this question.)");
+        return game.result();
+    }
+    party.new_board();
+
+    println!("--- PARTY ---");
+    println!("{}", game.question());
+    println!("Type your number and a name, e.g. '1:mahomes'. The first to name a player gets the points.");
+    println!("Three misses and you sit out the rest of the board. Type 'reveal' to give up.");
+
+    let stdin = io::stdin();
+    let mut renderer = BoardRenderer::new(game).without_status();
+    while !game.is_over() {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(renderer.render(game).as_bytes()).ok();
+        writeln!(stdout, "{}", party.standings_bar()).ok();
+        write!(stdout, "Guess: ").ok();
+        stdout.flush().ok();
+        drop(stdout);
+
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0) => {
+                game.reveal();
+                break;
+            }
+            Ok(_) => {}
+            Err(_) => {
+                println!("Error reading input, try again.");
+                continue;
+            }
+        }
+        let line = line.trim();
+        if line.eq_ignore_ascii_case("reveal") {
+            game.reveal();
+            break;
+        }
+        let (player, guess) = match party.parse_line(line) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                println!("{e}");
+                continue;
+            }
+        };
+        let outcome = party.guess(game, player, guess);
+        let name = |player: usize| party.players()[player].name.as_str();
+        match outcome {
+            PartyOutcome::Correct {
+                player,
+                name: answer,
+                points,
+                ..
+            } => println!("{} got it! {answer} (+{points} points)", name(player)),
+            PartyOutcome::TooLate { player } => {
+                println!("Too late, {}, that one's taken!", name(player))
+            }
+            PartyOutcome::Ambiguous { player, matches } => {
+                println!(
+                    "{matches} players match that, {}. Be more specific!",
+                    name(player)
+                )
+            }
+            PartyOutcome::Strike { player, strikes } if strikes >= MAX_STRIKES => {
+                println!(
+                    "Strike {strikes}! {} sits out the rest of this board.",
+                    name(player)
+                )
+            }
+            PartyOutcome::Strike { player, strikes } => {
+                println!("Strike {strikes} for {}!", name(player))
+            }
+            PartyOutcome::SittingOut { player } => {
+                println!("{} is out for this board.", name(player))
+            }
+            PartyOutcome::Ignored => continue,
+        }
+    }
+
+    print_final(game);
+    println!("{}\n", party.standings_bar());
+    game.result()
+}
+
+/// Width of the row number in front of each row of the current board
+const ROW_NUMBER: usize = " 1: ".len();
+
+/// Draws a board with unguessed names hidden, reusing one buffer across
+/// redraws. The columns and header are laid out once per game, and each
+/// redraw writes the cells straight into the buffer instead of collecting
+/// them first.
+pub struct BoardRenderer {
+    header: String,
+    layout: Layout,
+    out: String,
+    status: bool,
+    theme: Theme,
+}
+
+impl BoardRenderer {
+    /// A renderer in the [current](theme::current) theme, fitted to the
+    /// terminal
+    pub fn new(game: &TriviaGame) -> Self {
+        Self::with_theme(game, theme::current())
+    }
+
+    pub fn with_theme(game: &TriviaGame, theme: Theme) -> Self {
+        Self::with_width(game, theme, terminal_width())
+    }
+
+    /// A renderer that cuts long cells short to keep rows within `width`
+    /// columns, or never does for None
+    pub fn with_width(game: &TriviaGame, theme: Theme, width: Option<usize>) -> Self {
+        let board = game.board();
+        let mut layout = Layout::new(board).at_least(ANSWER_COL, theme.hidden_name.width());
+        if let Some(width) = width {
+            layout = layout.fit(width, ROW_NUMBER);
+        }
+        Self {
+            header: header(&board.column_names, &layout, ROW_NUMBER, &theme),
+            layout,
+            out: String::new(),
+            status: true,
+            theme,
+        }
+    }
+
+    /// Leaves out the correct, strikes, and score line, for modes that
+    /// show their own
+    pub fn without_status(mut self) -> Self {
+        self.status = false;
+        self
+    }
+
+    /// The current board as text, valid until the next call
+    pub fn render(&mut self, game: &TriviaGame) -> &str {
+        let out = &mut self.out;
+        out.clear();
+        // Writing to a String can't fail
+        let _ = write!(
+            out,
+            "\nQuestion: {}\n--- CURRENT BOARD ---",
+            game.question()
+        );
+        if let Some(left) = game.remaining() {
+            // Round up, so the clock reads 0:00 only once it has run out
+            let left = Duration::from_secs(left.as_secs_f64().ceil() as u64);
+            let _ = write!(out, " {} left", clock(left));
+        }
+        out.push('\n');
+        out.push_str(&self.header);
+        for (i, row) in game.board().rows.iter().enumerate() {
+            let _ = write!(out, "{:>2}: ", i + 1);
+            let guessed = game.is_guessed(i);
+            let passed = game.is_passed(i);
+            for (j, cell) in row.iter().enumerate() {
+                if j > 0 {
+                    out.push_str(SEPARATOR);
+                }
+                let (style, text) = match (j == ANSWER_COL, guessed, passed) {
+                    (true, true, _) => (self.theme.found, cell.as_str()),
+                    (true, false, true) => (self.theme.missed, cell.as_str()),
+                    (true, false, false) => (self.theme.hidden, self.theme.hidden_name),
+                    _ => (Style::PLAIN, cell.as_str()),
+                };
+                self.layout.push_cell(out, j, text, style);
+            }
+            out.push('\n');
+        }
+        if self.status {
+            let _ = write!(
+                out,
+                "Correct: {}/{}  Strikes: {}",
+                game.correct(),
+                game.total(),
+                game.strikes()
+            );
+            if let Some(max) = game.rules().strikes {
+                let _ = write!(out, "/{max}");
+            }
+            let _ = write!(out, "  Score: {}", game.score());
+            if game.streak_bonus() > 0 {
+                let _ = write!(out, " (+{} streak bonus)", game.streak_bonus());
+            }
+            if game.streak() >= 2 {
+                let _ = write!(out, "  Streak: {}", game.streak());
+            }
+            if game.on_streak() {
+                let _ = write!(out, " (x{STREAK_MULTIPLIER})");
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// The column names lined up over the cells, `indent` in to clear the row
+/// numbers, and a rule under them; nothing for a board without columns
+fn header(column_names: &[String], layout: &Layout, indent: usize, theme: &Theme) -> String {
+    if column_names.is_empty() {
+        return String::new();
+    }
+    let mut names = String::new();
+    for (j, name) in column_names.iter().enumerate() {
+        if j > 0 {
+            names.push_str(SEPARATOR);
+        }
+        layout.push_cell(&mut names, j, name, Style::PLAIN);
+    }
+    let pad = " ".repeat(indent);
+    format!(
+        "{pad}{}\n{pad}{}\n",
+        theme.heading.paint(&names),
+        "-".repeat(layout.width())
+    )
+}
+
+/// Columns on the terminal standard output is writing to, or None when it
+/// isn't writing to one
+fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(width, _)| usize::from(width.0))
+}
+
+/// Prints the full answers and how the round ended
+fn print_final(game: &TriviaGame) {
+    print!("{}", render_final(game));
+}
+
+/// The full answers, which ones were found, and how the round ended, in the
+/// [current](theme::current) theme
+pub fn render_final(game: &TriviaGame) -> String {
+    render_final_with_theme(game, &theme::current())
+}
+
+pub fn render_final_with_theme(game: &TriviaGame, theme: &Theme) -> String {
+    render_final_with_width(game, theme, terminal_width())
+}
+
+/// The final answers with long cells cut short to keep rows within `width`
+/// columns, or never for None
+pub fn render_final_with_width(game: &TriviaGame, theme: &Theme, width: Option<usize>) -> String {
+    let board = game.board();
+    // Room for the row number and mark before a row and the points after it
+    let indent = " 1 : ".len() + theme.found_mark.width().max(theme.missed_mark.width());
+    let mut layout = Layout::new(board);
+    if let Some(width) = width {
+        layout = layout.fit(width, indent + " (1000pts)".len());
+    }
+    let mut out = String::from("--- FINAL ANSWERS ---\n");
+    out.push_str(&header(&board.column_names, &layout, indent, theme));
+    for (i, row) in board.rows.iter().enumerate() {
+        let (style, mark) = if game.is_guessed(i) {
+            (theme.found, theme.found_mark)
+        } else {
+            (theme.missed, theme.missed_mark)
+        };
+        let _ = write!(out, "{:>2} {}: ", i + 1, style.paint(mark));
+        for (j, cell) in row.iter().enumerate() {
+            if j > 0 {
+                out.push_str(SEPARATOR);
+            }
+            layout.push_cell(&mut out, j, cell, Style::PLAIN);
+        }
+        let _ = writeln!(out, " ({}pts)", game.point_values()[i]);
+    }
+    let _ = match game.end() {
+        Some(GameEnd::Perfect) => writeln!(out, "Perfect! You got all {} answers!", game.total()),
+        Some(GameEnd::StruckOut) if game.strikes() == MAX_STRIKES => {
+            writeln!(out, "Three strikes, you're out!")
+        }
+        Some(GameEnd::StruckOut) => writeln!(out, "Strike {}, you're out!", game.strikes()),
+        Some(GameEnd::TimeUp) => writeln!(out, "Time's up! Here are the full answers:"),
+        Some(GameEnd::Skipped) => {
+            let possible: u32 = game.point_values().iter().sum();
+            writeln!(
+                out,
+                "Skipped with {} of {} named, banking {} points ({}% of the board). Here are the full answers:",
+                game.correct(),
+                game.total(),
+                game.score(),
+                (game.score() * 100).checked_div(possible).unwrap_or(0)
+            )
+        }
+        _ => writeln!(out, "Stopping early. Here are the full answers:"),
+    };
+    let _ = writeln!(out, "Final Score: {}/1000", game.score());
+    if game.streak_bonus() > 0 {
+        let _ = writeln!(
+            out,
+            "Streak bonus: +{} on top of the board score, {} with it (best streak {})",
+            game.streak_bonus(),
+            game.score() + game.streak_bonus(),
+            game.best_streak()
+        );
+    }
+    if game.hints() > 0 {
+        let _ = writeln!(out, "Hints used: {}", game.hints());
+    }
+    out.push_str("--- END ---\n\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::ThemeName;
+
+    #[test]
+    fn test_parse_pass() {
+        assert_eq!(parse_pass("pass 3"), Some(3));
+        assert_eq!(parse_pass("PASS  10 "), Some(10));
+        assert_eq!(parse_pass("pass"), None);
+        assert_eq!(parse_pass("pass three"), None);
+        assert_eq!(parse_pass("Passmore 3"), None);
+        assert_eq!(parse_hint("hint 2"), Some(2));
+        assert_eq!(parse_hint("pass 2"), None);
+    }
+
+    #[test]
+    fn test_which_one_shows_the_other_columns() {
+        let board = Board {
+            column_names: vec!["name".into(), "team_abbr".into(), "yards".into()],
+            rows: vec![
+                vec!["Calvin Johnson".into(), "DET".into(), "1681".into()],
+                vec!["Tom Brady".into(), "NE".into(), "5000".into()],
+                vec!["Chad Johnson".into(), "CIN".into(), "1440".into()],
+            ],
+        };
+        let mut game = TriviaGame::new("Most yards", board);
+        let GuessOutcome::Ambiguous { rows } = game.submit_guess("Johnson") else {
+            panic!("two Johnsons should be ambiguous");
+        };
+        assert_eq!(
+            which_one(&game, "Johnson", &rows),
+            "2 players match 'Johnson'. Which one?\n  \
+             1) #1: team_abbr DET, yards 1681\n  \
+             2) #3: team_abbr CIN, yards 1440\n\
+             Type its number, or keep guessing.\n"
+        );
+    }
+
+    #[test]
+    fn test_renderer_hides_unguessed_names() {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Drew Brees".into(), "4000".into()],
+            ],
+        };
+        let mut game = TriviaGame::new("Most yards", board);
+        let mut renderer = BoardRenderer::new(&game);
+        game.submit_guess("Brees");
+
+        let first = renderer.render(&game).to_string();
+        assert_eq!(
+            first,
+            "\nQuestion: Most yards\n--- CURRENT BOARD ---\n    \
+             name       | yards\n    ------------------\n \
+             1: -------    |  5000\n 2: Drew Brees |  4000\n\
+             Correct: 1/2  Strikes: 0/3  Score: 556\n\n"
+        );
+        let themed = BoardRenderer::with_theme(&game, Theme::new(ThemeName::Deuteranopia))
+            .render(&game)
+            .to_string();
+        assert!(themed.contains("\x1b[2m-------\x1b[0m    |  5000"));
+        assert!(themed.contains("\x1b[1;34mDrew Brees\x1b[0m |  4000"));
+        // A narrow terminal cuts names short but never the stats
+        let narrow = BoardRenderer::with_width(&game, Theme::default(), Some(16))
+            .render(&game)
+            .to_string();
+        assert!(narrow.contains(" 2: Drew … |  4000\n"));
+        // A redraw of the same size reuses the buffer
+        let buffer = renderer.render(&game).as_ptr();
+        assert_eq!(renderer.render(&game), first);
+        assert_eq!(renderer.render(&game).as_ptr(), buffer);
+    }
+
+    #[test]
+    fn test_streak_bonus_is_reported_apart() {
+        let names = ["Brady", "Brees", "Manning", "Rivers", "Rodgers", "Ryan"];
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: names
+                .iter()
+                .map(|name| vec![name.to_string(), "1000".into()])
+                .collect(),
+        };
+        let mut game = TriviaGame::new("Most yards", board);
+        for name in &names[..5] {
+            game.submit_guess(name);
+        }
+        let mut renderer = BoardRenderer::new(&game);
+        assert!(renderer.render(&game).contains("Streak: 5 (x1.1)"));
+        game.submit_guess("Ryan");
+        let bonus = game.streak_bonus();
+        assert!(bonus > 0);
+        let out = render_final(&game);
+        assert!(out.contains(&format!("Final Score: {}/1000", game.score())));
+        assert!(out.contains(&format!(
+            "Streak bonus: +{bonus} on top of the board score, {} with it (best streak 6)",
+            game.score() + bonus
+        )));
+    }
+}
@@ -0,0 +1,1077 @@
+//! Typed commands for the REPL
+//!
+//! `parse_command` turns a line of input into a [`Command`], or a
+//! [`CommandError`] that says what was wrong and, where it can, suggests the
+//! closest valid spelling.
+use crate::answer_match::edit_distance;
+use crate::blitz::BLITZ_TIME;
+use crate::export::ExportFormat;
+use crate::questions::league::is_team;
+use crate::questions::{
+    BoardId, Category, Difficulty, League, QuestionParams, QuestionRegistry, POSITIONS,
+};
+use crate::quiz::{DEFAULT_QUIZ_LENGTH, MAX_QUIZ_LENGTH};
+use crate::team_match::{Team, DEFAULT_BEST_OF};
+use crate::Question;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Keywords understood by the REPL, used for suggestions
+pub const COMMANDS: [&str; 28] = [
+    "start",
+    "daily",
+    "weekly",
+    "list",
+    "score",
+    "profile",
+    "highscores",
+    "history",
+    "replay",
+    "report",
+    "custom",
+    "export-quiz",
+    "export-html",
+    "export-anki",
+    "pack",
+    "challenge",
+    "party",
+    "match",
+    "survival",
+    "blitz",
+    "quiz",
+    "team",
+    "host",
+    "join",
+    "leaderboard",
+    "selftest",
+    "quit",
+    "exit",
+];
+
+const START_USAGE: &str = "start [CATEGORY] [easy | medium | hard]";
+
+const CUSTOM_USAGE: &str = "custom <question | stat> [TEAM] [YEAR | START END] [POSITION] [min=N]";
+
+const EXPORT_USAGE: &str = "export-quiz <code>... [--format md|html] [--out FILE]";
+
+const PAGE_USAGE: &str = "export-html <code> [--out FILE]";
+
+const ANKI_USAGE: &str = "export-anki [code] [--out FILE]";
+
+const PACK_USAGE: &str =
+    "pack create <file.kbq> <code>... [--no-hashes] [--title TITLE] | pack play <file.kbq>";
+
+const WEEKLY_USAGE: &str = "weekly | weekly add <NAME> <CODE> | weekly board [WEEK]";
+
+const LEADERBOARD_USAGE: &str = "leaderboard [join <URL> | leave]";
+
+const CHALLENGE_USAGE: &str = "challenge save <file.kbc> [NAME] | challenge play <file.kbc>";
+
+const QUIZ_USAGE: &str = "quiz [COUNT] [CATEGORY] [TEAM]";
+
+const MATCH_USAGE: &str =
+    "match <TEAM>=<captain>,<player>... <TEAM>=<captain>,<player>... [ROUNDS]";
+
+/// One parsed line of REPL input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Quit,
+    Score,
+    /// Show the lifetime stats of the profile given with `--profile`
+    Profile,
+    /// Show the best saved board for each question code, and the best of all
+    HighScores,
+    /// List the saved boards of one question code, e.g. `history last10passers_PIT`
+    History(String),
+    /// Deal a board again from the ID shown when it started, e.g.
+    /// `replay last10passers_TEAM:PIT:min=10`
+    Replay(BoardId),
+    List,
+    /// Run every question across a sweep of parameters and check the boards
+    SelfTest,
+    /// Play a random question, from one category or difficulty tier when
+    /// given, e.g. `start passing` or `start easy`
+    Start {
+        category: Option<Category>,
+        difficulty: Option<Difficulty>,
+    },
+    /// Play today's board, the same for everyone
+    Daily,
+    /// Play a question with parameters chosen by the player, e.g.
+    /// `custom recyds PIT 2005 2012`, or a custom question ranked by a stat,
+    /// e.g. `custom receiving_yards GB 2010 2015 WR`
+    Custom {
+        question: String,
+        params: QuestionParams,
+    },
+    /// Write a printable quiz of the given question codes, e.g.
+    /// `export-quiz last10passers_PIT recyds_yearrange_GB --format html`
+    ExportQuiz {
+        codes: Vec<String>,
+        format: ExportFormat,
+        /// File to write; the quiz is printed when None
+        out: Option<PathBuf>,
+    },
+    /// Write a single-file HTML page that plays one board in the browser,
+    /// e.g. `export-html last10passers_PIT --out steelers.html`
+    ExportHtml {
+        code: String,
+        /// File to write; the page is printed when None
+        out: Option<PathBuf>,
+    },
+    /// Write flashcards for the answers missed this session, or for every
+    /// row of one question's board, e.g. `export-anki --out missed.txt` or
+    /// `export-anki last10passers_PIT`
+    ExportAnki {
+        code: Option<String>,
+        /// File to write; the deck is printed when None
+        out: Option<PathBuf>,
+    },
+    /// Save generated questions as a shareable quiz pack, e.g.
+    /// `pack create steelers.kbq last10passers_PIT recyds_yearrange_PIT --title Steelers night`
+    PackCreate {
+        path: PathBuf,
+        codes: Vec<String>,
+        title: Option<String>,
+        /// Embed answer hashes so players can tell if their board differs
+        hashes: bool,
+    },
+    /// Play every question in a quiz pack, in order
+    PackPlay(PathBuf),
+    /// Save the last finished board as a challenge for a friend, e.g.
+    /// `challenge save brady.kbc Ann`
+    ChallengeSave {
+        path: PathBuf,
+        /// Who the challenge is from; the friend sees this name
+        from: Option<String>,
+    },
+    /// Play a friend's challenge, then compare the two results
+    ChallengePlay(PathBuf),
+    /// Render the session as a Markdown scoreboard, written to the file when
+    /// one is given, e.g. `report night.md`
+    Report(Option<PathBuf>),
+    /// Race on random boards with several players at one keyboard, e.g.
+    /// `party Ann Ben Cam`
+    Party(Vec<String>),
+    /// Two local teams trading boards over best-of-N rounds, e.g.
+    /// `match Red=Ann,Ben Blue=Cam,Dee 5`
+    Match {
+        teams: [Team; 2],
+        best_of: u32,
+    },
+    /// Random boards one after another, with strikes carried over until
+    /// they run out
+    Survival,
+    /// Random boards one after another against one clock, e.g. `blitz` for
+    /// three minutes or `blitz 60`
+    Blitz(Duration),
+    /// A fixed run of boards with one scorecard at the end, e.g.
+    /// `quiz 10 rushing PIT`
+    Quiz {
+        count: usize,
+        category: Option<Category>,
+        team: Option<String>,
+    },
+    /// Every question that takes a team, all for one franchise, e.g.
+    /// `team PIT`
+    Team(String),
+    /// Fill in a team for every question that takes one, e.g.
+    /// `team lock PIT`, or stop with `team unlock`
+    LockTeam(Option<String>),
+    /// Wait for an opponent on the LAN, on the given port or the default
+    Host(Option<u16>),
+    /// Play head to head against a host, e.g. `join 192.168.1.20:7878`
+    Join(String),
+    /// Show, join, or leave the global daily leaderboard
+    Leaderboard(LeaderboardAction),
+    /// Play the week's set, add a friend's result code, or show the week
+    Weekly(WeeklyAction),
+    /// Play a question by code, e.g. `last10rushers_PIT`
+    Play(String),
+}
+
+/// What `leaderboard` was asked to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaderboardAction {
+    /// Today's top scores
+    Show,
+    /// Opt in to sending daily scores to the server at this URL
+    Join(String),
+    /// Opt out and forget the anonymous token
+    Leave,
+}
+
+/// What `weekly` was asked to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WeeklyAction {
+    /// Play this week's set
+    Play,
+    /// Check a friend's result code and put it on the local leaderboard
+    Add { player: String, code: String },
+    /// The local standings for a week like `2026-W42`, or this week
+    Board(Option<String>),
+}
+
+/// Why a line of input could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CommandError {
+    #[error("unknown command '{input}'{}", did_you_mean(suggestion))]
+    Unknown {
+        input: String,
+        suggestion: Option<String>,
+    },
+    #[error("{problem}. Usage: {usage}")]
+    Usage {
+        problem: String,
+        usage: &'static str,
+    },
+}
+
+fn did_you_mean(suggestion: &Option<String>) -> String {
+    match suggestion {
+        Some(s) => format!(" (did you mean '{s}'?)"),
+        None => String::new(),
+    }
+}
+
+/// Parses one line of REPL input
+pub fn parse_command(input: &str) -> Result<Command, CommandError> {
+    let mut words = input.split_whitespace();
+    let Some(first) = words.next() else {
+        return Err(CommandError::Unknown {
+            input: String::new(),
+            suggestion: None,
+        });
+    };
+    let args: Vec<&str> = words.collect();
+    let keyword = first.to_lowercase();
+
+    let no_args = |command: Command, usage: &'static str| {
+        if args.is_empty() {
+            Ok(command)
+        } else {
+            Err(CommandError::Usage {
+                problem: format!("'{keyword}' takes no arguments"),
+                usage,
+            })
+        }
+    };
+
+    match keyword.as_str() {
+        "quit" | "exit" => no_args(Command::Quit, "quit"),
+        "score" => no_args(Command::Score, "score"),
+        "profile" => no_args(Command::Profile, "profile"),
+        "highscores" => no_args(Command::HighScores, "highscores"),
+        "history" => match args[..] {
+            [code] => Ok(Command::History(code.to_string())),
+            _ => Err(CommandError::Usage {
+                problem: "'history' needs one code".into(),
+                usage: "history <code>",
+            }),
+        },
+        "replay" => match args[..] {
+            [id] => id
+                .parse()
+                .map(Command::Replay)
+                .map_err(|e| CommandError::Usage {
+                    problem: format!("{e}"),
+                    usage: "replay <id>",
+                }),
+            _ => Err(CommandError::Usage {
+                problem: "'replay' needs one board ID".into(),
+                usage: "replay <id>",
+            }),
+        },
+        "list" => no_args(Command::List, "list"),
+        "selftest" => no_args(Command::SelfTest, "selftest"),
+        "report" => match args[..] {
+            [] => Ok(Command::Report(None)),
+            [path] => Ok(Command::Report(Some(PathBuf::from(path)))),
+            _ => Err(CommandError::Usage {
+                problem: "'report' takes at most one file".into(),
+                usage: "report [FILE]",
+            }),
+        },
+        "start" => parse_start(&args),
+        "daily" => no_args(Command::Daily, "daily"),
+        "custom" => parse_custom(&args),
+        "export-quiz" => parse_export(&args),
+        "export-html" => match args[..] {
+            [code] => Ok(Command::ExportHtml {
+                code: code.to_string(),
+                out: None,
+            }),
+            [code, "--out", path] => Ok(Command::ExportHtml {
+                code: code.to_string(),
+                out: Some(PathBuf::from(path)),
+            }),
+            _ => Err(CommandError::Usage {
+                problem: "'export-html' needs one code".into(),
+                usage: PAGE_USAGE,
+            }),
+        },
+        "export-anki" => {
+            let (code, out) = match args[..] {
+                [] => (None, None),
+                ["--out", path] => (None, Some(path)),
+                [code] => (Some(code), None),
+                [code, "--out", path] => (Some(code), Some(path)),
+                _ => {
+                    return Err(CommandError::Usage {
+                        problem: "'export-anki' takes at most one code".into(),
+                        usage: ANKI_USAGE,
+                    })
+                }
+            };
+            Ok(Command::ExportAnki {
+                code: code.map(str::to_string),
+                out: out.map(PathBuf::from),
+            })
+        }
+        "pack" => parse_pack(&args),
+        "challenge" => match args[..] {
+            ["play", path] => Ok(Command::ChallengePlay(PathBuf::from(path))),
+            ["save", path, ref from @ ..] => Ok(Command::ChallengeSave {
+                path: PathBuf::from(path),
+                from: (!from.is_empty()).then(|| from.join(" ")),
+            }),
+            _ => Err(CommandError::Usage {
+                problem: "expected 'challenge save' or 'challenge play'".into(),
+                usage: CHALLENGE_USAGE,
+            }),
+        },
+        "party" if args.len() >= 2 => {
+            Ok(Command::Party(args.iter().map(|s| s.to_string()).collect()))
+        }
+        "party" => Err(CommandError::Usage {
+            problem: "a party needs at least two players".into(),
+            usage: "party <name> <name>...",
+        }),
+        "match" => parse_match(&args),
+        "survival" => no_args(Command::Survival, "survival"),
+        "quiz" => parse_quiz(&args),
+        "blitz" => match args[..] {
+            [] => Ok(Command::Blitz(BLITZ_TIME)),
+            [secs] => match secs.parse::<u64>() {
+                Ok(secs) if secs > 0 => Ok(Command::Blitz(Duration::from_secs(secs))),
+                _ => Err(CommandError::Usage {
+                    problem: format!("'{secs}' is not a number of seconds"),
+                    usage: "blitz [SECS]",
+                }),
+            },
+            _ => Err(CommandError::Usage {
+                problem: "'blitz' takes at most a number of seconds".into(),
+                usage: "blitz [SECS]",
+            }),
+        },
+        "team" => match args[..] {
+            [team] if is_team(&team.to_ascii_uppercase()) => {
+                Ok(Command::Team(team.to_ascii_uppercase()))
+            }
+            ["lock", team] if is_team(&team.to_ascii_uppercase()) => {
+                Ok(Command::LockTeam(Some(team.to_ascii_uppercase())))
+            }
+            ["unlock"] => Ok(Command::LockTeam(None)),
+            _ => Err(CommandError::Usage {
+                problem: "'team' needs one team, e.g. PIT".into(),
+                usage: "team <TEAM> | team lock <TEAM> | team unlock",
+            }),
+        },
+        "host" => match args[..] {
+            [] => Ok(Command::Host(None)),
+            [port] => port
+                .parse()
+                .map(|port| Command::Host(Some(port)))
+                .map_err(|_| CommandError::Usage {
+                    problem: format!("'{port}' is not a port number"),
+                    usage: "host [PORT]",
+                }),
+            _ => Err(CommandError::Usage {
+                problem: "'host' takes at most a port".into(),
+                usage: "host [PORT]",
+            }),
+        },
+        "join" => match args[..] {
+            [addr] => Ok(Command::Join(addr.to_string())),
+            _ => Err(CommandError::Usage {
+                problem: "'join' needs the host's address".into(),
+                usage: "join <HOST[:PORT]>",
+            }),
+        },
+        "leaderboard" => match args[..] {
+            [] => Ok(Command::Leaderboard(LeaderboardAction::Show)),
+            ["join", url] => Ok(Command::Leaderboard(LeaderboardAction::Join(url.into()))),
+            ["leave"] => Ok(Command::Leaderboard(LeaderboardAction::Leave)),
+            _ => Err(CommandError::Usage {
+                problem: "expected 'leaderboard', 'leaderboard join', or 'leaderboard leave'"
+                    .into(),
+                usage: LEADERBOARD_USAGE,
+            }),
+        },
+        "weekly" => match args[..] {
+            [] => Ok(Command::Weekly(WeeklyAction::Play)),
+            ["add", player, code] => Ok(Command::Weekly(WeeklyAction::Add {
+                player: player.into(),
+                code: code.into(),
+            })),
+            ["board"] => Ok(Command::Weekly(WeeklyAction::Board(None))),
+            ["board", week] => Ok(Command::Weekly(WeeklyAction::Board(Some(week.into())))),
+            _ => Err(CommandError::Usage {
+                problem: "expected 'weekly', 'weekly add', or 'weekly board'".into(),
+                usage: WEEKLY_USAGE,
+            }),
+        },
+        _ if args.is_empty() => Ok(Command::Play(first.to_string())),
+        _ => Err(CommandError::Unknown {
+            suggestion: suggest(&keyword, COMMANDS),
+            input: keyword,
+        }),
+    }
+}
+
+/// Parses the arguments of `custom <question | stat> [TEAM] [YEAR | START END] [POSITION] [min=N]`
+fn parse_custom(args: &[&str]) -> Result<Command, CommandError> {
+    let usage = |problem: String| CommandError::Usage {
+        problem,
+        usage: CUSTOM_USAGE,
+    };
+
+    let Some((question, rest)) = args.split_first() else {
+        return Err(usage("'custom' needs a question".into()));
+    };
+
+    let mut params = QuestionParams::default();
+    let mut years = Vec::new();
+    for arg in rest {
+        if let Ok(year) = arg.parse::<i32>() {
+            years.push(year);
+            continue;
+        }
+        if let Some(min) = arg.strip_prefix("min=") {
+            let min = min
+                .parse::<u32>()
+                .map_err(|_| usage(format!("'{min}' is not a whole number")))?;
+            params.threshold = Some(min);
+            continue;
+        }
+        let team = arg.to_ascii_uppercase();
+        if params.team.is_none() && is_team(&team) {
+            params.team = Some(team);
+            continue;
+        }
+        if params.position.is_none() && POSITIONS.contains(&team.as_str()) {
+            params.position = Some(team);
+            continue;
+        }
+        let teams = League::ALL
+            .iter()
+            .flat_map(|league| league.teams().iter().copied());
+        let hint = did_you_mean(&suggest(&team, teams));
+        return Err(usage(format!("'{arg}' is not a team or year{hint}")));
+    }
+
+    match years[..] {
+        [] => {}
+        [year] => params.year = Some(year),
+        [start, end] => params.year_range = Some((start, end)),
+        _ => return Err(usage("too many years".into())),
+    }
+
+    Ok(Command::Custom {
+        question: question.to_string(),
+        params,
+    })
+}
+
+/// Parses the arguments of `export-quiz <code>... [--format md|html] [--out FILE]`
+fn parse_export(args: &[&str]) -> Result<Command, CommandError> {
+    let usage = |problem: String| CommandError::Usage {
+        problem,
+        usage: EXPORT_USAGE,
+    };
+
+    let mut codes = Vec::new();
+    let mut format = ExportFormat::default();
+    let mut out = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--format" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| usage("--format needs a value".into()))?;
+                format = name.parse().map_err(usage)?;
+            }
+            "--out" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| usage("--out needs a file".into()))?;
+                out = Some(PathBuf::from(path));
+            }
+            code => codes.push(code.to_string()),
+        }
+    }
+    if codes.is_empty() {
+        return Err(usage("'export-quiz' needs at least one code".into()));
+    }
+
+    Ok(Command::ExportQuiz { codes, format, out })
+}
+
+/// Parses the arguments of `pack create ...` and `pack play ...`
+fn parse_pack(args: &[&str]) -> Result<Command, CommandError> {
+    let usage = |problem: String| CommandError::Usage {
+        problem,
+        usage: PACK_USAGE,
+    };
+
+    match args {
+        ["play", path] => Ok(Command::PackPlay(PathBuf::from(path))),
+        ["create", path, rest @ ..] => {
+            let mut codes = Vec::new();
+            let mut title = None;
+            let mut hashes = true;
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match *arg {
+                    "--no-hashes" => hashes = false,
+                    // The title runs to the end of the line
+                    "--title" => {
+                        let words: Vec<&str> = rest.by_ref().copied().collect();
+                        if words.is_empty() {
+                            return Err(usage("--title needs a value".into()));
+                        }
+                        title = Some(words.join(" "));
+                    }
+                    code => codes.push(code.to_string()),
+                }
+            }
+            if codes.is_empty() {
+                return Err(usage("'pack create' needs at least one code".into()));
+            }
+            Ok(Command::PackCreate {
+                path: PathBuf::from(path),
+                codes,
+                title,
+                hashes,
+            })
+        }
+        _ => Err(usage("expected 'pack create' or 'pack play'".into())),
+    }
+}
+
+/// Parses the arguments of `start [CATEGORY] [DIFFICULTY]`, in either order
+fn parse_start(args: &[&str]) -> Result<Command, CommandError> {
+    let usage = |problem: String| CommandError::Usage {
+        problem,
+        usage: START_USAGE,
+    };
+
+    let (mut category, mut difficulty) = (None, None);
+    for arg in args {
+        if let Ok(d) = arg.parse::<Difficulty>() {
+            if difficulty.replace(d).is_some() {
+                return Err(usage("'start' takes one difficulty".into()));
+            }
+            continue;
+        }
+        let c = arg.parse::<Category>().map_err(usage)?;
+        if category.replace(c).is_some() {
+            return Err(usage("'start' takes one category".into()));
+        }
+    }
+    Ok(Command::Start {
+        category,
+        difficulty,
+    })
+}
+
+/// Parses the arguments of `quiz [COUNT] [CATEGORY] [TEAM]`, in any order
+fn parse_quiz(args: &[&str]) -> Result<Command, CommandError> {
+    let usage = |problem: String| CommandError::Usage {
+        problem,
+        usage: QUIZ_USAGE,
+    };
+
+    let (mut count, mut category, mut team) = (None, None, None);
+    for arg in args {
+        if let Ok(n) = arg.parse::<usize>() {
+            if n == 0 || count.is_some() {
+                return Err(usage(format!("'{arg}' is not a number of questions")));
+            }
+            if n > MAX_QUIZ_LENGTH {
+                return Err(usage(format!(
+                    "a quiz has at most {MAX_QUIZ_LENGTH} questions, not {n}"
+                )));
+            }
+            count = Some(n);
+            continue;
+        }
+        if let Ok(c) = arg.parse::<Category>() {
+            category = Some(c);
+            continue;
+        }
+        let upper = arg.to_ascii_uppercase();
+        if team.is_none() && is_team(&upper) {
+            team = Some(upper);
+            continue;
+        }
+        return Err(usage(format!("'{arg}' is not a count, category, or team")));
+    }
+    Ok(Command::Quiz {
+        count: count.unwrap_or(DEFAULT_QUIZ_LENGTH),
+        category,
+        team,
+    })
+}
+
+/// Parses the arguments of `match Red=Ann,Ben Blue=Cam,Dee [ROUNDS]`
+fn parse_match(args: &[&str]) -> Result<Command, CommandError> {
+    let usage = |problem: String| CommandError::Usage {
+        problem,
+        usage: MATCH_USAGE,
+    };
+    let team = |arg: &str| {
+        let (name, players) = arg
+            .split_once('=')
+            .ok_or_else(|| usage(format!("'{arg}' should look like Red=Ann,Ben")))?;
+        let players: Vec<String> = players
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .collect();
+        if name.is_empty() || players.is_empty() {
+            return Err(usage(format!("'{arg}' needs a team name and players")));
+        }
+        Ok(Team {
+            name: name.to_string(),
+            players,
+        })
+    };
+
+    let (first, second, best_of) = match args {
+        [first, second] => (first, second, DEFAULT_BEST_OF),
+        [first, second, rounds] => {
+            let best_of = rounds
+                .parse()
+                .map_err(|_| usage(format!("'{rounds}' is not a number of rounds")))?;
+            (first, second, best_of)
+        }
+        _ => return Err(usage("a match needs two teams".into())),
+    };
+    Ok(Command::Match {
+        teams: [team(first)?, team(second)?],
+        best_of,
+    })
+}
+
+/// Returns the candidate closest to `input`, if any is close enough to be a
+/// likely typo
+pub fn suggest<I>(input: &str, candidates: I) -> Option<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let input = input.to_lowercase();
+    let limit = (input.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .map(|c| {
+            let distance = edit_distance(&input, &c.as_ref().to_lowercase());
+            (distance, c.as_ref().to_string())
+        })
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, c)| c)
+}
+
+/// Finds the question a `custom` command names.
+///
+/// Takes an exact code, or a code prefix like "recyds" when only one family
+/// member fits; ties go to the code whose shape matches the given parameters
+/// (`_yearrange` for a range, `_year` for one season, `_TEAM` for a team).
+pub fn find_custom_question(
+    registry: &QuestionRegistry,
+    name: &str,
+    params: &QuestionParams,
+) -> Option<Arc<dyn Question>> {
+    let name = name.to_ascii_lowercase();
+    let fits = |code: &str| {
+        let mut score = 0;
+        if params.year_range.is_some() && code.contains("_yearrange") {
+            score += 1;
+        }
+        if params.year.is_some() && code.ends_with("_year") {
+            score += 1;
+        }
+        if params.team.is_some() && code.ends_with("_team") {
+            score += 1;
+        }
+        score
+    };
+
+    let prefix = format!("{name}_");
+    let mut candidates: Vec<_> = registry
+        .iter_lowercase()
+        .filter(|(code, _)| *code == name || code.starts_with(&prefix))
+        .collect();
+    candidates.sort_by(|(a, _), (b, _)| {
+        (*b == name)
+            .cmp(&(*a == name))
+            .then(fits(b).cmp(&fits(a)))
+            .then(a.cmp(b))
+    });
+    candidates.first().map(|(_, question)| Arc::clone(question))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::build_registry;
+
+    #[test]
+    fn test_simple_commands() {
+        assert_eq!(parse_command("quit"), Ok(Command::Quit));
+        assert_eq!(parse_command("EXIT"), Ok(Command::Quit));
+        assert_eq!(parse_command(" list "), Ok(Command::List));
+        assert_eq!(parse_command("selftest"), Ok(Command::SelfTest));
+        assert_eq!(parse_command("profile"), Ok(Command::Profile));
+        assert_eq!(parse_command("HighScores"), Ok(Command::HighScores));
+        assert_eq!(
+            parse_command("history last10passers_PIT"),
+            Ok(Command::History("last10passers_PIT".into()))
+        );
+        assert!(parse_command("history").is_err());
+        assert_eq!(
+            parse_command("replay passyds_TEAM:PIT"),
+            Ok(Command::Replay("passyds_TEAM:PIT".parse().unwrap()))
+        );
+        assert!(parse_command("replay passyds_TEAM:PIT:GB").is_err());
+        assert_eq!(parse_command("survival"), Ok(Command::Survival));
+        assert_eq!(
+            parse_command("start"),
+            Ok(Command::Start {
+                category: None,
+                difficulty: None
+            })
+        );
+        assert_eq!(
+            parse_command("start Special-Teams"),
+            Ok(Command::Start {
+                category: Some(Category::SpecialTeams),
+                difficulty: None
+            })
+        );
+        assert_eq!(
+            parse_command("start hard rushing"),
+            Ok(Command::Start {
+                category: Some(Category::Rushing),
+                difficulty: Some(Difficulty::Hard)
+            })
+        );
+        assert!(parse_command("start bowling").is_err());
+        assert!(parse_command("start easy hard").is_err());
+        assert_eq!(
+            parse_command("quiz rushing 5 pit"),
+            Ok(Command::Quiz {
+                count: 5,
+                category: Some(Category::Rushing),
+                team: Some("PIT".into()),
+            })
+        );
+        assert!(matches!(
+            parse_command("quiz"),
+            Ok(Command::Quiz { count: 10, .. })
+        ));
+        assert!(parse_command("quiz 0").is_err());
+        assert!(parse_command("quiz 100").is_ok());
+        assert!(parse_command("quiz 101").is_err());
+        assert!(parse_command("quiz 99999999999").is_err());
+        assert_eq!(parse_command("blitz"), Ok(Command::Blitz(BLITZ_TIME)));
+        assert_eq!(
+            parse_command("blitz 60"),
+            Ok(Command::Blitz(Duration::from_secs(60)))
+        );
+        assert!(parse_command("blitz soon").is_err());
+        assert_eq!(parse_command("team pit"), Ok(Command::Team("PIT".into())));
+        assert_eq!(
+            parse_command("team lock pit"),
+            Ok(Command::LockTeam(Some("PIT".into())))
+        );
+        assert_eq!(parse_command("team unlock"), Ok(Command::LockTeam(None)));
+        assert!(parse_command("team lock XYZ").is_err());
+        assert!(parse_command("team Steelers").is_err());
+        assert!(parse_command("quiz bowling").is_err());
+        assert_eq!(
+            parse_command("last10rushers_PIT"),
+            Ok(Command::Play("last10rushers_PIT".into()))
+        );
+        assert!(matches!(
+            parse_command("score 5"),
+            Err(CommandError::Usage { .. })
+        ));
+        assert_eq!(parse_command("report"), Ok(Command::Report(None)));
+        assert_eq!(
+            parse_command("report night.md"),
+            Ok(Command::Report(Some(PathBuf::from("night.md"))))
+        );
+        assert!(parse_command("report a.md b.md").is_err());
+    }
+
+    #[test]
+    fn test_custom_with_range() {
+        let cmd = parse_command("custom recyds pit 2005 2012").unwrap();
+        assert_eq!(
+            cmd,
+            Command::Custom {
+                question: "recyds".into(),
+                params: QuestionParams {
+                    team: Some("PIT".into()),
+                    year_range: Some((2005, 2012)),
+                    ..Default::default()
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_custom_position_and_threshold() {
+        let cmd = parse_command("custom last10passers GB min=50").unwrap();
+        let Command::Custom { params, .. } = cmd else {
+            panic!("expected custom");
+        };
+        assert_eq!(params.team.as_deref(), Some("GB"));
+        assert_eq!(params.threshold, Some(50));
+
+        let cmd = parse_command("custom top10rushtd rb 2010 2014").unwrap();
+        let Command::Custom { params, .. } = cmd else {
+            panic!("expected custom");
+        };
+        assert_eq!(params.position.as_deref(), Some("RB"));
+        assert!(parse_command("custom last10passers min=lots").is_err());
+    }
+
+    #[test]
+    fn test_custom_errors() {
+        assert!(parse_command("custom").is_err());
+        let err = parse_command("custom recyds PTT 2005").unwrap_err();
+        assert!(err.to_string().contains("did you mean 'PIT'"));
+        assert!(parse_command("custom recyds 2001 2002 2003").is_err());
+    }
+
+    #[test]
+    fn test_export_quiz() {
+        let cmd = parse_command("export-quiz last10passers_PIT passyds_GB --format HTML").unwrap();
+        assert_eq!(
+            cmd,
+            Command::ExportQuiz {
+                codes: vec!["last10passers_PIT".into(), "passyds_GB".into()],
+                format: ExportFormat::Html,
+                out: None,
+            }
+        );
+        let Command::ExportQuiz { format, out, .. } =
+            parse_command("export-quiz passyds_GB --out quiz.md").unwrap()
+        else {
+            panic!("expected export-quiz");
+        };
+        assert_eq!(format, ExportFormat::Markdown);
+        assert_eq!(out, Some(PathBuf::from("quiz.md")));
+
+        assert!(parse_command("export-quiz").is_err());
+        assert!(parse_command("export-quiz passyds_GB --format pdf").is_err());
+        assert!(parse_command("export-quiz passyds_GB --out").is_err());
+
+        assert_eq!(
+            parse_command("export-html passyds_GB --out gb.html"),
+            Ok(Command::ExportHtml {
+                code: "passyds_GB".into(),
+                out: Some(PathBuf::from("gb.html")),
+            })
+        );
+        assert!(parse_command("export-html").is_err());
+        assert_eq!(
+            parse_command("export-anki --out missed.txt"),
+            Ok(Command::ExportAnki {
+                code: None,
+                out: Some(PathBuf::from("missed.txt")),
+            })
+        );
+        assert_eq!(
+            parse_command("export-anki passyds_GB"),
+            Ok(Command::ExportAnki {
+                code: Some("passyds_GB".into()),
+                out: None,
+            })
+        );
+        assert!(parse_command("export-anki passyds_GB passyds_PIT").is_err());
+        assert!(parse_command("export-html passyds_GB passyds_PIT").is_err());
+    }
+
+    #[test]
+    fn test_pack_commands() {
+        assert_eq!(
+            parse_command("pack play steelers.kbq"),
+            Ok(Command::PackPlay(PathBuf::from("steelers.kbq")))
+        );
+        assert_eq!(
+            parse_command("pack create s.kbq passyds_PIT --no-hashes --title Steelers night"),
+            Ok(Command::PackCreate {
+                path: PathBuf::from("s.kbq"),
+                codes: vec!["passyds_PIT".into()],
+                title: Some("Steelers night".into()),
+                hashes: false,
+            })
+        );
+        assert!(parse_command("pack create s.kbq").is_err());
+        assert!(parse_command("pack play").is_err());
+        assert!(parse_command("pack share s.kbq").is_err());
+    }
+
+    #[test]
+    fn test_challenge_commands() {
+        assert_eq!(
+            parse_command("challenge play brady.kbc"),
+            Ok(Command::ChallengePlay(PathBuf::from("brady.kbc")))
+        );
+        assert_eq!(
+            parse_command("challenge save brady.kbc Ann Smith"),
+            Ok(Command::ChallengeSave {
+                path: PathBuf::from("brady.kbc"),
+                from: Some("Ann Smith".into()),
+            })
+        );
+        assert_eq!(
+            parse_command("challenge save brady.kbc"),
+            Ok(Command::ChallengeSave {
+                path: PathBuf::from("brady.kbc"),
+                from: None,
+            })
+        );
+        assert!(parse_command("challenge play").is_err());
+        assert!(parse_command("challenge brady.kbc").is_err());
+    }
+
+    #[test]
+    fn test_daily_and_leaderboard_commands() {
+        assert_eq!(parse_command("Daily"), Ok(Command::Daily));
+        assert_eq!(
+            parse_command("leaderboard"),
+            Ok(Command::Leaderboard(LeaderboardAction::Show))
+        );
+        assert_eq!(
+            parse_command("leaderboard join https://scores.example"),
+            Ok(Command::Leaderboard(LeaderboardAction::Join(
+                "https://scores.example".into()
+            )))
+        );
+        assert_eq!(
+            parse_command("leaderboard leave"),
+            Ok(Command::Leaderboard(LeaderboardAction::Leave))
+        );
+        assert!(parse_command("leaderboard join").is_err());
+        assert!(parse_command("daily 3").is_err());
+    }
+
+    #[test]
+    fn test_weekly_commands() {
+        assert_eq!(
+            parse_command("weekly"),
+            Ok(Command::Weekly(WeeklyAction::Play))
+        );
+        assert_eq!(
+            parse_command("weekly add Ann 2026-W42-1.2.3.4.5-abcd1234"),
+            Ok(Command::Weekly(WeeklyAction::Add {
+                player: "Ann".into(),
+                code: "2026-W42-1.2.3.4.5-abcd1234".into(),
+            }))
+        );
+        assert_eq!(
+            parse_command("weekly board 2026-W41"),
+            Ok(Command::Weekly(WeeklyAction::Board(Some(
+                "2026-W41".into()
+            ))))
+        );
+        assert!(parse_command("weekly add Ann").is_err());
+    }
+
+    #[test]
+    fn test_match_command() {
+        let team = |name: &str, players: &[&str]| Team {
+            name: name.into(),
+            players: players.iter().map(|p| p.to_string()).collect(),
+        };
+        assert_eq!(
+            parse_command("match Red=Ann,Ben Blue=Cam"),
+            Ok(Command::Match {
+                teams: [team("Red", &["Ann", "Ben"]), team("Blue", &["Cam"])],
+                best_of: DEFAULT_BEST_OF,
+            })
+        );
+        assert!(matches!(
+            parse_command("match Red=Ann Blue=Cam 5"),
+            Ok(Command::Match { best_of: 5, .. })
+        ));
+        assert!(parse_command("match Red=Ann").is_err());
+        assert!(parse_command("match Red=Ann Blue= 3").is_err());
+        assert!(parse_command("match Red=Ann Blue=Cam x").is_err());
+    }
+
+    #[test]
+    fn test_party_command() {
+        assert_eq!(
+            parse_command("party Ann Ben"),
+            Ok(Command::Party(vec!["Ann".into(), "Ben".into()]))
+        );
+        assert!(parse_command("party Ann").is_err());
+    }
+
+    #[test]
+    fn test_lan_commands() {
+        assert_eq!(parse_command("host"), Ok(Command::Host(None)));
+        assert_eq!(parse_command("host 9000"), Ok(Command::Host(Some(9000))));
+        assert_eq!(
+            parse_command("join 192.168.1.20:7878"),
+            Ok(Command::Join("192.168.1.20:7878".into()))
+        );
+        assert!(parse_command("host lots").is_err());
+        assert!(parse_command("join").is_err());
+    }
+
+    #[test]
+    fn test_unknown_command_suggests() {
+        let err = parse_command("strat now").unwrap_err();
+        assert_eq!(
+            err,
+            CommandError::Unknown {
+                input: "strat".into(),
+                suggestion: Some("start".into()),
+            }
+        );
+        assert_eq!(suggest("zzzzzz", COMMANDS), None);
+    }
+
+    #[test]
+    fn test_find_custom_question() {
+        let registry = build_registry();
+        let range = QuestionParams {
+            year_range: Some((2005, 2010)),
+            ..Default::default()
+        };
+        let year = QuestionParams {
+            year: Some(2010),
+            ..Default::default()
+        };
+
+        let q = find_custom_question(&registry, "recyds", &range).unwrap();
+        assert_eq!(q.meta().code, "recyds_yearrange_TEAM");
+        let q = find_custom_question(&registry, "top10passyds", &year).unwrap();
+        assert_eq!(q.meta().code, "top10passyds_year");
+        let q = find_custom_question(&registry, "top10rushingqb", &range).unwrap();
+        assert_eq!(q.meta().code, "top10rushingqb_yearrange");
+        assert!(find_custom_question(&registry, "nope", &range).is_none());
+    }
+}
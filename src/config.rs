@@ -0,0 +1,136 @@
+//! Player settings from `~/.config/knowball/config.toml`
+//!
+//! Every key is optional, and command-line flags override the file:
+//!
+//! ```toml
+//! theme = "deuteranopia"
+//! db = "/data/nfl-2024.sqlite"
+//!
+//! [nicknames]
+//! "Mr. Big Play" = "Willie Parker"
+//!
+//! [rules]
+//! preset = "sudden-death"
+//! time-limit = 60
+//! ```
+use crate::error::{KnowBallError, Result};
+use crate::rules::RuleSettings;
+use crate::theme::ThemeName;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the stats database, which `--db` overrides
+/// and which overrides the file's `db`
+pub const DB_ENV: &str = "KNOWBALL_DB";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The board's colors and symbols
+    pub theme: Option<ThemeName>,
+    /// The stats database, relative to the working directory like the
+    /// default `nfl.sqlite`
+    pub db: Option<PathBuf>,
+    /// Nicknames a guess may use, nickname → full name, on top of the
+    /// bundled ones, which they replace when spelled the same
+    #[serde(default)]
+    pub nicknames: BTreeMap<String, String>,
+    /// Changes to the classic rules, which flags on the command line change
+    /// further
+    #[serde(default)]
+    pub rules: RuleSettings,
+}
+
+impl Config {
+    /// The settings in `path`, or the defaults if there is no such file
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents)
+                .map_err(|e| KnowBallError::Parse(format!("{}: {e}", path.display()))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn parse(contents: &str) -> std::result::Result<Self, String> {
+        toml::from_str(contents).map_err(|e| e.message().to_string())
+    }
+
+    /// The database to read boards from: `flag` (from `--db`) if given, else
+    /// `KNOWBALL_DB`, else the file's `db`. None leaves the league's own file.
+    pub fn db_path(&self, flag: Option<&Path>) -> Option<PathBuf> {
+        pick_db(flag, std::env::var_os(DB_ENV), self.db.as_deref())
+    }
+}
+
+fn pick_db(flag: Option<&Path>, env: Option<OsString>, file: Option<&Path>) -> Option<PathBuf> {
+    flag.map(PathBuf::from)
+        .or_else(|| env.filter(|v| !v.is_empty()).map(PathBuf::from))
+        .or_else(|| file.map(PathBuf::from))
+}
+
+/// Where the config file lives: under `$XDG_CONFIG_HOME`, else `~/.config`.
+/// None when neither is set.
+pub fn default_path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(dir.join("knowball").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme() {
+        let config = Config::parse("theme = \"high-contrast\"\n").unwrap();
+        assert_eq!(config.theme, Some(ThemeName::HighContrast));
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert!(Config::parse("theme = \"neon\"").is_err());
+        assert!(Config::parse("colour = \"mono\"").is_err());
+    }
+
+    #[test]
+    fn test_db_flag_then_env_then_file() {
+        let config = Config::parse("db = \"file.sqlite\"\n").unwrap();
+        let file = config.db.as_deref();
+        let env = || Some(OsString::from("env.sqlite"));
+        let flag = Some(Path::new("flag.sqlite"));
+        assert_eq!(pick_db(flag, env(), file), Some("flag.sqlite".into()));
+        assert_eq!(pick_db(None, env(), file), Some("env.sqlite".into()));
+        assert_eq!(
+            pick_db(None, Some("".into()), file),
+            Some("file.sqlite".into())
+        );
+        assert_eq!(pick_db(None, None, None), None);
+    }
+
+    #[test]
+    fn test_parse_nicknames() {
+        let config = Config::parse("[nicknames]\n\"Mr. Big Play\" = \"Willie Parker\"\n").unwrap();
+        assert_eq!(config.nicknames["Mr. Big Play"], "Willie Parker");
+        assert!(Config::parse("nicknames = [\"Gronk\"]").is_err());
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let config = Config::parse("[rules]\npreset = \"casual\"\nreveal = false\n").unwrap();
+        let rules = config.rules.rules();
+        assert_eq!((rules.strikes, rules.allow_reveal), (None, false));
+        assert!(Config::parse("[rules]\nlives = 2\n").is_err());
+        let config = Config::parse("[rules]\ntime-limit = 60\n").unwrap();
+        assert_eq!(config.rules.time_limit, Some(60));
+        assert!(Config::parse("[rules]\ntime-limit = 0\n").is_err());
+    }
+
+    #[test]
+    fn test_missing_file_is_the_defaults() {
+        let path = Path::new("does/not/exist/config.toml");
+        assert_eq!(Config::load(path).unwrap(), Config::default());
+    }
+}
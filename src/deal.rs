@@ -0,0 +1,250 @@
+//! The commands that deal a board, played the same way everywhere
+//!
+//! A question code, `custom`, `start`, `daily`, and `replay` each pick a
+//! board and play it through the [`GameSession`]. [`deal`] does that for the
+//! REPL and for [`run_script`](crate::script::run_script) alike; each reports
+//! what happened and plays the board through its own [`Frontend`].
+use crate::command::{find_custom_question, suggest, Command, COMMANDS};
+use crate::engine::daily_seed;
+use crate::error::KnowBallError;
+use crate::game::{TriviaGame, TriviaResult};
+use crate::questions::{find_stat, CustomQuestion, League, Question, QuestionParams, QuestionSpec};
+use crate::session::GameSession;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Where a dealt board is played and what happened is reported
+pub trait Frontend {
+    /// A line about the board being dealt, e.g. its code. Only the text REPL
+    /// shows these.
+    fn say(&mut self, _text: &str) {}
+
+    /// A heads-up, such as a renamed code
+    fn notice(&mut self, message: String);
+
+    /// Why no board was dealt
+    fn error(&mut self, message: String);
+
+    /// Why a question's board couldn't be loaded
+    fn failed(&mut self, error: KnowBallError) {
+        self.error(error.to_string());
+    }
+
+    /// Plays the board just loaded
+    fn play(&mut self, spec: &QuestionSpec, game: &mut TriviaGame);
+
+    /// The daily board for `day` was played, e.g. to send it to a leaderboard
+    fn daily_played(&mut self, _day: u64, _result: &TriviaResult) {}
+}
+
+/// Deals and plays the board `command` asks for. Commands that don't deal a
+/// board are left alone.
+pub fn deal(session: &mut GameSession, command: &Command, frontend: &mut impl Frontend) {
+    match command {
+        Command::Play(code) => play_code(session, code, frontend),
+        Command::Custom { question, params } => play_custom(session, question, params, frontend),
+        Command::Start {
+            category,
+            difficulty,
+        } => match session
+            .engine_mut()
+            .random_question_in(*category, *difficulty)
+        {
+            Some(question) => {
+                frontend.say(&format!("Random code: {}", question.meta().code));
+                frontend.say(&format!("Description: {}", question.meta().description));
+                play(
+                    session,
+                    question.as_ref(),
+                    &QuestionParams::default(),
+                    frontend,
+                );
+            }
+            None if category.is_some() || difficulty.is_some() => {
+                frontend.error("no questions match that category and difficulty".into());
+            }
+            None => frontend.error("no questions registered".into()),
+        },
+        Command::Daily => {
+            let day = daily_seed(SystemTime::now());
+            frontend.say(&format!("Daily board for day {day}"));
+            let played = session.play_seeded(day, |spec, game| frontend.play(spec, game));
+            match played {
+                Ok(result) => frontend.daily_played(day, &result),
+                Err(e) => frontend.error(e.to_string()),
+            }
+        }
+        Command::Replay(id) => {
+            let played = session.replay(id, |spec, game| frontend.play(spec, game));
+            if let Err(e) = played {
+                frontend.error(e.to_string());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Plays a question code, with a team folded in or under an old name, or
+/// suggests the code that was probably meant
+fn play_code(session: &mut GameSession, code: &str, frontend: &mut impl Frontend) {
+    if let Some(parsed) = session.engine().lookup(code) {
+        if let Some(old) = parsed.renamed_from {
+            frontend.notice(format!(
+                "'{old}' has been renamed to '{}'; the old code will keep working for now.",
+                parsed.question.meta().code
+            ));
+        }
+        frontend.say(&format!("Code: {code}"));
+        if let Some(ref team) = parsed.team {
+            frontend.say(&format!("Team: {team}"));
+        }
+        let params = QuestionParams {
+            team: parsed.team,
+            ..Default::default()
+        };
+        play(session, parsed.question.as_ref(), &params, frontend);
+        return;
+    }
+
+    let matched = session
+        .engine()
+        .registry()
+        .get_ignore_case(code)
+        .map(Arc::clone);
+    if let Some(question) = matched {
+        frontend.say(&format!("Code: {}", question.meta().code));
+        frontend.say(&format!("Description: {}", question.meta().description));
+        play(
+            session,
+            question.as_ref(),
+            &QuestionParams::default(),
+            frontend,
+        );
+        return;
+    }
+
+    let code = code.to_lowercase();
+    let codes = session.engine().registry().keys();
+    let suggestion = suggest(
+        &code,
+        COMMANDS.iter().copied().chain(codes.map(String::as_str)),
+    );
+    let mut message = format!("unknown command or code '{code}'");
+    if let Some(s) = suggestion {
+        message.push_str(&format!("; did you mean '{s}'?"));
+    }
+    frontend.error(message);
+    frontend.say("Type 'list' to see available codes.\n");
+}
+
+/// Plays `custom`: a registered question with the given parameters, or a
+/// one-off question built from a stat column
+fn play_custom(
+    session: &mut GameSession,
+    question: &str,
+    params: &QuestionParams,
+    frontend: &mut impl Frontend,
+) {
+    match find_custom_question(session.engine().registry(), question, params) {
+        Some(found) => {
+            frontend.say(&format!("Code: {}", found.meta().code));
+            frontend.say(&format!("Description: {}", found.meta().description));
+            play(session, found.as_ref(), params, frontend);
+        }
+        None if find_stat(question).is_some() && session.engine().league() != League::Nfl => {
+            frontend.error(format!("custom stats like {question} are NFL only"));
+        }
+        None if find_stat(question).is_some() => {
+            match CustomQuestion::from_params(question, params) {
+                Ok(custom) => {
+                    frontend.say(&format!("Description: {}", custom.meta().description));
+                    play(session, &custom, &QuestionParams::default(), frontend);
+                }
+                Err(e) => frontend.error(e.to_string()),
+            }
+        }
+        None => {
+            frontend.error(format!("no question or stat matches '{question}'"));
+            frontend.say("Type 'list' to see available codes.\n");
+        }
+    }
+}
+
+/// Loads a board of `question` through the session and plays it
+fn play(
+    session: &mut GameSession,
+    question: &dyn Question,
+    params: &QuestionParams,
+    frontend: &mut impl Frontend,
+) {
+    let played = session.play_question(question, params, |spec, game| frontend.play(spec, game));
+    if let Err(e) = played {
+        frontend.failed(e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_runner::Board;
+    use crate::store::MockStore;
+    use crate::TriviaEngine;
+
+    /// Keeps what was reported and reveals every board
+    #[derive(Default)]
+    struct Recorder {
+        notices: Vec<String>,
+        errors: Vec<String>,
+        played: Vec<String>,
+    }
+
+    impl Frontend for Recorder {
+        fn notice(&mut self, message: String) {
+            self.notices.push(message);
+        }
+
+        fn error(&mut self, message: String) {
+            self.errors.push(message);
+        }
+
+        fn play(&mut self, spec: &QuestionSpec, game: &mut TriviaGame) {
+            self.played.push(spec.code.clone());
+            game.reveal();
+        }
+    }
+
+    fn session() -> GameSession {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![vec!["Tom Brady".into(), "5000".into()]],
+        };
+        GameSession::new(TriviaEngine::with_seed(490).with_store(MockStore::new(board)))
+    }
+
+    #[test]
+    fn test_codes_and_typos() {
+        let mut session = session();
+        let mut recorder = Recorder::default();
+        for code in ["recyds_PIT_yearrange", "lsit", "last10passers_PIT"] {
+            deal(&mut session, &Command::Play(code.into()), &mut recorder);
+        }
+        assert_eq!(
+            recorder.played,
+            ["recyds_yearrange_TEAM", "last10passers_TEAM"]
+        );
+        assert_eq!(recorder.notices.len(), 1);
+        assert_eq!(
+            recorder.errors,
+            ["unknown command or code 'lsit'; did you mean 'list'?"]
+        );
+    }
+
+    #[test]
+    fn test_other_commands_are_left_alone() {
+        let mut session = session();
+        let mut recorder = Recorder::default();
+        deal(&mut session, &Command::Survival, &mut recorder);
+        assert!(recorder.played.is_empty() && recorder.errors.is_empty());
+        assert!(session.history().is_empty());
+    }
+}
@@ -0,0 +1,331 @@
+//! A made-up league to play without downloading any stats
+//!
+//! [`fill`] builds a database with the schema of `nfl.sqlite` (or, for the
+//! NBA, the one documented in `packs/nba.toml`) and fills it with invented
+//! players, so `--demo` can deal every question from an in-memory copy.
+//! Every NFL team has a starting and a backup QB, three RBs, four WRs, and a
+//! TE in every season from `START_YEAR` to `END_YEAR`. Each role turns over
+//! every few seasons, the depth players every season, and the third WR
+//! throws a pass a year, so every question family finds ten players for any
+//! team and range. Names and stats come from a hash of the team, season, and
+//! role, so the same database is built every time.
+mod nba;
+
+use crate::questions::{League, END_YEAR, START_YEAR, TEAMS};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+/// Creates `league`'s tables in `conn` and fills them with the made-up players
+pub fn fill(conn: &mut Connection, league: League) -> rusqlite::Result<()> {
+    match league {
+        League::Nfl => fill_nfl(conn),
+        League::Nba => nba::fill(conn),
+    }
+}
+
+/// The schema of `nfl.sqlite`
+const SCHEMA: &str = "
+    CREATE TABLE players (
+        player_id   TEXT PRIMARY KEY,
+        name        TEXT,
+        position    TEXT,
+        college     TEXT,
+        latest_team TEXT
+    );
+    CREATE TABLE seasons (
+        player_id           TEXT,
+        season              INTEGER,
+        team_abbr           TEXT,
+        position            TEXT,
+        completions         INTEGER,
+        attempts            INTEGER,
+        passing_yards       INTEGER,
+        passing_tds         INTEGER,
+        interceptions       INTEGER,
+        passer_rating       REAL,
+        sacks               INTEGER,
+        sack_yards          INTEGER,
+        rushing_attempts    INTEGER,
+        rushing_yards       INTEGER,
+        rushing_tds         INTEGER,
+        targets             INTEGER,
+        receptions          INTEGER,
+        receiving_yards     INTEGER,
+        receiving_tds       INTEGER,
+        fumbles             INTEGER,
+        fumbles_lost        INTEGER,
+        solo_tackles        INTEGER,
+        assists             INTEGER,
+        sacks_def           REAL,
+        interceptions_def   INTEGER,
+        games               INTEGER,
+        games_started       INTEGER,
+        player_name         TEXT,
+        PRIMARY KEY (player_id, season),
+        FOREIGN KEY (player_id) REFERENCES players(player_id)
+    );
+";
+
+/// A roster spot: its code, position, and how many seasons a player keeps it
+struct Role {
+    code: &'static str,
+    position: &'static str,
+    stint: i32,
+}
+
+const ROLES: [Role; 10] = [
+    role("QB1", "QB", 4),
+    role("QB2", "QB", 2),
+    role("RB1", "RB", 3),
+    role("RB2", "RB", 2),
+    role("RB3", "RB", 1),
+    role("WR1", "WR", 4),
+    role("WR2", "WR", 3),
+    role("WR3", "WR", 2),
+    role("WR4", "WR", 1),
+    role("TE1", "TE", 3),
+];
+
+const fn role(code: &'static str, position: &'static str, stint: i32) -> Role {
+    Role {
+        code,
+        position,
+        stint,
+    }
+}
+
+const FIRST_NAMES: [&str; 24] = [
+    "Aaron", "Blake", "Caleb", "Darius", "Eli", "Felix", "Grant", "Hugo", "Isaiah", "Jalen",
+    "Kobe", "Landon", "Malik", "Nate", "Owen", "Pierce", "Quinn", "Reed", "Silas", "Trey", "Upton",
+    "Vince", "Wade", "Xavier",
+];
+
+const LAST_NAME_STARTS: [&str; 20] = [
+    "Ash", "Black", "Brook", "Cald", "Carr", "Dal", "East", "Fair", "Gold", "Hal", "Hart", "King",
+    "Lang", "Mar", "North", "Oak", "Pem", "Red", "Stan", "West",
+];
+
+const LAST_NAME_ENDS: [&str; 20] = [
+    "ford", "wood", "ley", "ton", "well", "by", "field", "more", "stead", "wick", "worth", "brook",
+    "dale", "ham", "man", "croft", "ridge", "son", "ward", "holt",
+];
+
+/// One season's counting stats
+#[derive(Default)]
+struct Line {
+    completions: u32,
+    attempts: u32,
+    passing_yards: u32,
+    passing_tds: u32,
+    interceptions: u32,
+    sacks: u32,
+    sack_yards: u32,
+    rushing_attempts: u32,
+    rushing_yards: u32,
+    rushing_tds: u32,
+    targets: u32,
+    receptions: u32,
+    receiving_yards: u32,
+    receiving_tds: u32,
+    fumbles: u32,
+    fumbles_lost: u32,
+}
+
+/// FNV-1a, so the stats don't depend on the standard library's hasher
+fn hash(parts: &[&str]) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain([0]) {
+            h ^= u64::from(byte);
+            h = h.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    h
+}
+
+/// A number in `lo..=hi` drawn from `seed` and `salt`
+fn pick(seed: u64, salt: &str, lo: u32, hi: u32) -> u32 {
+    let h = hash(&[&seed.to_string(), salt]);
+    lo + (h % u64::from(hi - lo + 1)) as u32
+}
+
+fn line(role: &Role, seed: u64) -> Line {
+    let p = |salt, lo, hi| pick(seed, salt, lo, hi);
+    let mut line = Line {
+        fumbles: p("fumbles", 0, 3),
+        ..Default::default()
+    };
+    line.fumbles_lost = line.fumbles / 2;
+    match role.code {
+        "QB1" => {
+            line.attempts = p("att", 420, 620);
+            line.completions = line.attempts * p("pct", 58, 69) / 100;
+            line.passing_yards = line.attempts * p("ypa", 62, 82) / 10;
+            line.passing_tds = p("td", 16, 38);
+            line.interceptions = p("int", 5, 16);
+            line.sacks = p("sk", 18, 48);
+            line.sack_yards = line.sacks * p("sky", 5, 8);
+            line.rushing_attempts = p("ratt", 25, 80);
+            line.rushing_yards = line.rushing_attempts * p("ypc", 20, 55) / 10;
+            line.rushing_tds = p("rtd", 0, 5);
+        }
+        "QB2" => {
+            line.attempts = p("att", 40, 160);
+            line.completions = line.attempts * p("pct", 52, 64) / 100;
+            line.passing_yards = line.attempts * p("ypa", 55, 72) / 10;
+            line.passing_tds = p("td", 3, 8);
+            line.interceptions = p("int", 1, 6);
+            line.sacks = p("sk", 2, 12);
+            line.sack_yards = line.sacks * 6;
+            line.rushing_attempts = p("ratt", 3, 15);
+            line.rushing_yards = line.rushing_attempts * 3;
+        }
+        "RB1" => {
+            line.rushing_attempts = p("ratt", 190, 320);
+            line.rushing_yards = line.rushing_attempts * p("ypc", 38, 52) / 10;
+            line.rushing_tds = p("rtd", 4, 14);
+            line.targets = p("tgt", 35, 70);
+            line.receptions = line.targets * p("catch", 65, 80) / 100;
+            line.receiving_yards = line.receptions * p("ypr", 6, 10);
+            line.receiving_tds = p("rectd", 0, 3);
+        }
+        "RB2" => {
+            line.rushing_attempts = p("ratt", 55, 130);
+            line.rushing_yards = line.rushing_attempts * p("ypc", 36, 48) / 10;
+            line.rushing_tds = p("rtd", 1, 5);
+            line.targets = p("tgt", 12, 30);
+            line.receptions = line.targets * p("catch", 65, 80) / 100;
+            line.receiving_yards = line.receptions * p("ypr", 6, 9);
+        }
+        "RB3" => {
+            line.rushing_attempts = p("ratt", 15, 45);
+            line.rushing_yards = line.rushing_attempts * p("ypc", 32, 45) / 10;
+            line.rushing_tds = p("rtd", 0, 2);
+            line.targets = p("tgt", 4, 14);
+            line.receptions = line.targets * p("catch", 60, 80) / 100;
+            line.receiving_yards = line.receptions * p("ypr", 5, 8);
+        }
+        "WR1" => {
+            line.targets = p("tgt", 115, 170);
+            line.receptions = line.targets * p("catch", 58, 70) / 100;
+            line.receiving_yards = line.receptions * p("ypr", 11, 16);
+            line.receiving_tds = p("rectd", 5, 14);
+            line.rushing_attempts = p("ratt", 0, 8);
+            line.rushing_yards = line.rushing_attempts * 6;
+        }
+        "WR2" => {
+            line.targets = p("tgt", 75, 110);
+            line.receptions = line.targets * p("catch", 55, 68) / 100;
+            line.receiving_yards = line.receptions * p("ypr", 10, 14);
+            line.receiving_tds = p("rectd", 2, 7);
+        }
+        "WR3" => {
+            line.targets = p("tgt", 50, 72);
+            line.receptions = line.targets * p("catch", 52, 64) / 100;
+            line.receiving_yards = line.receptions * p("ypr", 9, 13);
+            line.receiving_tds = p("rectd", 1, 4);
+            line.attempts = 1;
+            line.completions = p("cmp", 0, 1);
+            line.passing_yards = line.completions * p("pyds", 8, 40);
+        }
+        "WR4" => {
+            line.targets = p("tgt", 18, 45);
+            line.receptions = line.targets * p("catch", 50, 62) / 100;
+            line.receiving_yards = line.receptions * p("ypr", 9, 14);
+            line.receiving_tds = p("rectd", 0, 2);
+            line.rushing_attempts = p("ratt", 1, 5);
+            line.rushing_yards = line.rushing_attempts * p("ypc", 40, 90) / 10;
+        }
+        _ => {
+            line.targets = p("tgt", 55, 100);
+            line.receptions = line.targets * p("catch", 62, 72) / 100;
+            line.receiving_yards = line.receptions * p("ypr", 9, 12);
+            line.receiving_tds = p("rectd", 2, 9);
+        }
+    }
+    line
+}
+
+/// The season a player's stint in `role` started, for a team's `index`
+fn stint_start(role: &Role, index: usize, season: i32) -> i32 {
+    let offset = index as i32 % role.stint;
+    season - (season - START_YEAR + offset) % role.stint
+}
+
+/// The id of whoever held `role` (QB1, RB2, WR4, TE1, ...) for `team` in
+/// `season` of the NFL demo, e.g. `PIT-QB1-2021`. Panics on a team or role the
+/// demo doesn't have.
+pub fn player_id(team: &str, role: &str, season: i32) -> String {
+    let index = TEAMS.iter().position(|t| *t == team).expect("known team");
+    let role = ROLES.iter().find(|r| r.code == role).expect("known role");
+    format!("{team}-{}-{}", role.code, stint_start(role, index, season))
+}
+
+/// The `index`th player's name. Stepping through the combinations by a
+/// prime spreads each team's players over different surnames.
+fn name(index: usize) -> String {
+    let combinations = FIRST_NAMES.len() * LAST_NAME_STARTS.len() * LAST_NAME_ENDS.len();
+    let k = index * 7919 % combinations;
+    let first = FIRST_NAMES[k % FIRST_NAMES.len()];
+    let last = k / FIRST_NAMES.len();
+    let start = LAST_NAME_STARTS[last % LAST_NAME_STARTS.len()];
+    let end = LAST_NAME_ENDS[(last / LAST_NAME_STARTS.len()) % LAST_NAME_ENDS.len()];
+    format!("{first} {start}{end}")
+}
+
+/// Creates the NFL tables in `conn` and fills them
+fn fill_nfl(conn: &mut Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+    let mut names: HashMap<String, String> = HashMap::new();
+    {
+        let mut player = tx.prepare(
+            "INSERT INTO players (player_id, name, position, college, latest_team)
+                 VALUES (?1, ?2, ?3, '', ?4)",
+        )?;
+        let mut season_row = tx.prepare(
+            "INSERT INTO seasons VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, NULL, ?10,
+                 ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, NULL, NULL, NULL, NULL,
+                 ?21, NULL, ?22)",
+        )?;
+        for (index, team) in TEAMS.iter().enumerate() {
+            for season in START_YEAR..=END_YEAR {
+                for role in &ROLES {
+                    let id = format!("{team}-{}-{}", role.code, stint_start(role, index, season));
+                    if !names.contains_key(&id) {
+                        let full = name(names.len());
+                        player.execute(params![id, full, role.position, team])?;
+                        names.insert(id.clone(), full);
+                    }
+                    let l = line(role, hash(&[team, &season.to_string(), role.code]));
+                    let games = if season >= 2021 { 17 } else { 16 };
+                    season_row.execute(params![
+                        id,
+                        season,
+                        team,
+                        role.position,
+                        l.completions,
+                        l.attempts,
+                        l.passing_yards,
+                        l.passing_tds,
+                        l.interceptions,
+                        l.sacks,
+                        l.sack_yards,
+                        l.rushing_attempts,
+                        l.rushing_yards,
+                        l.rushing_tds,
+                        l.targets,
+                        l.receptions,
+                        l.receiving_yards,
+                        l.receiving_tds,
+                        l.fumbles,
+                        l.fumbles_lost,
+                        games,
+                        names[&id],
+                    ])?;
+                }
+            }
+        }
+    }
+    tx.commit()
+}
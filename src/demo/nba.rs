@@ -0,0 +1,77 @@
+//! The made-up NBA league
+//!
+//! Every franchise fields ten players a season, a new set each year, and
+//! each of them plays one game. The five starters score 30 or more in it, so
+//! the last-10 question always finds ten names.
+use super::{hash, name, pick};
+use crate::questions::{END_YEAR, NBA_TEAMS, START_YEAR};
+use rusqlite::{params, Connection};
+
+/// The schema documented in `packs/nba.toml`
+const SCHEMA: &str = "
+    CREATE TABLE players (
+        player_id TEXT PRIMARY KEY,
+        name      TEXT,
+        position  TEXT
+    );
+    CREATE TABLE seasons (
+        player_id TEXT,
+        season    INTEGER,
+        team_abbr TEXT,
+        games     INTEGER,
+        points    INTEGER,
+        rebounds  INTEGER,
+        assists   INTEGER,
+        PRIMARY KEY (player_id, season, team_abbr)
+    );
+    CREATE TABLE games (
+        player_id TEXT,
+        season    INTEGER,
+        game_date TEXT,
+        team_abbr TEXT,
+        points    INTEGER
+    );
+";
+
+const POSITIONS: [&str; 5] = ["PG", "SG", "SF", "PF", "C"];
+
+/// Creates the tables in `conn` and fills them
+pub(super) fn fill(conn: &mut Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+    {
+        let mut player = tx.prepare("INSERT INTO players VALUES (?1, ?2, ?3)")?;
+        let mut season_row =
+            tx.prepare("INSERT INTO seasons VALUES (?1, ?2, ?3, 82, ?4, ?5, ?6)")?;
+        let mut game = tx.prepare("INSERT INTO games VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        let mut count = 0;
+        for team in NBA_TEAMS {
+            for season in START_YEAR..=END_YEAR {
+                for slot in 0..10 {
+                    let id = format!("{team}-{slot}-{season}");
+                    let position = POSITIONS[slot % POSITIONS.len()];
+                    player.execute(params![id, name(count), position])?;
+                    count += 1;
+
+                    let seed = hash(&[team, &season.to_string(), &slot.to_string()]);
+                    let p = |salt, lo, hi| pick(seed, salt, lo, hi);
+                    let starter = slot < 5;
+                    let (points, rebounds, assists) = if starter {
+                        (p("pts", 900, 2400), p("reb", 200, 900), p("ast", 100, 700))
+                    } else {
+                        (p("pts", 100, 800), p("reb", 50, 300), p("ast", 20, 200))
+                    };
+                    season_row.execute(params![id, season, team, points, rebounds, assists])?;
+                    let date = format!("{season}-03-{:02}", slot + 1);
+                    let big = if starter {
+                        p("big", 30, 50)
+                    } else {
+                        p("big", 8, 29)
+                    };
+                    game.execute(params![id, season, date, team, big])?;
+                }
+            }
+        }
+    }
+    tx.commit()
+}
@@ -0,0 +1,536 @@
+//! UI-agnostic entry point for embedding Know Ball
+//!
+//! `TriviaEngine` owns the question registry and the session RNG and hands
+//! back generated questions and their boards without printing anything, so
+//! bots, web servers, and the CLI can all drive the same game.
+use crate::error::Result;
+use crate::quality::{check_board, MIN_BOARD_ROWS};
+#[cfg(feature = "packs")]
+use crate::questions::pack::{load_pack, merge_pack, PackError};
+use crate::questions::{
+    builtin_registry, choose_random_question, generate_question, parse_query, validate_params,
+    Category, Difficulty, League, ParsedRequest, Question, QuestionParams, QuestionRegistry,
+    QuestionSpec,
+};
+use crate::sql_runner::{Board, Query};
+#[cfg(not(feature = "sqlite"))]
+use crate::store::MockStore;
+#[cfg(feature = "sqlite")]
+use crate::store::SqliteStore;
+use crate::store::StatsStore;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use rand::{Rng, SeedableRng};
+use std::borrow::Cow;
+#[cfg(feature = "packs")]
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds in a UTC day, for [`daily_seed`]
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// How many times to reroll a question's parameters when its board fails the quality check
+pub const MAX_BOARD_ATTEMPTS: usize = 5;
+
+/// Generates questions and loads their boards for any frontend
+pub struct TriviaEngine {
+    /// The shared built-in registry until a pack is loaded into a copy
+    registry: Cow<'static, QuestionRegistry>,
+    rng: StdRng,
+    seed: u64,
+    store: Arc<dyn StatsStore>,
+    league: League,
+}
+
+impl TriviaEngine {
+    /// Creates an engine with a random seed
+    pub fn new() -> Self {
+        Self::with_seed(rand::thread_rng().gen())
+    }
+
+    /// Creates an engine whose random questions are fully determined by `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            registry: Cow::Borrowed(builtin_registry()),
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            store: default_store(),
+            league: League::Nfl,
+        }
+    }
+
+    /// Plays `league`: its bundled questions instead of the built-in NFL
+    /// ones, with boards read from its database file. Swap in another store
+    /// or load packs after this, not before.
+    #[cfg(feature = "packs")]
+    pub fn with_league(mut self, league: League) -> Self {
+        self.league = league;
+        self.registry = Cow::Borrowed(league.registry());
+        #[cfg(feature = "sqlite")]
+        {
+            self.store = Arc::new(SqliteStore::new(league.db_path()));
+        }
+        self
+    }
+
+    /// The league this engine plays
+    pub fn league(&self) -> League {
+        self.league
+    }
+
+    /// Loads boards from `store` instead of `nfl.sqlite`
+    pub fn with_store(mut self, store: impl StatsStore + 'static) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// The store boards are loaded from
+    pub fn store(&self) -> Arc<dyn StatsStore> {
+        Arc::clone(&self.store)
+    }
+
+    /// The seed this engine was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The engine's seeded RNG, for modes that need extra randomness (shuffles,
+    /// picks) that should replay with the same seed
+    pub fn rng_mut(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /// All registered question codes and their metadata
+    pub fn registry(&self) -> &QuestionRegistry {
+        &self.registry
+    }
+
+    /// Loads a question pack file into the registry (`packs` feature).
+    ///
+    /// Returns how many questions were added and the codes that were skipped
+    /// because a question with that code already exists. A pack for another
+    /// league is rejected, since its SQL won't run against this league's
+    /// database.
+    #[cfg(feature = "packs")]
+    pub fn load_pack(&mut self, path: &Path) -> Result<(usize, Vec<String>)> {
+        let questions = load_pack(path)?;
+        if let Some(other) = questions.iter().find(|q| q.league() != self.league) {
+            return Err(PackError::Invalid {
+                code: other.meta().code.clone(),
+                reason: format!(
+                    "it is an {} question and this session plays the {}",
+                    other.league().as_str().to_uppercase(),
+                    self.league.as_str().to_uppercase()
+                ),
+            }
+            .into());
+        }
+        let total = questions.len();
+        let skipped = merge_pack(self.registry.to_mut(), questions);
+        Ok((total - skipped.len(), skipped))
+    }
+
+    /// Picks a random question from the registry
+    pub fn random_question(&mut self) -> Option<Arc<dyn Question>> {
+        choose_random_question(&self.registry, &mut self.rng)
+    }
+
+    /// Picks a random question from `category` and of `difficulty`, each
+    /// left open when None
+    pub fn random_question_in(
+        &mut self,
+        category: Option<Category>,
+        difficulty: Option<Difficulty>,
+    ) -> Option<Arc<dyn Question>> {
+        if category.is_none() && difficulty.is_none() {
+            return self.random_question();
+        }
+        let subset = self.registry.filter(|meta| {
+            category.is_none_or(|category| meta.categories.contains(&category))
+                && difficulty.is_none_or(|difficulty| meta.difficulty == difficulty)
+        });
+        choose_random_question(&subset, &mut self.rng)
+    }
+
+    /// Resolves a user-typed code like "last10rushers_PIT" to a question and team
+    pub fn lookup(&self, input: &str) -> Option<ParsedRequest> {
+        parse_query(input, &self.registry)
+    }
+
+    /// Generates a question without touching the database.
+    ///
+    /// `fixed` holds any parameters the caller chose; the rest are random.
+    pub fn generate(&mut self, question: &dyn Question, fixed: &QuestionParams) -> QuestionSpec {
+        generate_question(question, fixed, &mut self.rng)
+    }
+
+    /// Runs an already-generated question's SQL, e.g. one from a quiz pack
+    pub fn load_board(&self, spec: &QuestionSpec) -> Result<Board> {
+        self.store.query_board(&spec.query)
+    }
+
+    /// Generates a question and loads its board, rerolling the parameters the
+    /// caller didn't fix while the board fails the quality check.
+    ///
+    /// Each attempt first counts the rows, so parameters that come up empty
+    /// are rerolled without fetching their board.
+    ///
+    /// Fixed parameters are checked against what the question supports first.
+    /// Once out of attempts the last board is returned anyway, since a
+    /// lopsided board beats no board at all. A board whose parameters are all
+    /// fixed is returned as-is, since rerolling would only repeat the query.
+    pub fn load_question(
+        &mut self,
+        question: &dyn Question,
+        fixed: &QuestionParams,
+    ) -> Result<(QuestionSpec, Board)> {
+        load_with(self.store.as_ref(), question, fixed, &mut self.rng)
+    }
+
+    /// Loads the board everyone gets for `seed`, such as the day's board from
+    /// [`daily_seed`].
+    ///
+    /// The question comes from the league's bundled questions only (the
+    /// built-in registry for the NFL), with an RNG of its own, so loaded packs
+    /// and earlier play don't change which board it is.
+    pub fn load_seeded(&self, seed: u64) -> Result<(QuestionSpec, Board)> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let question = choose_random_question(self.league.registry(), &mut rng)
+            .expect("every league ships questions");
+        load_with(
+            self.store.as_ref(),
+            question.as_ref(),
+            &QuestionParams::default(),
+            &mut rng,
+        )
+    }
+
+    /// Loads `count` different bundled questions everyone gets for `seed`,
+    /// such as the week's set, in the same order for everyone. As with
+    /// [`TriviaEngine::load_seeded`], packs and earlier play don't change them.
+    pub fn load_seeded_set(&self, seed: u64, count: usize) -> Result<Vec<(QuestionSpec, Board)>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let questions: Vec<_> = self.league.registry().values().collect();
+        let chosen: Vec<Arc<dyn Question>> = questions
+            .choose_multiple(&mut rng, count)
+            .map(|question| Arc::clone(question))
+            .collect();
+        chosen
+            .iter()
+            .map(|question| {
+                load_with(
+                    self.store.as_ref(),
+                    question.as_ref(),
+                    &QuestionParams::default(),
+                    &mut rng,
+                )
+            })
+            .collect()
+    }
+
+    /// Loads a set of questions at once, like [`TriviaEngine::load_question`]
+    /// for each, with one thread per question and one connection per thread
+    /// where the store can open more.
+    ///
+    /// Each question gets its own RNG seeded from the engine's, so a seed
+    /// gives the same set however the threads are scheduled. Results come
+    /// back in request order.
+    pub fn load_questions(
+        &mut self,
+        requests: &[(Arc<dyn Question>, QuestionParams)],
+    ) -> Vec<Result<(QuestionSpec, Board)>> {
+        let seeds: Vec<u64> = requests.iter().map(|_| self.rng.gen()).collect();
+        let store = self.store.as_ref();
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = requests
+                .iter()
+                .zip(seeds)
+                .map(|((question, fixed), seed)| {
+                    scope.spawn(move || {
+                        let own = store.for_thread();
+                        let store = own.as_deref().unwrap_or(store);
+                        let mut rng = StdRng::seed_from_u64(seed);
+                        load_with(store, question.as_ref(), fixed, &mut rng)
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        })
+    }
+}
+
+/// Generates `question` with `rng` and loads its board from `store`,
+/// rerolling the parameters the caller didn't fix while the board fails the
+/// quality check
+fn load_with(
+    store: &dyn StatsStore,
+    question: &dyn Question,
+    fixed: &QuestionParams,
+    rng: &mut impl RngCore,
+) -> Result<(QuestionSpec, Board)> {
+    validate_params(question, fixed)?;
+    let mut attempt = 1;
+    let mut last_query = None;
+    loop {
+        let spec = generate_question(question, fixed, rng);
+        let repeated = last_query.as_ref() == Some(&spec.query);
+        if !repeated && !worth_fetching(store, &spec.query, attempt)? {
+            attempt += 1;
+            last_query = Some(spec.query);
+            continue;
+        }
+        let board = store.query_board(&spec.query)?;
+        if !repeated && !keep_board(question, &board, attempt) {
+            attempt += 1;
+            last_query = Some(spec.query);
+            continue;
+        }
+        return Ok((spec, board));
+    }
+}
+
+/// The seed of the daily board at `time`: the number of whole UTC days since
+/// the Unix epoch, so it changes at midnight UTC for everyone
+pub fn daily_seed(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / SECS_PER_DAY)
+}
+
+/// Boards come from `nfl.sqlite` unless the caller picks another store
+#[cfg(feature = "sqlite")]
+fn default_store() -> Arc<dyn StatsStore> {
+    Arc::new(SqliteStore::default())
+}
+
+/// Without the `sqlite` feature there is no database to fall back on, so
+/// callers bring their own store with [`TriviaEngine::with_store`]
+#[cfg(not(feature = "sqlite"))]
+fn default_store() -> Arc<dyn StatsStore> {
+    Arc::new(MockStore::default())
+}
+
+/// Whether to fetch the board for `query` on the given attempt, or reroll
+/// because it has too few rows to pass the quality check
+pub(crate) fn worth_fetching(
+    store: &dyn StatsStore,
+    query: &Query,
+    attempt: usize,
+) -> Result<bool> {
+    Ok(attempt >= MAX_BOARD_ATTEMPTS || store.has_rows(query, MIN_BOARD_ROWS)?)
+}
+
+/// Whether a board on the given attempt should be played rather than rerolled
+pub(crate) fn keep_board(question: &dyn Question, board: &Board, attempt: usize) -> bool {
+    attempt >= MAX_BOARD_ATTEMPTS || check_board(board, question.is_last10()).is_ok()
+}
+
+impl Default for TriviaEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MockStore;
+
+    #[test]
+    fn test_same_seed_same_questions() {
+        let mut a = TriviaEngine::with_seed(99);
+        let mut b = TriviaEngine::with_seed(99);
+        assert_eq!(a.seed(), 99);
+
+        for _ in 0..5 {
+            let question_a = a.random_question().unwrap();
+            let question_b = b.random_question().unwrap();
+            assert_eq!(question_a.meta().code, question_b.meta().code);
+            assert_eq!(
+                a.generate(question_a.as_ref(), &QuestionParams::default())
+                    .query,
+                b.generate(question_b.as_ref(), &QuestionParams::default())
+                    .query
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_question_in_a_category_and_tier() {
+        let mut engine = TriviaEngine::with_seed(5);
+        for _ in 0..10 {
+            let question = engine
+                .random_question_in(Some(Category::Turnovers), None)
+                .unwrap();
+            assert!(question.meta().categories.contains(&Category::Turnovers));
+            let question = engine
+                .random_question_in(Some(Category::Rushing), Some(Difficulty::Easy))
+                .unwrap();
+            assert_eq!(question.meta().difficulty, Difficulty::Easy);
+            assert!(question.meta().categories.contains(&Category::Rushing));
+        }
+        assert!(engine.random_question_in(None, None).is_some());
+    }
+
+    #[test]
+    fn test_rng_mut_shares_the_seeded_stream() {
+        let mut a = TriviaEngine::with_seed(3);
+        let mut b = TriviaEngine::with_seed(3);
+        assert_eq!(a.rng_mut().gen::<u64>(), b.rng_mut().gen::<u64>());
+        assert_eq!(
+            a.random_question().unwrap().meta().code,
+            b.random_question().unwrap().meta().code
+        );
+    }
+
+    #[test]
+    fn test_load_question_from_store() {
+        let rows = (0..10)
+            .map(|i| vec![format!("Player {i}"), format!("{}", 1000 - i * 50)])
+            .collect();
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows,
+        };
+        let mut engine = TriviaEngine::with_seed(1).with_store(MockStore::new(board));
+
+        let parsed = engine.lookup("passyds_PIT").unwrap();
+        let fixed = QuestionParams::for_team(parsed.team.as_deref());
+        let (spec, board) = engine
+            .load_question(parsed.question.as_ref(), &fixed)
+            .unwrap();
+        assert_eq!(board.rows.len(), 10);
+        assert_eq!(spec.query.bindings[":team"], "PIT".into());
+    }
+
+    #[cfg(feature = "packs")]
+    #[test]
+    fn test_league_chooses_questions_and_packs() {
+        let pack = Path::new(env!("CARGO_MANIFEST_DIR")).join("packs/nba.toml");
+        let board = Board {
+            column_names: vec!["name".into(), "pts".into()],
+            rows: vec![vec!["Jayson Tatum".into(), "9000".into()]],
+        };
+        let nba = TriviaEngine::with_seed(1)
+            .with_league(League::Nba)
+            .with_store(MockStore::new(board));
+        assert_eq!(nba.league(), League::Nba);
+
+        let parsed = nba.lookup("points_BOS").unwrap();
+        assert_eq!(parsed.team.as_deref(), Some("BOS"));
+        assert!(nba.lookup("points_PIT").is_none());
+        assert!(nba.lookup("passyds_PIT").is_none());
+        let (spec, _) = nba.load_seeded(7).unwrap();
+        assert!(League::Nba.registry().contains_key(&spec.code));
+
+        let mut nfl = TriviaEngine::with_seed(1);
+        let e = nfl.load_pack(&pack).unwrap_err();
+        assert!(e
+            .to_string()
+            .contains("it is an NBA question and this session plays the NFL"));
+        assert_eq!(nfl.registry().len(), builtin_registry().len());
+    }
+
+    #[test]
+    fn test_empty_boards_are_counted_not_fetched() {
+        let empty = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![],
+        };
+        let store = Arc::new(MockStore::new(empty));
+        let mut engine = TriviaEngine::with_seed(1).with_store(Arc::clone(&store));
+
+        let question = engine.lookup("passyds_PIT").unwrap().question;
+        let (_, board) = engine
+            .load_question(question.as_ref(), &QuestionParams::default())
+            .unwrap();
+        assert!(board.rows.is_empty());
+        // Only the last attempt's board is fetched, since it's played anyway
+        assert_eq!(store.queries().len(), 1);
+    }
+
+    #[test]
+    fn test_load_questions_in_parallel() {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![vec!["Tom Brady".into(), "5000".into()]],
+        };
+        let load = |seed| {
+            let mut engine =
+                TriviaEngine::with_seed(seed).with_store(MockStore::new(board.clone()));
+            let question = |code: &str| Arc::clone(&engine.registry()[code]);
+            let requests = [
+                (question("passyds_TEAM"), QuestionParams::default()),
+                (question("last10rushers_TEAM"), QuestionParams::default()),
+                // Passing yards can't be fixed to one season
+                (
+                    question("passyds_TEAM"),
+                    QuestionParams {
+                        year: Some(2020),
+                        ..Default::default()
+                    },
+                ),
+            ];
+            engine.load_questions(&requests)
+        };
+
+        let results = load(5);
+        assert_eq!(results.len(), 3);
+        let (spec, board) = results[1].as_ref().unwrap();
+        assert_eq!(spec.code, "last10rushers_TEAM");
+        assert_eq!(board.rows[0][0], "Tom Brady");
+        assert!(results[2].is_err());
+        let again: Vec<_> = load(5)
+            .into_iter()
+            .map(|result| result.ok().map(|(spec, _)| spec))
+            .collect();
+        let specs: Vec<_> = results
+            .into_iter()
+            .map(|r| r.ok().map(|(spec, _)| spec))
+            .collect();
+        assert_eq!(specs, again);
+    }
+
+    #[test]
+    fn test_seeded_board_ignores_engine_rng() {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![vec!["Tom Brady".into(), "5000".into()]],
+        };
+        let a = TriviaEngine::with_seed(1).with_store(MockStore::new(board.clone()));
+        let mut b = TriviaEngine::with_seed(2).with_store(MockStore::new(board));
+        b.random_question();
+
+        let (spec_a, _) = a.load_seeded(20_000).unwrap();
+        let (spec_b, _) = b.load_seeded(20_000).unwrap();
+        assert_eq!(spec_a, spec_b);
+
+        let day = UNIX_EPOCH + std::time::Duration::from_secs(20_000 * SECS_PER_DAY + 3600);
+        assert_eq!(daily_seed(day), 20_000);
+
+        let set = a.load_seeded_set(20_000, 5).unwrap();
+        let codes: Vec<&str> = set.iter().map(|(spec, _)| spec.code.as_str()).collect();
+        assert_eq!(codes.len(), 5);
+        assert!(codes
+            .iter()
+            .enumerate()
+            .all(|(i, c)| !codes[..i].contains(c)));
+        assert_eq!(set, b.load_seeded_set(20_000, 5).unwrap());
+    }
+
+    #[test]
+    fn test_lookup_with_team() {
+        let engine = TriviaEngine::with_seed(1);
+        let parsed = engine.lookup("last10rushers_pit").unwrap();
+        assert_eq!(parsed.team.as_deref(), Some("PIT"));
+        assert!(engine.lookup("not_a_code").is_none());
+    }
+}
@@ -0,0 +1,107 @@
+//! Crate-wide error type
+//!
+//! Every fallible library call returns `KnowBallError`, so callers can tell a
+//! missing database apart from a bad query or a broken pack file and react
+//! without parsing messages.
+#[cfg(feature = "packs")]
+use crate::questions::pack::PackError;
+use crate::questions::ParamError;
+#[cfg(feature = "sqlite")]
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Anything that can go wrong while loading questions or boards
+#[derive(Debug, Error)]
+pub enum KnowBallError {
+    /// The stats database isn't where we expect it
+    #[cfg(feature = "sqlite")]
+    #[error(
+        "{} not found — run knowball from the directory that contains {}, or point --db or KNOWBALL_DB at it",
+        path.display(),
+        path.file_name().unwrap_or(path.as_os_str()).to_string_lossy()
+    )]
+    DbMissing { path: PathBuf },
+    /// A question's SQL failed to prepare or run
+    #[cfg(feature = "sqlite")]
+    #[error("SQL error: {0}")]
+    Sql(#[from] rusqlite::Error),
+    /// The database opened but lacks a table a question reads, so it is
+    /// probably not a stats database or is from an older build
+    #[cfg(feature = "sqlite")]
+    #[error("{path} has no {table} table — is it a Know Ball stats database?")]
+    MissingTable { path: String, table: String },
+    /// A question's SQL uses a named parameter its query doesn't bind
+    #[cfg(feature = "sqlite")]
+    #[error("unbound query parameter {0}")]
+    UnboundParameter(String),
+    /// The store has no board for a question's SQL
+    #[error("no board for query: {0}")]
+    NoBoard(String),
+    /// A store other than SQLite failed to run a query
+    #[error("store error: {0}")]
+    Store(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// Command-line or user input that couldn't be understood
+    #[error("{0}")]
+    Parse(String),
+    #[cfg(feature = "packs")]
+    #[error(transparent)]
+    Pack(#[from] PackError),
+    /// Caller-chosen question parameters the question can't use
+    #[error(transparent)]
+    Params(#[from] ParamError),
+    /// A blocking database task panicked or was cancelled
+    #[cfg(feature = "async")]
+    #[error("background task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+/// Shorthand for results that fail with [`KnowBallError`]
+pub type Result<T> = std::result::Result<T, KnowBallError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_db_missing_message_is_actionable() {
+        let e = KnowBallError::DbMissing {
+            path: PathBuf::from("nfl.sqlite"),
+        };
+        let msg = e.to_string();
+        assert!(msg.starts_with("nfl.sqlite not found"));
+        assert!(msg.contains("directory that contains nfl.sqlite"));
+        assert!(msg.ends_with("point --db or KNOWBALL_DB at it"));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_missing_table_names_the_file() {
+        let e = KnowBallError::MissingTable {
+            path: "stats.db".into(),
+            table: "seasons".into(),
+        };
+        assert_eq!(
+            e.to_string(),
+            "stats.db has no seasons table — is it a Know Ball stats database?"
+        );
+    }
+
+    #[test]
+    fn test_no_board_names_the_query() {
+        let e = KnowBallError::NoBoard("SELECT 1".into());
+        assert_eq!(e.to_string(), "no board for query: SELECT 1");
+    }
+
+    #[cfg(feature = "packs")]
+    #[test]
+    fn test_pack_error_is_transparent() {
+        let e: KnowBallError = PackError::UnknownFormat("xml".into()).into();
+        assert_eq!(
+            e.to_string(),
+            "unknown pack format 'xml', expected .toml, .json, .yaml, or .yml"
+        );
+    }
+}
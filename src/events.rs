@@ -0,0 +1,121 @@
+//! Typed game events and the observers that listen for them
+//!
+//! The game and session emit a `GameEvent` at each step of a round. Anything
+//! that wants to react (achievements, stats, overlays, another UI) subscribes
+//! a `GameObserver` to an `EventBus` instead of hooking into the game loop.
+use crate::game::{GameEnd, TriviaResult};
+use crate::sql_runner::Board;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Something that happened during a round
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+// One event per guess, so the size of `BoardFinished` doesn't matter
+#[allow(clippy::large_enum_variant)]
+pub enum GameEvent {
+    /// A board was loaded and play is about to begin
+    QuestionStarted {
+        code: String,
+        text: String,
+        total: usize,
+        /// The board with every name blank
+        board: Board,
+        /// Misses that end the round, or None for no limit
+        max_strikes: Option<usize>,
+    },
+    CorrectGuess {
+        row: usize,
+        name: String,
+        points: u32,
+    },
+    /// The player passed on a row, which is now showing for no points
+    RowPassed { row: usize, name: String },
+    /// A guess matched nothing; carries the new strike count
+    Strike { strikes: usize },
+    /// The round ended; sent once per board
+    BoardFinished { end: GameEnd, result: TriviaResult },
+}
+
+/// Receives every event emitted on the bus it is subscribed to
+pub trait GameObserver: Send {
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+impl<F> GameObserver for F
+where
+    F: FnMut(&GameEvent) + Send,
+{
+    fn on_event(&mut self, event: &GameEvent) {
+        self(event)
+    }
+}
+
+/// Fans events out to every subscribed observer, in subscription order
+#[derive(Default)]
+pub struct EventBus {
+    observers: Vec<Box<dyn GameObserver>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&mut self, observer: impl GameObserver + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    pub fn emit(&mut self, event: GameEvent) {
+        for observer in &mut self.observers {
+            observer.on_event(&event);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.observers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("observers", &self.observers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_every_observer_sees_event() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = EventBus::new();
+        for id in 0..2 {
+            let seen = Arc::clone(&seen);
+            bus.subscribe(move |e: &GameEvent| seen.lock().unwrap().push((id, e.clone())));
+        }
+
+        bus.emit(GameEvent::Strike { strikes: 1 });
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0], (0, GameEvent::Strike { strikes: 1 }));
+        assert_eq!(seen[1].0, 1);
+    }
+
+    #[test]
+    fn test_events_serialize_tagged() {
+        let event = GameEvent::Strike { strikes: 2 };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"type":"strike","strikes":2}"#);
+        assert_eq!(serde_json::from_str::<GameEvent>(&json).unwrap(), event);
+    }
+}
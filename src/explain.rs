@@ -0,0 +1,130 @@
+//! Query plan audit for maintainers (`--explain`)
+//!
+//! `ExplainStore` wraps a [`SqliteStore`] and, before each board query, asks
+//! SQLite for its plan with `EXPLAIN QUERY PLAN`. Every query is logged with
+//! its time, row count, and plan, and tables read without an index are
+//! flagged, so it's easy to see which questions need new indexes as the
+//! database grows.
+use crate::error::Result;
+use crate::sql_runner::{Board, Query};
+use crate::store::{SqliteStore, StatsStore};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Where `--explain` writes when no file is given
+pub const DEFAULT_EXPLAIN_LOG: &str = "know_ball_explain.log";
+
+/// A store that logs the plan and timing of every board query
+pub struct ExplainStore {
+    store: SqliteStore,
+    log: Mutex<Box<dyn Write + Send>>,
+}
+
+impl ExplainStore {
+    pub fn new(store: SqliteStore, log: impl Write + Send + 'static) -> Self {
+        Self {
+            store,
+            log: Mutex::new(Box::new(log)),
+        }
+    }
+}
+
+impl StatsStore for ExplainStore {
+    fn query_board(&self, query: &Query) -> Result<Board> {
+        let plan = self.store.query_plan(query)?;
+        let start = Instant::now();
+        let board = self.store.query_board(query)?;
+        let elapsed = start.elapsed();
+
+        let scans: Vec<&str> = plan.iter().filter_map(|step| full_scan(step)).collect();
+        let mut entry = format!(
+            "[{:.1} ms, {} rows]",
+            elapsed.as_secs_f64() * 1000.0,
+            board.rows.len()
+        );
+        if !scans.is_empty() {
+            entry.push_str(&format!(" FULL SCAN: {}", scans.join(", ")));
+        }
+        entry.push('\n');
+        for line in query.inline().trim().lines() {
+            entry.push_str(&format!("  {line}\n"));
+        }
+        for step in &plan {
+            entry.push_str(&format!("  | {step}\n"));
+        }
+        // The log is a diagnostic; a failed write shouldn't stop the game
+        let mut log = self.log.lock().unwrap();
+        let _ = writeln!(log, "{entry}").and_then(|()| log.flush());
+        Ok(board)
+    }
+
+    fn has_rows(&self, query: &Query, min: usize) -> Result<bool> {
+        self.store.has_rows(query, min)
+    }
+}
+
+/// The table a plan step reads row by row without an index, if it does.
+///
+/// SQLite describes those steps as `SCAN t` (`SCAN TABLE t` before 3.36);
+/// index scans say `USING`, and scans of subqueries and constant rows don't
+/// touch a table.
+fn full_scan(step: &str) -> Option<&str> {
+    let rest = step.strip_prefix("SCAN ")?;
+    let rest = rest.strip_prefix("TABLE ").unwrap_or(rest);
+    if rest.contains(" USING ") || rest.starts_with('(') || rest.starts_with("CONSTANT ROW") {
+        return None;
+    }
+    rest.split_whitespace().next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    #[test]
+    fn test_full_scan() {
+        assert_eq!(full_scan("SCAN s"), Some("s"));
+        assert_eq!(full_scan("SCAN TABLE seasons AS s"), Some("seasons"));
+        assert_eq!(full_scan("SCAN s USING INDEX idx_team"), None);
+        assert_eq!(
+            full_scan("SEARCH p USING INTEGER PRIMARY KEY (rowid=?)"),
+            None
+        );
+        assert_eq!(full_scan("SCAN (subquery-1)"), None);
+        assert_eq!(full_scan("SCAN CONSTANT ROW"), None);
+        assert_eq!(full_scan("USE TEMP B-TREE FOR ORDER BY"), None);
+    }
+
+    #[test]
+    fn test_logs_plans_and_flags_scans() {
+        let dir = std::env::temp_dir();
+        let db = dir.join(format!("know_ball_explain_{}.sqlite", std::process::id()));
+        let log = dir.join(format!("know_ball_explain_{}.log", std::process::id()));
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE players (id INTEGER PRIMARY KEY, name TEXT);
+             INSERT INTO players (name) VALUES ('Tom Brady');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = ExplainStore::new(SqliteStore::new(&db), std::fs::File::create(&log).unwrap());
+        store
+            .query_board(&Query::from("SELECT name FROM players"))
+            .unwrap();
+        store
+            .query_board(&Query::new("SELECT name FROM players WHERE id = :id").bind("id", 1))
+            .unwrap();
+        let written = std::fs::read_to_string(&log).unwrap();
+        std::fs::remove_file(&db).unwrap();
+        std::fs::remove_file(&log).unwrap();
+
+        let entries: Vec<&str> = written.split("\n\n").collect();
+        assert!(entries[0].contains(" ms, 1 rows] FULL SCAN: players\n"));
+        assert!(entries[0].contains("\n  SELECT name FROM players\n  | SCAN players"));
+        assert!(!entries[1].contains("FULL SCAN"));
+        assert!(entries[1].contains("| SEARCH players USING INTEGER PRIMARY KEY"));
+    }
+}
@@ -0,0 +1,645 @@
+//! Printable quizzes, playable quiz pages, and spreadsheet exports
+//!
+//! `render_quiz` lays out already-loaded boards as three parts: the questions
+//! with every clue column but the names, a blank answer sheet to hand out, and
+//! an answer key with point values for the host. `render_quiz_page` turns one
+//! board into a single HTML file that plays in the browser, `result_csv`
+//! turns a played board into CSV, and `render_anki` writes flashcards in
+//! Anki's text import format. Nothing here touches the database or the
+//! terminal.
+use crate::answer_match::{normalize_name, tokenize_name};
+use crate::game::{RowResult, TriviaResult, ANSWER_COL};
+use crate::questions::QuestionSpec;
+use crate::session::RoundRecord;
+use crate::sql_runner::Board;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Document format for [`render_quiz`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    /// A standalone page that starts the answer sheet and key on new pages
+    /// when printed
+    Html,
+}
+
+impl ExportFormat {
+    /// File extension for documents in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            _ => Err(format!("unknown format '{s}', expected md or html")),
+        }
+    }
+}
+
+/// One question of a quiz and the board its answers come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuizRound {
+    pub spec: QuestionSpec,
+    pub board: Board,
+}
+
+const TITLE: &str = "Know Ball Quiz";
+
+/// Renders the questions, a blank answer sheet, and the answer key
+pub fn render_quiz(rounds: &[QuizRound], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => render_markdown(rounds),
+        ExportFormat::Html => render_html(rounds),
+    }
+}
+
+/// Every column except the hidden names, with the row number first
+fn clue_table(board: &Board) -> (Vec<String>, Vec<Vec<String>>) {
+    let clues = |row: &[String]| -> Vec<String> {
+        row.iter()
+            .enumerate()
+            .filter(|(j, _)| *j != ANSWER_COL)
+            .map(|(_, v)| v.clone())
+            .collect()
+    };
+    let mut header = vec!["#".to_string()];
+    header.extend(clues(&board.column_names));
+    let rows = board
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut cells = vec![(i + 1).to_string()];
+            cells.extend(clues(row));
+            cells
+        })
+        .collect();
+    (header, rows)
+}
+
+/// Row number, name, scored stat, and points for the answer key
+fn key_table(board: &Board) -> (Vec<String>, Vec<Vec<String>>) {
+    let stat = board.column_names.last().cloned().unwrap_or_default();
+    let header = vec!["#".into(), "Player".into(), stat, "Points".into()];
+    let rows = board
+        .rows
+        .iter()
+        .zip(board.point_values())
+        .enumerate()
+        .map(|(i, (row, points))| {
+            vec![
+                (i + 1).to_string(),
+                row[ANSWER_COL].clone(),
+                row.last().cloned().unwrap_or_default(),
+                points.to_string(),
+            ]
+        })
+        .collect();
+    (header, rows)
+}
+
+/// Appends a Markdown table and a blank line, escaping pipes in cells
+pub(crate) fn markdown_table(out: &mut String, header: &[String], rows: &[Vec<String>]) {
+    let cell = |v: &String| v.replace('|', "\\|");
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = cells.iter().map(cell).collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+    out.push_str(&line(header));
+    out.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+    for row in rows {
+        out.push_str(&line(row));
+    }
+    out.push('\n');
+}
+
+fn render_markdown(rounds: &[QuizRound]) -> String {
+    let table = |out: &mut String, (header, rows): (Vec<String>, Vec<Vec<String>>)| {
+        markdown_table(out, &header, &rows)
+    };
+
+    let mut out = format!("# {TITLE}\n\n## Questions\n\n");
+    for (n, round) in rounds.iter().enumerate() {
+        let _ = writeln!(out, "### {}. {}\n", n + 1, round.spec.text);
+        table(&mut out, clue_table(&round.board));
+    }
+
+    out.push_str("## Answer sheet\n\nTeam: ______________________\n\n");
+    for (n, round) in rounds.iter().enumerate() {
+        let _ = writeln!(out, "### {}. {}\n", n + 1, round.spec.text);
+        for i in 1..=round.board.rows.len() {
+            let _ = writeln!(out, "{i}. ______________________");
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Answer key\n\n");
+    for (n, round) in rounds.iter().enumerate() {
+        let _ = writeln!(out, "### {}. {}\n", n + 1, round.spec.text);
+        table(&mut out, key_table(&round.board));
+    }
+    out
+}
+
+fn render_html(rounds: &[QuizRound]) -> String {
+    let table = |out: &mut String, (header, rows): (Vec<String>, Vec<Vec<String>>)| {
+        out.push_str("<table>\n<tr>");
+        for h in &header {
+            let _ = write!(out, "<th>{}</th>", escape_html(h));
+        }
+        out.push_str("</tr>\n");
+        for row in &rows {
+            out.push_str("<tr>");
+            for v in row {
+                let _ = write!(out, "<td>{}</td>", escape_html(v));
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    };
+    let heading = |out: &mut String, n: usize, round: &QuizRound| {
+        let _ = writeln!(out, "<h3>{}. {}</h3>", n + 1, escape_html(&round.spec.text));
+    };
+
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{TITLE}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; }}\n\
+         table {{ border-collapse: collapse; margin-bottom: 1em; }}\n\
+         th, td {{ border: 1px solid #999; padding: 2px 8px; }}\n\
+         .page {{ page-break-before: always; }}\n\
+         .blank {{ display: inline-block; width: 16em; border-bottom: 1px solid #000; }}\n\
+         </style>\n</head>\n<body>\n<h1>{TITLE}</h1>\n<section>\n<h2>Questions</h2>\n"
+    );
+    for (n, round) in rounds.iter().enumerate() {
+        heading(&mut out, n, round);
+        table(&mut out, clue_table(&round.board));
+    }
+
+    out.push_str(
+        "</section>\n<section class=\"page\">\n<h2>Answer sheet</h2>\n\
+         <p>Team: <span class=\"blank\"></span></p>\n",
+    );
+    for (n, round) in rounds.iter().enumerate() {
+        heading(&mut out, n, round);
+        out.push_str("<ol>\n");
+        for _ in &round.board.rows {
+            out.push_str("<li><span class=\"blank\"></span></li>\n");
+        }
+        out.push_str("</ol>\n");
+    }
+
+    out.push_str("</section>\n<section class=\"page\">\n<h2>Answer key</h2>\n");
+    for (n, round) in rounds.iter().enumerate() {
+        heading(&mut out, n, round);
+        table(&mut out, key_table(&round.board));
+    }
+    out.push_str("</section>\n</body>\n</html>\n");
+    out
+}
+
+const QUIZ_PAGE: &str = include_str!("templates/quiz_page.html");
+
+/// A self-contained page that plays one board in the browser.
+///
+/// The names never appear in the page. Each row carries a hash of every
+/// spelling a guess may use (the full name, or any run of its words that no
+/// other row shares) along with the name encrypted under a key from that
+/// spelling, so only a correct guess reveals it. This keeps the answers out
+/// of view, not out of reach of someone willing to hash a roster.
+pub fn render_quiz_page(round: &QuizRound) -> String {
+    let board = &round.board;
+    let spellings: Vec<(Vec<String>, Vec<String>)> = board
+        .rows
+        .iter()
+        .map(|row| spellings(&row[ANSWER_COL]))
+        .collect();
+    let shared = |run: &String, row: usize| {
+        spellings.iter().enumerate().any(|(other, (names, runs))| {
+            other != row && (names.contains(run) || runs.contains(run))
+        })
+    };
+    let list = |items: Vec<String>| format!("[{}]", items.join(","));
+
+    let columns = board
+        .column_names
+        .iter()
+        .enumerate()
+        .filter(|(j, _)| *j != ANSWER_COL)
+        .map(|(_, c)| js_string(c))
+        .collect();
+    let rows = board
+        .rows
+        .iter()
+        .zip(board.point_values())
+        .enumerate()
+        .map(|(i, (row, points))| {
+            let clues = row
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != ANSWER_COL)
+                .map(|(_, v)| js_string(v))
+                .collect();
+            let (names, runs) = &spellings[i];
+            let runs = runs.iter().filter(|run| !shared(run, i));
+            let answers = names
+                .iter()
+                .chain(runs)
+                .map(|spelling| {
+                    let check = format!("{:08x}", fnv1a32(&format!("kb:{spelling}")));
+                    let key = fnv1a32(&format!("key:{spelling}"));
+                    let name = xorshift_cipher(key, row[ANSWER_COL].as_bytes());
+                    let name: String = name.iter().map(|b| format!("{b:02x}")).collect();
+                    format!("[\"{check}\",\"{name}\"]")
+                })
+                .collect();
+            format!(
+                "{{\"clues\":{},\"points\":{points},\"answers\":{}}}",
+                list(clues),
+                list(answers)
+            )
+        })
+        .collect();
+    let quiz = format!("{{\"columns\":{},\"rows\":{}}}", list(columns), list(rows));
+
+    QUIZ_PAGE
+        .replace("{{title}}", TITLE)
+        .replace("{{question}}", &escape_html(&round.spec.text))
+        .replace("{{quiz}}", &quiz)
+}
+
+/// The normalized spellings that name this player: the full name with and
+/// without a suffix, and the shorter runs of consecutive words in it
+fn spellings(name: &str) -> (Vec<String>, Vec<String>) {
+    let full = normalize_name(name);
+    let base = tokenize_name(name).base;
+    let words: Vec<&str> = base.split(' ').collect();
+    let mut runs = Vec::new();
+    for len in (1..words.len()).rev() {
+        for run in words.windows(len) {
+            let run = run.join(" ");
+            if !runs.contains(&run) && run != full {
+                runs.push(run);
+            }
+        }
+    }
+    let mut names = vec![full];
+    if base != names[0] {
+        names.push(base);
+    }
+    (names, runs)
+}
+
+/// 32-bit FNV-1a, matching the page's `fnv1a`
+fn fnv1a32(text: &str) -> u32 {
+    text.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// XORs `bytes` with an xorshift32 stream seeded by `key`, matching the
+/// page's `unlock`
+fn xorshift_cipher(key: u32, bytes: &[u8]) -> Vec<u8> {
+    let mut x = key.max(1);
+    bytes
+        .iter()
+        .map(|b| {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            b ^ x as u8
+        })
+        .collect()
+}
+
+/// A JSON string literal that is also safe inside a `<script>` element
+fn js_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '<' => out.push_str("\\u003c"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// A played board as CSV: every board column, then each row's points and
+/// whether it was guessed
+pub fn result_csv(result: &TriviaResult) -> String {
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = cells.iter().map(|c| escape_csv(c)).collect();
+        cells.join(",") + "\n"
+    };
+    let mut header = result.column_names.clone();
+    header.extend(["points".to_string(), "guessed".to_string()]);
+    let mut out = line(&header);
+    for row in &result.rows {
+        let mut cells = row.cells.clone();
+        cells.extend([row.points.to_string(), row.guessed.to_string()]);
+        out.push_str(&line(&cells));
+    }
+    out
+}
+
+/// File name for a board's CSV, from its question code and a Unix timestamp
+pub fn result_csv_name(result: &TriviaResult, timestamp: u64) -> String {
+    let code = result
+        .spec
+        .as_ref()
+        .map_or("custom", |spec| spec.code.as_str());
+    let code: String = code
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .collect();
+    format!("{code}_{timestamp}.csv")
+}
+
+/// One flashcard: a board row's clues on the front, the player on the back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flashcard {
+    pub front: String,
+    pub back: String,
+    /// The question code, so a deck can be filtered by question in Anki
+    pub tag: String,
+}
+
+impl Flashcard {
+    /// The card for one row of `question`'s board. The front is the question
+    /// and every column but the name, labeled when the column names are known.
+    pub fn new(code: &str, question: &str, column_names: &[String], cells: &[String]) -> Self {
+        let clues: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != ANSWER_COL)
+            .map(|(j, value)| match column_names.get(j) {
+                Some(column) => format!("{column}: {value}"),
+                None => value.clone(),
+            })
+            .collect();
+        Self {
+            front: format!("{question} — {}", clues.join(", ")),
+            back: cells.get(ANSWER_COL).cloned().unwrap_or_default(),
+            tag: code.to_string(),
+        }
+    }
+}
+
+/// A card for every answer the player didn't find this session, oldest
+/// round first. Each row is carded once, however many times it was missed.
+pub fn missed_flashcards(history: &[RoundRecord]) -> Vec<Flashcard> {
+    let mut cards: Vec<Flashcard> = Vec::new();
+    for round in history {
+        let missed = round.rows.iter().filter(|row| !row.guessed);
+        for card in missed.map(|row| row_card(round, row)) {
+            if !cards.contains(&card) {
+                cards.push(card);
+            }
+        }
+    }
+    cards
+}
+
+fn row_card(round: &RoundRecord, row: &RowResult) -> Flashcard {
+    Flashcard::new(&round.code, &round.text, &round.column_names, &row.cells)
+}
+
+/// A card for every row of a loaded board
+pub fn board_flashcards(round: &QuizRound) -> Vec<Flashcard> {
+    round
+        .board
+        .rows
+        .iter()
+        .map(|row| {
+            Flashcard::new(
+                &round.spec.code,
+                &round.spec.text,
+                &round.board.column_names,
+                row,
+            )
+        })
+        .collect()
+}
+
+/// Cards as an Anki text import: one tab-separated front, back, and tag per
+/// line, after the header lines that tell Anki how to read them
+pub fn render_anki(cards: &[Flashcard]) -> String {
+    let mut out = String::from("#separator:tab\n#html:false\n#tags column:3\n");
+    for card in cards {
+        let _ = writeln!(
+            out,
+            "{}\t{}\t{}",
+            anki_field(&card.front),
+            anki_field(&card.back),
+            anki_field(&card.tag).replace(' ', "_")
+        );
+    }
+    out
+}
+
+/// Tabs and line breaks would start a new field or note, so they become
+/// spaces; a leading quote would start a quoted field, so it is doubled
+fn anki_field(s: &str) -> String {
+    let flat = s.replace(['\t', '\n', '\r'], " ");
+    if flat.starts_with('"') {
+        format!("\"{}\"", flat.replace('"', "\"\""))
+    } else {
+        flat
+    }
+}
+
+/// Quotes a field when it holds a comma, quote, or line break
+fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::QuestionParams;
+    use crate::sql_runner::Query;
+
+    fn round() -> QuizRound {
+        QuizRound {
+            spec: QuestionSpec {
+                code: "passyds_TEAM".into(),
+                params: QuestionParams::default(),
+                text: "Top passers for PIT & friends.".into(),
+                query: Query::default(),
+            },
+            board: Board {
+                column_names: vec!["name".into(), "team".into(), "yards".into()],
+                rows: vec![
+                    vec!["Ben Roethlisberger".into(), "PIT".into(), "5000".into()],
+                    vec!["Mason Rudolph".into(), "PIT".into(), "1000".into()],
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn test_markdown_hides_names_until_the_key() {
+        let md = render_quiz(&[round()], ExportFormat::Markdown);
+        let (questions, key) = md.split_once("## Answer key").unwrap();
+        assert!(questions.contains("| 1 | PIT | 5000 |"));
+        assert!(questions.contains("2. ______"));
+        assert!(!questions.contains("Rudolph"));
+
+        let points = round().board.point_values();
+        assert!(key.contains(&format!("| 2 | Mason Rudolph | 1000 | {} |", points[1])));
+    }
+
+    #[test]
+    fn test_html_escapes_and_breaks_pages() {
+        let html = render_quiz(&[round()], ExportFormat::Html);
+        assert!(html.contains("<h3>1. Top passers for PIT &amp; friends.</h3>"));
+        assert_eq!(html.matches("class=\"page\"").count(), 2);
+        assert_eq!(html.matches("<li>").count(), 2);
+    }
+
+    #[test]
+    fn test_quiz_page_hides_names_behind_hashes() {
+        let mut round = round();
+        round
+            .board
+            .rows
+            .push(vec!["Diontae Johnson".into(), "PIT".into(), "2000".into()]);
+        round
+            .board
+            .rows
+            .push(vec!["Calvin Johnson".into(), "DET".into(), "1500".into()]);
+        let page = render_quiz_page(&round);
+        assert!(!page.contains("Rudolph"));
+        assert!(page.contains("<h2>Top passers for PIT &amp; friends.</h2>"));
+        assert!(page.contains(r#""clues":["PIT","1000"]"#));
+
+        let hash = |s: &str| format!("{:08x}", fnv1a32(&format!("kb:{s}")));
+        assert!(page.contains(&hash("rudolph")));
+        assert!(page.contains(&hash("diontae johnson")));
+        // Shared by two rows, so it names neither
+        assert!(!page.contains(&hash("johnson")));
+
+        let key = fnv1a32("key:rudolph");
+        let locked = xorshift_cipher(key, b"Mason Rudolph");
+        assert_eq!(xorshift_cipher(key, &locked), b"Mason Rudolph");
+    }
+
+    #[test]
+    fn test_spellings() {
+        let (names, runs) = spellings("Amon-Ra St. Brown");
+        assert_eq!(names, ["amon ra st brown"]);
+        assert!(runs.contains(&"st brown".to_string()));
+        assert!(runs.contains(&"amon ra".to_string()));
+        let (names, runs) = spellings("Marvin Harrison Jr.");
+        assert_eq!(names, ["marvin harrison jr", "marvin harrison"]);
+        assert_eq!(runs, ["marvin", "harrison"]);
+    }
+
+    #[test]
+    fn test_result_csv() {
+        let QuizRound { spec, board } = round();
+        let mut game = crate::TriviaGame::new(spec.text.clone(), board).with_spec(spec);
+        game.submit_guess("Rudolph");
+        let mut result = game.result();
+        result.rows[0].cells[1] = "P,IT".into();
+
+        let csv = result_csv(&result);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "name,team,yards,points,guessed");
+        assert!(lines[1].starts_with("Ben Roethlisberger,\"P,IT\",5000,"));
+        assert!(lines[1].ends_with(",false"));
+        assert!(lines[2].ends_with(",true"));
+        assert_eq!(
+            result_csv_name(&result, 1700000000),
+            "passyds_TEAM_1700000000.csv"
+        );
+    }
+
+    #[test]
+    fn test_anki_deck() {
+        let round = round();
+        let cards = board_flashcards(&round);
+        assert_eq!(cards.len(), 2);
+        assert_eq!(
+            cards[1],
+            Flashcard {
+                front: "Top passers for PIT & friends. — team: PIT, yards: 1000".into(),
+                back: "Mason Rudolph".into(),
+                tag: "passyds_TEAM".into(),
+            }
+        );
+
+        let deck = render_anki(&cards);
+        let lines: Vec<&str> = deck.lines().collect();
+        assert_eq!(
+            lines[..3],
+            ["#separator:tab", "#html:false", "#tags column:3"]
+        );
+        assert_eq!(
+            lines[4],
+            "Top passers for PIT & friends. — team: PIT, yards: 1000\tMason Rudolph\tpassyds_TEAM"
+        );
+        assert_eq!(anki_field("a\tb\nc"), "a b c");
+        assert_eq!(anki_field("\"Q\" Ryan"), "\"\"\"Q\"\" Ryan\"");
+    }
+
+    #[test]
+    fn test_missed_flashcards_skip_found_and_repeats() {
+        let QuizRound { spec, board } = round();
+        let mut game = crate::TriviaGame::new(spec.text.clone(), board).with_spec(spec.clone());
+        game.submit_guess("Rudolph");
+        game.reveal();
+        let record = RoundRecord::from_result(&spec, &game.result());
+
+        let cards = missed_flashcards(&[record.clone(), record]);
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].back, "Ben Roethlisberger");
+        assert!(cards[0].front.ends_with("— team: PIT, yards: 5000"));
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!("MD".parse(), Ok(ExportFormat::Markdown));
+        assert_eq!("html".parse(), Ok(ExportFormat::Html));
+        assert!("pdf".parse::<ExportFormat>().is_err());
+    }
+}
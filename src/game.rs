@@ -0,0 +1,1074 @@
+//! IO-free trivia game state machine
+//!
+//! `TriviaGame` owns one board and tracks guesses, strikes, and score. It never
+//! reads input or prints anything, so the CLI, bots, and servers can all drive
+//! it and its rules can be unit-tested directly.
+use crate::answer_match::{is_last_name, tokenize_name, Matcher, SubstringMatcher};
+use crate::events::{EventBus, GameEvent};
+use crate::ghost::Ghost;
+use crate::questions::QuestionSpec;
+use crate::rules::GameRules;
+use crate::sql_runner::Board;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+// std's Instant panics on wasm32-unknown-unknown
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+/// Strikes allowed before the round ends under the classic rules
+pub const MAX_STRIKES: usize = 3;
+
+/// Correct guesses in a row before each further one earns a streak bonus
+pub const STREAK_LENGTH: usize = 5;
+
+/// Perfect boards in a row, counting the latest, from which each one earns a
+/// streak bonus
+pub const PERFECT_STREAK_LENGTH: usize = 2;
+
+/// What points are multiplied by while a streak is on
+pub const STREAK_MULTIPLIER: f64 = 1.1;
+
+/// The extra points [`STREAK_MULTIPLIER`] adds to `points`
+pub fn streak_bonus(points: u32) -> u32 {
+    (points as f64 * (STREAK_MULTIPLIER - 1.0)).round() as u32
+}
+
+/// Index of the hidden player-name column on every board
+pub const ANSWER_COL: usize = 0;
+
+/// Everything about a played round, so summaries, exports, and saved
+/// sessions don't have to rebuild it from the board
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriviaResult {
+    /// The generated question, when the game was started from one
+    pub spec: Option<QuestionSpec>,
+    pub question: String,
+    /// The board's column names, matching each row's `cells`
+    #[serde(default)]
+    pub column_names: Vec<String>,
+    pub score: u32,
+    /// Number of rows on the board
+    pub total: usize,
+    pub rows: Vec<RowResult>,
+    pub strikes: usize,
+    pub hints: usize,
+    /// Most correct guesses in a row
+    #[serde(default)]
+    pub best_streak: usize,
+    /// Extra points from guesses made on a streak, on top of `score`
+    #[serde(default)]
+    pub streak_bonus: u32,
+    /// Time from the start of the round to its end, or to now if still going
+    pub elapsed: Duration,
+    /// Why the round ended, or None if it is still in progress
+    pub end: Option<GameEnd>,
+}
+
+impl TriviaResult {
+    /// Number of rows the player found
+    pub fn correct(&self) -> usize {
+        self.rows.iter().filter(|r| r.guessed).count()
+    }
+}
+
+/// One board row in a result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowResult {
+    pub name: String,
+    /// The scored stat, from the board's last column
+    pub stat: String,
+    pub points: u32,
+    pub guessed: bool,
+    /// Whether the player passed on the row, revealing it for no points
+    #[serde(default)]
+    pub passed: bool,
+    /// Every column of the board row, name first
+    #[serde(default)]
+    pub cells: Vec<String>,
+    /// When the row was found, from the start of the round
+    #[serde(default)]
+    pub found_at: Option<Duration>,
+}
+
+/// What happened when a guess was submitted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GuessOutcome {
+    /// The guess revealed a row
+    Correct {
+        row: usize,
+        name: String,
+        points: u32,
+    },
+    /// The guess only matches rows that are already showing
+    AlreadyGuessed,
+    /// The guess fits several unguessed `rows` equally well. Nothing is
+    /// revealed and no strike is given: the player can say more, or settle
+    /// it with [`TriviaGame::choose`].
+    Ambiguous { rows: Vec<usize> },
+    /// The guess matched nothing; carries the new strike count
+    Strike { strikes: usize },
+    /// The guess was blank and was ignored
+    Empty,
+    /// The round is already over
+    GameOver,
+    /// The clock is paused; nothing is counted until the round resumes
+    Paused,
+}
+
+/// Why a round ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameEnd {
+    Perfect,
+    StruckOut,
+    GaveUp,
+    /// The board's time limit ran out
+    TimeUp,
+    /// The player moved on, keeping the points earned so far
+    Skipped,
+}
+
+/// State of one board being played
+#[derive(Debug)]
+pub struct TriviaGame {
+    question: String,
+    spec: Option<QuestionSpec>,
+    board: Board,
+    point_values: Vec<u32>,
+    guessed: Vec<bool>,
+    passed: Vec<bool>,
+    found_at: Vec<Option<Duration>>,
+    correct: usize,
+    strikes: usize,
+    score: u32,
+    /// Correct guesses since the last strike
+    streak: usize,
+    best_streak: usize,
+    streak_bonus: u32,
+    gave_up: bool,
+    skipped: bool,
+    hints: usize,
+    started: Instant,
+    finished: Option<Instant>,
+    /// When the clock was paused, while it is
+    paused_at: Option<Instant>,
+    /// Time spent paused before now, which the clock doesn't count
+    paused_for: Duration,
+    rules: GameRules,
+    matcher: Arc<dyn Matcher>,
+    events: EventBus,
+    ghost: Option<Ghost>,
+    /// The rows the last guess was ambiguous between
+    pending: Vec<usize>,
+}
+
+impl TriviaGame {
+    /// Starts a round on an already-fetched board
+    pub fn new(question: impl Into<String>, board: Board) -> Self {
+        Self::with_events(question, board, EventBus::new())
+    }
+
+    /// Starts a round that reports each guess and the finish to `events`
+    pub fn with_events(question: impl Into<String>, board: Board, events: EventBus) -> Self {
+        let point_values = board.point_values();
+        let guessed = vec![false; board.rows.len()];
+        let passed = vec![false; board.rows.len()];
+        let found_at = vec![None; board.rows.len()];
+        Self {
+            question: question.into(),
+            spec: None,
+            board,
+            point_values,
+            guessed,
+            passed,
+            found_at,
+            correct: 0,
+            strikes: 0,
+            score: 0,
+            streak: 0,
+            best_streak: 0,
+            streak_bonus: 0,
+            gave_up: false,
+            skipped: false,
+            hints: 0,
+            started: Instant::now(),
+            finished: None,
+            paused_at: None,
+            paused_for: Duration::ZERO,
+            rules: GameRules::default(),
+            matcher: Arc::new(SubstringMatcher),
+            events,
+            ghost: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Attaches the generated question so it is carried into the result
+    pub fn with_spec(mut self, spec: QuestionSpec) -> Self {
+        self.spec = Some(spec);
+        self
+    }
+
+    /// Checks guesses with `matcher` instead of the default substring matching
+    pub fn with_matcher(mut self, matcher: Arc<dyn Matcher>) -> Self {
+        self.matcher = matcher;
+        self
+    }
+
+    /// Plays the round under `rules` instead of the classic ones
+    pub fn with_rules(mut self, rules: GameRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Ends the round once `limit` has passed since it started
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.rules.time_limit = Some(limit);
+        self
+    }
+
+    /// Races a previous attempt at the same board, for frontends to show
+    /// alongside this one
+    pub fn with_ghost(mut self, ghost: Ghost) -> Self {
+        self.ghost = Some(ghost);
+        self
+    }
+
+    /// Hands back the event bus so it can be reused for the next board
+    pub fn take_events(&mut self) -> EventBus {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Checks a guess against the hidden names and updates the round.
+    ///
+    /// Unguessed rows are tried first, so guessing a name that is already
+    /// found can still find another player. Of the rows the matcher accepts,
+    /// the guess means the ones it names most closely: the full name with the
+    /// same suffix, then the full name, then the last name, then any part of
+    /// it. If that leaves players with different names, nothing is revealed
+    /// and the outcome is [`GuessOutcome::Ambiguous`]; rows that share one
+    /// name are found in board order, since typing more couldn't tell them
+    /// apart.
+    pub fn submit_guess(&mut self, guess: &str) -> GuessOutcome {
+        if self.check_clock() {
+            return GuessOutcome::GameOver;
+        }
+        if self.is_paused() {
+            return GuessOutcome::Paused;
+        }
+        let guess = guess.trim();
+        if guess.is_empty() {
+            return GuessOutcome::Empty;
+        }
+
+        self.pending.clear();
+        let rows = &self.board.rows;
+        let matches = |i: usize| self.matcher.matches(guess, &rows[i][ANSWER_COL]);
+        let mut candidates: Vec<usize> = (0..rows.len())
+            .filter(|&i| !self.guessed[i] && !self.passed[i] && matches(i))
+            .collect();
+        if candidates.len() > 1 {
+            let wanted = tokenize_name(guess);
+            let closeness = |i: &usize| {
+                let answer = &rows[*i][ANSWER_COL];
+                let name = tokenize_name(answer);
+                if name == wanted {
+                    3
+                } else if name.base == wanted.base {
+                    2
+                } else if is_last_name(guess, answer) {
+                    1
+                } else {
+                    0
+                }
+            };
+            let best = candidates.iter().map(closeness).max();
+            candidates.retain(|i| Some(closeness(i)) == best);
+            let first = tokenize_name(&rows[candidates[0]][ANSWER_COL]);
+            if candidates
+                .iter()
+                .any(|&i| tokenize_name(&rows[i][ANSWER_COL]) != first)
+            {
+                self.pending = candidates.clone();
+                return GuessOutcome::Ambiguous { rows: candidates };
+            }
+        }
+
+        if let Some(&i) = candidates.first() {
+            return self.find(i);
+        }
+
+        if (0..rows.len()).any(|i| (self.guessed[i] || self.passed[i]) && matches(i)) {
+            return GuessOutcome::AlreadyGuessed;
+        }
+
+        self.strikes += 1;
+        self.streak = 0;
+        self.events.emit(GameEvent::Strike {
+            strikes: self.strikes,
+        });
+        self.emit_finished();
+        GuessOutcome::Strike {
+            strikes: self.strikes,
+        }
+    }
+
+    /// Settles the last guess, if it was ambiguous, as its `candidate`th row
+    /// (counting from 0, in the order [`GuessOutcome::Ambiguous`] listed
+    /// them). None if there is no such candidate; the choice only stands until
+    /// the next guess.
+    pub fn choose(&mut self, candidate: usize) -> Option<GuessOutcome> {
+        let row = *self.pending.get(candidate)?;
+        if self.is_paused() {
+            return Some(GuessOutcome::Paused);
+        }
+        self.pending.clear();
+        if self.check_clock() || self.guessed[row] {
+            return None;
+        }
+        Some(self.find(row))
+    }
+
+    /// The rows the last guess was ambiguous between, empty if it wasn't
+    pub fn pending(&self) -> &[usize] {
+        &self.pending
+    }
+
+    /// Reveals unguessed `row` as found by the player
+    fn find(&mut self, row: usize) -> GuessOutcome {
+        self.guessed[row] = true;
+        self.found_at[row] = Some(self.elapsed());
+        self.correct += 1;
+        let points = self.point_values[row];
+        let name = self.board.rows[row][ANSWER_COL].clone();
+        self.score += points;
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        if self.streak > STREAK_LENGTH {
+            self.streak_bonus += streak_bonus(points);
+        }
+        self.events.emit(GameEvent::CorrectGuess {
+            row,
+            name: name.clone(),
+            points,
+        });
+        self.emit_finished();
+        GuessOutcome::Correct { row, name, points }
+    }
+
+    /// Whether the guess names any row, found or not, without counting it as
+    /// a guess. Modes that keep their own strikes check this first and only
+    /// submit guesses that can't miss.
+    pub fn names_any(&self, guess: &str) -> bool {
+        let guess = guess.trim();
+        !guess.is_empty()
+            && self
+                .board
+                .rows
+                .iter()
+                .any(|row| self.matcher.matches(guess, &row[ANSWER_COL]))
+    }
+
+    /// Gives up on one row: reveals it for no points and without a strike, so
+    /// an answer the player can't get doesn't cost the whole board. Returns
+    /// the name, or None if the round is over or paused, the row is already
+    /// showing, or the rules don't allow reveals.
+    pub fn pass(&mut self, row: usize) -> Option<String> {
+        if !self.rules.allow_reveal
+            || self.check_clock()
+            || self.is_paused()
+            || row >= self.total()
+            || self.guessed[row]
+            || self.passed[row]
+        {
+            return None;
+        }
+        self.pending.clear();
+        self.passed[row] = true;
+        let name = self.board.rows[row][ANSWER_COL].clone();
+        self.events.emit(GameEvent::RowPassed {
+            row,
+            name: name.clone(),
+        });
+        self.emit_finished();
+        Some(name)
+    }
+
+    /// Gives up on the round, keeping the points already earned
+    pub fn reveal(&mut self) {
+        if !self.check_clock() {
+            self.gave_up = true;
+            self.emit_finished();
+        }
+    }
+
+    /// Moves on from the round, keeping the points already earned. The board
+    /// ends like [`reveal`](Self::reveal), but as a skip rather than giving
+    /// up.
+    pub fn skip(&mut self) {
+        if !self.check_clock() {
+            self.skipped = true;
+            self.emit_finished();
+        }
+    }
+
+    /// Stops the clock of a timed round until [`resume`](Self::resume).
+    /// Frontends hide the board meanwhile, and guesses, passes, and hints
+    /// aren't taken. Returns false if the round has no clock, is over, or is
+    /// already paused.
+    pub fn pause(&mut self) -> bool {
+        if self.time_limit().is_none() || self.is_paused() || self.check_clock() {
+            return false;
+        }
+        self.paused_at = Some(Instant::now());
+        true
+    }
+
+    /// Starts the clock again after [`pause`](Self::pause). Returns false if
+    /// it wasn't paused.
+    pub fn resume(&mut self) -> bool {
+        match self.paused_at.take() {
+            Some(paused_at) => {
+                self.paused_for += paused_at.elapsed();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// The initials of a hidden row's name, e.g. "M.R." for Mason Rudolph,
+    /// counted as a hint. Returns None if the round is over or paused, the
+    /// row is already showing, or the rules don't allow hints.
+    pub fn hint(&mut self, row: usize) -> Option<String> {
+        if !self.rules.allow_hints
+            || self.check_clock()
+            || self.is_paused()
+            || row >= self.total()
+            || self.guessed[row]
+            || self.passed[row]
+        {
+            return None;
+        }
+        self.record_hint();
+        let initials = self.board.rows[row][ANSWER_COL]
+            .split_whitespace()
+            .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+            .map(|c| format!("{}.", c.to_uppercase()))
+            .collect();
+        Some(initials)
+    }
+
+    /// Counts a hint the frontend showed the player. Returns false, counting
+    /// nothing, if the rules don't allow hints.
+    pub fn record_hint(&mut self) -> bool {
+        if self.rules.allow_hints {
+            self.hints += 1;
+        }
+        self.rules.allow_hints
+    }
+
+    /// Ends the round if its time limit has run out, for frontends to call
+    /// while they wait on the player. Returns true once the round is over.
+    pub fn check_clock(&mut self) -> bool {
+        self.emit_finished();
+        self.is_over()
+    }
+
+    /// Stops the clock and sends `BoardFinished` if the last change ended the round
+    fn emit_finished(&mut self) {
+        if self.finished.is_some() {
+            return;
+        }
+        if let Some(end) = self.end() {
+            self.resume();
+            self.finished = Some(Instant::now());
+            let result = self.result();
+            self.events.emit(GameEvent::BoardFinished { end, result });
+        }
+    }
+
+    /// Returns true once every row is found, the strikes or the time run out,
+    /// or the player gives up
+    pub fn is_over(&self) -> bool {
+        self.end().is_some()
+    }
+
+    /// Why the round ended, or None while it is still in progress. Passing on
+    /// the last hidden row ends it as if the player gave up.
+    pub fn end(&self) -> Option<GameEnd> {
+        if self.correct == self.total() {
+            Some(GameEnd::Perfect)
+        } else if self.rules.strikes.is_some_and(|max| self.strikes >= max) {
+            Some(GameEnd::StruckOut)
+        } else if self.skipped {
+            Some(GameEnd::Skipped)
+        } else if self.gave_up || self.correct + self.passed() == self.total() {
+            Some(GameEnd::GaveUp)
+        } else if self.remaining() == Some(Duration::ZERO) {
+            Some(GameEnd::TimeUp)
+        } else {
+            None
+        }
+    }
+
+    pub fn question(&self) -> &str {
+        &self.question
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn point_values(&self) -> &[u32] {
+        &self.point_values
+    }
+
+    /// A copy of the board with hidden names blanked until the round ends
+    pub fn visible_board(&self) -> Board {
+        let mut board = self.board.clone();
+        if !self.is_over() {
+            for (i, row) in board.rows.iter_mut().enumerate() {
+                if !self.guessed[i] && !self.passed[i] {
+                    row[ANSWER_COL].clear();
+                }
+            }
+        }
+        board
+    }
+
+    /// Returns true if the row's name has been revealed by a correct guess
+    pub fn is_guessed(&self, row: usize) -> bool {
+        self.guessed[row]
+    }
+
+    /// Returns true if the player passed on the row
+    pub fn is_passed(&self, row: usize) -> bool {
+        self.passed[row]
+    }
+
+    pub fn correct(&self) -> usize {
+        self.correct
+    }
+
+    /// Number of rows the player passed on
+    pub fn passed(&self) -> usize {
+        self.passed.iter().filter(|&&p| p).count()
+    }
+
+    pub fn strikes(&self) -> usize {
+        self.strikes
+    }
+
+    /// Correct guesses in a row since the last strike
+    pub fn streak(&self) -> usize {
+        self.streak
+    }
+
+    /// Most correct guesses in a row this round
+    pub fn best_streak(&self) -> usize {
+        self.best_streak
+    }
+
+    /// Whether the next correct guess earns a streak bonus
+    pub fn on_streak(&self) -> bool {
+        self.streak >= STREAK_LENGTH
+    }
+
+    /// Extra points earned on streaks so far, on top of the score
+    pub fn streak_bonus(&self) -> u32 {
+        self.streak_bonus
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// Number of rows on the board
+    pub fn total(&self) -> usize {
+        self.board.rows.len()
+    }
+
+    pub fn hints(&self) -> usize {
+        self.hints
+    }
+
+    pub fn spec(&self) -> Option<&QuestionSpec> {
+        self.spec.as_ref()
+    }
+
+    /// The previous attempt being raced, if any
+    pub fn ghost(&self) -> Option<&Ghost> {
+        self.ghost.as_ref()
+    }
+
+    /// Time since the start of the round, not counting pauses, stopping when
+    /// it ends or at the time limit
+    pub fn elapsed(&self) -> Duration {
+        let end = self
+            .finished
+            .or(self.paused_at)
+            .unwrap_or_else(Instant::now);
+        let elapsed = end
+            .duration_since(self.started)
+            .saturating_sub(self.paused_for);
+        self.time_limit()
+            .map_or(elapsed, |limit| elapsed.min(limit))
+    }
+
+    pub fn rules(&self) -> &GameRules {
+        &self.rules
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.rules.time_limit
+    }
+
+    /// Time left on the clock, for a round with a time limit
+    pub fn remaining(&self) -> Option<Duration> {
+        self.time_limit()
+            .map(|limit| limit.saturating_sub(self.elapsed()))
+    }
+
+    pub fn result(&self) -> TriviaResult {
+        let rows = self
+            .board
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| RowResult {
+                name: row[ANSWER_COL].clone(),
+                stat: row.last().cloned().unwrap_or_default(),
+                points: self.point_values[i],
+                guessed: self.guessed[i],
+                passed: self.passed[i],
+                cells: row.clone(),
+                found_at: self.found_at[i],
+            })
+            .collect();
+        TriviaResult {
+            spec: self.spec.clone(),
+            question: self.question.clone(),
+            column_names: self.board.column_names.clone(),
+            score: self.score,
+            total: self.total(),
+            rows,
+            strikes: self.strikes,
+            hints: self.hints,
+            best_streak: self.best_streak,
+            streak_bonus: self.streak_bonus,
+            elapsed: self.elapsed(),
+            end: self.end(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(rows: &[(&str, &str)]) -> TriviaGame {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: rows
+                .iter()
+                .map(|(n, y)| vec![n.to_string(), y.to_string()])
+                .collect(),
+        };
+        TriviaGame::new("Test question", board)
+    }
+
+    #[test]
+    fn test_correct_guess_scores_points() {
+        let mut g = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")]);
+        let expected = g.point_values()[1];
+
+        let outcome = g.submit_guess("rudolph");
+        assert_eq!(
+            outcome,
+            GuessOutcome::Correct {
+                row: 1,
+                name: "Mason Rudolph".into(),
+                points: expected
+            }
+        );
+        assert_eq!(g.score(), expected);
+        assert!(g.is_guessed(1));
+        assert!(!g.is_over());
+    }
+
+    #[test]
+    fn test_repeat_guess_is_not_a_strike() {
+        let mut g = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")]);
+        g.submit_guess("Brady");
+        assert_eq!(g.submit_guess("tom brady"), GuessOutcome::AlreadyGuessed);
+        assert_eq!(g.strikes(), 0);
+    }
+
+    #[test]
+    fn test_streak_earns_a_bonus() {
+        let names = [
+            "Brady",
+            "Brees",
+            "Manning",
+            "Rivers",
+            "Rodgers",
+            "Roethlisberger",
+            "Ryan",
+        ];
+        let rows: Vec<(&str, &str)> = names.iter().map(|n| (*n, "1000")).collect();
+        let mut g = game(&rows);
+        let points = g.point_values()[0];
+        for name in &names[..4] {
+            g.submit_guess(name);
+        }
+        assert!(!g.on_streak());
+        g.submit_guess("Rodgers");
+        assert_eq!((g.streak(), g.streak_bonus()), (5, 0));
+        assert!(g.on_streak());
+        g.submit_guess("Roethlisberger");
+        assert_eq!(g.streak_bonus(), streak_bonus(points));
+        // A miss starts the streak over, but the best one is kept
+        g.submit_guess("Favre");
+        assert_eq!(g.streak(), 0);
+        g.submit_guess("Ryan");
+        let result = g.result();
+        assert_eq!(
+            (result.best_streak, result.streak_bonus),
+            (6, streak_bonus(points))
+        );
+        assert_eq!(result.score, points * 7);
+        assert_eq!(streak_bonus(455), 46);
+    }
+
+    #[test]
+    fn test_three_strikes_ends_round() {
+        let mut g = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")]);
+        assert_eq!(
+            g.submit_guess("Manning"),
+            GuessOutcome::Strike { strikes: 1 }
+        );
+        g.submit_guess("Rivers");
+        assert_eq!(g.submit_guess("Brees"), GuessOutcome::Strike { strikes: 3 });
+        assert_eq!(g.end(), Some(GameEnd::StruckOut));
+        assert_eq!(g.submit_guess("Brady"), GuessOutcome::GameOver);
+    }
+
+    #[test]
+    fn test_rules_change_the_strikes() {
+        use crate::rules::RulesPreset;
+
+        let board = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")])
+            .board()
+            .clone();
+        let mut g = TriviaGame::new("q", board.clone())
+            .with_rules(GameRules::preset(RulesPreset::SuddenDeath));
+        g.submit_guess("Manning");
+        assert_eq!(g.end(), Some(GameEnd::StruckOut));
+
+        let casual = GameRules {
+            allow_reveal: false,
+            allow_hints: false,
+            ..GameRules::preset(RulesPreset::Casual)
+        };
+        let mut g = TriviaGame::new("q", board).with_rules(casual);
+        for _ in 0..10 {
+            g.submit_guess("Manning");
+        }
+        assert!(!g.is_over());
+        assert_eq!(g.pass(0), None);
+        assert_eq!(g.hint(0), None);
+        assert!(!g.record_hint());
+        assert_eq!((g.strikes(), g.hints()), (10, 0));
+    }
+
+    #[test]
+    fn test_perfect_round() {
+        let mut g = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")]);
+        g.submit_guess("Brady");
+        g.submit_guess("Rudolph");
+        assert_eq!(g.end(), Some(GameEnd::Perfect));
+        assert_eq!(g.result().total, 2);
+    }
+
+    #[test]
+    fn test_reveal_keeps_points() {
+        let mut g = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")]);
+        g.submit_guess("Brady");
+        let score = g.score();
+        g.reveal();
+        assert_eq!(g.end(), Some(GameEnd::GaveUp));
+        assert_eq!(g.result().score, score);
+    }
+
+    #[test]
+    fn test_pass_reveals_a_row_for_nothing() {
+        let mut g = game(&[
+            ("Tom Brady", "5000"),
+            ("Mason Rudolph", "1000"),
+            ("Drew Brees", "4000"),
+        ]);
+        assert_eq!(g.pass(1), Some("Mason Rudolph".to_string()));
+        assert_eq!(g.pass(1), None);
+        assert_eq!(g.pass(3), None);
+        assert_eq!((g.score(), g.strikes(), g.passed()), (0, 0, 1));
+        assert_eq!(g.visible_board().rows[1][0], "Mason Rudolph");
+        // A passed row can't be guessed afterwards, but it isn't a miss either
+        assert_eq!(g.submit_guess("Rudolph"), GuessOutcome::AlreadyGuessed);
+
+        g.submit_guess("Brady");
+        assert_eq!(g.pass(0), None);
+        assert!(!g.is_over());
+        g.pass(2);
+        assert_eq!(g.end(), Some(GameEnd::GaveUp));
+        let result = g.result();
+        assert!(result.rows[1].passed && !result.rows[1].guessed);
+        assert_eq!(result.score, g.point_values()[0]);
+    }
+
+    #[test]
+    fn test_visible_board_hides_unguessed_names() {
+        let mut g = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")]);
+        g.submit_guess("Brady");
+        let rows = g.visible_board().rows;
+        assert_eq!(rows[0][0], "Tom Brady");
+        assert_eq!(rows[1], ["", "1000"]);
+        g.reveal();
+        assert_eq!(g.visible_board(), *g.board());
+    }
+
+    #[test]
+    fn test_skip_banks_the_points() {
+        let mut g = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")]);
+        g.submit_guess("Rudolph");
+        g.skip();
+        assert_eq!(g.end(), Some(GameEnd::Skipped));
+        assert_eq!(g.score(), g.point_values()[1]);
+        assert_eq!(g.visible_board(), *g.board());
+        let result = g.result();
+        assert_eq!((result.end, result.correct()), (Some(GameEnd::Skipped), 1));
+    }
+
+    #[test]
+    fn test_pause_stops_the_clock() {
+        let board = game(&[("Tom Brady", "5000")]).board().clone();
+        let mut untimed = TriviaGame::new("q", board.clone());
+        assert!(!untimed.pause());
+
+        let mut g = TriviaGame::new("q", board).with_time_limit(Duration::from_secs(90));
+        assert!(!g.resume());
+        assert!(g.pause());
+        assert!(g.is_paused() && !g.pause());
+        let left = g.remaining();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(g.remaining(), left);
+        assert!(g.resume());
+        assert!(!g.is_paused());
+        assert!(g.elapsed() < Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_paused_round_takes_no_input() {
+        let board = game(&[("Calvin Johnson", "1600"), ("Chad Johnson", "1400")])
+            .board()
+            .clone();
+        let mut g = TriviaGame::new("q", board).with_time_limit(Duration::from_secs(90));
+        assert!(matches!(
+            g.submit_guess("Johnson"),
+            GuessOutcome::Ambiguous { .. }
+        ));
+        g.pause();
+        assert_eq!(g.submit_guess("Calvin"), GuessOutcome::Paused);
+        assert_eq!(g.submit_guess("Nobody"), GuessOutcome::Paused);
+        assert_eq!(g.choose(0), Some(GuessOutcome::Paused));
+        assert_eq!(g.pass(0), None);
+        assert_eq!(g.hint(0), None);
+        assert_eq!((g.score(), g.strikes(), g.hints()), (0, 0, 0));
+
+        g.resume();
+        assert!(matches!(
+            g.choose(0),
+            Some(GuessOutcome::Correct { row: 0, .. })
+        ));
+        assert_eq!(g.pass(1).as_deref(), Some("Chad Johnson"));
+    }
+
+    #[test]
+    fn test_blank_guess_ignored() {
+        let mut g = game(&[("Tom Brady", "5000")]);
+        assert_eq!(g.submit_guess("   "), GuessOutcome::Empty);
+        assert_eq!(g.strikes(), 0);
+    }
+
+    #[test]
+    fn test_result_has_row_detail() {
+        let mut g = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")]);
+        g.submit_guess("Rudolph");
+        g.submit_guess("Manning");
+        assert_eq!(g.hint(1), None);
+        assert_eq!(g.hint(0).as_deref(), Some("T.B."));
+        assert_eq!(g.result().end, None);
+        g.reveal();
+
+        let result = g.result();
+        assert_eq!(result.question, "Test question");
+        assert_eq!(result.end, Some(GameEnd::GaveUp));
+        assert_eq!((result.strikes, result.hints), (1, 1));
+        assert_eq!(result.correct(), 1);
+        assert_eq!(
+            result.rows[1],
+            RowResult {
+                name: "Mason Rudolph".into(),
+                stat: "1000".into(),
+                points: g.point_values()[1],
+                guessed: true,
+                passed: false,
+                cells: vec!["Mason Rudolph".into(), "1000".into()],
+                found_at: result.rows[1].found_at,
+            }
+        );
+        assert!(result.rows[1].found_at.unwrap() <= result.elapsed);
+        assert!(!result.rows[0].guessed);
+        assert_eq!(result.rows[0].found_at, None);
+        // The clock stops when the round ends
+        assert_eq!(g.result().elapsed, result.elapsed);
+    }
+
+    #[test]
+    fn test_result_round_trips_through_json() {
+        let mut g = game(&[("Tom Brady", "5000"), ("Mason Rudolph", "1000")]);
+        g.submit_guess("Brady");
+        g.reveal();
+
+        let result = g.result();
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains(r#""end":"gave_up""#));
+        let back: TriviaResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, result);
+
+        let board: Board =
+            serde_json::from_str(&serde_json::to_string(g.board()).unwrap()).unwrap();
+        assert_eq!(&board, g.board());
+    }
+
+    #[test]
+    fn test_events_follow_play() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut events = EventBus::new();
+        let sink = Arc::clone(&seen);
+        events.subscribe(move |e: &GameEvent| sink.lock().unwrap().push(e.clone()));
+
+        let board = game(&[("Tom Brady", "5000")]).board().clone();
+        let mut g = TriviaGame::with_events("q", board, events);
+        g.submit_guess("Manning");
+        g.submit_guess("Brady");
+        g.submit_guess("Brady");
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 3);
+        assert_eq!(seen[0], GameEvent::Strike { strikes: 1 });
+        assert!(matches!(seen[1], GameEvent::CorrectGuess { row: 0, .. }));
+        assert_eq!(
+            seen[2],
+            GameEvent::BoardFinished {
+                end: GameEnd::Perfect,
+                result: g.result()
+            }
+        );
+    }
+
+    #[test]
+    fn test_time_limit_ends_the_round() {
+        let board = game(&[("Tom Brady", "5000")]).board().clone();
+        let mut g = TriviaGame::new("q", board.clone()).with_time_limit(Duration::from_secs(90));
+        assert!(!g.check_clock());
+        assert!(g.remaining().unwrap() <= Duration::from_secs(90));
+
+        let mut g = TriviaGame::new("q", board).with_time_limit(Duration::ZERO);
+        assert_eq!(g.submit_guess("Brady"), GuessOutcome::GameOver);
+        assert_eq!(g.end(), Some(GameEnd::TimeUp));
+        assert_eq!(g.pass(0), None);
+        let result = g.result();
+        assert_eq!((result.score, result.elapsed), (0, Duration::ZERO));
+    }
+
+    #[test]
+    fn test_last_name_guess_prefers_last_names() {
+        let mut g = game(&[("Johnson Bademosi", "300"), ("Calvin Johnson", "1600")]);
+        assert!(matches!(
+            g.submit_guess("johnson"),
+            GuessOutcome::Correct { row: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_ambiguous_guess_is_not_a_strike() {
+        let mut g = game(&[
+            ("Calvin Johnson", "1600"),
+            ("Chad Johnson", "1400"),
+            ("Tom Brady", "5000"),
+        ]);
+        assert_eq!(
+            g.submit_guess("Johnson"),
+            GuessOutcome::Ambiguous { rows: vec![0, 1] }
+        );
+        assert_eq!((g.strikes(), g.correct()), (0, 0));
+        assert!(matches!(
+            g.submit_guess("chad johnson"),
+            GuessOutcome::Correct { row: 1, .. }
+        ));
+        // Once only one Johnson is left, the last name is enough
+        assert!(matches!(
+            g.submit_guess("johnson"),
+            GuessOutcome::Correct { row: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn test_choose_settles_an_ambiguous_guess() {
+        let mut g = game(&[
+            ("Steve Smith", "1400"),
+            ("Tom Brady", "5000"),
+            ("Alex Smith", "900"),
+        ]);
+        assert_eq!(
+            g.submit_guess("smith"),
+            GuessOutcome::Ambiguous { rows: vec![0, 2] }
+        );
+        assert_eq!(g.pending(), [0, 2]);
+        assert_eq!(g.choose(2), None);
+        assert!(matches!(
+            g.choose(1),
+            Some(GuessOutcome::Correct { row: 2, .. })
+        ));
+        // The choice is used up, and another guess replaces it
+        assert!(g.pending().is_empty());
+        assert_eq!(g.choose(0), None);
+        assert!(matches!(
+            g.submit_guess("smith"),
+            GuessOutcome::Correct { row: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn test_shared_name_reveals_next_row() {
+        let mut g = game(&[("Marvin Harrison", "1000"), ("Marvin Harrison Jr.", "900")]);
+        assert!(matches!(
+            g.submit_guess("marvin harrison"),
+            GuessOutcome::Correct { row: 0, .. }
+        ));
+        assert!(matches!(
+            g.submit_guess("marvin harrison"),
+            GuessOutcome::Correct { row: 1, .. }
+        ));
+    }
+}
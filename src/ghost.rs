@@ -0,0 +1,296 @@
+//! Ghost racing: replay a previous attempt on the same board alongside a live one
+//!
+//! Every board records when each row was found, measured from the start of
+//! the round. A [`Ghost`] keeps that timeline for one finished board, and
+//! [`Ghost::progress_at`] tells how far the ghost had got at any point, so a
+//! frontend can show it racing the player guess by guess. A [`GhostStore`]
+//! keeps the latest attempt at each board, keyed by its question spec.
+use crate::game::TriviaResult;
+use crate::questions::QuestionSpec;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Where the CLI keeps ghosts between sessions
+pub const DEFAULT_GHOST_FILE: &str = "know_ball_ghosts.json";
+
+/// One finished attempt at a board, as a timeline of correct guesses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ghost {
+    pub spec: QuestionSpec,
+    /// Rows on the board, to tell a board that changed apart
+    pub total: usize,
+    pub score: u32,
+    /// How long the attempt took from start to finish
+    pub elapsed: Duration,
+    /// Correct guesses, earliest first
+    pub timeline: Vec<GhostGuess>,
+}
+
+/// When the ghost found one row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GhostGuess {
+    /// Time since the start of the round
+    pub at: Duration,
+    pub row: usize,
+    pub points: u32,
+}
+
+/// How far a ghost had got at some moment of its attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GhostProgress {
+    pub found: usize,
+    pub score: u32,
+    /// Whether the attempt had ended by then
+    pub finished: bool,
+}
+
+impl Ghost {
+    /// The ghost of a finished board, or None if the board wasn't generated
+    /// from a spec, was empty, or is still going
+    pub fn from_result(result: &TriviaResult) -> Option<Self> {
+        if result.total == 0 || result.end.is_none() {
+            return None;
+        }
+        let mut timeline: Vec<GhostGuess> = result
+            .rows
+            .iter()
+            .enumerate()
+            .filter_map(|(row, r)| {
+                r.found_at.map(|at| GhostGuess {
+                    at,
+                    row,
+                    points: r.points,
+                })
+            })
+            .collect();
+        timeline.sort_by_key(|guess| guess.at);
+        Some(Self {
+            spec: result.spec.clone()?,
+            total: result.total,
+            score: result.score,
+            elapsed: result.elapsed,
+            timeline,
+        })
+    }
+
+    /// Whether this ghost was recorded on the board `spec` loads
+    pub fn races(&self, spec: &QuestionSpec, total: usize) -> bool {
+        self.spec == *spec && self.total == total
+    }
+
+    /// The ghost's rows and points `elapsed` into its attempt
+    pub fn progress_at(&self, elapsed: Duration) -> GhostProgress {
+        let found = self.timeline.partition_point(|guess| guess.at <= elapsed);
+        GhostProgress {
+            found,
+            score: self.timeline[..found].iter().map(|g| g.points).sum(),
+            finished: elapsed >= self.elapsed,
+        }
+    }
+
+    /// One line for under the board, e.g.
+    /// `Ghost: 3/10, 320 pts at 0:42 (you're 40 ahead)`
+    pub fn race_line(&self, elapsed: Duration, score: u32) -> String {
+        let ghost = self.progress_at(elapsed);
+        let done = if ghost.finished { ", finished" } else { "" };
+        format!(
+            "Ghost: {}/{}, {} pts at {}{done} ({})",
+            ghost.found,
+            self.total,
+            ghost.score,
+            clock(elapsed),
+            lead(score, ghost.score)
+        )
+    }
+
+    /// How the finished attempt compares with the ghost's whole run
+    pub fn final_line(&self, result: &TriviaResult) -> String {
+        let verdict = match result.score.cmp(&self.score) {
+            std::cmp::Ordering::Greater => {
+                format!("You beat your ghost by {}!", result.score - self.score)
+            }
+            std::cmp::Ordering::Less => {
+                format!("Your ghost wins by {}.", self.score - result.score)
+            }
+            std::cmp::Ordering::Equal if result.elapsed < self.elapsed => {
+                "Same score as your ghost, but faster!".to_string()
+            }
+            std::cmp::Ordering::Equal => "Same score as your ghost.".to_string(),
+        };
+        format!(
+            "Ghost: {}/{}, {} pts in {}. {verdict}",
+            self.timeline.len(),
+            self.total,
+            self.score,
+            clock(self.elapsed)
+        )
+    }
+}
+
+/// `m:ss`
+pub(crate) fn clock(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn lead(yours: u32, ghost: u32) -> String {
+    match yours.cmp(&ghost) {
+        std::cmp::Ordering::Greater => format!("you're {} ahead", yours - ghost),
+        std::cmp::Ordering::Less => format!("you're {} behind", ghost - yours),
+        std::cmp::Ordering::Equal => "level".to_string(),
+    }
+}
+
+/// The latest attempt at each board
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GhostStore {
+    ghosts: Vec<Ghost>,
+}
+
+impl GhostStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ghost to race on the board `spec` loads, if there is one
+    pub fn get(&self, spec: &QuestionSpec, total: usize) -> Option<&Ghost> {
+        self.ghosts.iter().find(|ghost| ghost.races(spec, total))
+    }
+
+    /// Keeps `ghost`, replacing any earlier attempt at the same board
+    pub fn insert(&mut self, ghost: Ghost) {
+        self.ghosts.retain(|g| g.spec != ghost.spec);
+        self.ghosts.push(ghost);
+    }
+
+    pub fn len(&self) -> usize {
+        self.ghosts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ghosts.is_empty()
+    }
+
+    /// The saved ghosts, or none if the file doesn't exist yet
+    #[cfg(feature = "cli")]
+    pub fn load(path: &std::path::Path) -> crate::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|e| {
+                crate::KnowBallError::Parse(format!("{} is damaged: {e}", path.display()))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    pub fn save(&self, path: &std::path::Path) -> crate::Result<()> {
+        let json = serde_json::to_string(self).expect("ghosts serialize to JSON");
+        Ok(std::fs::write(path, json + "\n")?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::TriviaGame;
+    use crate::questions::QuestionParams;
+    use crate::sql_runner::Board;
+
+    fn spec() -> QuestionSpec {
+        QuestionSpec {
+            code: "passyds_TEAM".into(),
+            params: QuestionParams::default(),
+            text: "Most yards".into(),
+            query: "SELECT name, yards FROM passers".into(),
+        }
+    }
+
+    fn ghost() -> Ghost {
+        let secs = Duration::from_secs;
+        Ghost {
+            spec: spec(),
+            total: 3,
+            score: 500,
+            elapsed: secs(40),
+            timeline: vec![
+                GhostGuess {
+                    at: secs(5),
+                    row: 2,
+                    points: 200,
+                },
+                GhostGuess {
+                    at: secs(30),
+                    row: 0,
+                    points: 300,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_progress_follows_the_timeline() {
+        let ghost = ghost();
+        let at = |secs| ghost.progress_at(Duration::from_secs(secs));
+        assert_eq!(
+            at(0),
+            GhostProgress {
+                found: 0,
+                score: 0,
+                finished: false
+            }
+        );
+        assert_eq!((at(5).found, at(5).score), (1, 200));
+        assert_eq!((at(29).found, at(29).score), (1, 200));
+        assert!(at(40).finished);
+        assert_eq!(
+            ghost.race_line(Duration::from_secs(31), 250),
+            "Ghost: 2/3, 500 pts at 0:31 (you're 250 behind)"
+        );
+        assert_eq!(
+            ghost.race_line(Duration::from_secs(75), 500),
+            "Ghost: 2/3, 500 pts at 1:15, finished (level)"
+        );
+    }
+
+    #[test]
+    fn test_from_result_records_found_rows() {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Drew Brees".into(), "4000".into()],
+            ],
+        };
+        let mut game = TriviaGame::new("Most yards", board).with_spec(spec());
+        game.submit_guess("Brees");
+        assert_eq!(Ghost::from_result(&game.result()), None);
+        game.reveal();
+
+        let result = game.result();
+        let ghost = Ghost::from_result(&result).unwrap();
+        assert_eq!(ghost.timeline.len(), 1);
+        assert_eq!(ghost.timeline[0].row, 1);
+        assert_eq!(ghost.score, result.score);
+        assert!(ghost.progress_at(result.elapsed).finished);
+        assert!(ghost
+            .final_line(&result)
+            .ends_with("Same score as your ghost."));
+    }
+
+    #[test]
+    fn test_store_keeps_latest_attempt() {
+        let mut store = GhostStore::new();
+        store.insert(ghost());
+        let mut again = ghost();
+        again.score = 700;
+        store.insert(again);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(&spec(), 3).unwrap().score, 700);
+        assert!(store.get(&spec(), 4).is_none());
+
+        let json = serde_json::to_string(&store).unwrap();
+        assert_eq!(serde_json::from_str::<GhostStore>(&json).unwrap(), store);
+    }
+}
@@ -0,0 +1,331 @@
+//! gRPC service for question generation and guess evaluation (`grpc` feature)
+//!
+//! The messages and service traits are generated from `proto/know_ball.proto`
+//! into [`proto`]. `KnowBallService` keeps every game it starts in memory by
+//! id until a guess or reveal ends it, so a backend only has to pass the id
+//! along. Database work runs on tokio's blocking pool; the `grpc-server`
+//! binary serves it over tonic's transport.
+use crate::engine::TriviaEngine;
+use crate::game::{GameEnd, GuessOutcome, TriviaGame};
+use crate::questions::{Question, QuestionParams, QuestionSpec};
+use crate::sql_runner::Board;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tonic::{Request, Response, Status};
+
+/// Types generated from `proto/know_ball.proto`
+pub mod proto {
+    tonic::include_proto!("know_ball.v1");
+}
+
+pub use proto::know_ball_server::KnowBallServer;
+
+/// The `KnowBall` service, ready to hand to a tonic server
+pub struct KnowBallService {
+    state: Arc<Mutex<State>>,
+}
+
+struct State {
+    engine: TriviaEngine,
+    games: HashMap<u64, TriviaGame>,
+    next_id: u64,
+}
+
+impl KnowBallService {
+    pub fn new(engine: TriviaEngine) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                engine,
+                games: HashMap::new(),
+                next_id: 1,
+            })),
+        }
+    }
+
+    /// Wraps the service for `tonic::transport::Server::add_service`
+    pub fn into_server(self) -> KnowBallServer<Self> {
+        KnowBallServer::new(self)
+    }
+
+    /// Runs `f` on the blocking pool, since boards come from SQLite
+    async fn run<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut State) -> Result<T, Status> + Send + 'static,
+    ) -> Result<Response<T>, Status> {
+        let state = Arc::clone(&self.state);
+        tokio::task::spawn_blocking(move || {
+            let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+            f(&mut state)
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?
+        .map(Response::new)
+    }
+}
+
+impl State {
+    /// The question for `code` with its fixed parameters, or a random one
+    fn question(&mut self, code: &str) -> Result<(Arc<dyn Question>, QuestionParams), Status> {
+        if code.is_empty() {
+            let question = self
+                .engine
+                .random_question()
+                .ok_or_else(|| Status::failed_precondition("no questions are registered"))?;
+            return Ok((question, QuestionParams::default()));
+        }
+        let parsed = self
+            .engine
+            .lookup(code)
+            .ok_or_else(|| Status::not_found(format!("unknown question code '{code}'")))?;
+        let fixed = QuestionParams::for_team(parsed.team.as_deref());
+        Ok((parsed.question, fixed))
+    }
+
+    fn spec(&mut self, code: &str) -> Result<QuestionSpec, Status> {
+        let (question, fixed) = self.question(code)?;
+        Ok(self.engine.generate(question.as_ref(), &fixed))
+    }
+
+    fn start(&mut self, code: &str) -> Result<proto::Game, Status> {
+        let (question, fixed) = self.question(code)?;
+        let (spec, board) = self
+            .engine
+            .load_question(question.as_ref(), &fixed)
+            .map_err(|e| Status::unavailable(format!("couldn't load a board: {e}")))?;
+        if board.rows.is_empty() {
+            return Err(Status::not_found(
+                "that question came back empty; try another",
+            ));
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let game = TriviaGame::new(spec.text.clone(), board).with_spec(spec);
+        let message = game_message(id, &game);
+        self.games.insert(id, game);
+        Ok(message)
+    }
+
+    fn game_mut(&mut self, id: u64) -> Result<&mut TriviaGame, Status> {
+        self.games
+            .get_mut(&id)
+            .ok_or_else(|| Status::not_found(format!("no game with id {id}")))
+    }
+
+    /// The game's state, forgetting it if it just ended
+    fn finish(&mut self, id: u64) -> proto::Game {
+        let game = &self.games[&id];
+        let message = game_message(id, game);
+        if game.is_over() {
+            self.games.remove(&id);
+        }
+        message
+    }
+
+    fn guess(&mut self, id: u64, text: &str) -> Result<proto::GuessResponse, Status> {
+        let outcome = self.game_mut(id)?.submit_guess(text);
+        let mut response = proto::GuessResponse::default();
+        let outcome = match outcome {
+            GuessOutcome::Correct { row, name, points } => {
+                response.row = Some(row as u32);
+                response.name = Some(name);
+                response.points = Some(points);
+                proto::Outcome::Correct
+            }
+            GuessOutcome::AlreadyGuessed => proto::Outcome::AlreadyGuessed,
+            GuessOutcome::Ambiguous { .. } => proto::Outcome::Ambiguous,
+            GuessOutcome::Strike { .. } => proto::Outcome::Strike,
+            GuessOutcome::Empty => proto::Outcome::Empty,
+            // Finished games are dropped, so this only happens in a race
+            GuessOutcome::GameOver => return Err(Status::failed_precondition("game is over")),
+            GuessOutcome::Paused => return Err(Status::failed_precondition("game is paused")),
+        };
+        response.set_outcome(outcome);
+        response.game = Some(self.finish(id));
+        Ok(response)
+    }
+
+    fn reveal(&mut self, id: u64) -> Result<proto::Game, Status> {
+        self.game_mut(id)?.reveal();
+        Ok(self.finish(id))
+    }
+}
+
+#[tonic::async_trait]
+impl proto::know_ball_server::KnowBall for KnowBallService {
+    async fn list_questions(
+        &self,
+        _request: Request<proto::ListQuestionsRequest>,
+    ) -> Result<Response<proto::ListQuestionsResponse>, Status> {
+        self.run(|state| {
+            let mut questions: Vec<proto::QuestionInfo> = state
+                .engine
+                .registry()
+                .iter()
+                .map(|(code, question)| proto::QuestionInfo {
+                    code: code.clone(),
+                    description: question.meta().description.to_string(),
+                })
+                .collect();
+            questions.sort_by(|a, b| a.code.cmp(&b.code));
+            Ok(proto::ListQuestionsResponse { questions })
+        })
+        .await
+    }
+
+    async fn generate(
+        &self,
+        request: Request<proto::GenerateRequest>,
+    ) -> Result<Response<proto::QuestionSpec>, Status> {
+        let code = request.into_inner().code;
+        self.run(move |state| state.spec(code.trim()).map(|spec| spec_message(&spec)))
+            .await
+    }
+
+    async fn start_game(
+        &self,
+        request: Request<proto::GenerateRequest>,
+    ) -> Result<Response<proto::Game>, Status> {
+        let code = request.into_inner().code;
+        self.run(move |state| state.start(code.trim())).await
+    }
+
+    async fn guess(
+        &self,
+        request: Request<proto::GuessRequest>,
+    ) -> Result<Response<proto::GuessResponse>, Status> {
+        let proto::GuessRequest { game_id, text } = request.into_inner();
+        self.run(move |state| state.guess(game_id, &text)).await
+    }
+
+    async fn reveal(
+        &self,
+        request: Request<proto::GameRequest>,
+    ) -> Result<Response<proto::Game>, Status> {
+        let id = request.into_inner().game_id;
+        self.run(move |state| state.reveal(id)).await
+    }
+}
+
+fn spec_message(spec: &QuestionSpec) -> proto::QuestionSpec {
+    let params = &spec.params;
+    proto::QuestionSpec {
+        code: spec.code.clone(),
+        params: Some(proto::QuestionParams {
+            team: params.team.clone(),
+            year: params.year,
+            first_year: params.year_range.map(|(first, _)| first),
+            last_year: params.year_range.map(|(_, last)| last),
+            threshold: params.threshold,
+            position: params.position.clone(),
+        }),
+        text: spec.text.clone(),
+        sql: spec.query.inline(),
+    }
+}
+
+fn board_message(board: &Board) -> proto::Board {
+    proto::Board {
+        column_names: board.column_names.clone(),
+        rows: board
+            .rows
+            .iter()
+            .map(|row| proto::Row { cells: row.clone() })
+            .collect(),
+    }
+}
+
+fn game_message(id: u64, game: &TriviaGame) -> proto::Game {
+    let mut message = proto::Game {
+        id,
+        spec: game.spec().map(spec_message),
+        board: Some(board_message(&game.visible_board())),
+        point_values: game.point_values().to_vec(),
+        score: game.score(),
+        strikes: game.strikes() as u32,
+        end: 0,
+    };
+    message.set_end(match game.end() {
+        None => proto::GameEnd::Unspecified,
+        Some(GameEnd::Perfect) => proto::GameEnd::Perfect,
+        Some(GameEnd::StruckOut) => proto::GameEnd::StruckOut,
+        Some(GameEnd::GaveUp) => proto::GameEnd::GaveUp,
+        Some(GameEnd::TimeUp) => proto::GameEnd::TimeUp,
+        Some(GameEnd::Skipped) => proto::GameEnd::Skipped,
+    });
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::proto::know_ball_server::KnowBall;
+    use super::*;
+    use crate::store::MockStore;
+
+    fn service() -> KnowBallService {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Mason Rudolph".into(), "1000".into()],
+            ],
+        };
+        KnowBallService::new(TriviaEngine::with_seed(1).with_store(MockStore::new(board)))
+    }
+
+    async fn guess(service: &KnowBallService, game_id: u64, text: &str) -> proto::GuessResponse {
+        let request = Request::new(proto::GuessRequest {
+            game_id,
+            text: text.into(),
+        });
+        service.guess(request).await.unwrap().into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_play_a_game_to_the_end() {
+        let service = service();
+        let request = Request::new(proto::GenerateRequest {
+            code: "last10passers_PIT".into(),
+        });
+        let game = service.start_game(request).await.unwrap().into_inner();
+        assert_eq!(game.end(), proto::GameEnd::Unspecified);
+        let params = game.spec.and_then(|spec| spec.params).unwrap();
+        assert_eq!(params.team.as_deref(), Some("PIT"));
+        assert_eq!(game.board.unwrap().rows[0].cells, ["", "5000"]);
+
+        let response = guess(&service, game.id, "brady").await;
+        assert_eq!(response.outcome(), proto::Outcome::Correct);
+        assert_eq!(response.name.as_deref(), Some("Tom Brady"));
+        let response = guess(&service, game.id, "Manning").await;
+        assert_eq!(response.outcome(), proto::Outcome::Strike);
+        assert_eq!(response.game.unwrap().strikes, 1);
+
+        let response = guess(&service, game.id, "Rudolph").await;
+        let finished = response.game.unwrap();
+        assert_eq!(finished.end(), proto::GameEnd::Perfect);
+        // Finished games are forgotten
+        let request = Request::new(proto::GameRequest { game_id: game.id });
+        let err = service.reveal(request).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_generate_and_bad_codes() {
+        let service = service();
+        let request = Request::new(proto::GenerateRequest {
+            code: String::new(),
+        });
+        let spec = service.generate(request).await.unwrap().into_inner();
+        assert!(!spec.sql.is_empty());
+
+        let request = Request::new(proto::GenerateRequest {
+            code: "nope".into(),
+        });
+        let err = service.start_game(request).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        let request = Request::new(proto::ListQuestionsRequest {});
+        let list = service.list_questions(request).await.unwrap().into_inner();
+        assert!(list.questions.windows(2).all(|w| w[0].code < w[1].code));
+    }
+}
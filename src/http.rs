@@ -0,0 +1,374 @@
+//! JSON HTTP API for question generation and guess evaluation (`http` feature)
+//!
+//! The gRPC service's calls as plain HTTP routes, for frontends that would
+//! rather not speak gRPC. Every handler is annotated for utoipa, so [`openapi`]
+//! describes the whole API; the server also serves it at `/openapi.json`, and
+//! the checked-in `openapi/know_ball.json` is the contract clients code
+//! against. Bodies are the types in [`crate::api`]. As with gRPC, games are
+//! kept in memory by id and forgotten once they end.
+use crate::api::{
+    ApiError, GenerateRequest, GuessRequest, GuessResponse, QuestionInfo, QuestionSpec,
+};
+use crate::engine::TriviaEngine;
+use crate::game::TriviaGame;
+use crate::questions::{Question, QuestionParams};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use utoipa::OpenApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Know Ball",
+        description = "NFL trivia boards: generate questions, start games, and check guesses"
+    ),
+    paths(list_questions, generate, start_game, get_game, guess, reveal)
+)]
+struct ApiDoc;
+
+/// The OpenAPI document for every route in [`router`]
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let mut doc = ApiDoc::openapi();
+    // utoipa fills this from Cargo.toml, which names no license
+    doc.info.license = None;
+    doc
+}
+
+/// The API's routes, ready for `axum::serve`
+pub fn router(engine: TriviaEngine) -> Router {
+    let state = Arc::new(Mutex::new(Games {
+        engine,
+        games: HashMap::new(),
+        next_id: 1,
+    }));
+    Router::new()
+        .route("/questions", get(list_questions))
+        .route("/questions/generate", post(generate))
+        .route("/games", post(start_game))
+        .route("/games/{id}", get(get_game))
+        .route("/games/{id}/guesses", post(guess))
+        .route("/games/{id}/reveal", post(reveal))
+        .route("/openapi.json", get(|| async { Json(openapi()) }))
+        .with_state(state)
+}
+
+type Shared = Arc<Mutex<Games>>;
+
+struct Games {
+    engine: TriviaEngine,
+    games: HashMap<u64, TriviaGame>,
+    next_id: u64,
+}
+
+/// An error status, sent with an `ApiError` body
+#[derive(Debug)]
+struct Failure(StatusCode, String);
+
+impl IntoResponse for Failure {
+    fn into_response(self) -> Response {
+        (self.0, Json(ApiError { error: self.1 })).into_response()
+    }
+}
+
+/// Runs `f` on the blocking pool, since boards come from SQLite
+async fn run<T: Send + 'static>(
+    state: Shared,
+    f: impl FnOnce(&mut Games) -> Result<T, Failure> + Send + 'static,
+) -> Result<Json<T>, Failure> {
+    tokio::task::spawn_blocking(move || {
+        let mut games = state.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut games)
+    })
+    .await
+    .map_err(|e| Failure(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map(Json)
+}
+
+impl Games {
+    /// The question for `code` with its fixed parameters, or a random one
+    fn question(&mut self, code: &str) -> Result<(Arc<dyn Question>, QuestionParams), Failure> {
+        if code.is_empty() {
+            let question = self.engine.random_question().ok_or_else(|| {
+                Failure(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "no questions are registered".into(),
+                )
+            })?;
+            return Ok((question, QuestionParams::default()));
+        }
+        let parsed = self.engine.lookup(code).ok_or_else(|| {
+            Failure(
+                StatusCode::NOT_FOUND,
+                format!("unknown question code '{code}'"),
+            )
+        })?;
+        let fixed = QuestionParams::for_team(parsed.team.as_deref());
+        Ok((parsed.question, fixed))
+    }
+
+    fn start(&mut self, code: &str) -> Result<crate::api::Game, Failure> {
+        let (question, fixed) = self.question(code)?;
+        let (spec, board) = self
+            .engine
+            .load_question(question.as_ref(), &fixed)
+            .map_err(|e| {
+                Failure(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    format!("couldn't load a board: {e}"),
+                )
+            })?;
+        if board.rows.is_empty() {
+            return Err(Failure(
+                StatusCode::NOT_FOUND,
+                "that question came back empty; try another".into(),
+            ));
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let game = TriviaGame::new(spec.text.clone(), board).with_spec(spec);
+        let body = crate::api::Game::new(id, &game);
+        self.games.insert(id, game);
+        Ok(body)
+    }
+
+    fn game_mut(&mut self, id: u64) -> Result<&mut TriviaGame, Failure> {
+        self.games
+            .get_mut(&id)
+            .ok_or_else(|| Failure(StatusCode::NOT_FOUND, format!("no game with id {id}")))
+    }
+
+    /// The game's state, forgetting it if it just ended
+    fn finish(&mut self, id: u64) -> crate::api::Game {
+        let game = &self.games[&id];
+        let body = crate::api::Game::new(id, game);
+        if game.is_over() {
+            self.games.remove(&id);
+        }
+        body
+    }
+}
+
+/// Every registered question code, sorted
+#[utoipa::path(
+    get,
+    path = "/questions",
+    responses((status = 200, description = "The registered questions", body = [QuestionInfo]))
+)]
+async fn list_questions(State(state): State<Shared>) -> Result<Json<Vec<QuestionInfo>>, Failure> {
+    run(state, |games| {
+        let mut questions: Vec<QuestionInfo> = games
+            .engine
+            .registry()
+            .iter()
+            .map(|(code, question)| QuestionInfo {
+                code: code.clone(),
+                description: question.meta().description.to_string(),
+            })
+            .collect();
+        questions.sort_by(|a, b| a.code.cmp(&b.code));
+        Ok(questions)
+    })
+    .await
+}
+
+/// Generates a question's text and SQL without loading its board
+#[utoipa::path(
+    post,
+    path = "/questions/generate",
+    request_body = GenerateRequest,
+    responses(
+        (status = 200, description = "The generated question", body = QuestionSpec),
+        (status = 404, description = "Unknown question code", body = ApiError),
+    )
+)]
+async fn generate(
+    State(state): State<Shared>,
+    Json(request): Json<GenerateRequest>,
+) -> Result<Json<QuestionSpec>, Failure> {
+    run(state, move |games| {
+        let (question, fixed) = games.question(request.code.trim())?;
+        let spec = games.engine.generate(question.as_ref(), &fixed);
+        Ok(QuestionSpec::from(&spec))
+    })
+    .await
+}
+
+/// Generates a question, loads its board, and starts a game on it
+#[utoipa::path(
+    post,
+    path = "/games",
+    request_body = GenerateRequest,
+    responses(
+        (status = 201, description = "The new game, with every name hidden", body = crate::api::Game),
+        (status = 404, description = "Unknown question code, or an empty board", body = ApiError),
+        (status = 503, description = "The board couldn't be loaded", body = ApiError),
+    )
+)]
+async fn start_game(
+    State(state): State<Shared>,
+    Json(request): Json<GenerateRequest>,
+) -> Result<(StatusCode, Json<crate::api::Game>), Failure> {
+    let game = run(state, move |games| games.start(request.code.trim())).await?;
+    Ok((StatusCode::CREATED, game))
+}
+
+/// A game in progress
+#[utoipa::path(
+    get,
+    path = "/games/{id}",
+    params(("id" = u64, Path, description = "Game id from `POST /games`")),
+    responses(
+        (status = 200, description = "The game", body = crate::api::Game),
+        (status = 404, description = "No such game, or it has ended", body = ApiError),
+    )
+)]
+async fn get_game(
+    State(state): State<Shared>,
+    Path(id): Path<u64>,
+) -> Result<Json<crate::api::Game>, Failure> {
+    run(state, move |games| {
+        let game = games.game_mut(id)?;
+        Ok(crate::api::Game::new(id, game))
+    })
+    .await
+}
+
+/// Checks a guess against the game's hidden names
+#[utoipa::path(
+    post,
+    path = "/games/{id}/guesses",
+    params(("id" = u64, Path, description = "Game id from `POST /games`")),
+    request_body = GuessRequest,
+    responses(
+        (status = 200, description = "The outcome and the game after it", body = GuessResponse),
+        (status = 404, description = "No such game, or it has ended", body = ApiError),
+    )
+)]
+async fn guess(
+    State(state): State<Shared>,
+    Path(id): Path<u64>,
+    Json(request): Json<GuessRequest>,
+) -> Result<Json<GuessResponse>, Failure> {
+    run(state, move |games| {
+        let outcome = games.game_mut(id)?.submit_guess(&request.text);
+        // Finished games are dropped, so a finished one only shows up in a race
+        GuessResponse::new(outcome, games.finish(id))
+            .ok_or_else(|| Failure(StatusCode::CONFLICT, "game is over".into()))
+    })
+    .await
+}
+
+/// Gives up, ending the game and showing every name
+#[utoipa::path(
+    post,
+    path = "/games/{id}/reveal",
+    params(("id" = u64, Path, description = "Game id from `POST /games`")),
+    responses(
+        (status = 200, description = "The finished game", body = crate::api::Game),
+        (status = 404, description = "No such game, or it has ended", body = ApiError),
+    )
+)]
+async fn reveal(
+    State(state): State<Shared>,
+    Path(id): Path<u64>,
+) -> Result<Json<crate::api::Game>, Failure> {
+    run(state, move |games| {
+        games.game_mut(id)?.reveal();
+        Ok(games.finish(id))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{End, Outcome};
+    use crate::sql_runner::Board;
+    use crate::store::MockStore;
+
+    fn state() -> Shared {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Mason Rudolph".into(), "1000".into()],
+            ],
+        };
+        let engine = TriviaEngine::with_seed(1).with_store(MockStore::new(board));
+        Arc::new(Mutex::new(Games {
+            engine,
+            games: HashMap::new(),
+            next_id: 1,
+        }))
+    }
+
+    async fn guess_text(state: &Shared, id: u64, text: &str) -> GuessResponse {
+        let request = Json(GuessRequest { text: text.into() });
+        let response = guess(State(Arc::clone(state)), Path(id), request).await;
+        response.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn test_play_a_game_to_the_end() {
+        let state = state();
+        let request = Json(GenerateRequest {
+            code: "last10passers_PIT".into(),
+        });
+        let (status, Json(game)) = start_game(State(Arc::clone(&state)), request)
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(game.end, None);
+        assert_eq!(game.spec.unwrap().params.team.as_deref(), Some("PIT"));
+        assert_eq!(game.board.rows[0], ["", "5000"]);
+
+        let response = guess_text(&state, game.id, "brady").await;
+        assert_eq!(response.outcome, Outcome::Correct);
+        assert_eq!(response.name.as_deref(), Some("Tom Brady"));
+        let response = guess_text(&state, game.id, "Manning").await;
+        assert_eq!(response.outcome, Outcome::Strike);
+        assert_eq!(response.game.strikes, 1);
+
+        let response = guess_text(&state, game.id, "Rudolph").await;
+        assert_eq!(response.game.end, Some(End::Perfect));
+        // Finished games are forgotten
+        let err = reveal(State(state), Path(game.id)).await.unwrap_err();
+        assert_eq!(err.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_generate_and_bad_codes() {
+        let state = state();
+        let request = Json(GenerateRequest::default());
+        let spec = generate(State(Arc::clone(&state)), request).await.unwrap();
+        assert!(!spec.sql.is_empty());
+
+        let request = Json(GenerateRequest {
+            code: "nope".into(),
+        });
+        let err = start_game(State(Arc::clone(&state)), request)
+            .await
+            .unwrap_err();
+        assert_eq!(err.0, StatusCode::NOT_FOUND);
+
+        let list = list_questions(State(state)).await.unwrap();
+        assert!(list.windows(2).all(|w| w[0].code < w[1].code));
+    }
+
+    #[test]
+    fn test_openapi_document_is_up_to_date() {
+        let generated = openapi().to_pretty_json().unwrap();
+        let checked_in = include_str!("../openapi/know_ball.json");
+        assert!(
+            generated.trim() == checked_in.trim(),
+            "openapi/know_ball.json is stale; regenerate it with \
+             `cargo run --features http --bin http-server -- --openapi > openapi/know_ball.json`"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Typed blocking client for the HTTP API (`http-client` feature)
+//!
+//! One method per route in `openapi/know_ball.json`, returning the bodies in
+//! [`crate::api`], so a Rust frontend never builds URLs or parses JSON by hand.
+//! It speaks plain HTTP only; put a TLS-terminating proxy in front of a server
+//! that is reachable from outside.
+//!
+//! ```no_run
+//! use know_ball::http_client::Client;
+//!
+//! let client = Client::new("http://127.0.0.1:8080");
+//! let game = client.start_game("last10passers_PIT")?;
+//! let response = client.guess(game.id, "Roethlisberger")?;
+//! println!("{:?}", response.outcome);
+//! # Ok::<(), know_ball::http_client::ClientError>(())
+//! ```
+use crate::api::{
+    ApiError, Game, GenerateRequest, GuessRequest, GuessResponse, QuestionInfo, QuestionSpec,
+};
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use thiserror::Error;
+
+/// How long a request may take, including loading a board
+pub const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Why a call failed
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// The server answered with an error status
+    #[error("HTTP {status}: {message}")]
+    Api { status: u16, message: String },
+    /// The server couldn't be reached, or its answer wasn't the expected JSON
+    #[error("request failed: {0}")]
+    Transport(String),
+}
+
+/// A connection to one Know Ball HTTP server
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    /// A client for the server at `base_url`, e.g. `http://127.0.0.1:8080`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+        Self { base_url, agent }
+    }
+
+    /// Every registered question code, sorted
+    pub fn list_questions(&self) -> Result<Vec<QuestionInfo>, ClientError> {
+        self.read(self.agent.get(&self.url("/questions")).call())
+    }
+
+    /// A question's text and SQL for `code`, or a random question if empty
+    pub fn generate(&self, code: &str) -> Result<QuestionSpec, ClientError> {
+        let body = GenerateRequest { code: code.into() };
+        self.read(self.post("/questions/generate").send_json(body))
+    }
+
+    /// Starts a game on `code`'s board, or a random question's if empty
+    pub fn start_game(&self, code: &str) -> Result<Game, ClientError> {
+        let body = GenerateRequest { code: code.into() };
+        self.read(self.post("/games").send_json(body))
+    }
+
+    /// A game in progress
+    pub fn game(&self, id: u64) -> Result<Game, ClientError> {
+        self.read(self.agent.get(&self.url(&format!("/games/{id}"))).call())
+    }
+
+    pub fn guess(&self, id: u64, text: &str) -> Result<GuessResponse, ClientError> {
+        let body = GuessRequest { text: text.into() };
+        self.read(self.post(&format!("/games/{id}/guesses")).send_json(body))
+    }
+
+    /// Gives up on a game, returning it with every name shown
+    pub fn reveal(&self, id: u64) -> Result<Game, ClientError> {
+        self.read(self.post(&format!("/games/{id}/reveal")).call())
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    fn post(&self, path: &str) -> ureq::Request {
+        self.agent.post(&self.url(path))
+    }
+
+    /// Decodes a response body, or the `ApiError` of an error status
+    fn read<T: DeserializeOwned>(
+        &self,
+        response: Result<ureq::Response, ureq::Error>,
+    ) -> Result<T, ClientError> {
+        match response {
+            Ok(response) => response
+                .into_json()
+                .map_err(|e| ClientError::Transport(e.to_string())),
+            Err(ureq::Error::Status(status, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                // Errors from axum's own extractors are plain text
+                let message = serde_json::from_str::<ApiError>(&body)
+                    .map(|e| e.error)
+                    .unwrap_or(body);
+                Err(ClientError::Api { status, message })
+            }
+            Err(e) => Err(ClientError::Transport(e.to_string())),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use super::*;
+    use crate::api::{End, Outcome};
+    use crate::sql_runner::Board;
+    use crate::store::MockStore;
+    use crate::TriviaEngine;
+
+    /// Serves the API on a free local port and returns its URL
+    async fn serve() -> String {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![vec!["Tom Brady".into(), "5000".into()]],
+        };
+        let engine = TriviaEngine::with_seed(1).with_store(MockStore::new(board));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, crate::http::router(engine)).await });
+        format!("http://{addr}/")
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_client_against_server() {
+        let client = Client::new(serve().await);
+        tokio::task::spawn_blocking(move || {
+            let questions = client.list_questions().unwrap();
+            assert!(!questions.is_empty());
+
+            let game = client.start_game("last10passers_PIT").unwrap();
+            assert_eq!(client.game(game.id).unwrap(), game);
+            let response = client.guess(game.id, "Brady").unwrap();
+            assert_eq!(response.outcome, Outcome::Correct);
+            assert_eq!(response.game.end, Some(End::Perfect));
+
+            match client.reveal(game.id) {
+                Err(ClientError::Api { status, message }) => {
+                    assert_eq!(status, 404);
+                    assert_eq!(message, format!("no game with id {}", game.id));
+                }
+                other => panic!("expected a 404, got {other:?}"),
+            }
+            assert!(matches!(
+                client.generate("nope"),
+                Err(ClientError::Api { status: 404, .. })
+            ));
+        })
+        .await
+        .unwrap();
+    }
+}
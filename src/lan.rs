@@ -0,0 +1,382 @@
+//! Head-to-head play between two terminals on a LAN (`lan` feature)
+//!
+//! `host` listens for one opponent and runs a two-player [`Rooms`] room, so it
+//! arbitrates every guess the same way the WebSocket server does: the first to
+//! name a player scores the row, and the room shares three strikes. `join`
+//! connects to a host and only sends what the player types and prints what
+//! comes back. Both sides see the other's hits and misses as they happen.
+//!
+//! The wire protocol is the rooms messages as JSON, one per line:
+//! [`ClientMessage`] from the guest and [`ServerMessage`] from the host.
+use crate::engine::TriviaEngine;
+use crate::game::{GameEnd, MAX_STRIKES};
+use crate::rooms::{ClientMessage, Emote, PlayerScore, Rooms, ServerMessage};
+use crate::sql_runner::Board;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+/// The port `host` listens on when none is given
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// The one room a LAN match is played in
+const ROOM: &str = "lan";
+
+const HOST: u64 = 1;
+const GUEST: u64 = 2;
+
+/// Writes one message as a line of JSON
+pub fn send<T: Serialize>(stream: &mut impl Write, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).map_err(io::Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    stream.flush()
+}
+
+/// Reads the next message, or None once the other side hangs up
+pub fn recv<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// What a line typed during a match asks for, or None for `quit`.
+///
+/// `start [code]`, `reveal`, `score`, `say <message>`, and `react <emote>`
+/// are commands; anything else is a guess.
+pub fn parse_input(line: &str) -> Option<ClientMessage> {
+    let line = line.trim();
+    let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let rest = rest.trim();
+    Some(match word.to_ascii_lowercase().as_str() {
+        "quit" | "exit" if rest.is_empty() => return None,
+        "start" => ClientMessage::Start {
+            code: (!rest.is_empty()).then(|| rest.to_string()),
+        },
+        "reveal" if rest.is_empty() => ClientMessage::Reveal,
+        "score" if rest.is_empty() => ClientMessage::Report,
+        "say" if !rest.is_empty() => ClientMessage::Chat { text: rest.into() },
+        "react" if !rest.is_empty() => ClientMessage::Emote { emote: rest.into() },
+        _ => ClientMessage::Guess { text: line.into() },
+    })
+}
+
+/// Plays against the opponent on `stream`, arbitrating every guess, until
+/// either side quits.
+///
+/// Hands the engine back afterwards so the REPL can carry on with it.
+pub fn host(engine: TriviaEngine, stream: TcpStream) -> TriviaEngine {
+    let mut reader = match stream.try_clone() {
+        Ok(stream) => BufReader::new(stream),
+        Err(e) => {
+            println!("Error: {e}");
+            return engine;
+        }
+    };
+    let mut writer = stream;
+    let mut rooms = Rooms::new(engine);
+
+    let name = match recv(&mut reader) {
+        Ok(Some(ClientMessage::Join { name, .. })) => name,
+        _ => "Guest".to_string(),
+    };
+    println!("{name} connected.");
+    let mut out = rooms.handle(HOST, join_message("Host"));
+    out.extend(rooms.handle(GUEST, join_message(&name)));
+
+    let (events, incoming) = mpsc::channel();
+    let more = read_lines(events.clone());
+    read_messages::<ClientMessage>(reader, events);
+    loop {
+        for (client, message) in out.drain(..) {
+            if client == HOST {
+                print!("{}", describe(&message, "Host"));
+            } else {
+                // A dropped connection shows up on the reader thread
+                send(&mut writer, &message).ok();
+            }
+        }
+        io::stdout().flush().ok();
+        match incoming.recv() {
+            Ok(Event::Typed(line)) => match parse_input(&line) {
+                Some(message) => {
+                    out = rooms.handle(HOST, message);
+                    more.send(true).ok();
+                }
+                None => {
+                    more.send(false).ok();
+                    out = rooms.leave(HOST);
+                    for (_, message) in out.drain(..) {
+                        send(&mut writer, &message).ok();
+                    }
+                    break;
+                }
+            },
+            Ok(Event::Received(ClientMessage::Join { .. })) => {}
+            Ok(Event::Received(ClientMessage::Leave)) | Ok(Event::Closed) | Err(_) => {
+                println!("{name} left the match. Press Enter to go back.");
+                wait_for_enter(&incoming, &more);
+                break;
+            }
+            Ok(Event::Received(message)) => out = rooms.handle(GUEST, message),
+        }
+    }
+    // Also ends the reader thread, which holds a clone of the stream
+    writer.shutdown(Shutdown::Both).ok();
+    rooms.into_engine()
+}
+
+/// Connects to a host as `name` and plays until either side quits
+pub fn join(addr: impl ToSocketAddrs, name: &str) -> io::Result<()> {
+    let mut writer = TcpStream::connect(addr)?;
+    let reader = BufReader::new(writer.try_clone()?);
+    send(&mut writer, &join_message(name))?;
+
+    let (events, incoming) = mpsc::channel();
+    let more = read_lines(events.clone());
+    read_messages::<ServerMessage>(reader, events);
+    loop {
+        match incoming.recv() {
+            Ok(Event::Typed(line)) => match parse_input(&line) {
+                Some(message) => {
+                    send(&mut writer, &message)?;
+                    more.send(true).ok();
+                }
+                None => {
+                    more.send(false).ok();
+                    send(&mut writer, &ClientMessage::Leave).ok();
+                    writer.shutdown(Shutdown::Both).ok();
+                    return Ok(());
+                }
+            },
+            Ok(Event::Received(message)) => {
+                print!("{}", describe(&message, name));
+                io::stdout().flush().ok();
+            }
+            Ok(Event::Closed) | Err(_) => {
+                println!("The host ended the match. Press Enter to go back.");
+                wait_for_enter(&incoming, &more);
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn join_message(name: &str) -> ClientMessage {
+    ClientMessage::Join {
+        room: ROOM.into(),
+        name: name.into(),
+    }
+}
+
+/// How a message reads at the terminal of the player called `me`
+pub fn describe(message: &ServerMessage, me: &str) -> String {
+    let who = |player: &str| {
+        if player == me {
+            "You".to_string()
+        } else {
+            player.to_string()
+        }
+    };
+    match message {
+        ServerMessage::Joined { players, .. } => format!(
+            "Playing head to head: {}. Type 'start' or 'start <code>' for a board, \
+             then guess names; 'reveal' gives up a board and 'quit' ends the match. \
+             'say <message>' chats, and 'react {}' sends a reaction.\n",
+            names(players),
+            Emote::ALL.map(Emote::as_str).join("|")
+        ),
+        ServerMessage::PlayerJoined { name } => format!("{name} joined.\n"),
+        ServerMessage::PlayerLeft { name } => format!("{name} left.\n"),
+        ServerMessage::Board { question, board } => {
+            format!("\nQuestion: {question}\n{}", table(board))
+        }
+        ServerMessage::Correct {
+            player,
+            row,
+            name,
+            points,
+        } => format!("{} got #{}: {name} (+{points})\n", who(player), row + 1),
+        ServerMessage::Strike { player, strikes } => {
+            format!("{} missed. Strike {strikes}/{MAX_STRIKES}\n", who(player))
+        }
+        ServerMessage::TooLate { guess } => format!("Too late, '{guess}' was already taken\n"),
+        ServerMessage::Ambiguous { guess, matches } => {
+            format!("{matches} players match '{guess}' — be more specific\n")
+        }
+        ServerMessage::Scores { players } => format!("Scores: {}\n", names(players)),
+        ServerMessage::Finished { end, board } => {
+            let how = match end {
+                GameEnd::Perfect => "Every name found!",
+                GameEnd::StruckOut => "Three strikes, the board is over.",
+                GameEnd::GaveUp => "Board revealed.",
+                GameEnd::TimeUp => "Time's up!",
+                GameEnd::Skipped => "Board skipped.",
+            };
+            format!("{how}\n{}\n", table(board))
+        }
+        ServerMessage::Report { markdown } => format!("{markdown}\n"),
+        ServerMessage::Chat { player, text } => format!("[{}] {text}\n", who(player)),
+        ServerMessage::Emote { player, emote } => {
+            format!("{} reacts: {}\n", who(player), emote.text())
+        }
+        ServerMessage::Error { message } => format!("Error: {message}\n"),
+    }
+}
+
+/// "Host 120, Guest 300"
+fn names(players: &[PlayerScore]) -> String {
+    players
+        .iter()
+        .map(|p| format!("{} {}", p.name, p.score))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A board as the REPL shows it, with blank names hidden
+fn table(board: &Board) -> String {
+    let mut out = format!("{}\n", board.column_names.join(" | "));
+    for (i, row) in board.rows.iter().enumerate() {
+        let cells: Vec<&str> = row
+            .iter()
+            .map(|cell| if cell.is_empty() { "-------" } else { cell })
+            .collect();
+        out.push_str(&format!("{:>2}: {}\n", i + 1, cells.join(" | ")));
+    }
+    out
+}
+
+enum Event<M> {
+    Typed(String),
+    Received(M),
+    Closed,
+}
+
+/// Reads stdin on its own thread, one line per go-ahead on the returned
+/// channel, so no line meant for the REPL is read once the match is over
+fn read_lines<M: Send + 'static>(events: Sender<Event<M>>) -> Sender<bool> {
+    let (more, go_ahead) = mpsc::channel();
+    thread::spawn(move || loop {
+        let mut line = String::new();
+        let line = match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => "quit".to_string(),
+            Ok(_) => line,
+        };
+        if events.send(Event::Typed(line)).is_err() || !go_ahead.recv().unwrap_or(false) {
+            break;
+        }
+    });
+    more
+}
+
+/// Forwards messages from the other side until it hangs up
+fn read_messages<M: DeserializeOwned + Send + 'static>(
+    mut reader: BufReader<TcpStream>,
+    events: Sender<Event<M>>,
+) {
+    thread::spawn(move || {
+        while let Ok(Some(message)) = recv(&mut reader) {
+            if events.send(Event::Received(message)).is_err() {
+                return;
+            }
+        }
+        events.send(Event::Closed).ok();
+    });
+}
+
+/// Lets the stdin thread read one last line, then stops it
+fn wait_for_enter<M>(incoming: &Receiver<Event<M>>, more: &Sender<bool>) {
+    while let Ok(event) = incoming.recv() {
+        if let Event::Typed(_) = event {
+            break;
+        }
+    }
+    more.send(false).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(parse_input("quit\n"), None);
+        assert_eq!(
+            parse_input("start last10passers_PIT"),
+            Some(ClientMessage::Start {
+                code: Some("last10passers_PIT".into())
+            })
+        );
+        assert_eq!(
+            parse_input("Start"),
+            Some(ClientMessage::Start { code: None })
+        );
+        assert_eq!(parse_input("reveal"), Some(ClientMessage::Reveal));
+        assert_eq!(
+            parse_input("say too slow"),
+            Some(ClientMessage::Chat {
+                text: "too slow".into()
+            })
+        );
+        assert_eq!(
+            parse_input("react gg"),
+            Some(ClientMessage::Emote { emote: "gg".into() })
+        );
+        assert_eq!(
+            parse_input(" Ben Roethlisberger "),
+            Some(ClientMessage::Guess {
+                text: "Ben Roethlisberger".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_messages_round_trip_as_lines() {
+        let mut wire = Vec::new();
+        send(&mut wire, &ClientMessage::Reveal).unwrap();
+        send(&mut wire, &join_message("Ann")).unwrap();
+        assert_eq!(wire.iter().filter(|&&b| b == b'\n').count(), 2);
+
+        let mut reader = io::Cursor::new(wire);
+        assert_eq!(recv(&mut reader).unwrap(), Some(ClientMessage::Reveal));
+        assert_eq!(recv(&mut reader).unwrap(), Some(join_message("Ann")));
+        assert_eq!(recv::<ClientMessage>(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_describe_from_each_side() {
+        let hit = ServerMessage::Correct {
+            player: "Ann".into(),
+            row: 2,
+            name: "Tom Brady".into(),
+            points: 120,
+        };
+        assert_eq!(describe(&hit, "Ann"), "You got #3: Tom Brady (+120)\n");
+        assert_eq!(describe(&hit, "Host"), "Ann got #3: Tom Brady (+120)\n");
+        let chat = ServerMessage::Chat {
+            player: "Ann".into(),
+            text: "too slow".into(),
+        };
+        assert_eq!(describe(&chat, "Host"), "[Ann] too slow\n");
+        assert_eq!(describe(&chat, "Ann"), "[You] too slow\n");
+
+        let board = ServerMessage::Board {
+            question: "Most yards".into(),
+            board: Board {
+                column_names: vec!["name".into(), "yards".into()],
+                rows: vec![vec!["".into(), "5000".into()]],
+            },
+        };
+        assert_eq!(
+            describe(&board, "Ann"),
+            "\nQuestion: Most yards\nname | yards\n 1: ------- | 5000\n"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Board columns laid out to line up in a terminal
+//!
+//! Each column is as wide as its widest cell, numbers are right-aligned, and
+//! when a row would be wider than the terminal the widest text columns are cut
+//! short with an ellipsis. Widths are measured in terminal cells, so accented
+//! and wide characters don't throw the columns off.
+use crate::sql_runner::Board;
+use crate::theme::Style;
+use std::borrow::Cow;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Between two cells of a row
+pub const SEPARATOR: &str = " | ";
+
+/// Text columns are never cut narrower than this
+const MIN_WIDTH: usize = 6;
+
+/// How wide each column of a board is drawn and which side it lines up on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    widths: Vec<usize>,
+    numeric: Vec<bool>,
+}
+
+impl Layout {
+    /// Columns wide enough for `board`'s column names and every cell, with
+    /// the ones holding only numbers right-aligned
+    pub fn new(board: &Board) -> Self {
+        let mut widths: Vec<usize> = board.column_names.iter().map(|n| n.width()).collect();
+        let mut numeric = vec![false; widths.len()];
+        let mut filled = vec![false; widths.len()];
+        for row in &board.rows {
+            if row.len() > widths.len() {
+                widths.resize(row.len(), 0);
+                numeric.resize(row.len(), false);
+                filled.resize(row.len(), false);
+            }
+            for (col, cell) in row.iter().enumerate() {
+                widths[col] = widths[col].max(cell.width());
+                if cell.is_empty() {
+                    continue;
+                }
+                numeric[col] = (numeric[col] || !filled[col]) && is_number(cell);
+                filled[col] = true;
+            }
+        }
+        Self { widths, numeric }
+    }
+
+    /// Widens `col` to at least `width`, e.g. for a placeholder drawn in place
+    /// of its cells
+    pub fn at_least(mut self, col: usize, width: usize) -> Self {
+        if let Some(w) = self.widths.get_mut(col) {
+            *w = (*w).max(width);
+        }
+        self
+    }
+
+    /// Narrows the widest text columns until a row, plus `margin` cells of
+    /// row numbers or marks around it, fits in `width`. Numbers are never cut,
+    /// so a very narrow terminal can still wrap.
+    pub fn fit(mut self, width: usize, margin: usize) -> Self {
+        while self.width() + margin > width {
+            let widest = (0..self.widths.len())
+                .filter(|&col| !self.numeric[col] && self.widths[col] > MIN_WIDTH)
+                .max_by_key(|&col| (self.widths[col], std::cmp::Reverse(col)));
+            match widest {
+                Some(col) => self.widths[col] -= 1,
+                None => break,
+            }
+        }
+        self
+    }
+
+    /// The width of a whole row, separators included
+    pub fn width(&self) -> usize {
+        let cells: usize = self.widths.iter().sum();
+        cells + SEPARATOR.len() * self.widths.len().saturating_sub(1)
+    }
+
+    /// Whether `col` holds only numbers
+    pub fn is_numeric(&self, col: usize) -> bool {
+        self.numeric.get(col).copied().unwrap_or(false)
+    }
+
+    /// `text` cut and padded to `col`'s width. The last column isn't padded
+    /// on the right, so lines don't end in spaces.
+    pub fn cell(&self, col: usize, text: &str) -> String {
+        let mut out = String::new();
+        self.push_cell(&mut out, col, text, Style::PLAIN);
+        out
+    }
+
+    /// Appends `text` to `out` as [`cell`](Self::cell) would lay it out, in
+    /// `style` but with the padding left plain
+    pub fn push_cell(&self, out: &mut String, col: usize, text: &str, style: Style) {
+        let Some(&width) = self.widths.get(col) else {
+            style.push(out, text);
+            return;
+        };
+        let text = truncate(text, width);
+        let pad = width.saturating_sub(text.width());
+        if self.is_numeric(col) {
+            push_spaces(out, pad);
+            style.push(out, &text);
+        } else {
+            style.push(out, &text);
+            if col + 1 < self.widths.len() {
+                push_spaces(out, pad);
+            }
+        }
+    }
+}
+
+fn push_spaces(out: &mut String, n: usize) {
+    out.extend(std::iter::repeat_n(' ', n));
+}
+
+/// A stat like `1234`, `-4`, `17.5`, or `1,681`
+fn is_number(cell: &str) -> bool {
+    cell.replace(',', "").parse::<f64>().is_ok()
+}
+
+/// `text` if it fits in `width` cells, else as much as fits with an ellipsis
+fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    if text.width() <= width {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('…');
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> Board {
+        Board {
+            column_names: vec!["name".into(), "team_abbr".into(), "yards".into()],
+            rows: vec![
+                vec!["Equanimeous St. Brown".into(), "CHI".into(), "-4".into()],
+                vec!["Ja'Marr Chase".into(), "CIN".into(), "3,717".into()],
+            ],
+        }
+    }
+
+    #[test]
+    fn test_cells_line_up() {
+        let layout = Layout::new(&board());
+        assert!(layout.is_numeric(2) && !layout.is_numeric(1));
+        assert_eq!(layout.cell(0, "Ja'Marr Chase"), "Ja'Marr Chase        ");
+        assert_eq!(layout.cell(2, "-4"), "   -4");
+        assert_eq!(layout.width(), 21 + 9 + 5 + 6);
+        // The last column isn't padded unless it lines up on the right
+        let names = Layout::new(&Board {
+            column_names: vec!["name".into(), "team".into()],
+            rows: vec![vec!["Tom Brady".into(), "TB".into()]],
+        });
+        assert_eq!(names.cell(1, "TB"), "TB");
+    }
+
+    #[test]
+    fn test_fit_cuts_the_widest_text_column() {
+        let layout = Layout::new(&board()).fit(36, 4);
+        assert_eq!(layout.width() + 4, 36);
+        assert_eq!(layout.cell(0, "Equanimeous St. Brown"), "Equanimeous…");
+        assert_eq!(layout.cell(1, "CHI"), "CHI      ");
+        assert_eq!(layout.cell(2, "3,717"), "3,717");
+        // Too narrow for anything: text stops at the minimum
+        let layout = Layout::new(&board()).fit(10, 0);
+        assert_eq!(layout.cell(0, "Equanimeous St. Brown"), "Equan…");
+    }
+
+    #[test]
+    fn test_wide_characters_count_double() {
+        assert_eq!(truncate("大谷翔平", 5), "大谷…");
+        assert_eq!(
+            "Patrick Mahomes".width(),
+            truncate("Patrick Mahomes", 15).width()
+        );
+    }
+}
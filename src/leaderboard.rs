@@ -0,0 +1,342 @@
+//! Opt-in global leaderboard for the daily board (`leaderboard` feature)
+//!
+//! Nothing is sent until the player runs `leaderboard join <URL>`, which
+//! saves the endpoint and a random anonymous token to
+//! [`DEFAULT_LEADERBOARD_FILE`]. From then on each finished daily board
+//! submits the day, question code, and score under that token; no name or
+//! anything else about the player leaves the machine. `leaderboard leave`
+//! deletes the file, token included.
+//!
+//! The endpoint is any server with two routes:
+//!
+//! - `POST {endpoint}/daily/{day}/scores` takes a [`Submission`]
+//! - `GET {endpoint}/daily/{day}/top?limit=N` returns [`Standing`]s, best first
+//!
+//! Server errors and dropped connections are retried a few times with
+//! backoff; other errors are returned straight away.
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Where the opt-in, endpoint, and token are kept
+pub const DEFAULT_LEADERBOARD_FILE: &str = "know_ball_leaderboard.json";
+
+/// How many times a request is tried before giving up
+pub const ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry; it doubles for each one after
+pub const BACKOFF: Duration = Duration::from_millis(500);
+
+/// How long one request may take
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Why a leaderboard call failed
+#[derive(Debug, Error)]
+pub enum LeaderboardError {
+    /// The server answered with an error status
+    #[error("HTTP {status}: {message}")]
+    Api { status: u16, message: String },
+    /// The server couldn't be reached, or its answer wasn't the expected JSON
+    #[error("request failed: {0}")]
+    Transport(String),
+    #[error("could not read or write the leaderboard settings: {0}")]
+    Io(#[from] io::Error),
+    #[error("the leaderboard settings are damaged: {0}")]
+    Parse(String),
+}
+
+impl LeaderboardError {
+    /// Whether trying again might help: server trouble, rate limits, and
+    /// network errors, but not requests the server rejected
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LeaderboardError::Api { status, .. } => *status >= 500 || *status == 429,
+            LeaderboardError::Transport(_) => true,
+            LeaderboardError::Io(_) | LeaderboardError::Parse(_) => false,
+        }
+    }
+}
+
+/// The player's opt-in: where scores go and the token they go under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardConfig {
+    pub endpoint: String,
+    /// Random and unrelated to the player, so the server can tell a
+    /// player's scores apart without knowing who they are
+    pub token: String,
+}
+
+impl LeaderboardConfig {
+    /// Opts in to `endpoint` with a fresh anonymous token
+    pub fn join(endpoint: impl Into<String>) -> Self {
+        let token: u128 = rand::thread_rng().gen();
+        Self {
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            token: format!("{token:032x}"),
+        }
+    }
+
+    /// The saved opt-in, or None if the player hasn't joined
+    pub fn load(path: &Path) -> Result<Option<Self>, LeaderboardError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map(Some)
+                .map_err(|e| LeaderboardError::Parse(e.to_string())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), LeaderboardError> {
+        let json = serde_json::to_string_pretty(self).expect("settings serialize to JSON");
+        Ok(std::fs::write(path, json + "\n")?)
+    }
+
+    /// Opts out, forgetting the token. Returns whether there was one.
+    pub fn leave(path: &Path) -> Result<bool, LeaderboardError> {
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// One finished daily board, as sent to the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submission {
+    pub token: String,
+    pub code: String,
+    pub score: u32,
+    pub correct: usize,
+    pub total: usize,
+}
+
+/// One place in the global top list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Standing {
+    pub rank: u32,
+    /// The server's public name for a token; never the token itself
+    pub player: String,
+    pub score: u32,
+}
+
+/// A connection to one leaderboard server
+#[derive(Debug, Clone)]
+pub struct LeaderboardClient {
+    config: LeaderboardConfig,
+    agent: ureq::Agent,
+    backoff: Duration,
+}
+
+impl LeaderboardClient {
+    pub fn new(config: LeaderboardConfig) -> Self {
+        let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+        Self {
+            config,
+            agent,
+            backoff: BACKOFF,
+        }
+    }
+
+    /// Waits `backoff` before the first retry instead of [`BACKOFF`]
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sends a finished daily board's score
+    pub fn submit(
+        &self,
+        day: u64,
+        code: &str,
+        score: u32,
+        correct: usize,
+        total: usize,
+    ) -> Result<(), LeaderboardError> {
+        let body = Submission {
+            token: self.config.token.clone(),
+            code: code.into(),
+            score,
+            correct,
+            total,
+        };
+        let url = format!("{}/daily/{day}/scores", self.config.endpoint);
+        with_retry(self.backoff, || {
+            read::<serde_json::Value>(self.agent.post(&url).send_json(&body))
+        })
+        .map(|_| ())
+    }
+
+    /// The day's best scores, best first
+    pub fn top(&self, day: u64, limit: usize) -> Result<Vec<Standing>, LeaderboardError> {
+        let url = format!("{}/daily/{day}/top", self.config.endpoint);
+        with_retry(self.backoff, || {
+            read(
+                self.agent
+                    .get(&url)
+                    .query("limit", &limit.to_string())
+                    .call(),
+            )
+        })
+    }
+}
+
+/// Calls `call` up to [`ATTEMPTS`] times while it fails with a retryable
+/// error, doubling the wait from `backoff` each time
+pub fn with_retry<T>(
+    backoff: Duration,
+    mut call: impl FnMut() -> Result<T, LeaderboardError>,
+) -> Result<T, LeaderboardError> {
+    let mut wait = backoff;
+    let mut attempt = 1;
+    loop {
+        match call() {
+            Err(e) if attempt < ATTEMPTS && e.is_retryable() => {
+                std::thread::sleep(wait);
+                wait *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Decodes a response body, or the error of a failed request
+fn read<T: DeserializeOwned>(
+    response: Result<ureq::Response, ureq::Error>,
+) -> Result<T, LeaderboardError> {
+    match response {
+        Ok(response) => response
+            .into_json()
+            .map_err(|e| LeaderboardError::Transport(e.to_string())),
+        Err(ureq::Error::Status(status, response)) => Err(LeaderboardError::Api {
+            status,
+            message: response.into_string().unwrap_or_default(),
+        }),
+        Err(e) => Err(LeaderboardError::Transport(e.to_string())),
+    }
+}
+
+/// The top list as numbered lines
+pub fn standings_text(standings: &[Standing]) -> String {
+    standings
+        .iter()
+        .map(|s| format!("{:>3}. {} {}\n", s.rank, s.player, s.score))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Answers each connection with the next of `responses`, as a status
+    /// line and a JSON body, and returns the server's URL
+    fn serve(responses: Vec<(&'static str, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut request_body = vec![0; length];
+                std::io::Read::read_exact(&mut reader, &mut request_body).unwrap();
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    fn client(url: String) -> LeaderboardClient {
+        let config = LeaderboardConfig {
+            endpoint: url,
+            token: "t".into(),
+        };
+        LeaderboardClient::new(config).with_backoff(Duration::ZERO)
+    }
+
+    #[test]
+    fn test_retries_server_errors() {
+        let url = serve(vec![
+            ("503 Service Unavailable", "{}"),
+            (
+                "200 OK",
+                r#"[{"rank":1,"player":"swift-otter","score":870}]"#,
+            ),
+        ]);
+        let top = client(url).top(20_000, 10).unwrap();
+        assert_eq!(
+            top,
+            [Standing {
+                rank: 1,
+                player: "swift-otter".into(),
+                score: 870,
+            }]
+        );
+        assert_eq!(standings_text(&top), "  1. swift-otter 870\n");
+    }
+
+    #[test]
+    fn test_does_not_retry_rejected_requests() {
+        let url = serve(vec![("400 Bad Request", "bad day")]);
+        match client(url).submit(20_000, "passyds_TEAM", 500, 4, 10) {
+            Err(LeaderboardError::Api { status, message }) => {
+                assert_eq!(status, 400);
+                assert_eq!(message, "bad day");
+            }
+            other => panic!("expected a 400, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_gives_up_after_attempts() {
+        let mut calls = 0;
+        let result: Result<(), _> = with_retry(Duration::ZERO, || {
+            calls += 1;
+            Err(LeaderboardError::Transport("refused".into()))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, ATTEMPTS);
+    }
+
+    #[test]
+    fn test_join_save_and_leave() {
+        let path =
+            std::env::temp_dir().join(format!("know_ball_leaderboard_{}.json", std::process::id()));
+        assert_eq!(LeaderboardConfig::load(&path).unwrap(), None);
+
+        let config = LeaderboardConfig::join("https://scores.example/");
+        assert_eq!(config.endpoint, "https://scores.example");
+        assert_eq!(config.token.len(), 32);
+        assert_ne!(config.token, LeaderboardConfig::join("x").token);
+
+        config.save(&path).unwrap();
+        assert_eq!(LeaderboardConfig::load(&path).unwrap(), Some(config));
+        assert!(LeaderboardConfig::leave(&path).unwrap());
+        assert!(!LeaderboardConfig::leave(&path).unwrap());
+    }
+}
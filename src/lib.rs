@@ -0,0 +1,97 @@
+//! Know Ball: NFL trivia boards generated from a local SQLite database.
+//!
+//! The binary is a thin REPL over this library. Other programs can embed the
+//! game through [`TriviaEngine`], which generates questions and loads boards,
+//! and [`TriviaGame`], which plays one board without doing any terminal IO.
+//! The lower-level pieces the engine is built from, [`build_registry`],
+//! [`parse_query`], and [`generate_sql_for_kind`], are re-exported here too.
+//!
+//! Frontends sit behind cargo features so the engine builds on its own with
+//! `--no-default-features`: `cli` (the terminal REPL), `packs` (TOML/JSON
+//! question packs, challenge files, and weekly result codes), `sqlite` (boards
+//! read from `nfl.sqlite`, or from the made-up league in `demo`), `async`
+//! (tokio wrappers), `bot` (shared chat-channel boards), `discord`, `irc`, and
+//! `matrix` (the chat bot binaries), `rooms` (multiplayer rooms), `lan`
+//! (head-to-head REPL matches over TCP), `leaderboard` (opt-in global daily
+//! scores), `server` (the WebSocket server binary), `grpc` (the gRPC service
+//! and its server binary), `http` and `http-client` (the JSON HTTP API, its
+//! server binary, and a typed client), and `wasm` (browser bindings). `full`
+//! enables all of them.
+pub mod answer_match;
+#[cfg(any(feature = "http", feature = "http-client"))]
+pub mod api;
+#[cfg(feature = "async")]
+pub mod async_engine;
+pub mod blitz;
+#[cfg(feature = "bot")]
+pub mod bot;
+#[cfg(feature = "packs")]
+pub mod challenge;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
+pub mod command;
+#[cfg(feature = "cli")]
+pub mod config;
+#[cfg(feature = "cli")]
+pub mod deal;
+#[cfg(feature = "sqlite")]
+pub mod demo;
+pub mod engine;
+pub mod error;
+pub mod events;
+#[cfg(feature = "sqlite")]
+pub mod explain;
+pub mod export;
+pub mod game;
+pub mod ghost;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "http-client")]
+pub mod http_client;
+#[cfg(feature = "lan")]
+pub mod lan;
+#[cfg(feature = "cli")]
+pub mod layout;
+#[cfg(feature = "leaderboard")]
+pub mod leaderboard;
+pub mod party;
+#[cfg(feature = "sqlite")]
+pub mod profile;
+pub mod quality;
+pub mod questions;
+pub mod quiz;
+pub mod report;
+#[cfg(feature = "sqlite")]
+pub mod results;
+#[cfg(feature = "rooms")]
+pub mod rooms;
+pub mod rules;
+mod run;
+#[cfg(feature = "cli")]
+pub mod script;
+pub mod selftest;
+pub mod session;
+#[cfg(feature = "cli")]
+pub mod spectate;
+pub mod speech;
+pub mod sql_runner;
+pub mod store;
+pub mod survival;
+pub mod team_match;
+#[cfg(feature = "cli")]
+pub mod theme;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "packs")]
+pub mod weekly;
+
+pub use engine::TriviaEngine;
+pub use error::{KnowBallError, Result};
+pub use game::{GuessOutcome, TriviaGame, TriviaResult};
+pub use questions::{
+    build_registry, generate_question, generate_sql_for_kind, parse_query, Question,
+    QuestionRegistry, QuestionSpec,
+};
@@ -1,144 +1,1706 @@
-mod questions;
-mod sql_runner;
-
-use crate::questions::{
-    build_registry, choose_random_question, generate_sql_for_kind, parse_query,
+use clap::Parser;
+use know_ball::answer_match::{AliasMatcher, MatcherKind};
+use know_ball::blitz::Blitz;
+#[cfg(feature = "packs")]
+use know_ball::challenge::{Challenge, CHALLENGE_EXTENSION};
+use know_ball::cli::{
+    emit_json, play_game, play_game_json, play_game_speech, play_party, JsonMessage,
+};
+#[cfg(feature = "leaderboard")]
+use know_ball::command::LeaderboardAction;
+#[cfg(feature = "packs")]
+use know_ball::command::WeeklyAction;
+use know_ball::command::{parse_command, Command};
+use know_ball::config::{self, Config};
+use know_ball::deal::{deal, Frontend};
+#[cfg(feature = "leaderboard")]
+use know_ball::engine::daily_seed;
+use know_ball::events::GameEvent;
+use know_ball::explain::{ExplainStore, DEFAULT_EXPLAIN_LOG};
+use know_ball::export::{
+    board_flashcards, missed_flashcards, render_anki, render_quiz, render_quiz_page, result_csv,
+    result_csv_name, ExportFormat, QuizRound,
+};
+#[cfg(any(feature = "packs", feature = "leaderboard"))]
+use know_ball::game::TriviaResult;
+use know_ball::game::{MAX_STRIKES, PERFECT_STREAK_LENGTH};
+use know_ball::ghost::{Ghost, GhostStore, DEFAULT_GHOST_FILE};
+#[cfg(feature = "lan")]
+use know_ball::lan;
+#[cfg(feature = "leaderboard")]
+use know_ball::leaderboard::{
+    standings_text, LeaderboardClient, LeaderboardConfig, DEFAULT_LEADERBOARD_FILE,
+};
+use know_ball::party::Party;
+use know_ball::profile::{self, ProfileDb, DEFAULT_PROFILE_DB};
+#[cfg(feature = "packs")]
+use know_ball::questions::pack::{pack_files, QUESTIONS_DIR};
+#[cfg(feature = "packs")]
+use know_ball::questions::quiz_pack::{QuizPack, QUIZ_PACK_EXTENSION};
+use know_ball::questions::{
+    BoardId, Category, Difficulty, League, Question, QuestionParams, QuestionSpec,
+};
+use know_ball::quiz::{Quiz, DEFAULT_QUIZ_LENGTH, MAX_QUIZ_LENGTH};
+use know_ball::report::Scoreboard;
+use know_ball::results::{self, ResultsDb};
+use know_ball::rules::{RuleSettings, RulesPreset};
+use know_ball::selftest;
+use know_ball::session::{GameSession, SessionSummary};
+use know_ball::spectate::{SpectateTarget, Spectator};
+use know_ball::speech;
+use know_ball::store::SqliteStore;
+use know_ball::survival::Survival;
+use know_ball::team_match::{Team, TeamMatch};
+use know_ball::theme::{self, Theme, ThemeName};
+#[cfg(feature = "packs")]
+use know_ball::weekly::{
+    IsoWeek, WeeklyBoard, WeeklyEntry, WeeklyResult, DEFAULT_WEEKLY_FILE, WEEKLY_BOARDS,
 };
+use know_ball::{KnowBallError, TriviaEngine, TriviaGame};
 use std::io::{self, Write};
+#[cfg(feature = "lan")]
+use std::net::TcpListener;
+#[cfg(any(feature = "packs", feature = "leaderboard"))]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-fn main() {
-    let registry = build_registry();
-    let mut session_score = 0u32;
-    let mut questions_played = 0u32;
+/// Name the players on a stats board, one guess at a time.
+///
+/// Without a subcommand, starts the interactive prompt.
+#[derive(Debug, Parser)]
+#[command(name = "know_ball", version)]
+struct CliArgs {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    /// Replay the random questions of an earlier session
+    #[arg(long, global = true, value_parser = parse_seed)]
+    seed: Option<u64>,
+    /// How close a guess must be: exact, substring, or fuzzy
+    #[arg(long = "match", global = true, value_name = "MATCHER")]
+    matcher: Option<MatcherKind>,
+    /// Print JSON messages instead of text
+    #[arg(long, global = true, conflicts_with = "speech")]
+    json: bool,
+    /// Print plain sentences for text-to-speech instead of tables
+    #[arg(long, global = true)]
+    speech: bool,
+    /// Directory to write each finished board to as CSV
+    #[arg(long, global = true, value_name = "DIR")]
+    export_csv: Option<PathBuf>,
+    /// The stats database, overriding `KNOWBALL_DB` and the config file's
+    #[arg(long, global = true, value_name = "FILE")]
+    db: Option<PathBuf>,
+    /// Copy the database into memory before the first question
+    #[arg(long, global = true)]
+    preload: bool,
+    /// Play a made-up league built in memory instead of reading a database
+    #[arg(long, global = true, conflicts_with_all = ["db", "preload"])]
+    demo: bool,
+    /// Mirror each board's state as JSON lines to a file, tcp:HOST:PORT, or unix:PATH
+    #[arg(long, global = true, value_name = "TARGET")]
+    spectate: Option<SpectateTarget>,
+    /// Race the previous attempt at each board
+    #[arg(long, global = true)]
+    ghost: bool,
+    /// Keep lifetime stats under this name, in know_ball_profiles.sqlite
+    #[arg(long, global = true, value_name = "NAME", value_parser = parse_profile)]
+    profile: Option<String>,
+    /// Give each board a countdown, 90 seconds unless given, e.g. --timed=60
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        value_parser = parse_secs,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "90"
+    )]
+    timed: Option<u64>,
+    /// The rules to play by: classic (3 strikes), sudden-death (1), or
+    /// casual (no limit)
+    #[arg(long, global = true, value_name = "PRESET")]
+    rules: Option<RulesPreset>,
+    /// Misses that end a board, 0 for no limit, overriding the rules'
+    #[arg(long, global = true, value_name = "N")]
+    strikes: Option<usize>,
+    /// Turn off 'reveal' and 'pass', so a board ends only by the strikes or the clock
+    #[arg(long, global = true)]
+    no_reveal: bool,
+    /// Turn off the `hint` command
+    #[arg(long, global = true)]
+    no_hints: bool,
+    /// Colors and symbols, overriding the config file's: default,
+    /// high-contrast, deuteranopia, or mono
+    #[arg(long, global = true, value_name = "NAME")]
+    theme: Option<ThemeName>,
+    /// Log every query's plan and timing to this file. Left out of the help
+    /// on purpose: it is for maintainers hunting slow questions.
+    #[arg(
+        long,
+        global = true,
+        hide = true,
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = DEFAULT_EXPLAIN_LOG
+    )]
+    explain: Option<PathBuf>,
+    /// A question pack to load; repeatable
+    #[cfg(feature = "packs")]
+    #[arg(long = "pack", global = true, value_name = "FILE")]
+    packs: Vec<PathBuf>,
+    /// The league to play, whose questions and database replace the NFL's
+    #[cfg(feature = "packs")]
+    #[arg(long, global = true, value_name = "nfl|nba")]
+    league: Option<League>,
+}
 
-    println!("Welcome to Know Ball (Rust / SQLite edition)");
-    println!("Commands:");
-    println!("  start  -> random question");
-    println!("  list   -> show all question codes");
-    println!("  score  -> show session score");
-    println!("  <code> -> run a specific question (e.g., recyds_TEAM_yearrange)");
-    println!("  quit   -> exit");
-    println!();
+/// What to do instead of starting the prompt
+#[derive(Debug, clap::Subcommand)]
+enum CliCommand {
+    /// Play one board by its code (e.g. passyds_PIT), or a random one
+    Play { code: Option<String> },
+    /// Deal a board again from the ID shown when it started
+    Replay { id: BoardId },
+    /// Play a random board
+    Random {
+        /// Only boards from this category, e.g. passing
+        #[arg(long)]
+        category: Option<Category>,
+        /// Only boards of this difficulty: easy, medium, or hard
+        #[arg(long)]
+        difficulty: Option<Difficulty>,
+    },
+    /// List every question code
+    List,
+    /// Play random boards until three strikes in all, across boards
+    Survival,
+    /// Play random boards against one clock until it runs out
+    Blitz {
+        /// How long the clock runs
+        #[arg(long, value_name = "SECS", value_parser = parse_secs, default_value = "180")]
+        secs: u64,
+    },
+    /// Play a fixed run of boards, then one scorecard for them all
+    Quiz {
+        /// How many boards, up to 100
+        #[arg(long, value_parser = parse_quiz_length, default_value_t = DEFAULT_QUIZ_LENGTH)]
+        count: usize,
+        /// Only boards from this category, e.g. rushing
+        #[arg(long)]
+        category: Option<Category>,
+        /// Only boards about this team, e.g. PIT
+        #[arg(long)]
+        team: Option<String>,
+    },
+    /// Play every question that takes a team for one franchise, then get a
+    /// fan score
+    Team {
+        /// e.g. PIT
+        team: String,
+    },
+    /// Print a quiz and answer key for the given codes
+    Export {
+        #[arg(required = true)]
+        codes: Vec<String>,
+        /// md or html
+        #[arg(long, default_value = "md")]
+        format: ExportFormat,
+        /// File to write instead of printing
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+    },
+    /// The interactive prompt, the same as no subcommand
+    Repl,
+}
 
-    let stdin = io::stdin();
+fn parse_seed(v: &str) -> Result<u64, String> {
+    v.parse()
+        .map_err(|_| format!("invalid seed '{v}', expected a non-negative integer"))
+}
+
+fn parse_profile(v: &str) -> Result<String, String> {
+    profile::check_name(v)
+        .map(|()| v.to_string())
+        .map_err(|e| e.to_string())
+}
+
+fn parse_quiz_length(v: &str) -> Result<usize, String> {
+    match v.parse() {
+        Ok(count) if (1..=MAX_QUIZ_LENGTH).contains(&count) => Ok(count),
+        _ => Err(format!(
+            "invalid count '{v}', expected 1 to {MAX_QUIZ_LENGTH} boards"
+        )),
+    }
+}
+
+fn parse_secs(v: &str) -> Result<u64, String> {
+    match v.parse() {
+        Ok(secs) if secs > 0 => Ok(secs),
+        _ => Err(format!(
+            "invalid time '{v}', expected a whole number of seconds"
+        )),
+    }
+}
+
+/// How the REPL reports back: readable text, one JSON message per line, or
+/// plain sentences for text-to-speech
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Text,
+    Json,
+    Speech,
+}
+
+impl Output {
+    /// Prints a line of text; JSON and speech modes drop it, since the same
+    /// facts arrive in the structured messages or the spoken board
+    fn say(self, text: &str) {
+        if self == Output::Text {
+            println!("{text}");
+        }
+    }
+
+    /// Prints an error as text, or as an `error` message
+    fn error(self, message: String) {
+        match self {
+            Output::Text => println!("Error: {message}\n"),
+            Output::Json => emit_json(&JsonMessage::Error { message }),
+            Output::Speech => println!("{}", speech::sentence(&format!("Sorry, {message}"))),
+        }
+    }
+
+    /// Prints a note, or a `notice` message
+    fn notice(self, message: String) {
+        match self {
+            Output::Text => println!("Note: {message}"),
+            Output::Json => emit_json(&JsonMessage::Notice { message }),
+            Output::Speech => println!("{}", speech::sentence(&message)),
+        }
+    }
+
+    /// Prints that something finished, e.g. a file was written, or a
+    /// `notice` message
+    fn done(self, message: String) {
+        match self {
+            Output::Text => println!("{message}\n"),
+            Output::Json => emit_json(&JsonMessage::Notice { message }),
+            Output::Speech => println!("{}", speech::sentence(&message)),
+        }
+    }
+
+    /// Prints a generated document, or writes it to `out` and says where.
+    /// `what` describes it, e.g. "a 2-question quiz".
+    fn document(
+        self,
+        document: String,
+        out: Option<PathBuf>,
+        what: &str,
+        as_json: impl FnOnce(String) -> JsonMessage<'static>,
+    ) {
+        let Some(path) = out else {
+            match self {
+                Output::Text | Output::Speech => println!("{document}"),
+                Output::Json => emit_json(&as_json(document)),
+            }
+            return;
+        };
+        match std::fs::write(&path, document) {
+            Ok(()) => self.done(format!("Wrote {what} to {}", path.display())),
+            Err(e) => self.error(format!("couldn't write {}: {e}", path.display())),
+        }
+    }
+
+    /// Prints the session totals under a heading, or as a `summary` message
+    fn summary(self, heading: &str, summary: SessionSummary) {
+        match self {
+            Output::Text => print_summary(heading, &summary),
+            Output::Json => emit_json(&JsonMessage::Summary { summary }),
+            Output::Speech => println!("{}", speech::summary_sentence(&summary)),
+        }
+    }
+}
+
+/// Boards dealt at the REPL play at the terminal
+impl Frontend for Output {
+    fn say(&mut self, text: &str) {
+        Output::say(*self, text);
+    }
+
+    fn notice(&mut self, message: String) {
+        Output::notice(*self, message);
+    }
+
+    fn error(&mut self, message: String) {
+        Output::error(*self, message);
+    }
+
+    fn failed(&mut self, error: KnowBallError) {
+        match self {
+            Output::Text => eprintln!("Error: {error}"),
+            _ => Output::error(*self, error.to_string()),
+        }
+    }
+
+    fn play(&mut self, spec: &QuestionSpec, game: &mut TriviaGame) {
+        run_game(spec, game, *self);
+    }
+
+    #[cfg(feature = "leaderboard")]
+    fn daily_played(&mut self, day: u64, result: &TriviaResult) {
+        submit_daily(day, result, *self);
+    }
+}
+
+/// Writes each finished board into `dir` as `<code>_<timestamp>.csv`
+fn csv_exporter(dir: PathBuf, output: Output) -> impl FnMut(&GameEvent) + Send {
+    move |event| {
+        let GameEvent::BoardFinished { result, .. } = event else {
+            return;
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let name = result_csv_name(result, timestamp);
+        let mut path = dir.join(&name);
+        // Two boards of the same question finished within a second
+        let stem = name.trim_end_matches(".csv");
+        for n in 2.. {
+            if !path.exists() {
+                break;
+            }
+            path = dir.join(format!("{stem}-{n}.csv"));
+        }
+        if let Err(e) = std::fs::write(&path, result_csv(result)) {
+            output.error(format!("couldn't write {}: {e}", path.display()));
+        }
+    }
+}
+
+/// How many places `leaderboard` shows
+#[cfg(feature = "leaderboard")]
+const LEADERBOARD_SIZE: usize = 10;
+
+/// Mirrors each board to a spectator stream, with one note if it stops
+/// accepting writes, e.g. because the overlay closed
+fn spectator(out: Box<dyn Write + Send>, output: Output) -> impl FnMut(&GameEvent) + Send {
+    let mut spectator = Spectator::new(out);
+    let mut closed = false;
+    move |event| {
+        if closed {
+            return;
+        }
+        if let Err(e) = spectator.write(event) {
+            output.notice(format!("stopped writing the spectator stream: {e}"));
+            closed = true;
+        }
+    }
+}
+
+/// Keeps each finished board as the ghost to race next time, saving the
+/// store after every board
+fn ghost_recorder(
+    ghosts: Arc<Mutex<GhostStore>>,
+    path: PathBuf,
+    output: Output,
+) -> impl FnMut(&GameEvent) + Send {
+    move |event| {
+        let GameEvent::BoardFinished { result, .. } = event else {
+            return;
+        };
+        let Some(ghost) = Ghost::from_result(result) else {
+            return;
+        };
+        let mut ghosts = ghosts.lock().unwrap();
+        ghosts.insert(ghost);
+        if let Err(e) = ghosts.save(&path) {
+            output.error(format!("couldn't save ghosts to {}: {e}", path.display()));
+        }
+    }
+}
 
+/// Adds each finished board to the profile's lifetime stats
+fn profile_recorder(
+    name: String,
+    profiles: Arc<Mutex<ProfileDb>>,
+    output: Output,
+) -> impl FnMut(&GameEvent) + Send {
+    move |event| {
+        let GameEvent::BoardFinished { result, .. } = event else {
+            return;
+        };
+        if let Err(e) = profiles.lock().unwrap().record(&name, result) {
+            output.error(format!("couldn't save the {name} profile: {e}"));
+        }
+    }
+}
+
+/// Says how a question code has gone before each board of it, and saves each
+/// finished board to the results file, under the profile if there is one
+fn results_recorder(
+    profile: Option<String>,
+    results: Arc<Mutex<ResultsDb>>,
+    output: Output,
+) -> impl FnMut(&GameEvent) + Send {
+    move |event| {
+        let results = results.lock().unwrap();
+        match event {
+            GameEvent::QuestionStarted { code, .. } => {
+                if let Ok(Some(stats)) = results.code_stats(profile.as_deref(), code) {
+                    output.notice(stats.to_string());
+                }
+            }
+            GameEvent::BoardFinished { result, .. } => {
+                if let Err(e) = results.record(profile.as_deref(), result, SystemTime::now()) {
+                    output.error(format!("couldn't save the board: {e}"));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Prints what `read` finds in the saved boards, given the `--profile` if
+/// there is one
+fn saved_boards(
+    context: &Context,
+    read: impl FnOnce(&ResultsDb, Option<&str>) -> know_ball::Result<String>,
+) {
+    let output = context.output;
+    let Some(results) = &context.results else {
+        output.error("no boards are being saved this session".into());
+        return;
+    };
+    let profile = context.profile.as_ref().map(|(name, _)| name.as_str());
+    match read(&results.lock().unwrap(), profile) {
+        Ok(text) => output.done(text),
+        Err(e) => output.error(format!("couldn't read the saved boards: {e}")),
+    }
+}
+
+/// Plays random boards as a buzzer party until the players are done, then
+/// prints the standings
+fn party(session: &mut GameSession, names: Vec<String>, output: Output) {
+    if output != Output::Text {
+        output.error("party mode is only available as text".into());
+        return;
+    }
+    let mut party = match Party::new(names) {
+        Ok(party) => party,
+        Err(e) => {
+            output.error(e.to_string());
+            return;
+        }
+    };
     loop {
-        print!("> ");
+        let Some(question) = session.engine_mut().random_question() else {
+            output.error("no questions registered".into());
+            return;
+        };
+        let played =
+            session.play_question(question.as_ref(), &QuestionParams::default(), |_, game| {
+                play_party(game, &mut party);
+            });
+        if let Err(e) = played {
+            output.error(e.to_string());
+        }
+        let another = loop {
+            print!("Press Enter for another board, or type 'done' to end the party: ");
+            io::stdout().flush().ok();
+            let mut line = String::new();
+            match io::stdin().read_line(&mut line) {
+                Ok(0) | Err(_) => break false,
+                Ok(_) if line.trim().is_empty() => break true,
+                Ok(_) if line.trim().eq_ignore_ascii_case("done") => break false,
+                Ok(_) => {}
+            }
+        };
+        if !another {
+            break;
+        }
+    }
+    println!("\n{}", party.final_standings());
+}
+
+/// Runs a best-of-N match between two local teams, asking each team's
+/// captain for a category before its board, then prints the match report
+fn team_match(session: &mut GameSession, teams: [Team; 2], best_of: u32, output: Output) {
+    if output != Output::Text {
+        output.error("team matches are only available as text".into());
+        return;
+    }
+    let [first, second] = teams;
+    let mut team_match = match TeamMatch::new(first, second, best_of) {
+        Ok(team_match) => team_match,
+        Err(e) => {
+            output.error(e.to_string());
+            return;
+        }
+    };
+    while !team_match.is_over() {
+        let team = &team_match.teams()[team_match.up()];
+        print!(
+            "\nRound {}: {} is up. {}, pick a category ({}) or press Enter for any: ",
+            team_match.round(),
+            team.name,
+            team.captain(),
+            Category::ALL.map(|c| c.as_str()).join(", ")
+        );
         io::stdout().flush().ok();
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let category = match line.trim() {
+            "" => None,
+            "done" => break,
+            name => match name.parse::<Category>() {
+                Ok(category) => Some(category),
+                Err(e) => {
+                    output.error(e);
+                    continue;
+                }
+            },
+        };
+        let played =
+            team_match.play_board(session, category, |spec, game| run_game(spec, game, output));
+        match played {
+            Ok(_) => println!("{}", team_match.score_line()),
+            Err(e) => eprintln!("Error: {e}"),
+        }
+    }
+    println!("\n{}", team_match.report());
+}
 
-        let mut input = String::new();
-        if stdin.read_line(&mut input).is_err() {
-            eprintln!("Error reading input, try again.");
-            continue;
+/// Plays random boards with strikes carried from one to the next until they
+/// run out or the player gives up a board
+fn survival(session: &mut GameSession, output: Output) {
+    let mut run = Survival::new(session.rules().strikes.unwrap_or(MAX_STRIKES));
+    output.say(&format!("{}\n", run.intro_line()));
+    while !run.is_over() {
+        match run.play_board(session, |spec, game| run_game(spec, game, output)) {
+            Ok(result) if result.total == 0 => {}
+            Ok(_) if !run.is_over() => output.say(&format!("{}\n", run.status_line())),
+            Ok(_) => {}
+            Err(e) => {
+                output.error(e.to_string());
+                return;
+            }
         }
+    }
+    output.done(run.final_line());
+}
 
-        let raw = input.trim().to_string();
-        if raw.is_empty() {
-            continue;
+/// Plays random boards against one clock until it runs out or the player
+/// gives up a board
+fn blitz(session: &mut GameSession, limit: Duration, output: Output) {
+    let mut run = Blitz::new(limit);
+    output.say(&format!("{}\n", run.intro_line()));
+    while !run.is_over() {
+        match run.play_board(session, |spec, game| run_game(spec, game, output)) {
+            Ok(result) if result.total == 0 => {}
+            Ok(_) if !run.is_over() => output.say(&format!("{}\n", run.status_line())),
+            Ok(_) => {}
+            Err(e) => {
+                output.error(e.to_string());
+                return;
+            }
         }
+    }
+    output.done(run.final_line());
+}
 
-        let lc_cmd = raw.to_lowercase();
+/// Plays a quiz's boards in order, then prints one scorecard for them all
+fn quiz(
+    session: &mut GameSession,
+    count: usize,
+    category: Option<Category>,
+    team: Option<String>,
+    output: Output,
+) {
+    let mut quiz = match Quiz::new(session, count, category, team) {
+        Ok(quiz) => quiz,
+        Err(e) => {
+            output.error(e.to_string());
+            return;
+        }
+    };
+    output.say(&format!("{}\n", quiz.intro_line()));
+    play_quiz(session, &mut quiz, output);
+    output.done(quiz.scorecard());
+}
+
+/// Plays every question that takes a team for one franchise, then prints
+/// the scorecard and the player's fan score
+fn team_marathon(session: &mut GameSession, team: &str, output: Output) {
+    let mut quiz = match Quiz::marathon(session, team) {
+        Ok(quiz) => quiz,
+        Err(e) => {
+            output.error(e.to_string());
+            return;
+        }
+    };
+    output.say(&format!(
+        "{} marathon: {} boards, every question that takes a team.\n",
+        team.to_ascii_uppercase(),
+        quiz.len()
+    ));
+    play_quiz(session, &mut quiz, output);
+    output.done(format!("{}\n{}", quiz.scorecard(), quiz.fan_line()));
+}
+
+/// Plays a quiz's boards in order, going on past any that fail to load
+fn play_quiz(session: &mut GameSession, quiz: &mut Quiz, output: Output) {
+    while !quiz.is_over() {
+        output.say(&format!("Board {} of {}", quiz.number(), quiz.len()));
+        if let Err(e) = quiz.play_board(session, |spec, game| run_game(spec, game, output)) {
+            output.error(e.to_string());
+        }
+    }
+}
+
+/// Keeps the last finished board, for `challenge save`
+#[cfg(feature = "packs")]
+fn last_board(last: Arc<Mutex<Option<TriviaResult>>>) -> impl FnMut(&GameEvent) + Send {
+    move |event| {
+        if let GameEvent::BoardFinished { result, .. } = event {
+            if result.total > 0 {
+                *last.lock().unwrap() = Some(result.clone());
+            }
+        }
+    }
+}
+
+/// Plays a loaded board with the text, JSON, or speech game loop
+fn run_game(spec: &QuestionSpec, game: &mut TriviaGame, output: Output) {
+    match output {
+        Output::Text => {
+            println!("Question: {}", spec.text);
+            println!("Board ID: {}", BoardId::of(spec));
+            play_game(game);
+        }
+        Output::Json => {
+            play_game_json(game);
+        }
+        Output::Speech => {
+            play_game_speech(game);
+        }
+    }
+}
 
-        match lc_cmd.as_str() {
-            "quit" | "exit" => {
-                println!("\n=== SESSION SUMMARY ===");
-                println!("Questions played: {}", questions_played);
-                println!("Total score: {}/{}", session_score, questions_played * 1000);
-                if questions_played > 0 {
-                    let avg = session_score as f64 / questions_played as f64;
-                    println!("Average: {:.1}/1000", avg);
+/// Generates questions by code and saves them as a quiz pack
+#[cfg(feature = "packs")]
+fn create_pack(
+    session: &mut GameSession,
+    path: &Path,
+    codes: &[String],
+    title: Option<String>,
+    hashes: bool,
+    output: Output,
+) {
+    let path = match path.extension() {
+        Some(_) => path.to_path_buf(),
+        None => path.with_extension(QUIZ_PACK_EXTENSION),
+    };
+    let title = title.unwrap_or_else(|| {
+        let stem = path.file_stem().unwrap_or_default();
+        stem.to_string_lossy().into_owned()
+    });
+    let mut pack = QuizPack::new(title);
+    for round in quiz_rounds(session, codes, output) {
+        pack.push(round.spec, hashes.then_some(&round.board));
+    }
+    if pack.questions.is_empty() {
+        return;
+    }
+    match pack.save(&path) {
+        Ok(()) => output.done(format!(
+            "Saved {} question(s) to {}",
+            pack.questions.len(),
+            path.display()
+        )),
+        Err(e) => output.error(e.to_string()),
+    }
+}
+
+/// Plays every question in a quiz pack in order, warning when a board differs
+/// from the one the pack was made with
+#[cfg(feature = "packs")]
+fn play_pack(session: &mut GameSession, path: &Path, output: Output) {
+    let pack = match QuizPack::load(path) {
+        Ok(pack) => pack,
+        Err(e) => {
+            output.error(format!("{}: {e}", path.display()));
+            return;
+        }
+    };
+    let by = pack
+        .author
+        .as_ref()
+        .map(|a| format!(" by {a}"))
+        .unwrap_or_default();
+    output.say(&format!("Quiz pack: {}{by}", pack.title));
+    if let Some(description) = &pack.description {
+        output.say(description);
+    }
+
+    let total = pack.questions.len();
+    for (n, entry) in pack.questions.iter().enumerate() {
+        output.say(&format!("\nQuestion {} of {total}", n + 1));
+        let played = session.play_spec(entry.spec.clone(), |spec, game| {
+            if entry.matches_board(game.board()) == Some(false) {
+                output.notice(
+                    "this board differs from the one the pack was made with; \
+                     your nfl.sqlite may be a different version"
+                        .into(),
+                );
+            }
+            run_game(spec, game, output);
+        });
+        if let Err(e) = played {
+            output.error(e.to_string());
+        }
+    }
+}
+
+/// Plays the week's set, checks in a friend's result code, or shows a
+/// week's standings on the local weekly leaderboard
+#[cfg(feature = "packs")]
+fn weekly(session: &mut GameSession, action: WeeklyAction, output: Output) {
+    let path = Path::new(DEFAULT_WEEKLY_FILE);
+    let mut board = match WeeklyBoard::load(path) {
+        Ok(board) => board,
+        Err(e) => {
+            output.error(e.to_string());
+            return;
+        }
+    };
+    let this_week = IsoWeek::of(SystemTime::now());
+    let viewing = matches!(action, WeeklyAction::Board(_));
+    let week = match action {
+        WeeklyAction::Play => {
+            output.say(&format!(
+                "Weekly challenge for {this_week}: {WEEKLY_BOARDS} boards"
+            ));
+            let mut boards = Vec::new();
+            let played =
+                session.play_seeded_set(this_week.seed(), WEEKLY_BOARDS, |i, spec, game| {
+                    output.say(&format!("Board {} of {WEEKLY_BOARDS}", i + 1));
+                    boards.push(game.board().clone());
+                    run_game(spec, game, output);
+                });
+            let results = match played {
+                Ok(results) => results,
+                Err(e) => {
+                    output.error(e.to_string());
+                    return;
                 }
-                println!("Goodbye!");
-                break;
+            };
+            let result = WeeklyResult {
+                week: this_week,
+                scores: results.iter().map(|r| r.score).collect(),
+            };
+            let code = result.code(&boards);
+            output.done(format!(
+                "You scored {} this week. Your result code is {code}; friends can add it \
+                 with 'weekly add <NAME> <CODE>'.",
+                result.total()
+            ));
+            board.add(WeeklyEntry {
+                player: "You".into(),
+                result,
+                code,
+            });
+            this_week
+        }
+        WeeklyAction::Add { player, code } => {
+            let week = match WeeklyResult::parse(&code) {
+                Ok((result, _)) => result.week,
+                Err(e) => {
+                    output.error(e.to_string());
+                    return;
+                }
+            };
+            let loaded = session.engine().load_seeded_set(week.seed(), WEEKLY_BOARDS);
+            let boards: Vec<_> = match loaded {
+                Ok(set) => set.into_iter().map(|(_, board)| board).collect(),
+                Err(e) => {
+                    output.error(e.to_string());
+                    return;
+                }
+            };
+            let result = match WeeklyResult::verify(&code, &boards) {
+                Ok(result) => result,
+                Err(e) => {
+                    output.error(e.to_string());
+                    return;
+                }
+            };
+            let total = result.total();
+            let entry = WeeklyEntry {
+                player: player.clone(),
+                result,
+                code,
+            };
+            if board.add(entry) {
+                output.done(format!("Added {player}'s {total} for {week}."));
+            } else {
+                output.done(format!("{player} already has a better score for {week}."));
             }
-            "score" => {
-                println!("\n=== SESSION SCORE ===");
-                println!("Questions played: {}", questions_played);
-                println!("Total score: {}/{}", session_score, questions_played * 1000);
-                if questions_played > 0 {
-                    let avg = session_score as f64 / questions_played as f64;
-                    println!("Average: {:.1}/1000", avg);
-                }
-                println!();
-            }
-            "list" => {
-                println!("Available question codes:");
-                let mut codes: Vec<_> = registry.iter().collect();
-                codes.sort_by_key(|(code, _)| *code);
-                for (code, meta) in codes {
-                    println!(" - {code}: {}", meta.description);
-                }
-                println!();
-            }
-            "start" => match choose_random_question(&registry) {
-                Some((code, meta)) => {
-                    println!("Random code: {code}");
-                    println!("Description: {}", meta.description);
-                    let (q_text, sql) = generate_sql_for_kind(meta.kind, None);
-                    println!("Question: {q_text}");
-
-                    match sql_runner::run_trivia(&q_text, &sql) {
-                        Ok(result) => {
-                            if result.total > 0 {
-                                session_score += result.score;
-                                questions_played += 1;
-                            }
-                        }
-                        Err(e) => eprintln!("Error running SQL: {e}"),
-                    }
+            week
+        }
+        WeeklyAction::Board(week) => match week.map(|w| w.parse()).unwrap_or(Ok(this_week)) {
+            Ok(week) => week,
+            Err(e) => {
+                output.error(e.to_string());
+                return;
+            }
+        },
+    };
+    if !viewing {
+        if let Err(e) = board.save(path) {
+            output.error(e.to_string());
+        }
+    }
+
+    let standings = board.standings(week);
+    if output == Output::Json {
+        let standings = standings.into_iter().cloned().collect();
+        emit_json(&JsonMessage::Weekly { week, standings });
+    } else if standings.is_empty() {
+        output.done(format!("No results for {week} yet."));
+    } else {
+        output.say(&format!("--- WEEKLY STANDINGS, {week} ---"));
+        print!("{}", board.standings_text(week));
+        output.say("");
+    }
+}
+
+/// Sends a finished daily board's score, if the player opted in
+#[cfg(feature = "leaderboard")]
+fn submit_daily(day: u64, result: &TriviaResult, output: Output) {
+    if result.total == 0 {
+        return;
+    }
+    let config = match LeaderboardConfig::load(Path::new(DEFAULT_LEADERBOARD_FILE)) {
+        Ok(Some(config)) => config,
+        Ok(None) => return,
+        Err(e) => {
+            output.error(e.to_string());
+            return;
+        }
+    };
+    let code = result.spec.as_ref().map_or("", |spec| spec.code.as_str());
+    let client = LeaderboardClient::new(config);
+    match client.submit(day, code, result.score, result.correct(), result.total) {
+        Ok(()) => output.done(
+            "Sent your score to the leaderboard; 'leaderboard' shows today's top scores.".into(),
+        ),
+        Err(e) => output.notice(format!("couldn't send your score to the leaderboard: {e}")),
+    }
+}
+
+/// Shows today's top scores, or opts in or out of sending daily scores
+#[cfg(feature = "leaderboard")]
+fn leaderboard(action: LeaderboardAction, output: Output) {
+    let path = Path::new(DEFAULT_LEADERBOARD_FILE);
+    match action {
+        LeaderboardAction::Show => {
+            let config = match LeaderboardConfig::load(path) {
+                Ok(Some(config)) => config,
+                Ok(None) => {
+                    output.error(
+                        "you haven't joined a leaderboard; 'leaderboard join <URL>' opts in".into(),
+                    );
+                    return;
+                }
+                Err(e) => {
+                    output.error(e.to_string());
+                    return;
+                }
+            };
+            let day = daily_seed(SystemTime::now());
+            match LeaderboardClient::new(config).top(day, LEADERBOARD_SIZE) {
+                Ok(standings) if output == Output::Json => {
+                    emit_json(&JsonMessage::Leaderboard { day, standings });
+                }
+                Ok(standings) if standings.is_empty() => {
+                    output.done("No scores yet today.".into());
+                }
+                Ok(standings) => {
+                    output.say(&format!("--- TOP SCORES, DAY {day} ---"));
+                    println!("{}", standings_text(&standings));
+                }
+                Err(e) => output.error(format!("couldn't fetch the leaderboard: {e}")),
+            }
+        }
+        LeaderboardAction::Join(endpoint) => {
+            let config = LeaderboardConfig::join(endpoint);
+            match config.save(path) {
+                Ok(()) => output.done(format!(
+                    "Joined {}. After each daily board, its code and your score are sent \
+                     under an anonymous token kept in {}; nothing else about you is. \
+                     'leaderboard leave' deletes the token.",
+                    config.endpoint, DEFAULT_LEADERBOARD_FILE
+                )),
+                Err(e) => output.error(e.to_string()),
+            }
+        }
+        LeaderboardAction::Leave => match LeaderboardConfig::leave(path) {
+            Ok(true) => output.done("Left the leaderboard; no more scores will be sent.".into()),
+            Ok(false) => output.done("You weren't on a leaderboard.".into()),
+            Err(e) => output.error(e.to_string()),
+        },
+    }
+}
+
+/// Saves the last finished board as a challenge file
+#[cfg(feature = "packs")]
+fn save_challenge(last: Option<&TriviaResult>, path: &Path, from: Option<String>, output: Output) {
+    let Some(result) = last else {
+        output.error("no finished board yet; play one, then save it as a challenge".into());
+        return;
+    };
+    let Some(challenge) = Challenge::from_result(from.unwrap_or("Challenger".into()), result)
+    else {
+        output.error("the last board can't be replayed, so it can't be a challenge".into());
+        return;
+    };
+    let path = match path.extension() {
+        Some(_) => path.to_path_buf(),
+        None => path.with_extension(CHALLENGE_EXTENSION),
+    };
+    match challenge.save(&path) {
+        Ok(()) => output.done(format!(
+            "Saved your {} points on {} to {}. A friend can play it with 'challenge play'.",
+            challenge.score,
+            challenge.spec.code,
+            path.display()
+        )),
+        Err(e) => output.error(e.to_string()),
+    }
+}
+
+/// Plays a friend's challenge and compares the two results row by row
+#[cfg(feature = "packs")]
+fn play_challenge(session: &mut GameSession, path: &Path, output: Output) {
+    let challenge = match Challenge::load(path) {
+        Ok(challenge) => challenge,
+        Err(e) => {
+            output.error(format!("{}: {e}", path.display()));
+            return;
+        }
+    };
+    output.say(&format!(
+        "Challenge from {}: {} points to beat",
+        challenge.from, challenge.score
+    ));
+    let played = session.play_spec(challenge.spec.clone(), |spec, game| {
+        if !challenge.matches_board(game.board()) {
+            output.notice(
+                "this board differs from the one the challenge was played on; \
+                 your nfl.sqlite may be a different version"
+                    .into(),
+            );
+        }
+        run_game(spec, game, output);
+    });
+    let result = match played {
+        Ok(result) if result.total > 0 => result,
+        Ok(_) => return,
+        Err(e) => {
+            output.error(e.to_string());
+            return;
+        }
+    };
+    let comparison = challenge.head_to_head(&result);
+    match output {
+        Output::Text => println!("{}", comparison.to_text()),
+        Output::Json => emit_json(&JsonMessage::HeadToHead { comparison }),
+        Output::Speech => println!(
+            "{} scored {}. You scored {}. {}",
+            comparison.opponent,
+            comparison.their_score,
+            comparison.your_score,
+            comparison.verdict()
+        ),
+    }
+}
+
+/// The question a quiz code names, with its team fixed if the code has one
+fn resolve_code(
+    session: &GameSession,
+    code: &str,
+) -> Result<(Arc<dyn Question>, QuestionParams), KnowBallError> {
+    match session.engine().lookup(code) {
+        Some(parsed) => Ok((
+            parsed.question,
+            QuestionParams::for_team(parsed.team.as_deref()),
+        )),
+        None => session
+            .engine()
+            .registry()
+            .get_ignore_case(code)
+            .map(|question| (Arc::clone(question), QuestionParams::default()))
+            .ok_or_else(|| KnowBallError::Parse(format!("unknown code '{code}'"))),
+    }
+}
+
+/// Generates a question by code and loads its board for a printable quiz
+fn quiz_round(session: &mut GameSession, code: &str) -> Result<QuizRound, KnowBallError> {
+    let (question, params) = resolve_code(session, code)?;
+    let (spec, board) = session
+        .engine_mut()
+        .load_question(question.as_ref(), &params)?;
+    Ok(QuizRound { spec, board })
+}
+
+/// Generates a question for each code and loads their boards in parallel,
+/// reporting the codes that fail and returning the rest in order
+fn quiz_rounds(session: &mut GameSession, codes: &[String], output: Output) -> Vec<QuizRound> {
+    let mut requested = Vec::new();
+    let mut requests = Vec::new();
+    for code in codes {
+        match resolve_code(session, code) {
+            Ok(request) => {
+                requested.push(code);
+                requests.push(request);
+            }
+            Err(e) => output.error(format!("{code}: {e}")),
+        }
+    }
+    let loaded = session.engine_mut().load_questions(&requests);
+    let mut rounds = Vec::new();
+    for (code, result) in requested.into_iter().zip(loaded) {
+        match result {
+            Ok((spec, board)) => rounds.push(QuizRound { spec, board }),
+            Err(e) => output.error(format!("{code}: {e}")),
+        }
+    }
+    rounds
+}
+
+/// Waits for one opponent on the LAN and plays a head-to-head match with the
+/// session's engine
+#[cfg(feature = "lan")]
+fn host_match(session: &mut GameSession, port: Option<u16>, output: Output) {
+    if output != Output::Text {
+        output.error("LAN matches are only played in text mode".into());
+        return;
+    }
+    let port = port.unwrap_or(lan::DEFAULT_PORT);
+    let listener = match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => listener,
+        Err(e) => return output.error(format!("couldn't listen on port {port}: {e}")),
+    };
+    let port = listener.local_addr().map_or(port, |addr| addr.port());
+    println!(
+        "Waiting for an opponent. On their machine, run: join <this machine's address>:{port}"
+    );
+    match listener.accept() {
+        Ok((stream, _)) => {
+            let engine = std::mem::take(session.engine_mut());
+            *session.engine_mut() = lan::host(engine, stream);
+        }
+        Err(e) => output.error(format!("couldn't accept an opponent: {e}")),
+    }
+}
+
+/// Plays a head-to-head match against a host, on the default port unless
+/// the address names one
+#[cfg(feature = "lan")]
+fn join_match(addr: &str, output: Output) {
+    if output != Output::Text {
+        output.error("LAN matches are only played in text mode".into());
+        return;
+    }
+    let addr = if addr.contains(':') {
+        addr.to_string()
+    } else {
+        format!("{addr}:{}", lan::DEFAULT_PORT)
+    };
+    println!("Connecting to {addr}...");
+    if let Err(e) = lan::join(addr.as_str(), "Guest") {
+        output.error(format!("couldn't play against {addr}: {e}"));
+    }
+}
+
+/// Loads every pack in the `questions/` directory and then every `--pack`
+/// file into the engine, exiting if one can't be read
+#[cfg(feature = "packs")]
+fn load_packs(mut engine: TriviaEngine, packs: &[PathBuf]) -> TriviaEngine {
+    let found = match pack_files(Path::new(QUESTIONS_DIR)) {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("Error reading {QUESTIONS_DIR}/: {e}");
+            std::process::exit(2);
+        }
+    };
+    for path in found.iter().chain(packs) {
+        match engine.load_pack(path) {
+            Ok((loaded, skipped)) => {
+                println!("Loaded {loaded} question(s) from {}", path.display());
+                for code in skipped {
+                    println!("  skipped '{code}': a question with that code already exists");
+                }
+            }
+            Err(e) => {
+                eprintln!("Error loading {}: {e}", path.display());
+                std::process::exit(2);
+            }
+        }
+    }
+    engine
+}
+
+/// Prints the session totals under a heading
+fn print_summary(heading: &str, summary: &SessionSummary) {
+    println!("\n=== {heading} ===");
+    println!("Questions played: {}", summary.questions_played);
+    if summary.questions_skipped > 0 {
+        println!("Questions skipped: {}", summary.questions_skipped);
+        println!(
+            "Total score: {}/{}, skipped boards included",
+            summary.total_score, summary.max_score
+        );
+    } else {
+        println!("Total score: {}/{}", summary.total_score, summary.max_score);
+    }
+    if let Some(avg) = summary.average {
+        println!("Average: {:.1}/1000", avg);
+    }
+    if summary.streak_bonus > 0 {
+        println!("Streak bonus: +{}", summary.streak_bonus);
+    }
+    if summary.perfect_streak >= PERFECT_STREAK_LENGTH {
+        println!("Perfect boards in a row: {}", summary.perfect_streak);
+    }
+}
+
+fn main() {
+    let cli = CliArgs::parse();
+    let config = match config::default_path().map(|path| Config::load(&path)) {
+        Some(Ok(config)) => config,
+        Some(Err(e)) => {
+            eprintln!("Error: couldn't read the config file: {e}");
+            std::process::exit(2);
+        }
+        None => Config::default(),
+    };
+    theme::set(Theme::for_stdout(
+        cli.theme.or(config.theme).unwrap_or_default(),
+    ));
+    let engine = match cli.seed {
+        Some(seed) => TriviaEngine::with_seed(seed),
+        None => TriviaEngine::new(),
+    };
+    #[cfg(feature = "packs")]
+    let engine = engine.with_league(cli.league.unwrap_or_default());
+    let db = config.db_path(cli.db.as_deref());
+    let db_path = db
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(engine.league().db_path()));
+    let store = if cli.demo {
+        match SqliteStore::demo(engine.league()) {
+            Ok(store) => Some(store),
+            Err(e) => {
+                eprintln!("Error: couldn't build the demo league: {e}");
+                std::process::exit(2);
+            }
+        }
+    } else if cli.preload {
+        match SqliteStore::preload(&db_path) {
+            Ok(store) => Some(store),
+            Err(e) => {
+                eprintln!("Error: couldn't preload {}: {e}", db_path.display());
+                std::process::exit(2);
+            }
+        }
+    } else {
+        db.map(SqliteStore::new)
+    };
+    let engine = match (store, cli.explain) {
+        (store, Some(path)) => match std::fs::File::options()
+            .append(true)
+            .create(true)
+            .open(&path)
+        {
+            Ok(log) => {
+                let store = store.unwrap_or_else(|| SqliteStore::new(&db_path));
+                engine.with_store(ExplainStore::new(store, log))
+            }
+            Err(e) => {
+                eprintln!("Error: couldn't open {}: {e}", path.display());
+                std::process::exit(2);
+            }
+        },
+        (Some(store), None) => engine.with_store(store),
+        (None, None) => engine,
+    };
+    #[cfg(feature = "packs")]
+    let engine = load_packs(engine, &cli.packs);
+    let seed = engine.seed();
+    let league = engine.league();
+    let mut session = GameSession::new(engine);
+    let matcher = AliasMatcher::with_nicknames(cli.matcher.unwrap_or_default().matcher())
+        .aliases(&config.nicknames);
+    session.set_matcher(Arc::new(matcher));
+    let rules = RuleSettings {
+        preset: cli.rules,
+        strikes: cli.strikes,
+        reveal: cli.no_reveal.then_some(false),
+        hints: cli.no_hints.then_some(false),
+        time_limit: cli.timed,
+    };
+    session.set_rules(rules.over(config.rules).rules());
+    let output = if cli.json {
+        Output::Json
+    } else if cli.speech {
+        Output::Speech
+    } else {
+        Output::Text
+    };
+    if let Some(target) = cli.spectate {
+        match target.open() {
+            Ok(out) => session.subscribe(spectator(out, output)),
+            Err(e) => {
+                eprintln!("Error: couldn't open the spectator stream: {e}");
+                std::process::exit(2);
+            }
+        }
+    }
+    if cli.ghost {
+        let path = PathBuf::from(DEFAULT_GHOST_FILE);
+        match GhostStore::load(&path) {
+            Ok(store) => {
+                let ghosts = Arc::new(Mutex::new(store));
+                session.set_ghosts(Arc::clone(&ghosts));
+                session.subscribe(ghost_recorder(ghosts, path, output));
+            }
+            Err(e) => {
+                eprintln!("Error: couldn't load ghosts: {e}");
+                std::process::exit(2);
+            }
+        }
+    }
+    let results_path = results::default_path();
+    let results = match ResultsDb::open(&results_path) {
+        Ok(db) => {
+            let results = Arc::new(Mutex::new(db));
+            session.subscribe(results_recorder(
+                cli.profile.clone(),
+                Arc::clone(&results),
+                output,
+            ));
+            Some(results)
+        }
+        Err(e) => {
+            eprintln!(
+                "Note: boards won't be saved, couldn't open {}: {e}",
+                results_path.display()
+            );
+            None
+        }
+    };
+    let profile =
+        cli.profile.map(
+            |name| match ProfileDb::open(&PathBuf::from(DEFAULT_PROFILE_DB)) {
+                Ok(db) => {
+                    let profiles = Arc::new(Mutex::new(db));
+                    session.subscribe(profile_recorder(
+                        name.clone(),
+                        Arc::clone(&profiles),
+                        output,
+                    ));
+                    (name, profiles)
                 }
-                None => {
-                    println!("No questions registered.");
+                Err(e) => {
+                    eprintln!("Error: couldn't open {DEFAULT_PROFILE_DB}: {e}");
+                    std::process::exit(2);
                 }
             },
-            other => {
-                // Try team-aware parser
-                if let Some(parsed) = parse_query(&raw, &registry) {
-                    println!("Code: {raw}");
-                    if let Some(ref team) = parsed.team {
-                        println!("Team: {team}");
-                    }
+        );
+    #[cfg(feature = "packs")]
+    let last = Arc::new(Mutex::new(None));
+    #[cfg(feature = "packs")]
+    session.subscribe(last_board(Arc::clone(&last)));
+    if let Some(dir) = cli.export_csv {
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            eprintln!("Error: couldn't create {}: {e}", dir.display());
+            std::process::exit(2);
+        }
+        session.subscribe(csv_exporter(dir, output));
+    }
+    let context = Context {
+        output,
+        profile,
+        results,
+        #[cfg(feature = "packs")]
+        last,
+    };
 
-                    let (q_text, sql) = generate_sql_for_kind(parsed.kind, parsed.team.as_deref());
-                    println!("Question: {q_text}");
-
-                    match sql_runner::run_trivia(&q_text, &sql) {
-                        Ok(result) => {
-                            if result.total > 0 {
-                                session_score += result.score;
-                                questions_played += 1;
-                            }
-                        }
-                        Err(e) => eprintln!("Error running SQL: {e}"),
-                    }
-                    continue;
+    let command = match cli.command.unwrap_or(CliCommand::Repl) {
+        CliCommand::Repl => None,
+        CliCommand::Play { code: Some(code) } => Some(Command::Play(code)),
+        CliCommand::Replay { id } => Some(Command::Replay(id)),
+        CliCommand::Play { code: None } => Some(Command::Start {
+            category: None,
+            difficulty: None,
+        }),
+        CliCommand::Random {
+            category,
+            difficulty,
+        } => Some(Command::Start {
+            category,
+            difficulty,
+        }),
+        CliCommand::List => Some(Command::List),
+        CliCommand::Survival => Some(Command::Survival),
+        CliCommand::Blitz { secs } => Some(Command::Blitz(Duration::from_secs(secs))),
+        CliCommand::Quiz {
+            count,
+            category,
+            team,
+        } => Some(Command::Quiz {
+            count,
+            category,
+            team,
+        }),
+        CliCommand::Team { team } => Some(Command::Team(team)),
+        CliCommand::Export { codes, format, out } => {
+            Some(Command::ExportQuiz { codes, format, out })
+        }
+    };
+    if let Some(command) = command {
+        let plays = matches!(
+            command,
+            Command::Play(_)
+                | Command::Replay(_)
+                | Command::Start { .. }
+                | Command::Quiz { .. }
+                | Command::Team(_)
+        );
+        run_command(&mut session, command, &context);
+        // A board that never started is a failure to a calling script
+        if plays && session.history().is_empty() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    match output {
+        Output::Text => {
+            print_banner(seed);
+            if cli.demo {
+                println!(
+                    "Demo {}: every player and stat is made up\n",
+                    league.as_str().to_uppercase()
+                );
+            } else if league != League::Nfl {
+                println!(
+                    "League: {} (boards from {})\n",
+                    league.as_str().to_uppercase(),
+                    db_path.display()
+                );
+            }
+            if let Some((name, profiles)) = &context.profile {
+                if let Ok(stats) = profiles.lock().unwrap().stats(name) {
+                    println!("Profile {stats}\n");
                 }
+            }
+        }
+        Output::Json => emit_json(&JsonMessage::Ready { seed }),
+        Output::Speech => println!(
+            "Welcome to Know Ball. Say start for a random question, list for the \
+             question codes, score for your total, or quit to stop."
+        ),
+    }
 
-                // Fallback to registry lookup
-                let matched = registry
-                    .iter()
-                    .find(|(k, _)| k.to_ascii_lowercase() == other);
-
-                if let Some((canon_key, meta)) = matched {
-                    println!("Code: {canon_key}");
-                    println!("Description: {}", meta.description);
-                    let (q_text, sql) = generate_sql_for_kind(meta.kind, None);
-                    println!("Question: {q_text}");
-
-                    match sql_runner::run_trivia(&q_text, &sql) {
-                        Ok(result) => {
-                            if result.total > 0 {
-                                session_score += result.score;
-                                questions_played += 1;
-                            }
-                        }
-                        Err(e) => eprintln!("Error running SQL: {e}"),
-                    }
-                } else {
-                    println!("Unknown command or code: '{other}'");
-                    println!("Type 'list' to see available codes.\n");
+    let stdin = io::stdin();
+
+    loop {
+        if output == Output::Text {
+            print!("> ");
+            io::stdout().flush().ok();
+        }
+
+        let mut input = String::new();
+        match stdin.read_line(&mut input) {
+            // Input closed; end the session as if the user typed quit
+            Ok(0) => {
+                output.say("");
+                run_command(&mut session, Command::Quit, &context);
+                break;
+            }
+            Ok(_) => {}
+            Err(_) => {
+                eprintln!("Error reading input, try again.");
+                continue;
+            }
+        }
+
+        let raw = input.trim();
+        if raw.is_empty() {
+            continue;
+        }
+
+        let command = match parse_command(raw) {
+            Ok(command) => command,
+            Err(e) => {
+                output.error(e.to_string());
+                continue;
+            }
+        };
+        if let Flow::Quit = run_command(&mut session, command, &context) {
+            break;
+        }
+    }
+}
+
+/// What the REPL does after a command
+enum Flow {
+    Next,
+    Quit,
+}
+
+/// What commands need besides the session
+struct Context {
+    output: Output,
+    /// The `--profile` name and where its stats are kept
+    profile: Option<(String, Arc<Mutex<ProfileDb>>)>,
+    /// Every finished board, for `highscores`; None if the file couldn't be
+    /// opened
+    results: Option<Arc<Mutex<ResultsDb>>>,
+    /// The last finished board, for `challenge save`
+    #[cfg(feature = "packs")]
+    last: Arc<Mutex<Option<TriviaResult>>>,
+}
+
+/// Runs one command, from the REPL or a subcommand
+fn run_command(session: &mut GameSession, command: Command, context: &Context) -> Flow {
+    let mut output = context.output;
+    match command {
+        Command::Quit => {
+            output.summary("SESSION SUMMARY", session.summary());
+            output.say("Goodbye!");
+            return Flow::Quit;
+        }
+        Command::Score => {
+            output.summary("SESSION SCORE", session.summary());
+            output.say("");
+        }
+        Command::Profile => match &context.profile {
+            Some((name, profiles)) => match profiles.lock().unwrap().stats(name) {
+                Ok(stats) => output.done(format!("Profile {stats}")),
+                Err(e) => output.error(format!("couldn't read the {name} profile: {e}")),
+            },
+            None => {
+                output.error("no profile; start with --profile NAME to keep lifetime stats".into())
+            }
+        },
+        Command::HighScores => {
+            saved_boards(context, |results, profile| results.highscores_text(profile));
+        }
+        Command::History(code) => match session.engine().lookup(&code) {
+            Some(found) => saved_boards(context, |results, profile| {
+                let code = &found.question.meta().code;
+                results.history_text(profile, code, found.team.as_deref())
+            }),
+            None => output.error(format!("unknown question code '{code}'")),
+        },
+        Command::SelfTest => {
+            let engine = session.engine();
+            let report = selftest::run(engine.registry(), engine.store().as_ref());
+            for failure in &report.failures {
+                output.error(failure.to_string());
+            }
+            output.done(report.summary());
+        }
+        Command::List => {
+            let codes: Vec<_> = session.engine().registry().iter().collect();
+            if output == Output::Json {
+                let questions = codes.iter().map(|(_, q)| q.meta()).collect();
+                emit_json(&JsonMessage::Codes { questions });
+                return Flow::Next;
+            }
+            if output == Output::Speech {
+                println!("There are {} questions.", codes.len());
+                for (code, question) in codes {
+                    let description = speech::sentence(&question.meta().description);
+                    println!("{description} Code {code}.");
                 }
+                return Flow::Next;
+            }
+            println!("Available question codes:");
+            for (code, question) in codes {
+                let meta = question.meta();
+                println!(" - {code}: {} [{}]", meta.description, meta.facets());
             }
+            println!();
+        }
+        Command::Play(_)
+        | Command::Custom { .. }
+        | Command::Start { .. }
+        | Command::Daily
+        | Command::Replay(_) => deal(session, &command, &mut output),
+        Command::ExportQuiz { codes, format, out } => {
+            let rounds = quiz_rounds(session, &codes, output);
+            if rounds.is_empty() {
+                return Flow::Next;
+            }
+            let what = format!("a {}-question quiz", rounds.len());
+            let document = render_quiz(&rounds, format);
+            output.document(document, out, &what, |document| JsonMessage::Quiz {
+                document,
+            });
+        }
+        Command::ExportHtml { code, out } => match quiz_round(session, &code) {
+            Ok(round) => {
+                let html = render_quiz_page(&round);
+                output.document(html, out, "a playable quiz page", |html| {
+                    JsonMessage::Page { html }
+                });
+            }
+            Err(e) => output.error(format!("{code}: {e}")),
+        },
+        Command::ExportAnki { code, out } => {
+            let cards = match code {
+                Some(code) => match quiz_round(session, &code) {
+                    Ok(round) => board_flashcards(&round),
+                    Err(e) => {
+                        output.error(format!("{code}: {e}"));
+                        return Flow::Next;
+                    }
+                },
+                None => missed_flashcards(session.history()),
+            };
+            if cards.is_empty() {
+                output.notice("no missed answers to study yet".into());
+                return Flow::Next;
+            }
+            let what = format!("{} flashcard(s)", cards.len());
+            output.document(render_anki(&cards), out, &what, |deck| JsonMessage::Deck {
+                deck,
+            });
+        }
+        Command::Report(out) => {
+            let markdown = Scoreboard::for_session("You", session).to_markdown();
+            output.document(markdown, out, "the scoreboard", |markdown| {
+                JsonMessage::Report { markdown }
+            });
+        }
+        #[cfg(feature = "packs")]
+        Command::PackCreate {
+            path,
+            codes,
+            title,
+            hashes,
+        } => create_pack(session, &path, &codes, title, hashes, output),
+        #[cfg(feature = "packs")]
+        Command::PackPlay(path) => play_pack(session, &path, output),
+        #[cfg(not(feature = "packs"))]
+        Command::PackCreate { .. } | Command::PackPlay(_) => {
+            output.error("quiz packs need the `packs` feature".into());
+        }
+        #[cfg(feature = "packs")]
+        Command::ChallengeSave { path, from } => {
+            let last = context.last.lock().unwrap().clone();
+            save_challenge(last.as_ref(), &path, from, output);
+        }
+        #[cfg(feature = "packs")]
+        Command::ChallengePlay(path) => play_challenge(session, &path, output),
+        #[cfg(not(feature = "packs"))]
+        Command::ChallengeSave { .. } | Command::ChallengePlay(_) => {
+            output.error("challenges need the `packs` feature".into());
+        }
+        #[cfg(feature = "packs")]
+        Command::Weekly(action) => weekly(session, action, output),
+        #[cfg(not(feature = "packs"))]
+        Command::Weekly(_) => {
+            output.error("the weekly challenge needs the `packs` feature".into());
+        }
+        #[cfg(feature = "leaderboard")]
+        Command::Leaderboard(action) => leaderboard(action, output),
+        #[cfg(not(feature = "leaderboard"))]
+        Command::Leaderboard(_) => {
+            output.error("the global leaderboard needs the `leaderboard` feature".into());
+        }
+        Command::Party(names) => party(session, names, output),
+        Command::Match { teams, best_of } => team_match(session, teams, best_of, output),
+        Command::Survival => survival(session, output),
+        Command::Blitz(limit) => blitz(session, limit, output),
+        Command::Quiz {
+            count,
+            category,
+            team,
+        } => quiz(session, count, category, team, output),
+        Command::Team(team) => team_marathon(session, &team, output),
+        Command::LockTeam(team) => {
+            match &team {
+                Some(team) => output.notice(format!(
+                    "Questions that take a team are now for {team} until 'team unlock'."
+                )),
+                None => output.notice("Questions that take a team are open again.".into()),
+            }
+            session.set_locked_team(team);
+        }
+        #[cfg(feature = "lan")]
+        Command::Host(port) => host_match(session, port, output),
+        #[cfg(feature = "lan")]
+        Command::Join(addr) => join_match(&addr, output),
+        #[cfg(not(feature = "lan"))]
+        Command::Host(_) | Command::Join(_) => {
+            output.error("LAN matches need the `lan` feature".into());
         }
     }
+    Flow::Next
+}
+
+/// Prints the welcome text and command help
+fn print_banner(seed: u64) {
+    println!("Welcome to Know Ball (Rust / SQLite edition)");
+    println!("Commands:");
+    println!("  start [CATEGORY] [easy|medium|hard] -> random question, e.g. 'start passing' or 'start easy'");
+    println!("  daily  -> today's board, the same for everyone");
+    println!("  weekly [add <NAME> <CODE> | board [WEEK]] -> this week's five boards and local standings");
+    println!("  list   -> show all question codes");
+    println!("  score  -> show session score");
+    println!("  profile -> lifetime stats of the --profile you're playing as");
+    println!("  highscores -> your best score on each question code, and overall");
+    println!("  history <code> -> every saved board of one question code");
+    println!("  replay <id> -> the same board again, from the Board ID shown when it started");
+    println!("  <code> -> run a specific question (e.g., recyds_TEAM_yearrange)");
+    println!("  custom <question | stat> [TEAM] [YEAR | START END] [POSITION] [min=N] -> pick the parameters yourself");
+    println!(
+        "  export-quiz <code>... [--format md|html] [--out FILE] -> printable quiz and answer key"
+    );
+    println!(
+        "  pack create <file.kbq> <code>... [--no-hashes] [--title TITLE] -> save a shareable quiz"
+    );
+    println!("  pack play <file.kbq> -> play a shared quiz");
+    println!("  challenge save <file.kbc> [NAME] / challenge play <file.kbc> -> challenge a friend to beat your last board");
+    println!("  export-html <code> [--out FILE] -> single-file quiz page to share");
+    println!("  export-anki [code] [--out FILE] -> flashcards of your misses, or of a whole board");
+    println!("  report [FILE] -> Markdown scoreboard of this session");
+    println!("  party <name> <name>... -> buzzer race for several players at one keyboard");
+    println!("  match Red=Ann,Ben Blue=Cam,Dee [N] -> best-of-N match between two teams");
+    println!("  survival -> random boards until your strikes run out, counted across boards");
+    println!("  blitz [SECS] -> random boards against one clock, 3 minutes unless given");
+    println!("  quiz [COUNT] [CATEGORY] [TEAM] -> a run of boards, 10 unless given, with one scorecard at the end");
+    println!("  team <TEAM> -> every team question for one franchise, and your fan score");
+    println!(
+        "  team lock <TEAM> / team unlock -> fill in one team for every question that takes one"
+    );
+    #[cfg(feature = "lan")]
+    println!("  host [PORT] / join <address> -> head-to-head match against another terminal");
+    #[cfg(feature = "leaderboard")]
+    println!("  leaderboard [join <URL> | leave] -> today's global top scores, opt-in");
+    println!("  selftest -> run every question against the database and check the boards");
+    println!("  quit   -> exit");
+    println!("Seed: {seed} (run with --seed {seed} to replay this session's questions)");
+    println!();
 }
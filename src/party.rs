@@ -0,0 +1,299 @@
+//! Buzzer party mode: several players sharing one keyboard
+//!
+//! Every line starts with the player's number or name, e.g. `1:mahomes` or
+//! `ann: mahomes`. Lines are handled in the order they arrive, so whoever
+//! hits Enter first with a right answer takes the row and its points; a
+//! later guess at the same name is too late but costs nothing.
+//!
+//! Strikes are kept per player rather than per board, so one player's wild
+//! guesses can't end the board for everyone. A player with three misses sits
+//! out the rest of the board, and the board ends when every row is found,
+//! everyone is out, or someone types `reveal`.
+use crate::game::{GuessOutcome, TriviaGame, MAX_STRIKES};
+use std::fmt::Write as _;
+use thiserror::Error;
+
+/// Players that fit on the number keys
+pub const MAX_PLAYERS: usize = 9;
+
+/// One player and their points across every board of the party
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartyPlayer {
+    pub name: String,
+    pub score: u32,
+    /// Misses on the current board
+    pub strikes: usize,
+}
+
+/// Why a party couldn't start or a line couldn't be attributed
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PartyError {
+    #[error("a party needs 2 to {MAX_PLAYERS} players")]
+    PlayerCount,
+    #[error("two players are called '{0}'")]
+    DuplicateName(String),
+    #[error("start each guess with your number, e.g. '1:mahomes'")]
+    NoPrefix,
+    #[error("there's no player '{0}'")]
+    UnknownPlayer(String),
+}
+
+/// What one player's guess did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartyOutcome {
+    /// The player was first to name this row
+    Correct {
+        player: usize,
+        row: usize,
+        name: String,
+        points: u32,
+    },
+    /// Someone else already found that name
+    TooLate { player: usize },
+    /// The guess fits `matches` players; the player can say more, unpunished
+    Ambiguous { player: usize, matches: usize },
+    /// A miss; at [`MAX_STRIKES`] the player sits out the board
+    Strike { player: usize, strikes: usize },
+    /// The player is out of strikes for this board
+    SittingOut { player: usize },
+    /// Blank guess, or the board is already over
+    Ignored,
+}
+
+/// The players of a party and their running scores
+#[derive(Debug, Clone)]
+pub struct Party {
+    players: Vec<PartyPlayer>,
+}
+
+impl Party {
+    pub fn new(names: Vec<String>) -> Result<Self, PartyError> {
+        if !(2..=MAX_PLAYERS).contains(&names.len()) {
+            return Err(PartyError::PlayerCount);
+        }
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                return Err(PartyError::DuplicateName(name.clone()));
+            }
+        }
+        let players = names
+            .into_iter()
+            .map(|name| PartyPlayer {
+                name,
+                score: 0,
+                strikes: 0,
+            })
+            .collect();
+        Ok(Self { players })
+    }
+
+    /// In the order they were given, which is their numbering from 1
+    pub fn players(&self) -> &[PartyPlayer] {
+        &self.players
+    }
+
+    /// Splits a line like `2:mahomes` or `ben: mahomes` into the player's
+    /// index and the guess
+    pub fn parse_line<'a>(&self, line: &'a str) -> Result<(usize, &'a str), PartyError> {
+        let (who, guess) = line.split_once(':').ok_or(PartyError::NoPrefix)?;
+        let who = who.trim();
+        let player = match who.parse::<usize>() {
+            Ok(n) if (1..=self.players.len()).contains(&n) => Some(n - 1),
+            Ok(_) => None,
+            Err(_) => self
+                .players
+                .iter()
+                .position(|p| p.name.eq_ignore_ascii_case(who)),
+        };
+        match player {
+            Some(player) => Ok((player, guess.trim())),
+            None => Err(PartyError::UnknownPlayer(who.into())),
+        }
+    }
+
+    /// Gives everyone their strikes back for a new board
+    pub fn new_board(&mut self) {
+        for player in &mut self.players {
+            player.strikes = 0;
+        }
+    }
+
+    /// Applies `player`'s guess to the board, ending it once everyone is out.
+    ///
+    /// Only a guess that names a row reaches the game, so misses count
+    /// against the player instead of the board's shared strikes.
+    pub fn guess(&mut self, game: &mut TriviaGame, player: usize, guess: &str) -> PartyOutcome {
+        if game.is_over() || guess.trim().is_empty() {
+            return PartyOutcome::Ignored;
+        }
+        if self.players[player].strikes >= MAX_STRIKES {
+            return PartyOutcome::SittingOut { player };
+        }
+        if game.names_any(guess) {
+            return match game.submit_guess(guess) {
+                GuessOutcome::Correct { row, name, points } => {
+                    self.players[player].score += points;
+                    PartyOutcome::Correct {
+                        player,
+                        row,
+                        name,
+                        points,
+                    }
+                }
+                GuessOutcome::AlreadyGuessed => PartyOutcome::TooLate { player },
+                GuessOutcome::Ambiguous { rows } => PartyOutcome::Ambiguous {
+                    player,
+                    matches: rows.len(),
+                },
+                _ => PartyOutcome::Ignored,
+            };
+        }
+        self.players[player].strikes += 1;
+        let strikes = self.players[player].strikes;
+        if self.everyone_out() {
+            game.reveal();
+        }
+        PartyOutcome::Strike { player, strikes }
+    }
+
+    /// Whether every player has used up their strikes on this board
+    pub fn everyone_out(&self) -> bool {
+        self.players.iter().all(|p| p.strikes >= MAX_STRIKES)
+    }
+
+    /// One line with every player's number, score, and strikes this board,
+    /// e.g. `[1] Ann 450 | [2] Ben 120 ✗ | [3] Cam 0 OUT`
+    pub fn standings_bar(&self) -> String {
+        let mut bar = String::new();
+        for (i, player) in self.players.iter().enumerate() {
+            if i > 0 {
+                bar.push_str(" | ");
+            }
+            let _ = write!(bar, "[{}] {} {}", i + 1, player.name, player.score);
+            if player.strikes >= MAX_STRIKES {
+                bar.push_str(" OUT");
+            } else if player.strikes > 0 {
+                let _ = write!(bar, " {}", "✗".repeat(player.strikes));
+            }
+        }
+        bar
+    }
+
+    /// Players from most points to fewest, with the winner or a tie called
+    pub fn final_standings(&self) -> String {
+        let mut ranked: Vec<&PartyPlayer> = self.players.iter().collect();
+        ranked.sort_by_key(|p| std::cmp::Reverse(p.score));
+        let mut out = String::from("--- PARTY STANDINGS ---\n");
+        for (i, player) in ranked.iter().enumerate() {
+            let _ = writeln!(out, "{}. {} {}", i + 1, player.name, player.score);
+        }
+        let best = ranked[0].score;
+        let leaders: Vec<&str> = ranked
+            .iter()
+            .filter(|p| p.score == best)
+            .map(|p| p.name.as_str())
+            .collect();
+        match leaders[..] {
+            [winner] => {
+                let _ = writeln!(out, "{winner} wins!");
+            }
+            _ => {
+                let _ = writeln!(out, "Tie between {} at {best}.", leaders.join(" and "));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_runner::Board;
+
+    fn game() -> TriviaGame {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Drew Brees".into(), "4000".into()],
+            ],
+        };
+        TriviaGame::new("Most yards", board)
+    }
+
+    fn party() -> Party {
+        Party::new(vec!["Ann".into(), "Ben".into()]).unwrap()
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let party = party();
+        assert_eq!(party.parse_line("1:mahomes"), Ok((0, "mahomes")));
+        assert_eq!(
+            party.parse_line(" ben : Drew Brees "),
+            Ok((1, "Drew Brees"))
+        );
+        assert_eq!(party.parse_line("mahomes"), Err(PartyError::NoPrefix));
+        assert_eq!(
+            party.parse_line("3:mahomes"),
+            Err(PartyError::UnknownPlayer("3".into()))
+        );
+        assert_eq!(
+            Party::new(vec!["Ann".into()]).unwrap_err(),
+            PartyError::PlayerCount
+        );
+        assert_eq!(
+            Party::new(vec!["Ann".into(), "ann".into()]).unwrap_err(),
+            PartyError::DuplicateName("ann".into())
+        );
+    }
+
+    #[test]
+    fn test_first_correct_guess_scores() {
+        let mut party = party();
+        let mut game = game();
+        assert!(matches!(
+            party.guess(&mut game, 1, "brees"),
+            PartyOutcome::Correct {
+                player: 1,
+                row: 1,
+                ..
+            }
+        ));
+        assert_eq!(
+            party.guess(&mut game, 0, "Brees"),
+            PartyOutcome::TooLate { player: 0 }
+        );
+        assert_eq!(party.players()[0].score, 0);
+        assert_eq!(party.players()[1].score, game.score());
+        assert_eq!(game.strikes(), 0);
+    }
+
+    #[test]
+    fn test_strikes_are_per_player() {
+        let mut party = party();
+        let mut game = game();
+        for strikes in 1..=MAX_STRIKES {
+            assert_eq!(
+                party.guess(&mut game, 0, "Manning"),
+                PartyOutcome::Strike { player: 0, strikes }
+            );
+        }
+        assert_eq!(
+            party.guess(&mut game, 0, "Brady"),
+            PartyOutcome::SittingOut { player: 0 }
+        );
+        assert!(!game.is_over());
+        assert_eq!(party.standings_bar(), "[1] Ann 0 OUT | [2] Ben 0");
+
+        party.guess(&mut game, 1, "Brady");
+        for _ in 0..MAX_STRIKES {
+            party.guess(&mut game, 1, "Manning");
+        }
+        assert!(game.is_over());
+
+        party.new_board();
+        assert!(!party.everyone_out());
+        assert!(party.final_standings().ends_with("Ben wins!\n"));
+    }
+}
@@ -0,0 +1,205 @@
+//! Player profiles that outlast a session
+//!
+//! A profile is a name with lifetime totals: boards played, points scored,
+//! and perfect boards. They live in a small SQLite file of their own,
+//! `know_ball_profiles.sqlite` in the REPL, so the stats database stays
+//! read-only and can be swapped out without losing anyone's record.
+use crate::error::{KnowBallError, Result};
+use crate::game::TriviaResult;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fmt;
+use std::path::Path;
+
+/// Where profiles are kept when no other file is given
+pub const DEFAULT_PROFILE_DB: &str = "know_ball_profiles.sqlite";
+
+/// Longest name a profile can have
+const MAX_NAME_LEN: usize = 32;
+
+/// Lifetime totals for one profile
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileStats {
+    pub name: String,
+    /// Boards finished, counting only ones with rows
+    pub games: u32,
+    pub total_score: u64,
+    /// Boards with every row named
+    pub perfect_boards: u32,
+}
+
+impl ProfileStats {
+    /// Points per board, rounded down, or 0 before the first one
+    pub fn average(&self) -> u64 {
+        self.total_score.checked_div(self.games.into()).unwrap_or(0)
+    }
+}
+
+/// e.g. "marcus: 12 games, average 480, 3 perfect boards"
+impl fmt::Display for ProfileStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.games == 0 {
+            return write!(f, "{}: no games yet", self.name);
+        }
+        write!(
+            f,
+            "{}: {} {}, average {}, {} perfect {}",
+            self.name,
+            self.games,
+            if self.games == 1 { "game" } else { "games" },
+            self.average(),
+            self.perfect_boards,
+            if self.perfect_boards == 1 {
+                "board"
+            } else {
+                "boards"
+            }
+        )
+    }
+}
+
+/// The profiles file, open for reading and writing
+#[derive(Debug)]
+pub struct ProfileDb {
+    conn: Connection,
+}
+
+impl ProfileDb {
+    /// Opens the profiles file at `path`, creating it if it doesn't exist
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Profiles kept in memory, gone when dropped
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS profiles (
+                name TEXT PRIMARY KEY,
+                games INTEGER NOT NULL DEFAULT 0,
+                total_score INTEGER NOT NULL DEFAULT 0,
+                perfect_boards INTEGER NOT NULL DEFAULT 0
+            )",
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// `name`'s totals, all zero for a profile that hasn't played yet
+    pub fn stats(&self, name: &str) -> Result<ProfileStats> {
+        let stats = self
+            .conn
+            .query_row(
+                "SELECT games, total_score, perfect_boards FROM profiles WHERE name = ?1",
+                [name],
+                |row| {
+                    Ok(ProfileStats {
+                        name: name.to_string(),
+                        games: row.get(0)?,
+                        total_score: row.get(1)?,
+                        perfect_boards: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(stats.unwrap_or_else(|| ProfileStats {
+            name: name.to_string(),
+            ..ProfileStats::default()
+        }))
+    }
+
+    /// Adds a finished board to `name`'s totals and returns the new ones. An
+    /// empty board isn't counted.
+    pub fn record(&self, name: &str, result: &TriviaResult) -> Result<ProfileStats> {
+        if result.total > 0 {
+            let perfect = result.correct() == result.total;
+            self.conn.execute(
+                "INSERT INTO profiles (name, games, total_score, perfect_boards)
+                 VALUES (?1, 1, ?2, ?3)
+                 ON CONFLICT (name) DO UPDATE SET
+                    games = games + 1,
+                    total_score = total_score + excluded.total_score,
+                    perfect_boards = perfect_boards + excluded.perfect_boards",
+                params![name, result.score, u32::from(perfect)],
+            )?;
+        }
+        self.stats(name)
+    }
+}
+
+/// Checks a name given to `--profile`: letters, digits, `-`, and `_`, up to
+/// 32 of them
+pub fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(KnowBallError::Parse(format!(
+            "bad profile name '{name}': use up to {MAX_NAME_LEN} letters, digits, '-', or '_'"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::RowResult;
+
+    fn result(score: u32, correct: usize, total: usize) -> TriviaResult {
+        TriviaResult {
+            score,
+            total,
+            rows: (0..total)
+                .map(|i| RowResult {
+                    name: format!("Player {i}"),
+                    stat: "1".into(),
+                    points: 100,
+                    guessed: i < correct,
+                    passed: false,
+                    cells: Vec::new(),
+                    found_at: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_adds_up_lifetime_stats() {
+        let db = ProfileDb::in_memory().unwrap();
+        assert_eq!(
+            db.stats("marcus").unwrap().to_string(),
+            "marcus: no games yet"
+        );
+        db.record("marcus", &result(1000, 10, 10)).unwrap();
+        db.record("marcus", &result(0, 0, 0)).unwrap();
+        let stats = db.record("marcus", &result(450, 4, 10)).unwrap();
+        assert_eq!(
+            stats,
+            ProfileStats {
+                name: "marcus".into(),
+                games: 2,
+                total_score: 1450,
+                perfect_boards: 1,
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "marcus: 2 games, average 725, 1 perfect board"
+        );
+        assert_eq!(db.stats("ann").unwrap().games, 0);
+    }
+
+    #[test]
+    fn test_check_name() {
+        assert!(check_name("marcus_r-2").is_ok());
+        assert!(check_name("").is_err());
+        assert!(check_name("two words").is_err());
+        assert!(check_name(&"x".repeat(33)).is_err());
+    }
+}
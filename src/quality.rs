@@ -0,0 +1,153 @@
+//! Pre-flight quality checks for generated boards
+//!
+//! Random parameters occasionally produce boards that aren't fun to play, like
+//! a single superstar soaking up nearly all the yards. These checks let the
+//! caller reroll the parameters before the board is ever shown.
+use crate::sql_runner::{calculate_point_values, Board};
+use std::fmt;
+
+/// Boards with fewer rows than this are rejected as empty
+pub const MIN_BOARD_ROWS: usize = 1;
+
+/// Rows worth fewer points than this mean one player dominates the board
+pub const MIN_ROW_POINTS: u32 = 15;
+
+/// Last-10 boards must span at least this many distinct seasons
+pub const MIN_DISTINCT_SEASONS: usize = 3;
+
+/// Reason a board was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QualityIssue {
+    Empty,
+    NullName,
+    DominatedBy { name: String, points: u32 },
+    TooFewSeasons { count: usize },
+}
+
+impl fmt::Display for QualityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QualityIssue::Empty => write!(f, "no rows returned"),
+            QualityIssue::NullName => write!(f, "a row is missing its player name"),
+            QualityIssue::DominatedBy { name, points } => {
+                write!(f, "{name} dominates the board ({points} points)")
+            }
+            QualityIssue::TooFewSeasons { count } => {
+                write!(f, "only {count} distinct season(s) on a last-10 board")
+            }
+        }
+    }
+}
+
+/// Checks a board before it is presented to the player.
+///
+/// `is_last10` enables the distinct-season check, which only makes sense for
+/// the "last 10 players to ..." question family.
+pub fn check_board(board: &Board, is_last10: bool) -> Result<(), QualityIssue> {
+    if board.rows.len() < MIN_BOARD_ROWS {
+        return Err(QualityIssue::Empty);
+    }
+
+    if board.rows.iter().any(|row| {
+        row.first()
+            .is_none_or(|name| name.is_empty() || name == "NULL")
+    }) {
+        return Err(QualityIssue::NullName);
+    }
+
+    let points = calculate_point_values(&board.rows, &board.column_names);
+    if let Some((i, &p)) = points.iter().enumerate().find(|(_, &p)| p < MIN_ROW_POINTS) {
+        return Err(QualityIssue::DominatedBy {
+            name: board.rows[i][0].clone(),
+            points: p,
+        });
+    }
+
+    if is_last10 {
+        if let Some(season_col) = board.column_names.iter().position(|c| c == "season") {
+            let mut seasons: Vec<&str> = board
+                .rows
+                .iter()
+                .filter_map(|row| row.get(season_col).map(String::as_str))
+                .collect();
+            seasons.sort_unstable();
+            seasons.dedup();
+            if seasons.len() < MIN_DISTINCT_SEASONS {
+                return Err(QualityIssue::TooFewSeasons {
+                    count: seasons.len(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(columns: &[&str], rows: &[&[&str]]) -> Board {
+        Board {
+            column_names: columns.iter().map(|c| c.to_string()).collect(),
+            rows: rows
+                .iter()
+                .map(|r| r.iter().map(|v| v.to_string()).collect())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_good_board_passes() {
+        let b = board(
+            &["name", "team_abbr", "season", "attempts"],
+            &[
+                &["A", "PIT", "2024", "300"],
+                &["B", "PIT", "2023", "250"],
+                &["C", "PIT", "2022", "200"],
+            ],
+        );
+        assert_eq!(check_board(&b, true), Ok(()));
+    }
+
+    #[test]
+    fn test_empty_board_rejected() {
+        let b = board(&["name", "yards"], &[]);
+        assert_eq!(check_board(&b, false), Err(QualityIssue::Empty));
+    }
+
+    #[test]
+    fn test_null_name_rejected() {
+        let b = board(&["name", "yards"], &[&["A", "100"], &["NULL", "90"]]);
+        assert_eq!(check_board(&b, false), Err(QualityIssue::NullName));
+    }
+
+    #[test]
+    fn test_superstar_board_rejected() {
+        let mut rows: Vec<&[&str]> = vec![&["Star", "5000"]];
+        rows.extend(std::iter::repeat_n(&["Role", "100"][..], 9));
+        let b = board(&["name", "yards"], &rows);
+        assert!(matches!(
+            check_board(&b, false),
+            Err(QualityIssue::DominatedBy { .. })
+        ));
+    }
+
+    #[test]
+    fn test_last10_needs_three_seasons() {
+        let b = board(
+            &["name", "team_abbr", "season", "attempts"],
+            &[
+                &["A", "PIT", "2024", "300"],
+                &["B", "PIT", "2024", "250"],
+                &["C", "PIT", "2023", "200"],
+            ],
+        );
+        assert_eq!(
+            check_board(&b, true),
+            Err(QualityIssue::TooFewSeasons { count: 2 })
+        );
+        // Same board is fine when it isn't a last-10 question
+        assert_eq!(check_board(&b, false), Ok(()));
+    }
+}
@@ -0,0 +1,70 @@
+//! Old question codes that still work after a rename
+//!
+//! Players memorize codes, so renaming one shouldn't break it. Each entry maps
+//! an old code to the current one; `TEAM` stands for any team abbreviation and
+//! may sit anywhere in the code.
+
+/// Old code → current code
+pub const ALIASES: [(&str, &str); 2] = [
+    ("recyds_TEAM_yearrange", "recyds_yearrange_TEAM"),
+    ("rushyds_TEAM_yearrange", "rushyds_yearrange_TEAM"),
+];
+
+/// An old code typed by the player, resolved to its current code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedAlias {
+    /// The old code, with `TEAM` in place of the team
+    pub old: &'static str,
+    /// The current code, with `TEAM` in place of the team
+    pub current: &'static str,
+    /// The team the player typed, if the code takes one
+    pub team: Option<String>,
+}
+
+/// Looks up an old code, case-insensitively, pulling out the team from
+/// wherever the old code had it
+pub fn resolve_alias(input: &str) -> Option<ResolvedAlias> {
+    let mut team = None;
+    let pattern: Vec<String> = input
+        .trim()
+        .split('_')
+        .map(|part| {
+            let upper = part.to_ascii_uppercase();
+            if team.is_none() && super::TEAMS.contains(&upper.as_str()) {
+                team = Some(upper);
+                "team".to_string()
+            } else {
+                part.to_ascii_lowercase()
+            }
+        })
+        .collect();
+    let pattern = pattern.join("_");
+
+    ALIASES
+        .iter()
+        .find(|(old, _)| old.eq_ignore_ascii_case(&pattern))
+        .map(|&(old, current)| ResolvedAlias { old, current, team })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::build_registry;
+
+    #[test]
+    fn test_aliases_point_at_real_codes() {
+        let registry = build_registry();
+        for (old, current) in ALIASES {
+            assert!(registry.contains_key(current), "{old} -> {current}");
+            assert!(!registry.contains_key(old), "{old} is still registered");
+        }
+    }
+
+    #[test]
+    fn test_resolve_alias_with_team() {
+        let resolved = resolve_alias("recyds_pit_yearrange").unwrap();
+        assert_eq!(resolved.current, "recyds_yearrange_TEAM");
+        assert_eq!(resolved.team.as_deref(), Some("PIT"));
+        assert!(resolve_alias("recyds_yearrange_PIT").is_none());
+    }
+}
@@ -0,0 +1,150 @@
+//! Board IDs for replaying a generated question
+//!
+//! A question's text and SQL come from its code and the parameters chosen
+//! for it, whatever seed picked them, so those two are all it takes to deal
+//! the same board again. An ID writes them on one line, e.g.
+//! `recyds_yearrange_TEAM:PIT:2005-2012`, short enough to retry later or send
+//! to a friend.
+use super::{QuestionParams, QuestionSpec, POSITIONS};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A question code and the parameters its board was generated with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardId {
+    pub code: String,
+    pub params: QuestionParams,
+}
+
+/// An ID that couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("bad board ID '{0}', expected CODE[:TEAM][:YEAR | :START-END][:POSITION][:min=N]")]
+pub struct BoardIdError(String);
+
+impl BoardId {
+    /// The ID of a generated question
+    pub fn of(spec: &QuestionSpec) -> Self {
+        Self {
+            code: spec.code.clone(),
+            params: spec.params.clone(),
+        }
+    }
+}
+
+/// e.g. `last10passers_TEAM:PIT:min=10`
+impl fmt::Display for BoardId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.code)?;
+        for part in self.params.parts() {
+            write!(f, ":{part}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for BoardId {
+    type Err = BoardIdError;
+
+    /// Reads the parameters by their shape, in any order: `min=N` is the
+    /// threshold, `START-END` the seasons, a four-digit number the season, a
+    /// position like `WR` the position, and anything else the team. Whether
+    /// the question takes them is checked when it's played.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || BoardIdError(s.to_string());
+        let mut parts = s.trim().split(':');
+        let code = parts
+            .next()
+            .filter(|code| !code.is_empty())
+            .ok_or_else(bad)?;
+        let mut params = QuestionParams::default();
+        for part in parts {
+            let taken = if let Some(min) = part.strip_prefix("min=") {
+                params
+                    .threshold
+                    .replace(min.parse().map_err(|_| bad())?)
+                    .is_some()
+            } else if let Some((start, end)) = part.split_once('-') {
+                let range = (year(start).ok_or_else(bad)?, year(end).ok_or_else(bad)?);
+                params.year_range.replace(range).is_some()
+            } else if let Some(season) = year(part) {
+                params.year.replace(season).is_some()
+            } else if POSITIONS.contains(&part.to_ascii_uppercase().as_str()) {
+                params.position.replace(part.to_ascii_uppercase()).is_some()
+            } else if !part.is_empty() && part.chars().all(|c| c.is_ascii_alphabetic()) {
+                params.team.replace(part.to_ascii_uppercase()).is_some()
+            } else {
+                true
+            };
+            if taken {
+                return Err(bad());
+            }
+        }
+        Ok(Self {
+            code: code.to_string(),
+            params,
+        })
+    }
+}
+
+/// A four-digit season
+fn year(s: &str) -> Option<i32> {
+    (s.len() == 4 && s.chars().all(|c| c.is_ascii_digit()))
+        .then(|| s.parse().ok())
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_round_trip() {
+        let id = BoardId {
+            code: "recyds_yearrange_TEAM".into(),
+            params: QuestionParams {
+                team: Some("PIT".into()),
+                year_range: Some((2005, 2012)),
+                threshold: Some(10),
+                position: Some("WR".into()),
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            id.to_string(),
+            "recyds_yearrange_TEAM:PIT:2005-2012:WR:min=10"
+        );
+        assert_eq!(id.to_string().parse(), Ok(id));
+        assert_eq!(
+            "top10passyds_year:2010"
+                .parse::<BoardId>()
+                .unwrap()
+                .params
+                .year,
+            Some(2010)
+        );
+        assert_eq!(
+            "passyds_TEAM:pit"
+                .parse::<BoardId>()
+                .unwrap()
+                .params
+                .team
+                .as_deref(),
+            Some("PIT")
+        );
+    }
+
+    #[test]
+    fn test_bad_ids() {
+        for bad in [
+            "",
+            ":PIT",
+            "passyds_TEAM:PIT:GB",
+            "x:min=ten",
+            "x:2005-05",
+            "x:20o5",
+        ] {
+            assert!(bad.parse::<BoardId>().is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! Questions assembled at runtime from a stat, team, seasons, and position
+//!
+//! ```no_run
+//! use know_ball::questions::CustomQuestion;
+//!
+//! let question = CustomQuestion::new()
+//!     .stat("receiving_yards")
+//!     .team("GB")
+//!     .years(2010..=2015)
+//!     .position("WR");
+//! let spec = question.spec().unwrap();
+//! ```
+use super::sql::{last_team_in_range, player_seasons};
+use super::{
+    Category, Era, ParamError, ParamKind, Question, QuestionMeta, QuestionParams, QuestionSpec,
+    Scope, END_YEAR, POSITIONS, START_YEAR, TEAMS,
+};
+use crate::sql_runner::Query;
+use rand::RngCore;
+use std::ops::RangeInclusive;
+
+/// A `seasons` column a custom question can rank players by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomStat {
+    pub column: &'static str,
+    /// How the stat reads in question text, e.g. "receiving yards"
+    pub label: &'static str,
+    /// Only quarterbacks put up this stat, so other positions are rejected
+    pub qb_only: bool,
+}
+
+/// Every stat a custom question can use
+pub const CUSTOM_STATS: [CustomStat; 14] = [
+    stat("passing_yards", "passing yards", true),
+    stat("passing_tds", "passing TDs", true),
+    stat("interceptions", "interceptions thrown", true),
+    stat("completions", "completions", true),
+    stat("attempts", "pass attempts", true),
+    stat("sacks", "times sacked", true),
+    stat("rushing_yards", "rushing yards", false),
+    stat("rushing_tds", "rushing TDs", false),
+    stat("rushing_attempts", "rush attempts", false),
+    stat("receiving_yards", "receiving yards", false),
+    stat("receiving_tds", "receiving TDs", false),
+    stat("receptions", "receptions", false),
+    stat("targets", "targets", false),
+    stat("fumbles_lost", "fumbles lost", false),
+];
+
+const fn stat(column: &'static str, label: &'static str, qb_only: bool) -> CustomStat {
+    CustomStat {
+        column,
+        label,
+        qb_only,
+    }
+}
+
+/// Looks up a custom stat by column name (case-insensitive)
+pub fn find_stat(name: &str) -> Option<&'static CustomStat> {
+    CUSTOM_STATS
+        .iter()
+        .find(|s| s.column.eq_ignore_ascii_case(name))
+}
+
+/// Top 10 players by a summed stat, narrowed to whatever team, seasons, and
+/// position the caller picks.
+///
+/// Nothing is random: every parameter comes from the builder, and seasons
+/// default to every year in the database. Call [`CustomQuestion::validate`]
+/// (or [`CustomQuestion::spec`], which does) before playing one.
+#[derive(Debug, Clone)]
+pub struct CustomQuestion {
+    meta: QuestionMeta,
+    stat: String,
+    team: Option<String>,
+    years: Option<(i32, i32)>,
+    position: Option<String>,
+}
+
+impl CustomQuestion {
+    pub fn new() -> Self {
+        Self {
+            meta: QuestionMeta::new("custom", "Custom question"),
+            stat: String::new(),
+            team: None,
+            years: None,
+            position: None,
+        }
+        .refresh_meta()
+    }
+
+    /// Builds a question from `custom` command parameters
+    pub fn from_params(stat: &str, params: &QuestionParams) -> Result<Self, ParamError> {
+        if params.threshold.is_some() {
+            return Err(ParamError::Unsupported {
+                code: "custom".into(),
+                param: ParamKind::Threshold,
+            });
+        }
+        let mut question = Self::new().stat(stat);
+        if let Some(team) = &params.team {
+            question = question.team(team);
+        }
+        if let Some(year) = params.year {
+            question = question.season(year);
+        }
+        if let Some((start, end)) = params.year_range {
+            question = question.years(start..=end);
+        }
+        if let Some(position) = &params.position {
+            question = question.position(position);
+        }
+        question.validate()?;
+        Ok(question)
+    }
+
+    /// The stat to rank by, a `seasons` column such as "receiving_yards"
+    pub fn stat(mut self, stat: &str) -> Self {
+        self.stat = stat.to_ascii_lowercase();
+        self.refresh_meta()
+    }
+
+    pub fn team(mut self, team: &str) -> Self {
+        self.team = Some(team.to_ascii_uppercase());
+        self.refresh_meta()
+    }
+
+    /// Inclusive season range; a one-season range asks about that year only
+    pub fn years(mut self, years: RangeInclusive<i32>) -> Self {
+        self.years = Some((*years.start(), *years.end()));
+        self.refresh_meta()
+    }
+
+    pub fn season(self, year: i32) -> Self {
+        self.years(year..=year)
+    }
+
+    pub fn position(mut self, position: &str) -> Self {
+        self.position = Some(position.to_ascii_uppercase());
+        self.refresh_meta()
+    }
+
+    /// Rejects unknown stats, teams, and positions, seasons outside the
+    /// database, and positions that can't have the stat (e.g. WR passing yards)
+    pub fn validate(&self) -> Result<(), ParamError> {
+        if self.stat.is_empty() {
+            return Err(ParamError::MissingStat);
+        }
+        let stat =
+            find_stat(&self.stat).ok_or_else(|| ParamError::UnknownStat(self.stat.clone()))?;
+        if let Some(team) = &self.team {
+            if !TEAMS.contains(&team.as_str()) {
+                return Err(ParamError::UnknownTeam(team.clone()));
+            }
+        }
+        if let Some((start, end)) = self.years {
+            for year in [start, end] {
+                if !(START_YEAR..=END_YEAR).contains(&year) {
+                    return Err(ParamError::YearOutOfRange(year));
+                }
+            }
+            if start > end {
+                return Err(ParamError::BackwardsRange(start, end));
+            }
+        }
+        if let Some(position) = &self.position {
+            if !POSITIONS.contains(&position.as_str()) {
+                return Err(ParamError::UnknownPosition(position.clone()));
+            }
+            if stat.qb_only && position != "QB" {
+                return Err(ParamError::PositionMismatch {
+                    stat: stat.column.to_string(),
+                    position: position.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the question and generates its text and SQL
+    pub fn spec(&self) -> Result<QuestionSpec, ParamError> {
+        self.validate()?;
+        let params = self.params();
+        let (text, query) = self.build(&params);
+        Ok(QuestionSpec {
+            code: self.meta.code.clone(),
+            params,
+            text,
+            query,
+        })
+    }
+
+    fn params(&self) -> QuestionParams {
+        let (year, year_range) = match self.years {
+            Some((start, end)) if start == end => (Some(start), None),
+            range => (None, range),
+        };
+        QuestionParams {
+            team: self.team.clone(),
+            year,
+            year_range,
+            position: self.position.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Rebuilds the metadata after a builder change
+    fn refresh_meta(mut self) -> Self {
+        let era = match self.years {
+            None => Era::AllTime,
+            Some((start, end)) if start == end => Era::Season,
+            Some(_) => Era::YearRange,
+        };
+        let mut meta = QuestionMeta::new("custom", "Custom question")
+            .position(self.position.as_deref())
+            .scope(if self.team.is_some() {
+                Scope::Team
+            } else {
+                Scope::League
+            })
+            .era(era);
+        if let Some(stat) = find_stat(&self.stat) {
+            meta = meta.category(Category::for_stat(stat.column));
+            meta.description = format!("Top 10 players in {}", stat.label);
+        }
+        self.meta = meta;
+        self
+    }
+}
+
+impl Default for CustomQuestion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Question for CustomQuestion {
+    fn meta(&self) -> &QuestionMeta {
+        &self.meta
+    }
+
+    fn sample_params(&self, _fixed: &QuestionParams, _rng: &mut dyn RngCore) -> QuestionParams {
+        self.params()
+    }
+
+    /// Everything is set through the builder, so callers can't fix anything else
+    fn supported_params(&self) -> Vec<ParamKind> {
+        Vec::new()
+    }
+
+    fn build(&self, _params: &QuestionParams) -> (String, Query) {
+        let stat = find_stat(&self.stat).expect("custom question was not validated");
+        let (start, end) = self.years.unwrap_or((START_YEAR, END_YEAR));
+
+        let players = match &self.position {
+            Some(pos) => format!("{pos}s"),
+            None => "players".to_string(),
+        };
+        let team = match &self.team {
+            Some(team) => format!(" for {team}"),
+            None => String::new(),
+        };
+        let when = match self.years {
+            None => format!("since {START_YEAR}"),
+            Some(_) if start == end => format!("in {start}"),
+            Some(_) => format!("between {start}–{end}"),
+        };
+        let q = format!("Top 10 {players} in {}{team} {when}.", stat.label);
+
+        let team_column = match &self.team {
+            Some(_) => "s.team_abbr".to_string(),
+            None => last_team_in_range(self.position.is_some()),
+        };
+        let mut select = player_seasons()
+            .column(team_column)
+            .column(format!("SUM(s.{col}) AS {col}", col = stat.column))
+            .filter("s.season BETWEEN :start AND :end");
+        if self.team.is_some() {
+            select = select.filter("s.team_abbr = :team");
+        }
+        if self.position.is_some() {
+            select = select.filter("s.position = :position");
+        }
+        let sql = select
+            .group_by("s.player_id")
+            .order_by_desc(stat.column)
+            .limit(10)
+            .to_sql();
+        let mut query = Query::new(sql).bind("start", start).bind("end", end);
+        if let Some(team) = &self.team {
+            query = query.bind("team", team.as_str());
+        }
+        if let Some(pos) = &self.position {
+            query = query.bind("position", pos.as_str());
+        }
+        (q, query)
+    }
+
+    fn columns(&self) -> Vec<String> {
+        let team = if self.team.is_some() {
+            "team_abbr"
+        } else {
+            "last_team"
+        };
+        vec!["name".into(), team.into(), self.stat.clone()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_spec() {
+        let spec = CustomQuestion::new()
+            .stat("receiving_yards")
+            .team("gb")
+            .years(2010..=2015)
+            .position("WR")
+            .spec()
+            .unwrap();
+        assert_eq!(
+            spec.text,
+            "Top 10 WRs in receiving yards for GB between 2010–2015."
+        );
+        let sql = spec.query.inline();
+        assert!(sql.contains("s.team_abbr = 'GB'"));
+        assert!(sql.contains("s.position = 'WR'"));
+        assert!(sql.contains("s.season BETWEEN 2010 AND 2015"));
+        assert_eq!(spec.params.year_range, Some((2010, 2015)));
+
+        let spec = CustomQuestion::new()
+            .stat("rushing_tds")
+            .season(2012)
+            .spec();
+        assert_eq!(spec.unwrap().text, "Top 10 players in rushing TDs in 2012.");
+    }
+
+    #[test]
+    fn test_rejects_nonsense() {
+        let err = |q: CustomQuestion| q.validate().unwrap_err();
+        assert_eq!(err(CustomQuestion::new()), ParamError::MissingStat);
+        assert_eq!(
+            err(CustomQuestion::new().stat("yards")),
+            ParamError::UnknownStat("yards".into())
+        );
+        assert_eq!(
+            err(CustomQuestion::new().stat("passing_yards").position("WR")),
+            ParamError::PositionMismatch {
+                stat: "passing_yards".into(),
+                position: "WR".into(),
+            }
+        );
+        assert_eq!(
+            err(CustomQuestion::new()
+                .stat("targets")
+                .years(RangeInclusive::new(2015, 2010))),
+            ParamError::BackwardsRange(2015, 2010)
+        );
+        assert_eq!(
+            err(CustomQuestion::new().stat("targets").team("XYZ")),
+            ParamError::UnknownTeam("XYZ".into())
+        );
+    }
+
+    #[test]
+    fn test_from_params() {
+        let params = QuestionParams {
+            team: Some("PIT".into()),
+            year: Some(2010),
+            ..Default::default()
+        };
+        let q = CustomQuestion::from_params("Rushing_Yards", &params).unwrap();
+        assert_eq!(q.meta().era, Era::Season);
+        assert_eq!(q.meta().scope, Scope::Team);
+        assert_eq!(q.columns(), ["name", "team_abbr", "rushing_yards"]);
+
+        let threshold = QuestionParams {
+            threshold: Some(5),
+            ..Default::default()
+        };
+        assert!(CustomQuestion::from_params("targets", &threshold).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! "Last 10 players to ..." questions for one team, most recent season first
+use super::sql::{career_totals, last10, SelectQuery};
+use super::{
+    register, team_or_random, Category, Difficulty, Era, ParamKind, Question, QuestionMeta,
+    QuestionParams, QuestionRegistry, Scope,
+};
+use crate::sql_runner::Query;
+use rand::RngCore;
+
+/// Last 10 players whose latest season for a team met a stat threshold
+pub struct Last10Question {
+    meta: QuestionMeta,
+    /// Question text with `{team}` and `{min}` placeholders
+    text: &'static str,
+    /// Column in `seasons` shown as the stat and compared to the threshold
+    stat: &'static str,
+    /// Default minimum of `stat` for a season to count
+    min: u32,
+    /// Other conditions a season must meet, e.g. `"s.position <> 'QB'"`
+    conditions: &'static [&'static str],
+}
+
+impl Question for Last10Question {
+    fn meta(&self) -> &QuestionMeta {
+        &self.meta
+    }
+
+    fn sample_params(&self, fixed: &QuestionParams, rng: &mut dyn RngCore) -> QuestionParams {
+        QuestionParams {
+            team: Some(team_or_random(fixed, rng)),
+            threshold: Some(fixed.threshold.unwrap_or(self.min)),
+            ..Default::default()
+        }
+    }
+
+    fn supported_params(&self) -> Vec<ParamKind> {
+        vec![ParamKind::Team, ParamKind::Threshold]
+    }
+
+    fn build(&self, params: &QuestionParams) -> (String, Query) {
+        let team = params.team();
+        let min = params.threshold();
+        let q = self
+            .text
+            .replace("{team}", team)
+            .replace("{min}", &min.to_string());
+        let eligible = SelectQuery::from("seasons s")
+            .columns(["s.player_id", "s.team_abbr", "s.season"])
+            .column(format!("s.{}", self.stat))
+            .filter("s.team_abbr = :team")
+            .filter_all(self.conditions.iter().copied())
+            .filter(format!("s.{} >= :min", self.stat));
+        let query = Query::new(last10(eligible, &[self.stat]).to_sql())
+            .bind("team", team)
+            .bind("min", min);
+        (q, query)
+    }
+
+    fn columns(&self) -> Vec<String> {
+        vec![
+            "name".into(),
+            "team_abbr".into(),
+            "season".into(),
+            self.stat.into(),
+        ]
+    }
+
+    fn is_last10(&self) -> bool {
+        true
+    }
+}
+
+/// Last 10 lesser-known players at a position (200 < career yards < 3000) to
+/// score a TD for a team
+pub struct Last10MidQuestion {
+    meta: QuestionMeta,
+    /// Question text with a `{team}` placeholder
+    text: &'static str,
+    position: &'static str,
+    /// Column summed for the career yardage window
+    yards: &'static str,
+    /// Alias for the career yardage column
+    career_alias: &'static str,
+    /// TD column that must be positive in the season
+    tds: &'static str,
+}
+
+impl Question for Last10MidQuestion {
+    fn meta(&self) -> &QuestionMeta {
+        &self.meta
+    }
+
+    fn sample_params(&self, fixed: &QuestionParams, rng: &mut dyn RngCore) -> QuestionParams {
+        QuestionParams {
+            team: Some(team_or_random(fixed, rng)),
+            ..Default::default()
+        }
+    }
+
+    fn supported_params(&self) -> Vec<ParamKind> {
+        vec![ParamKind::Team]
+    }
+
+    fn build(&self, params: &QuestionParams) -> (String, Query) {
+        let team = params.team();
+        let q = self.text.replace("{team}", team);
+        let career = self.career_alias;
+        let eligible = SelectQuery::from("seasons s")
+            .with("career", career_totals(self.yards, career))
+            .columns(["s.player_id", "s.team_abbr", "s.season"])
+            .column(format!("s.{}", self.tds))
+            .column(format!("career.{career}"))
+            .join("JOIN career ON career.player_id = s.player_id")
+            .filter("s.team_abbr = :team")
+            .filter(format!("s.position = '{}'", self.position))
+            .filter(format!("career.{career} < 3000"))
+            .filter(format!("career.{career} > 200"))
+            .filter(format!("s.{} > 0", self.tds));
+        let query = Query::new(last10(eligible, &[self.tds, career]).to_sql()).bind("team", team);
+        (q, query)
+    }
+
+    fn columns(&self) -> Vec<String> {
+        vec![
+            "name".into(),
+            "team_abbr".into(),
+            "season".into(),
+            self.tds.into(),
+            self.career_alias.into(),
+        ]
+    }
+
+    fn is_last10(&self) -> bool {
+        true
+    }
+}
+
+/// Registers every last-10 question
+pub(super) fn register_all(m: &mut QuestionRegistry) {
+    let add = |m: &mut QuestionRegistry,
+               code: &str,
+               desc: &str,
+               text: &'static str,
+               stat: &'static str,
+               min: u32,
+               conditions: &'static [&'static str],
+               difficulty: Difficulty| {
+        register(
+            m,
+            Last10Question {
+                meta: QuestionMeta::new(code, desc)
+                    .category(Category::for_stat(stat))
+                    .scope(Scope::Team)
+                    .era(Era::Recent)
+                    .difficulty(difficulty),
+                text,
+                stat,
+                min,
+                conditions,
+            },
+        )
+    };
+
+    add(
+        m,
+        "last10passers_TEAM",
+        "Last 10 players to attempt at least 10 passes for a team",
+        "Last 10 player-seasons with ≥{min} pass attempts for {team} (most recent first).",
+        "attempts",
+        10,
+        &[],
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "last10rushers_TEAM",
+        "Last 10 non-QBs to attempt at least 30 rushes for a team",
+        "Last 10 non-QB player-seasons with ≥{min} rush attempts for {team} (most recent first).",
+        "rushing_attempts",
+        30,
+        &["s.position <> 'QB'"],
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "last10receivers_TEAM",
+        "Last 10 players to record at least 20 receptions for a team",
+        "Last 10 player-seasons with ≥{min} receptions for {team} (most recent first).",
+        "receptions",
+        20,
+        &[],
+        Difficulty::Hard,
+    );
+    add(
+        m,
+        "last10intthrowers_TEAM",
+        "Last 10 players to throw an interception for a team",
+        "Last 10 player-seasons with ≥{min} interception thrown for {team} (most recent first).",
+        "interceptions",
+        1,
+        &[],
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "last10tdpassers_TEAM",
+        "Last 10 players to throw a passing TD for a team",
+        "Last 10 player-seasons with ≥{min} passing TD for {team} (most recent first).",
+        "passing_tds",
+        3,
+        &[],
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "last10nonqbp_TEAM",
+        "Last 10 non-QBs to attempt a pass for a team",
+        "Last 10 non-QB player-seasons with ≥{min} pass attempt for {team} (most recent first).",
+        "attempts",
+        1,
+        &["s.position <> 'QB'"],
+        Difficulty::Hard,
+    );
+
+    register(
+        m,
+        Last10MidQuestion {
+            meta: QuestionMeta::new(
+                "last10midwrs_TEAM",
+                "Last 10 WRs (<3000 career rec yards) to score a rec TD for a team",
+            )
+            .category(Category::Receiving)
+            .position(Some("WR"))
+            .scope(Scope::Team)
+            .era(Era::Recent)
+            .difficulty(Difficulty::Hard),
+            text: "Last 10 WRs (200 < career rec yards < 3000) to score a receiving TD for {team} (most recent first).",
+            position: "WR",
+            yards: "receiving_yards",
+            career_alias: "career_rec_yds",
+            tds: "receiving_tds",
+        },
+    );
+    register(
+        m,
+        Last10MidQuestion {
+            meta: QuestionMeta::new(
+                "last10midrbs_TEAM",
+                "Last 10 RBs (<3000 career rush yards) to score a rush TD for a team",
+            )
+            .category(Category::Rushing)
+            .position(Some("RB"))
+            .scope(Scope::Team)
+            .era(Era::Recent)
+            .difficulty(Difficulty::Hard),
+            text: "Last 10 RBs (200 < career rush yards < 3000) to score a rushing TD for {team} (most recent first).",
+            position: "RB",
+            yards: "rushing_yards",
+            career_alias: "career_rush_yds",
+            tds: "rushing_tds",
+        },
+    );
+}
@@ -0,0 +1,147 @@
+//! Leagues a question can be about
+//!
+//! The built-in questions are NFL questions read from `nfl.sqlite`. A pack
+//! can declare another league, which gives its questions that league's
+//! teams and sends the engine to that league's database. Seasons are labeled
+//! by the year they end and share the `START_YEAR`–`END_YEAR` span.
+use super::{builtin_registry, QuestionRegistry, TEAMS};
+use crate::sql_runner::DB_PATH;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// All 30 NBA team abbreviations, by current franchise
+pub const NBA_TEAMS: [&str; 30] = [
+    "ATL", "BOS", "BKN", "CHA", "CHI", "CLE", "DAL", "DEN", "DET", "GSW", "HOU", "IND", "LAC",
+    "LAL", "MEM", "MIA", "MIL", "MIN", "NOP", "NYK", "OKC", "ORL", "PHI", "PHX", "POR", "SAC",
+    "SAS", "TOR", "UTA", "WAS",
+];
+
+/// The NBA pack that `--league nba` plays
+#[cfg(feature = "packs")]
+const NBA_PACK: &str = include_str!("../../packs/nba.toml");
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum League {
+    #[default]
+    Nfl,
+    Nba,
+}
+
+impl League {
+    pub const ALL: [League; 2] = [League::Nfl, League::Nba];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            League::Nfl => "nfl",
+            League::Nba => "nba",
+        }
+    }
+
+    /// Team abbreviations a `{team}` parameter is drawn from
+    pub fn teams(&self) -> &'static [&'static str] {
+        match self {
+            League::Nfl => &TEAMS,
+            League::Nba => &NBA_TEAMS,
+        }
+    }
+
+    /// The database file this league's boards are read from
+    pub fn db_path(&self) -> &'static str {
+        match self {
+            League::Nfl => DB_PATH,
+            League::Nba => "nba.sqlite",
+        }
+    }
+
+    /// The questions that ship for this league: the built-in ones for the
+    /// NFL, the bundled pack for the others (empty without the `packs`
+    /// feature)
+    pub fn registry(&self) -> &'static QuestionRegistry {
+        match self {
+            League::Nfl => builtin_registry(),
+            League::Nba => {
+                static REGISTRY: OnceLock<QuestionRegistry> = OnceLock::new();
+                REGISTRY.get_or_init(nba_registry)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "packs")]
+fn nba_registry() -> QuestionRegistry {
+    let questions = super::pack::parse_pack(NBA_PACK, "toml").expect("the NBA pack is valid");
+    let mut registry = QuestionRegistry::new();
+    super::pack::merge_pack(&mut registry, questions);
+    registry
+}
+
+#[cfg(not(feature = "packs"))]
+fn nba_registry() -> QuestionRegistry {
+    QuestionRegistry::new()
+}
+
+/// Whether `code` is a team in any league
+pub fn is_team(code: &str) -> bool {
+    League::ALL
+        .iter()
+        .any(|league| league.teams().contains(&code))
+}
+
+impl fmt::Display for League {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for League {
+    type Err = String;
+
+    /// Parses "nfl" or "nba" (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        League::ALL
+            .into_iter()
+            .find(|league| league.as_str() == name)
+            .ok_or_else(|| format!("unknown league '{s}', expected nfl or nba"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_league_names_round_trip() {
+        for league in League::ALL {
+            assert_eq!(league.as_str().parse::<League>(), Ok(league));
+        }
+        assert_eq!("NBA".parse::<League>(), Ok(League::Nba));
+        assert_eq!(
+            "mlb".parse::<League>().unwrap_err(),
+            "unknown league 'mlb', expected nfl or nba"
+        );
+    }
+
+    #[test]
+    fn test_teams_belong_to_their_league() {
+        assert!(is_team("PIT") && is_team("BOS") && is_team("CHI"));
+        assert!(!is_team("XYZ"));
+        assert!(!League::Nfl.teams().contains(&"BOS"));
+        assert!(!League::Nba.teams().contains(&"PIT"));
+    }
+
+    #[cfg(feature = "packs")]
+    #[test]
+    fn test_nba_registry_is_all_nba() {
+        let registry = League::Nba.registry();
+        assert!(registry.len() >= 8);
+        assert!(registry.values().all(|q| q.league() == League::Nba));
+        assert!(League::Nfl
+            .registry()
+            .values()
+            .all(|q| q.league() == League::Nfl));
+    }
+}
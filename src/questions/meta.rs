@@ -0,0 +1,319 @@
+//! Question metadata: code, description, and the tags used to filter the registry
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Which part of the game a question's stat comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Passing,
+    Rushing,
+    Receiving,
+    Turnovers,
+    SpecialTeams,
+}
+
+impl Category {
+    pub const ALL: [Category; 5] = [
+        Category::Passing,
+        Category::Rushing,
+        Category::Receiving,
+        Category::Turnovers,
+        Category::SpecialTeams,
+    ];
+
+    /// Guesses the category from a `seasons` column or stat expression
+    pub fn for_stat(stat: &str) -> Category {
+        let stat = stat.to_ascii_lowercase();
+        if stat.contains("interception") || stat.contains("fumble") {
+            Category::Turnovers
+        } else if stat.contains("rush") {
+            Category::Rushing
+        } else if stat.contains("rec") || stat.contains("target") {
+            Category::Receiving
+        } else if stat.contains("kick") || stat.contains("punt") || stat.contains("return") {
+            Category::SpecialTeams
+        } else {
+            Category::Passing
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Passing => "passing",
+            Category::Rushing => "rushing",
+            Category::Receiving => "receiving",
+            Category::Turnovers => "turnovers",
+            Category::SpecialTeams => "special_teams",
+        }
+    }
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    /// Parses a name like "rushing" or "special-teams" (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase().replace('-', "_");
+        Category::ALL
+            .into_iter()
+            .find(|c| c.as_str() == name)
+            .ok_or_else(|| {
+                format!(
+                    "unknown category '{s}', expected passing, rushing, receiving, \
+                     turnovers, or special_teams"
+                )
+            })
+    }
+}
+
+/// Whether a question is about one team or the whole league
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    Team,
+    #[default]
+    League,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Team => "team",
+            Scope::League => "league",
+        }
+    }
+}
+
+/// The stretch of seasons a question covers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Era {
+    /// One season
+    Season,
+    /// A random range of seasons
+    YearRange,
+    /// Every season in the database
+    #[default]
+    AllTime,
+    /// The most recent seasons that qualify ("last 10 ...")
+    Recent,
+}
+
+impl Era {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Era::Season => "season",
+            Era::YearRange => "year_range",
+            Era::AllTime => "all_time",
+            Era::Recent => "recent",
+        }
+    }
+}
+
+/// How hard a board usually is for a casual fan
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    /// Parses "easy", "medium", or "hard" (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        Difficulty::ALL
+            .into_iter()
+            .find(|d| d.as_str() == name)
+            .ok_or_else(|| format!("unknown difficulty '{s}', expected easy, medium, or hard"))
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Tables every built-in question reads
+pub const DEFAULT_TABLES: [&str; 2] = ["players", "seasons"];
+
+/// Registry code, description, and filtering metadata for a question
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestionMeta {
+    pub code: String,
+    pub description: String,
+    pub categories: Vec<Category>,
+    /// Position the question is limited to, e.g. "QB"
+    pub position: Option<String>,
+    pub scope: Scope,
+    pub era: Era,
+    pub difficulty: Difficulty,
+    /// Free-form tags beyond the ones implied by the fields above
+    pub tags: Vec<String>,
+    /// Database tables the question's SQL reads
+    pub tables: Vec<String>,
+}
+
+impl QuestionMeta {
+    pub fn new(code: &str, description: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            description: description.to_string(),
+            categories: Vec::new(),
+            position: None,
+            scope: Scope::default(),
+            era: Era::default(),
+            difficulty: Difficulty::default(),
+            tags: Vec::new(),
+            tables: DEFAULT_TABLES.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    pub fn category(mut self, category: Category) -> Self {
+        if !self.categories.contains(&category) {
+            self.categories.push(category);
+        }
+        self
+    }
+
+    pub fn position(mut self, position: Option<&str>) -> Self {
+        self.position = position.map(str::to_string);
+        self
+    }
+
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn era(mut self, era: Era) -> Self {
+        self.era = era;
+        self
+    }
+
+    pub fn difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Every tag the question answers to: its categories, position, scope,
+    /// era, difficulty, and free-form tags, all lowercase
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .categories
+            .iter()
+            .map(|c| c.as_str().to_string())
+            .collect();
+        if let Some(position) = &self.position {
+            tags.push(position.to_ascii_lowercase());
+        }
+        tags.push(self.scope.as_str().to_string());
+        tags.push(self.era.as_str().to_string());
+        tags.push(self.difficulty.as_str().to_string());
+        tags.extend(self.tags.iter().map(|t| t.to_ascii_lowercase()));
+        tags
+    }
+
+    /// Its categories, difficulty, and free-form tags for a listing, e.g.
+    /// "rushing, receiving · hard · fantasy"
+    pub fn facets(&self) -> String {
+        let categories: Vec<&str> = self.categories.iter().map(Category::as_str).collect();
+        let mut parts = vec![categories.join(", "), self.difficulty.to_string()];
+        if !self.tags.is_empty() {
+            parts.push(self.tags.join(", "));
+        }
+        parts.retain(|part| !part.is_empty());
+        parts.join(" · ")
+    }
+
+    /// Returns true if the question carries the tag (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.to_ascii_lowercase();
+        self.all_tags().contains(&tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_for_stat() {
+        assert_eq!(Category::for_stat("rushing_attempts"), Category::Rushing);
+        assert_eq!(Category::for_stat("attempts"), Category::Passing);
+        assert_eq!(Category::for_stat("targets"), Category::Receiving);
+        assert_eq!(Category::for_stat("fumbles_lost"), Category::Turnovers);
+        assert_eq!(
+            Category::for_stat("1.0 * s.receiving_yards / s.receptions AS ypr"),
+            Category::Receiving
+        );
+    }
+
+    #[test]
+    fn test_parse_category() {
+        assert_eq!("Rushing".parse(), Ok(Category::Rushing));
+        assert_eq!("special-teams".parse(), Ok(Category::SpecialTeams));
+        assert!("defense".parse::<Category>().is_err());
+    }
+
+    #[test]
+    fn test_parse_difficulty() {
+        assert_eq!("HARD".parse(), Ok(Difficulty::Hard));
+        assert!("brutal".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn test_all_tags() {
+        let meta = QuestionMeta::new("x", "x")
+            .category(Category::Rushing)
+            .position(Some("QB"))
+            .scope(Scope::Team)
+            .era(Era::Recent)
+            .difficulty(Difficulty::Hard)
+            .tag("Fantasy");
+        assert!(meta.has_tag("qb"));
+        assert!(meta.has_tag("rushing"));
+        assert!(meta.has_tag("team"));
+        assert!(meta.has_tag("recent"));
+        assert!(meta.has_tag("hard"));
+        assert!(meta.has_tag("fantasy"));
+        assert!(!meta.has_tag("league"));
+    }
+
+    #[test]
+    fn test_facets() {
+        let meta = QuestionMeta::new("x", "x")
+            .category(Category::Rushing)
+            .category(Category::Receiving)
+            .difficulty(Difficulty::Hard)
+            .tag("fantasy");
+        assert_eq!(meta.facets(), "rushing, receiving · hard · fantasy");
+        assert_eq!(QuestionMeta::new("x", "x").facets(), "medium");
+    }
+}
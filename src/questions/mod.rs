@@ -0,0 +1,664 @@
+//! NFL trivia question types, SQL generation, and question registry.
+//!
+//! Every question implements the [`Question`] trait, which covers its
+//! description, random parameter sampling, SQL building, and display schema.
+//! Related questions live together in family modules (`team_totals`, `last10`,
+//! `year_range`, `single_season`), and the registry holds boxed implementations
+//! so new kinds can be added without touching a central match. Question packs
+//! (`pack`) add more kinds from TOML/JSON files at runtime, and
+//! [`CustomQuestion`] builds one-off questions from a stat and filters.
+use crate::sql_runner::Query;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::collections::btree_map::{self, BTreeMap};
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
+
+pub mod aliases;
+pub mod board_id;
+pub mod custom;
+mod last10;
+pub mod league;
+pub mod meta;
+#[cfg(feature = "packs")]
+pub mod pack;
+mod params;
+#[cfg(feature = "packs")]
+pub mod quiz_pack;
+mod single_season;
+pub mod sql;
+mod team_totals;
+mod year_range;
+
+pub use board_id::{BoardId, BoardIdError};
+pub use custom::{find_stat, CustomQuestion, CustomStat, CUSTOM_STATS};
+pub use last10::{Last10MidQuestion, Last10Question};
+pub use league::{League, NBA_TEAMS};
+pub use meta::{Category, Difficulty, Era, QuestionMeta, Scope};
+pub use params::{validate_params, ParamError, ParamKind, QuestionParams, POSITIONS};
+pub use single_season::SingleSeasonQuestion;
+pub use team_totals::TeamTotalQuestion;
+pub use year_range::YearRangeQuestion;
+
+/// Starting year for data (2000)
+pub const START_YEAR: i32 = 2000;
+
+/// Ending year for data (2024)
+pub const END_YEAR: i32 = 2024;
+
+/// All 32 NFL team abbreviations
+pub const TEAMS: [&str; 32] = [
+    "BUF", "MIA", "NE", "NYJ", "BAL", "CIN", "CLE", "PIT", "HOU", "IND", "JAX", "TEN", "DEN", "KC",
+    "LV", "LAC", "DAL", "NYG", "PHI", "WAS", "CHI", "DET", "GB", "MIN", "ATL", "CAR", "NO", "TB",
+    "ARI", "LAR", "SF", "SEA",
+];
+
+/// A trivia question definition.
+///
+/// Implementations describe themselves, pick random parameters, and turn those
+/// parameters into question text plus the SQL that produces the board. The
+/// first column of the board is the hidden player name and the last column is
+/// the stat used for scoring.
+pub trait Question: Send + Sync {
+    /// Registry code and description
+    fn meta(&self) -> &QuestionMeta;
+
+    /// Picks random values for every parameter the caller didn't fix
+    fn sample_params(&self, fixed: &QuestionParams, rng: &mut dyn RngCore) -> QuestionParams;
+
+    /// Parameters a caller may fix for this question
+    fn supported_params(&self) -> Vec<ParamKind>;
+
+    /// Builds the English question text and SQL query for the given
+    /// parameters, which are bound to the query rather than written into it
+    fn build(&self, params: &QuestionParams) -> (String, Query);
+
+    /// Column headers of the board this question produces
+    fn columns(&self) -> Vec<String>;
+
+    /// Returns true for the "last 10 players to ..." question family
+    fn is_last10(&self) -> bool {
+        false
+    }
+
+    /// The league the question is about, which decides its teams
+    fn league(&self) -> League {
+        League::Nfl
+    }
+}
+
+/// Maps question codes to their implementations.
+///
+/// Reads like a `BTreeMap` from code to question, so it iterates in code
+/// order and a seeded pick or a listing comes out the same every run. It also
+/// keeps each code in lowercase, so a code the player typed in any case is
+/// found with one lookup instead of a scan.
+#[derive(Clone, Default)]
+pub struct QuestionRegistry {
+    questions: BTreeMap<String, Arc<dyn Question>>,
+    lowercase: HashMap<String, String>,
+}
+
+impl QuestionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a question under its code, returning the one it replaced
+    pub fn insert(&mut self, question: Arc<dyn Question>) -> Option<Arc<dyn Question>> {
+        let code = question.meta().code.clone();
+        self.lowercase
+            .insert(code.to_ascii_lowercase(), code.clone());
+        self.questions.insert(code, question)
+    }
+
+    /// The question whose code matches `code` ignoring ASCII case
+    pub fn get_ignore_case(&self, code: &str) -> Option<&Arc<dyn Question>> {
+        let code = self.lowercase.get(&code.to_ascii_lowercase())?;
+        self.questions.get(code)
+    }
+
+    /// The questions whose metadata passes `keep`, as a registry of their
+    /// own, e.g. `registry.filter(|m| m.categories.contains(&Category::Rushing))`
+    /// to pick a random rushing question with [`choose_random_question`]
+    pub fn filter(&self, keep: impl Fn(&QuestionMeta) -> bool) -> QuestionRegistry {
+        let mut subset = QuestionRegistry::new();
+        for question in self.questions.values() {
+            if keep(question.meta()) {
+                subset.insert(Arc::clone(question));
+            }
+        }
+        subset
+    }
+
+    /// Every question with its code in lowercase, for prefix matching
+    pub fn iter_lowercase(&self) -> impl Iterator<Item = (&str, &Arc<dyn Question>)> {
+        self.lowercase
+            .iter()
+            .map(|(lower, code)| (lower.as_str(), &self.questions[code]))
+    }
+}
+
+impl Deref for QuestionRegistry {
+    type Target = BTreeMap<String, Arc<dyn Question>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.questions
+    }
+}
+
+impl<'a> IntoIterator for &'a QuestionRegistry {
+    type Item = (&'a String, &'a Arc<dyn Question>);
+    type IntoIter = btree_map::Iter<'a, String, Arc<dyn Question>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.questions.iter()
+    }
+}
+
+/// A fully generated question: its code, chosen parameters, English text, and
+/// the query for its board
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestionSpec {
+    pub code: String,
+    pub params: QuestionParams,
+    pub text: String,
+    /// Saved as `sql` and `bindings` next to the other fields
+    #[serde(flatten)]
+    pub query: Query,
+}
+
+/// Selects a random team
+pub(crate) fn random_team<R: Rng + ?Sized>(rng: &mut R) -> &'static str {
+    TEAMS.choose(rng).copied().unwrap()
+}
+
+/// Selects a random year between START_YEAR and END_YEAR (inclusive)
+pub(crate) fn random_year<R: Rng + ?Sized>(rng: &mut R) -> i32 {
+    rng.gen_range(START_YEAR..=END_YEAR)
+}
+
+/// Selects a random year range between START_YEAR and END_YEAR (inclusive)
+pub(crate) fn random_year_range<R: Rng + ?Sized>(rng: &mut R) -> (i32, i32) {
+    // inclusive, at least 2 years long
+    let start = rng.gen_range(START_YEAR..END_YEAR);
+    let end = rng.gen_range((start + 1)..=END_YEAR);
+    (start, end)
+}
+
+/// Uses the caller's team if given, otherwise a random one
+pub(crate) fn team_or_random<R: Rng + ?Sized>(fixed: &QuestionParams, rng: &mut R) -> String {
+    match &fixed.team {
+        Some(t) => t.clone(),
+        None => random_team(rng).to_string(),
+    }
+}
+
+/// Uses the caller's year if given, otherwise a random one
+pub(crate) fn year_or_random<R: Rng + ?Sized>(fixed: &QuestionParams, rng: &mut R) -> i32 {
+    fixed.year.unwrap_or_else(|| random_year(rng))
+}
+
+/// Uses the caller's year range if given, otherwise a random one
+pub(crate) fn year_range_or_random<R: Rng + ?Sized>(
+    fixed: &QuestionParams,
+    rng: &mut R,
+) -> (i32, i32) {
+    fixed.year_range.unwrap_or_else(|| random_year_range(rng))
+}
+
+// Parsed user request containing the question and optional team filter
+pub struct ParsedRequest {
+    pub question: Arc<dyn Question>,
+    pub team: Option<String>,
+    /// The old code the player typed, when it was found through an alias
+    pub renamed_from: Option<&'static str>,
+}
+
+/// Parses user input to extract the question and team (if specified).
+///
+/// Supports inputs like "last10rushers_PIT" where PIT is the team code, and
+/// falls back to [`aliases`] so renamed codes keep working.
+pub fn parse_query(input: &str, registry: &QuestionRegistry) -> Option<ParsedRequest> {
+    let raw = input.trim();
+
+    // Split into parts on underscore
+    let parts: Vec<&str> = raw.split('_').collect();
+    if parts.is_empty() {
+        return None;
+    }
+
+    // Check if last part is a valid team code
+    let last = parts.last().unwrap().to_ascii_uppercase();
+    let team = if league::is_team(&last) {
+        Some(last)
+    } else {
+        None
+    };
+
+    // Extract base code without team suffix
+    let base = if team.is_some() {
+        parts[..parts.len() - 1].join("_")
+    } else {
+        raw.to_string()
+    };
+
+    let found = registry
+        .get_ignore_case(&base)
+        .or_else(|| {
+            team.as_ref()
+                .and_then(|_| registry.get_ignore_case(&format!("{base}_team")))
+        })
+        // Another league's team, e.g. an NFL code with BOS
+        .filter(|question| {
+            team.as_ref()
+                .is_none_or(|team| question.league().teams().contains(&team.as_str()))
+        });
+
+    if let Some(question) = found {
+        return Some(ParsedRequest {
+            question: Arc::clone(question),
+            team,
+            renamed_from: None,
+        });
+    }
+
+    let alias = aliases::resolve_alias(raw)?;
+    let question = registry.get(alias.current)?;
+    Some(ParsedRequest {
+        question: Arc::clone(question),
+        team: alias.team,
+        renamed_from: Some(alias.old),
+    })
+}
+
+/// Adds a question to the registry under its code
+pub fn register(registry: &mut QuestionRegistry, question: impl Question + 'static) {
+    registry.insert(Arc::new(question));
+}
+
+/// Builds registry mapping question codes to their implementations.
+///
+/// Each call builds a fresh copy; [`builtin_registry`] shares one.
+pub fn build_registry() -> QuestionRegistry {
+    let mut m = QuestionRegistry::new();
+    team_totals::register_all(&mut m);
+    last10::register_all(&mut m);
+    year_range::register_all(&mut m);
+    single_season::register_all(&mut m);
+    m
+}
+
+/// The built-in questions, built on first use and shared from then on
+pub fn builtin_registry() -> &'static QuestionRegistry {
+    static REGISTRY: OnceLock<QuestionRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(build_registry)
+}
+
+/// Questions carrying a tag (category, position, scope, era, difficulty, or
+/// free-form), sorted by code
+pub fn by_tag(registry: &QuestionRegistry, tag: &str) -> Vec<Arc<dyn Question>> {
+    sorted_where(registry, |meta| meta.has_tag(tag))
+}
+
+/// Questions in a category, sorted by code
+pub fn by_category(registry: &QuestionRegistry, category: Category) -> Vec<Arc<dyn Question>> {
+    sorted_where(registry, |meta| meta.categories.contains(&category))
+}
+
+fn sorted_where(
+    registry: &QuestionRegistry,
+    keep: impl Fn(&QuestionMeta) -> bool,
+) -> Vec<Arc<dyn Question>> {
+    registry.filter(keep).values().map(Arc::clone).collect()
+}
+
+/// Chooses a random question from the registry, or from a subset of it made
+/// with [`QuestionRegistry::filter`].
+///
+/// The registry iterates in code order, so a seeded RNG always picks the same
+/// question.
+pub fn choose_random_question<R: Rng + ?Sized>(
+    registry: &QuestionRegistry,
+    rng: &mut R,
+) -> Option<Arc<dyn Question>> {
+    registry.values().choose(rng).map(Arc::clone)
+}
+
+/// Generates a question, picking any parameters the caller didn't fix with
+/// the given RNG.
+///
+/// `fixed` is assumed valid for the question; see [`validate_params`]. Any
+/// RNG works, including a `&mut dyn RngCore`.
+pub fn generate_question<R: RngCore + ?Sized>(
+    question: &dyn Question,
+    fixed: &QuestionParams,
+    rng: &mut R,
+) -> QuestionSpec {
+    let params = question.sample_params(fixed, &mut &mut *rng);
+    let (text, query) = question.build(&params);
+    QuestionSpec {
+        code: question.meta().code.clone(),
+        params,
+        text,
+        query,
+    }
+}
+
+/// Generates question text and SQL query for a given question.
+///
+/// Randomly selects parameters (teams, years, year ranges) using the given RNG
+/// and constructs the appropriate SQL query. The same seed always produces the
+/// same question. Callers that need the chosen parameters, e.g. to log or
+/// replay the question, should use [`generate_question`], whose
+/// [`QuestionSpec`] keeps them alongside the code.
+pub fn generate_sql_for_kind<R: RngCore + ?Sized>(
+    question: &dyn Question,
+    fixed: &QuestionParams,
+    rng: &mut R,
+) -> (String, Query) {
+    let spec = generate_question(question, fixed, rng);
+    (spec.text, spec.query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    #[allow(clippy::manual_range_contains)]
+    fn test_random_year_in_range() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let year = random_year(&mut rng);
+            assert!(year >= START_YEAR && year <= END_YEAR);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::int_plus_one)]
+    fn test_random_year_range_valid() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let (start, end) = random_year_range(&mut rng);
+            assert!(start >= START_YEAR);
+            assert!(end <= END_YEAR);
+            assert!(end > start); // At least 2 years
+            assert!(end >= start + 1);
+        }
+    }
+
+    #[test]
+    fn test_parse_query_with_team() {
+        let registry = build_registry();
+        let result = parse_query("last10passers_PIT", &registry);
+
+        assert!(result.is_some());
+        let parsed = result.unwrap();
+        assert_eq!(parsed.team, Some("PIT".to_string()));
+    }
+
+    #[test]
+    fn test_parse_query_without_team() {
+        let registry = build_registry();
+        let result = parse_query("top10fumlost_yearrange", &registry);
+
+        assert!(result.is_some());
+        let parsed = result.unwrap();
+        assert_eq!(parsed.team, None);
+    }
+
+    #[test]
+    fn test_parse_query_follows_aliases() {
+        let registry = build_registry();
+        let parsed = parse_query("recyds_PIT_yearrange", &registry).unwrap();
+        assert_eq!(parsed.question.meta().code, "recyds_yearrange_TEAM");
+        assert_eq!(parsed.team.as_deref(), Some("PIT"));
+        assert_eq!(parsed.renamed_from, Some("recyds_TEAM_yearrange"));
+
+        let parsed = parse_query("recyds_yearrange_PIT", &registry).unwrap();
+        assert_eq!(parsed.renamed_from, None);
+    }
+
+    #[test]
+    fn test_parse_query_invalid_team() {
+        let registry = build_registry();
+        // XYZ is not a valid team
+        let result = parse_query("last10passers_XYZ", &registry);
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_parse_query_case_insensitive() {
+        let registry = build_registry();
+        let result = parse_query("LAST10PASSERS_pit", &registry);
+
+        assert!(result.is_some());
+        let parsed = result.unwrap();
+        assert_eq!(parsed.team, Some("PIT".to_string()));
+    }
+
+    #[test]
+    fn test_build_registry_not_empty() {
+        let registry = build_registry();
+        assert!(!registry.is_empty());
+        assert!(registry.len() > 20); // Should have lots of questions
+    }
+
+    #[test]
+    fn test_all_teams_valid() {
+        // Make sure all teams in TEAMS array are 2-3 chars
+        for team in TEAMS.iter() {
+            assert!(team.len() >= 2 && team.len() <= 3);
+            assert!(team.chars().all(|c| c.is_ascii_uppercase()));
+        }
+    }
+
+    #[test]
+    fn test_generate_sql_contains_team() {
+        let registry = build_registry();
+        let (question, query) = generate_sql_for_kind(
+            registry["last10passers_TEAM"].as_ref(),
+            &QuestionParams::for_team(Some("IND")),
+            &mut rand::thread_rng(),
+        );
+
+        assert!(query.sql.contains("s.team_abbr = :team"));
+        assert!(!query.sql.contains("IND"));
+        assert_eq!(query.bindings[":team"], "IND".into());
+        assert!(question.contains("IND"));
+    }
+
+    #[test]
+    fn test_choose_random_question_returns_valid() {
+        let registry = build_registry();
+        let result = choose_random_question(&registry, &mut rand::thread_rng());
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn test_same_seed_same_question() {
+        let registry = build_registry();
+        let mut rng_a = StdRng::seed_from_u64(4217);
+        let mut rng_b = StdRng::seed_from_u64(4217);
+
+        for _ in 0..10 {
+            let question_a = choose_random_question(&registry, &mut rng_a).unwrap();
+            let question_b = choose_random_question(&registry, &mut rng_b).unwrap();
+            assert_eq!(question_a.meta().code, question_b.meta().code);
+
+            let (q_a, sql_a) =
+                generate_sql_for_kind(question_a.as_ref(), &QuestionParams::default(), &mut rng_a);
+            let (q_b, sql_b) =
+                generate_sql_for_kind(question_b.as_ref(), &QuestionParams::default(), &mut rng_b);
+            assert_eq!(q_a, q_b);
+            assert_eq!(sql_a, sql_b);
+        }
+    }
+
+    #[test]
+    fn test_generate_with_dyn_rng() {
+        let registry = build_registry();
+        let question = registry["top10rushtd_yearrange"].as_ref();
+        let mut seeded = StdRng::seed_from_u64(4217);
+        let rng: &mut dyn RngCore = &mut StdRng::seed_from_u64(4217);
+        assert_eq!(
+            generate_sql_for_kind(question, &QuestionParams::default(), rng),
+            generate_sql_for_kind(question, &QuestionParams::default(), &mut seeded)
+        );
+    }
+
+    #[test]
+    fn test_sql_has_order_by_and_limit() {
+        // All queries should have ORDER BY and LIMIT
+        let registry = build_registry();
+        let (_, query) = generate_sql_for_kind(
+            registry["top10passyds_year"].as_ref(),
+            &QuestionParams::default(),
+            &mut rand::thread_rng(),
+        );
+        assert!(query.sql.contains("ORDER BY"));
+        assert!(query.sql.contains("LIMIT 10"));
+    }
+
+    #[test]
+    fn test_year_range_questions_have_between() {
+        let registry = build_registry();
+        let (_, query) = generate_sql_for_kind(
+            registry["top10rushtd_yearrange"].as_ref(),
+            &QuestionParams::default(),
+            &mut rand::thread_rng(),
+        );
+        assert!(query.sql.contains("BETWEEN :start AND :end"));
+    }
+
+    #[test]
+    fn test_registry_ignores_case() {
+        let registry = builtin_registry();
+        assert!(std::ptr::eq(registry, builtin_registry()));
+        let question = registry.get_ignore_case("LAST10RUSHERS_team").unwrap();
+        assert_eq!(question.meta().code, "last10rushers_TEAM");
+        assert!(registry.get_ignore_case("last10rushers").is_none());
+        assert_eq!(registry.iter_lowercase().count(), registry.len());
+    }
+
+    #[test]
+    fn test_registry_keys_match_codes() {
+        let registry = build_registry();
+        assert_eq!(registry.len(), 29);
+        for (code, question) in &registry {
+            assert_eq!(code, &question.meta().code);
+        }
+    }
+
+    #[test]
+    fn test_builtins_have_metadata() {
+        let registry = build_registry();
+        for question in registry.values() {
+            let meta = question.meta();
+            assert!(!meta.categories.is_empty(), "{} has no category", meta.code);
+            assert_eq!(
+                meta.scope == Scope::Team,
+                meta.code.ends_with("_TEAM"),
+                "{} scope",
+                meta.code
+            );
+        }
+    }
+
+    #[test]
+    fn test_registry_queries() {
+        let registry = build_registry();
+
+        let qbs = by_tag(&registry, "QB");
+        let codes: Vec<_> = qbs.iter().map(|q| q.meta().code.as_str()).collect();
+        assert!(codes.contains(&"top10rushingqb_yearrange"));
+        assert!(codes.windows(2).all(|w| w[0] <= w[1]));
+
+        let turnovers = by_category(&registry, Category::Turnovers);
+        assert!(turnovers
+            .iter()
+            .any(|q| q.meta().code == "top10fumlost_yearrange"));
+        assert!(by_tag(&registry, "recent").iter().all(|q| q.is_last10()));
+    }
+
+    #[test]
+    fn test_filter_keeps_code_order() {
+        let registry = build_registry();
+        let codes: Vec<_> = registry.keys().collect();
+        assert!(codes.windows(2).all(|w| w[0] < w[1]));
+
+        let rushing = registry.filter(|m| m.categories.contains(&Category::Rushing));
+        assert!(!rushing.is_empty() && rushing.len() < registry.len());
+        assert!(rushing.get_ignore_case("RUSHYDS_YEARRANGE_team").is_some());
+        let mut rng = StdRng::seed_from_u64(9);
+        for _ in 0..20 {
+            let question = choose_random_question(&rushing, &mut rng).unwrap();
+            assert!(question.meta().categories.contains(&Category::Rushing));
+        }
+        assert!(choose_random_question(&registry.filter(|_| false), &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_fixed_params_are_kept() {
+        let registry = build_registry();
+        let mut rng = StdRng::seed_from_u64(5);
+
+        let fixed = QuestionParams {
+            team: Some("GB".into()),
+            threshold: Some(50),
+            ..Default::default()
+        };
+        let spec = generate_question(registry["last10passers_TEAM"].as_ref(), &fixed, &mut rng);
+        assert!(spec.text.contains("≥50 pass attempts for GB"));
+        assert!(spec.query.inline().contains("s.attempts >= 50"));
+
+        let fixed = QuestionParams {
+            year_range: Some((2010, 2014)),
+            position: Some("RB".into()),
+            ..Default::default()
+        };
+        let spec = generate_question(registry["top10rectd_yearrange"].as_ref(), &fixed, &mut rng);
+        assert!(spec
+            .text
+            .starts_with("Top 10 RBs with most receiving TDs between 2010–2014"));
+        assert!(spec.query.inline().contains("s.position = 'RB'"));
+    }
+
+    #[test]
+    fn test_generate_question_records_params() {
+        let registry = build_registry();
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let spec = generate_question(
+            registry["last10rushers_TEAM"].as_ref(),
+            &QuestionParams::for_team(Some("GB")),
+            &mut rng,
+        );
+        assert_eq!(spec.code, "last10rushers_TEAM");
+        assert_eq!(spec.params.team.as_deref(), Some("GB"));
+
+        let spec = generate_question(
+            registry["top10ypc_year"].as_ref(),
+            &QuestionParams::default(),
+            &mut rng,
+        );
+        let year = spec.params.year.unwrap();
+        assert!(spec.text.contains(&year.to_string()));
+        assert_eq!(spec.query.bindings[":year"], year.into());
+
+        let spec = generate_question(
+            registry["top10rectd_yearrange"].as_ref(),
+            &QuestionParams::default(),
+            &mut rng,
+        );
+        let (start, end) = spec.params.year_range.unwrap();
+        let inlined = spec.query.inline();
+        assert!(inlined.contains(&format!("BETWEEN {start} AND {end}")));
+    }
+}
@@ -0,0 +1,596 @@
+//! Question packs declared in TOML, JSON, or YAML files
+//!
+//! A pack lists question definitions (code, description, parameter slots,
+//! question text and SQL templates with named placeholders, display schema)
+//! that are validated and merged into the registry at startup, so new trivia
+//! can be shared without recompiling. Placeholders in the SQL become bound
+//! parameters, so a quoted `'{team}'` runs as the team code, not as a string
+//! spliced into the statement.
+//!
+//! ```toml
+//! [[question]]
+//! code = "top10sacked_year"
+//! description = "Top 10 most-sacked QBs in one season"
+//! params = ["year"]
+//! text = "Top 10 most-sacked QBs in {year}."
+//! sql = "SELECT p.name, s.team_abbr, s.sacks FROM seasons s ..."
+//! columns = ["name", "team_abbr", "sacks"]
+//! ```
+use super::{
+    year_or_random, year_range_or_random, Category, Difficulty, Era, League, ParamKind, Question,
+    QuestionMeta, QuestionParams, QuestionRegistry, Scope,
+};
+use crate::sql_runner::Query;
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A parameter a pack question can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamSlot {
+    /// Fills `{team}`
+    Team,
+    /// Fills `{year}`
+    Year,
+    /// Fills `{start}` and `{end}`
+    YearRange,
+}
+
+impl ParamSlot {
+    /// Placeholder names this slot provides
+    fn placeholders(&self) -> &'static [&'static str] {
+        match self {
+            ParamSlot::Team => &["team"],
+            ParamSlot::Year => &["year"],
+            ParamSlot::YearRange => &["start", "end"],
+        }
+    }
+}
+
+/// One question as written in a pack file
+#[derive(Debug, Clone, Deserialize)]
+pub struct PackQuestionDef {
+    pub code: String,
+    pub description: String,
+    #[serde(default)]
+    pub params: Vec<ParamSlot>,
+    pub text: String,
+    pub sql: String,
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub last10: bool,
+    #[serde(default)]
+    pub categories: Vec<Category>,
+    #[serde(default)]
+    pub position: Option<String>,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Tables the SQL reads; defaults to players and seasons
+    #[serde(default)]
+    pub tables: Vec<String>,
+}
+
+impl PackQuestionDef {
+    /// Builds the registry metadata, inferring scope and era from the params
+    fn meta(&self) -> QuestionMeta {
+        let has = |slot| self.params.contains(&slot);
+        let scope = if has(ParamSlot::Team) {
+            Scope::Team
+        } else {
+            Scope::League
+        };
+        let era = if self.last10 {
+            Era::Recent
+        } else if has(ParamSlot::Year) {
+            Era::Season
+        } else if has(ParamSlot::YearRange) {
+            Era::YearRange
+        } else {
+            Era::AllTime
+        };
+
+        let mut meta = QuestionMeta::new(&self.code, &self.description)
+            .position(self.position.as_deref())
+            .scope(scope)
+            .era(era)
+            .difficulty(self.difficulty);
+        for category in &self.categories {
+            meta = meta.category(*category);
+        }
+        for tag in &self.tags {
+            meta = meta.tag(tag);
+        }
+        if !self.tables.is_empty() {
+            meta.tables = self.tables.clone();
+        }
+        meta
+    }
+}
+
+/// Top-level layout of a pack file
+#[derive(Debug, Clone, Deserialize)]
+pub struct PackFile {
+    /// The league every question in the pack is about; NFL when left out
+    #[serde(default)]
+    pub league: League,
+    #[serde(rename = "question", default)]
+    pub questions: Vec<PackQuestionDef>,
+}
+
+/// Why a pack could not be loaded
+#[derive(Debug, Error)]
+pub enum PackError {
+    #[error("could not read pack: {0}")]
+    Io(#[source] std::io::Error),
+    #[error("could not parse pack: {0}")]
+    Parse(String),
+    #[error("unknown pack format '{0}', expected .toml, .json, .yaml, or .yml")]
+    UnknownFormat(String),
+    #[error("invalid question '{code}': {reason}")]
+    Invalid { code: String, reason: String },
+}
+
+/// A question backed by text and SQL templates from a pack file
+#[derive(Debug, Clone)]
+pub struct TemplateQuestion {
+    meta: QuestionMeta,
+    params: Vec<ParamSlot>,
+    text: String,
+    /// The SQL template with its placeholders turned into named parameters
+    sql: String,
+    columns: Vec<String>,
+    last10: bool,
+    league: League,
+}
+
+impl TemplateQuestion {
+    /// Validates a definition and turns it into an NFL question
+    pub fn from_def(def: PackQuestionDef) -> Result<Self, PackError> {
+        validate(&def)?;
+        Ok(Self {
+            meta: def.meta(),
+            params: def.params,
+            sql: parameterize(&def.sql),
+            text: def.text,
+            columns: def.columns,
+            last10: def.last10,
+            league: League::Nfl,
+        })
+    }
+
+    /// Makes the question about `league`, drawing teams from its list
+    pub fn with_league(mut self, league: League) -> Self {
+        self.league = league;
+        self
+    }
+
+    /// Replaces every placeholder in the question text with the chosen parameters
+    fn fill(&self, template: &str, params: &QuestionParams) -> String {
+        let mut out = template.to_string();
+        for slot in &self.params {
+            match slot {
+                ParamSlot::Team => out = out.replace("{team}", params.team()),
+                ParamSlot::Year => out = out.replace("{year}", &params.year().to_string()),
+                ParamSlot::YearRange => {
+                    let (s, e) = params.year_range();
+                    out = out
+                        .replace("{start}", &s.to_string())
+                        .replace("{end}", &e.to_string());
+                }
+            }
+        }
+        out
+    }
+
+    /// The SQL with each of the chosen parameters bound
+    fn query(&self, params: &QuestionParams) -> Query {
+        let mut query = Query::new(self.sql.clone());
+        for slot in &self.params {
+            query = match slot {
+                ParamSlot::Team => query.bind("team", params.team()),
+                ParamSlot::Year => query.bind("year", params.year()),
+                ParamSlot::YearRange => {
+                    let (s, e) = params.year_range();
+                    query.bind("start", s).bind("end", e)
+                }
+            };
+        }
+        query
+    }
+}
+
+impl Question for TemplateQuestion {
+    fn meta(&self) -> &QuestionMeta {
+        &self.meta
+    }
+
+    fn sample_params(&self, fixed: &QuestionParams, rng: &mut dyn RngCore) -> QuestionParams {
+        let mut params = QuestionParams::default();
+        for slot in &self.params {
+            match slot {
+                ParamSlot::Team => {
+                    params.team = Some(match &fixed.team {
+                        Some(team) => team.clone(),
+                        None => self.league.teams().choose(rng).unwrap().to_string(),
+                    })
+                }
+                ParamSlot::Year => params.year = Some(year_or_random(fixed, rng)),
+                ParamSlot::YearRange => params.year_range = Some(year_range_or_random(fixed, rng)),
+            }
+        }
+        params
+    }
+
+    fn supported_params(&self) -> Vec<ParamKind> {
+        self.params
+            .iter()
+            .map(|slot| match slot {
+                ParamSlot::Team => ParamKind::Team,
+                ParamSlot::Year => ParamKind::Year,
+                ParamSlot::YearRange => ParamKind::YearRange,
+            })
+            .collect()
+    }
+
+    fn build(&self, params: &QuestionParams) -> (String, Query) {
+        (self.fill(&self.text, params), self.query(params))
+    }
+
+    fn columns(&self) -> Vec<String> {
+        self.columns.clone()
+    }
+
+    fn is_last10(&self) -> bool {
+        self.last10
+    }
+
+    fn league(&self) -> League {
+        self.league
+    }
+}
+
+/// Names of every `{placeholder}` in a template
+fn placeholders(template: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let after = &rest[open + 1..];
+        match after.find('}') {
+            Some(close) => {
+                found.push(&after[..close]);
+                rest = &after[close + 1..];
+            }
+            None => break,
+        }
+    }
+    found
+}
+
+/// Turns each `{placeholder}` in a SQL template into a `:placeholder`
+/// parameter, dropping any quotes written around it
+fn parameterize(sql: &str) -> String {
+    let mut out = sql.to_string();
+    for name in placeholders(sql) {
+        out = out
+            .replace(&format!("'{{{name}}}'"), &format!(":{name}"))
+            .replace(&format!("{{{name}}}"), &format!(":{name}"));
+    }
+    out
+}
+
+/// Checks a definition before it is allowed into the registry.
+///
+/// Every placeholder must come from a declared parameter, every declared
+/// parameter must be used by the SQL, the SQL must be a single SELECT, and
+/// the display schema must list at least the name and stat columns.
+pub fn validate(def: &PackQuestionDef) -> Result<(), PackError> {
+    let invalid = |reason: String| PackError::Invalid {
+        code: def.code.clone(),
+        reason,
+    };
+
+    if def.code.trim().is_empty() || def.code.contains(char::is_whitespace) {
+        return Err(invalid("code must be a single non-empty word".into()));
+    }
+
+    let allowed: Vec<&str> = def
+        .params
+        .iter()
+        .flat_map(|slot| slot.placeholders().iter().copied())
+        .collect();
+    for template in [&def.text, &def.sql] {
+        for name in placeholders(template) {
+            if !allowed.contains(&name) {
+                return Err(invalid(format!(
+                    "placeholder {{{name}}} is not provided by params"
+                )));
+            }
+        }
+    }
+    let used = placeholders(&def.sql);
+    if let Some(unused) = allowed.iter().find(|name| !used.contains(name)) {
+        return Err(invalid(format!("param {{{unused}}} is never used in sql")));
+    }
+
+    check_select(&def.sql).map_err(|reason| invalid(reason.into()))?;
+
+    if def.columns.len() < 2 {
+        return Err(invalid(
+            "columns must list at least the name and stat columns".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Rejects SQL that isn't a single SELECT statement
+pub(crate) fn check_select(sql: &str) -> Result<(), &'static str> {
+    let upper = sql.trim_start().to_ascii_uppercase();
+    if !(upper.starts_with("SELECT") || upper.starts_with("WITH")) {
+        return Err("sql must be a SELECT query");
+    }
+    if sql.trim().trim_end_matches(';').contains(';') {
+        return Err("sql must be a single statement");
+    }
+    Ok(())
+}
+
+/// Directory whose packs are loaded at startup, relative to the working
+/// directory like the database
+pub const QUESTIONS_DIR: &str = "questions";
+
+/// File extensions [`load_pack`] can read
+const PACK_EXTENSIONS: [&str; 4] = ["toml", "json", "yaml", "yml"];
+
+/// Parses and validates a pack from a string in the given format ("toml",
+/// "json", "yaml", or "yml")
+pub fn parse_pack(contents: &str, format: &str) -> Result<Vec<TemplateQuestion>, PackError> {
+    let file: PackFile = match format {
+        "toml" => toml::from_str(contents).map_err(|e| PackError::Parse(e.to_string()))?,
+        "json" => serde_json::from_str(contents).map_err(|e| PackError::Parse(e.to_string()))?,
+        "yaml" | "yml" => {
+            serde_yaml::from_str(contents).map_err(|e| PackError::Parse(e.to_string()))?
+        }
+        other => return Err(PackError::UnknownFormat(other.to_string())),
+    };
+    file.questions
+        .into_iter()
+        .map(|def| Ok(TemplateQuestion::from_def(def)?.with_league(file.league)))
+        .collect()
+}
+
+/// Reads a pack file, picking the format from its extension
+pub fn load_pack(path: &Path) -> Result<Vec<TemplateQuestion>, PackError> {
+    let format = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let contents = std::fs::read_to_string(path).map_err(PackError::Io)?;
+    parse_pack(&contents, &format)
+}
+
+/// Every pack file directly inside `dir`, sorted by name so packs load in
+/// the same order everywhere. A missing directory has none.
+pub fn pack_files(dir: &Path) -> Result<Vec<PathBuf>, PackError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(PackError::Io(e)),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(PackError::Io)?.path();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        if path.is_file() && extension.is_some_and(|e| PACK_EXTENSIONS.contains(&e.as_str())) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Merges pack questions into the registry.
+///
+/// Built-in codes can't be replaced; returns the codes that were skipped
+/// because they already existed.
+pub fn merge_pack(
+    registry: &mut QuestionRegistry,
+    questions: Vec<TemplateQuestion>,
+) -> Vec<String> {
+    let mut skipped = Vec::new();
+    for question in questions {
+        if registry.contains_key(&question.meta.code) {
+            skipped.push(question.meta.code.clone());
+        } else {
+            registry.insert(Arc::new(question));
+        }
+    }
+    skipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::{build_registry, NBA_TEAMS};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const TOML_PACK: &str = r#"
+[[question]]
+code = "top10targets_yearrange_TEAM"
+description = "Top 10 targets for a team in a year range"
+params = ["team", "year_range"]
+text = "Top 10 players in targets for {team} between {start}–{end}."
+sql = "SELECT p.name, s.team_abbr, SUM(s.targets) AS tgts FROM seasons s JOIN players p ON p.player_id = s.player_id WHERE s.team_abbr = '{team}' AND s.season BETWEEN {start} AND {end} GROUP BY s.player_id ORDER BY tgts DESC LIMIT 10"
+columns = ["name", "team_abbr", "tgts"]
+"#;
+
+    fn def(params: Vec<ParamSlot>, text: &str, sql: &str) -> PackQuestionDef {
+        PackQuestionDef {
+            code: "test_code".into(),
+            description: "test".into(),
+            params,
+            text: text.into(),
+            sql: sql.into(),
+            columns: vec!["name".into(), "stat".into()],
+            last10: false,
+            categories: vec![],
+            position: None,
+            difficulty: Difficulty::default(),
+            tags: vec![],
+            tables: vec![],
+        }
+    }
+
+    #[test]
+    fn test_parse_toml_pack() {
+        let questions = parse_pack(TOML_PACK, "toml").unwrap();
+        assert_eq!(questions.len(), 1);
+
+        let q = &questions[0];
+        let mut rng = StdRng::seed_from_u64(1);
+        let params = q.sample_params(&QuestionParams::for_team(Some("PIT")), &mut rng);
+        let (text, query) = q.build(&params);
+        let (s, e) = params.year_range.unwrap();
+        assert!(text.contains("PIT"));
+        assert!(text.contains(&format!("{s}–{e}")));
+        assert!(query
+            .sql
+            .contains("s.team_abbr = :team AND s.season BETWEEN :start AND :end"));
+        assert_eq!(query.bindings[":team"], "PIT".into());
+        assert!(query.inline().contains(&format!("BETWEEN {s} AND {e}")));
+    }
+
+    #[test]
+    fn test_pack_metadata() {
+        let pack = TOML_PACK.replace(
+            "columns = [",
+            "categories = [\"receiving\"]\ndifficulty = \"hard\"\ntags = [\"volume\"]\ncolumns = [",
+        );
+        let questions = parse_pack(&pack, "toml").unwrap();
+        let meta = questions[0].meta();
+        assert_eq!(meta.categories, vec![Category::Receiving]);
+        assert_eq!(meta.difficulty, Difficulty::Hard);
+        assert_eq!(meta.scope, Scope::Team);
+        assert_eq!(meta.era, Era::YearRange);
+        assert!(meta.has_tag("volume"));
+    }
+
+    #[test]
+    fn test_pack_league_draws_its_teams() {
+        let pack = format!("league = \"nba\"\n{TOML_PACK}");
+        let questions = parse_pack(&pack, "toml").unwrap();
+        assert_eq!(questions[0].league(), League::Nba);
+        assert_eq!(
+            parse_pack(TOML_PACK, "toml").unwrap()[0].league(),
+            League::Nfl
+        );
+
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..20 {
+            let params = questions[0].sample_params(&QuestionParams::default(), &mut rng);
+            assert!(NBA_TEAMS.contains(&params.team.as_deref().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_parse_json_pack() {
+        let json = r#"{"question": [{
+            "code": "top10sacked_year",
+            "description": "Most sacked QBs",
+            "params": ["year"],
+            "text": "Most sacked in {year}",
+            "sql": "SELECT p.name, s.sacks FROM seasons s JOIN players p ON p.player_id = s.player_id WHERE s.season = {year} ORDER BY s.sacks DESC LIMIT 10",
+            "columns": ["name", "sacks"]
+        }]}"#;
+        let questions = parse_pack(json, "json").unwrap();
+        assert_eq!(questions[0].meta().code, "top10sacked_year");
+    }
+
+    #[test]
+    fn test_parse_yaml_pack() {
+        let yaml = "
+league: nba
+question:
+  - code: points_year
+    description: Top scorers in one season
+    params: [year]
+    text: Top 10 scorers in {year}.
+    sql: |
+      SELECT p.name, s.points FROM seasons s
+      JOIN players p ON p.player_id = s.player_id
+      WHERE s.season = {year} ORDER BY s.points DESC LIMIT 10
+    columns: [name, points]
+";
+        let questions = parse_pack(yaml, "yml").unwrap();
+        assert_eq!(questions[0].meta().code, "points_year");
+        assert_eq!(questions[0].league(), League::Nba);
+        assert!(questions[0].sql.contains("s.season = :year"));
+        assert!(matches!(
+            parse_pack("question: [", "yaml"),
+            Err(PackError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_pack_files_in_a_directory() {
+        let dir = std::env::temp_dir().join(format!("know_ball_questions_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.yaml", "a.TOML", "notes.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let files = pack_files(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files, [dir.join("a.TOML"), dir.join("b.yaml")]);
+        assert!(pack_files(&dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_placeholder_rejected() {
+        let d = def(vec![ParamSlot::Year], "{year}", "SELECT {year}, {team}");
+        assert!(matches!(validate(&d), Err(PackError::Invalid { .. })));
+    }
+
+    #[test]
+    fn test_unused_param_rejected() {
+        let d = def(
+            vec![ParamSlot::Team, ParamSlot::Year],
+            "{team}",
+            "SELECT {team}",
+        );
+        assert!(matches!(validate(&d), Err(PackError::Invalid { .. })));
+    }
+
+    #[test]
+    fn test_non_select_rejected() {
+        let d = def(vec![], "text", "DELETE FROM players");
+        assert!(validate(&d).is_err());
+        let d = def(vec![], "text", "SELECT 1; DROP TABLE players");
+        assert!(validate(&d).is_err());
+    }
+
+    #[test]
+    fn test_merge_keeps_builtins() {
+        let mut registry = build_registry();
+        let before = registry.len();
+        let mut questions = parse_pack(TOML_PACK, "toml").unwrap();
+        let mut clash = questions[0].clone();
+        clash.meta.code = "passyds_TEAM".into();
+        questions.push(clash);
+
+        let skipped = merge_pack(&mut registry, questions);
+        assert_eq!(skipped, vec!["passyds_TEAM".to_string()]);
+        assert_eq!(registry.len(), before + 1);
+        assert!(registry.contains_key("top10targets_yearrange_TEAM"));
+    }
+}
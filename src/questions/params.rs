@@ -0,0 +1,268 @@
+//! Question parameters and the checks that keep them within what a question supports
+use super::{Question, END_YEAR, START_YEAR};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// Positions a question can be narrowed to
+pub const POSITIONS: [&str; 4] = ["QB", "RB", "WR", "TE"];
+
+/// Parameters chosen for one generated question.
+///
+/// Callers fill in the fields they want fixed and leave the rest as `None`;
+/// the question samples whatever is left.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuestionParams {
+    pub team: Option<String>,
+    pub year: Option<i32>,
+    pub year_range: Option<(i32, i32)>,
+    /// Minimum stat a season needs to qualify, e.g. 10 pass attempts
+    pub threshold: Option<u32>,
+    /// Position to narrow a league-wide question to, e.g. "RB"
+    pub position: Option<String>,
+}
+
+impl QuestionParams {
+    /// Fixes only the team
+    pub fn for_team(team: Option<&str>) -> Self {
+        Self {
+            team: team.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    /// The team, for questions that require one
+    pub fn team(&self) -> &str {
+        self.team.as_deref().expect("question requires a team")
+    }
+
+    /// The single season, for questions that require one
+    pub fn year(&self) -> i32 {
+        self.year.expect("question requires a year")
+    }
+
+    /// The inclusive season range, for questions that require one
+    pub fn year_range(&self) -> (i32, i32) {
+        self.year_range.expect("question requires a year range")
+    }
+
+    /// The qualifying minimum, for questions that require one
+    pub fn threshold(&self) -> u32 {
+        self.threshold.expect("question requires a threshold")
+    }
+
+    /// The parameter kinds that are set
+    fn kinds(&self) -> Vec<ParamKind> {
+        let mut kinds = Vec::new();
+        if self.team.is_some() {
+            kinds.push(ParamKind::Team);
+        }
+        if self.year.is_some() {
+            kinds.push(ParamKind::Year);
+        }
+        if self.year_range.is_some() {
+            kinds.push(ParamKind::YearRange);
+        }
+        if self.threshold.is_some() {
+            kinds.push(ParamKind::Threshold);
+        }
+        if self.position.is_some() {
+            kinds.push(ParamKind::Position);
+        }
+        kinds
+    }
+
+    /// Each parameter that is set as text, e.g. "PIT" or "min=10"
+    pub(crate) fn parts(&self) -> Vec<String> {
+        let mut parts = Vec::new();
+        if let Some(team) = &self.team {
+            parts.push(team.clone());
+        }
+        if let Some(year) = self.year {
+            parts.push(year.to_string());
+        }
+        if let Some((start, end)) = self.year_range {
+            parts.push(format!("{start}-{end}"));
+        }
+        if let Some(position) = &self.position {
+            parts.push(position.clone());
+        }
+        if let Some(min) = self.threshold {
+            parts.push(format!("min={min}"));
+        }
+        parts
+    }
+}
+
+/// The parameters that are set, e.g. "PIT 2005-2012 WR min=10", or nothing
+/// when none are
+impl fmt::Display for QuestionParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.parts().join(" "))
+    }
+}
+
+/// A parameter a question can accept from the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Team,
+    Year,
+    YearRange,
+    Threshold,
+    Position,
+}
+
+impl ParamKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParamKind::Team => "team",
+            ParamKind::Year => "year",
+            ParamKind::YearRange => "year range",
+            ParamKind::Threshold => "threshold",
+            ParamKind::Position => "position",
+        }
+    }
+}
+
+/// Why caller-supplied parameters were rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParamError {
+    #[error("{code} doesn't take a {}", param.as_str())]
+    Unsupported { code: String, param: ParamKind },
+    #[error("unknown team '{0}'")]
+    UnknownTeam(String),
+    #[error("{0} is outside the {START_YEAR}–{END_YEAR} seasons")]
+    YearOutOfRange(i32),
+    #[error("year range {0}–{1} must go from an earlier season to a later one")]
+    BackwardsRange(i32, i32),
+    #[error("threshold must be at least 1")]
+    ZeroThreshold,
+    #[error("unknown position '{0}', expected one of QB, RB, WR, TE")]
+    UnknownPosition(String),
+    #[error("a custom question needs a stat to rank by")]
+    MissingStat,
+    #[error("unknown stat '{0}'")]
+    UnknownStat(String),
+    #[error("{stat} only applies to QBs, not {position}s")]
+    PositionMismatch { stat: String, position: String },
+}
+
+/// Checks that every fixed parameter is one the question accepts and holds a
+/// sensible value
+pub fn validate_params(question: &dyn Question, params: &QuestionParams) -> Result<(), ParamError> {
+    let supported = question.supported_params();
+    if let Some(param) = params.kinds().into_iter().find(|k| !supported.contains(k)) {
+        return Err(ParamError::Unsupported {
+            code: question.meta().code.clone(),
+            param,
+        });
+    }
+
+    if let Some(team) = &params.team {
+        if !question.league().teams().contains(&team.as_str()) {
+            return Err(ParamError::UnknownTeam(team.clone()));
+        }
+    }
+    let in_range = |year: i32| {
+        if (START_YEAR..=END_YEAR).contains(&year) {
+            Ok(())
+        } else {
+            Err(ParamError::YearOutOfRange(year))
+        }
+    };
+    if let Some(year) = params.year {
+        in_range(year)?;
+    }
+    if let Some((start, end)) = params.year_range {
+        in_range(start)?;
+        in_range(end)?;
+        if start >= end {
+            return Err(ParamError::BackwardsRange(start, end));
+        }
+    }
+    if params.threshold == Some(0) {
+        return Err(ParamError::ZeroThreshold);
+    }
+    if let Some(position) = &params.position {
+        if !POSITIONS.contains(&position.as_str()) {
+            return Err(ParamError::UnknownPosition(position.clone()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::build_registry;
+
+    #[test]
+    fn test_validate_rejects_unsupported() {
+        let registry = build_registry();
+        let year = QuestionParams {
+            year: Some(2010),
+            ..Default::default()
+        };
+        assert!(validate_params(registry["top10passyds_year"].as_ref(), &year).is_ok());
+        assert_eq!(
+            validate_params(registry["passyds_TEAM"].as_ref(), &year),
+            Err(ParamError::Unsupported {
+                code: "passyds_TEAM".into(),
+                param: ParamKind::Year,
+            })
+        );
+
+        let position = QuestionParams {
+            position: Some("RB".into()),
+            ..Default::default()
+        };
+        assert!(validate_params(registry["top10rushtd_yearrange"].as_ref(), &position).is_ok());
+        assert!(validate_params(registry["top10rushingqb_yearrange"].as_ref(), &position).is_err());
+    }
+
+    #[test]
+    fn test_validate_values() {
+        let registry = build_registry();
+        let question = registry["recyds_yearrange_TEAM"].as_ref();
+        let check = |params: QuestionParams| validate_params(question, &params);
+
+        assert_eq!(
+            check(QuestionParams::for_team(Some("XYZ"))),
+            Err(ParamError::UnknownTeam("XYZ".into()))
+        );
+        assert_eq!(
+            check(QuestionParams {
+                year_range: Some((2012, 2005)),
+                ..Default::default()
+            }),
+            Err(ParamError::BackwardsRange(2012, 2005))
+        );
+        assert_eq!(
+            check(QuestionParams {
+                year_range: Some((1990, 2005)),
+                ..Default::default()
+            }),
+            Err(ParamError::YearOutOfRange(1990))
+        );
+        assert!(check(QuestionParams {
+            team: Some("PIT".into()),
+            year_range: Some((2005, 2012)),
+            ..Default::default()
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn test_display_lists_the_set_params() {
+        assert_eq!(QuestionParams::default().to_string(), "");
+        let params = QuestionParams {
+            team: Some("PIT".into()),
+            year_range: Some((2005, 2012)),
+            threshold: Some(10),
+            position: Some("WR".into()),
+            ..Default::default()
+        };
+        assert_eq!(params.to_string(), "PIT 2005-2012 WR min=10");
+    }
+}
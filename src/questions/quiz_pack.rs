@@ -0,0 +1,210 @@
+//! Shareable quiz packs (`.kbq` files)
+//!
+//! A quiz pack is a fixed list of already-generated questions, so everyone who
+//! plays it gets the same questions in the same order. Each entry keeps the
+//! question's spec (text and SQL) and, optionally, a hash of its answers that
+//! tells the player whether their database produced the same board the pack
+//! was made with. The hash only checks the board; it doesn't hide the answers
+//! from anyone willing to guess at it.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "title": "Steelers night",
+//!   "questions": [{ "spec": { "code": "last10passers_TEAM", ... }, "answers": "9c1e..." }]
+//! }
+//! ```
+use super::pack::{check_select, PackError};
+use super::QuestionSpec;
+use crate::game::ANSWER_COL;
+use crate::sql_runner::Board;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File extension for quiz packs
+pub const QUIZ_PACK_EXTENSION: &str = "kbq";
+
+/// Format version written to new packs
+pub const QUIZ_PACK_VERSION: u32 = 1;
+
+/// A curated list of questions to play in order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuizPack {
+    pub version: u32,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub questions: Vec<QuizPackEntry>,
+}
+
+/// One question in a quiz pack
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuizPackEntry {
+    pub spec: QuestionSpec,
+    /// Hash of the board's answers when the pack was made
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answers: Option<String>,
+}
+
+impl QuizPackEntry {
+    /// Whether `board` has the answers the pack was made with, or None if the
+    /// pack has no hash for this question
+    pub fn matches_board(&self, board: &Board) -> Option<bool> {
+        self.answers
+            .as_ref()
+            .map(|hash| *hash == answer_hash(board))
+    }
+}
+
+impl QuizPack {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            version: QUIZ_PACK_VERSION,
+            title: title.into(),
+            author: None,
+            description: None,
+            questions: Vec::new(),
+        }
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds a question, hashing its answers when the board is given
+    pub fn push(&mut self, spec: QuestionSpec, board: Option<&Board>) {
+        self.questions.push(QuizPackEntry {
+            spec,
+            answers: board.map(answer_hash),
+        });
+    }
+
+    /// Parses and checks a pack. Only read-only queries are accepted, since
+    /// a pack's SQL comes from whoever shared it.
+    pub fn from_json(contents: &str) -> Result<Self, PackError> {
+        let pack: QuizPack =
+            serde_json::from_str(contents).map_err(|e| PackError::Parse(e.to_string()))?;
+        if pack.version > QUIZ_PACK_VERSION {
+            return Err(PackError::Parse(format!(
+                "quiz pack version {} is newer than this build supports ({QUIZ_PACK_VERSION})",
+                pack.version
+            )));
+        }
+        for entry in &pack.questions {
+            check_select(&entry.spec.query.sql).map_err(|reason| PackError::Invalid {
+                code: entry.spec.code.clone(),
+                reason: reason.into(),
+            })?;
+        }
+        Ok(pack)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("quiz packs serialize to JSON")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, PackError> {
+        let contents = std::fs::read_to_string(path).map_err(PackError::Io)?;
+        Self::from_json(&contents)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), PackError> {
+        std::fs::write(path, self.to_json() + "\n").map_err(PackError::Io)
+    }
+}
+
+/// Order-insensitive hash of a board's answer names, as 16 hex digits.
+///
+/// FNV-1a, so the value is the same on every platform and Rust version.
+pub fn answer_hash(board: &Board) -> String {
+    let mut names: Vec<String> = board
+        .rows
+        .iter()
+        .filter_map(|row| row.get(ANSWER_COL))
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    names.sort();
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in names.join("\n").bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::QuestionParams;
+
+    fn spec(sql: &str) -> QuestionSpec {
+        QuestionSpec {
+            code: "passyds_TEAM".into(),
+            params: QuestionParams::for_team(Some("PIT")),
+            text: "Top passers for PIT.".into(),
+            query: sql.into(),
+        }
+    }
+
+    fn board(names: &[&str]) -> Board {
+        Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: names
+                .iter()
+                .map(|n| vec![n.to_string(), "1".into()])
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_answer_check() {
+        let mut pack = QuizPack::new("Steelers night").author("Marcus");
+        pack.push(
+            spec("SELECT 1"),
+            Some(&board(&["Ben Roethlisberger", "Mason Rudolph"])),
+        );
+        pack.push(spec("WITH x AS (SELECT 1) SELECT * FROM x"), None);
+
+        let loaded = QuizPack::from_json(&pack.to_json()).unwrap();
+        assert_eq!(loaded, pack);
+        let entry = &loaded.questions[0];
+        assert_eq!(
+            entry.matches_board(&board(&["mason rudolph", "Ben Roethlisberger"])),
+            Some(true)
+        );
+        assert_eq!(entry.matches_board(&board(&["Kenny Pickett"])), Some(false));
+        assert_eq!(loaded.questions[1].matches_board(&board(&[])), None);
+    }
+
+    #[test]
+    fn test_rejects_writes_and_newer_versions() {
+        let mut pack = QuizPack::new("Bad");
+        pack.push(spec("DROP TABLE players"), None);
+        assert!(matches!(
+            QuizPack::from_json(&pack.to_json()),
+            Err(PackError::Invalid { .. })
+        ));
+        let mut pack = QuizPack::new("Sneaky");
+        pack.push(spec("SELECT 1; DELETE FROM players"), None);
+        assert!(QuizPack::from_json(&pack.to_json()).is_err());
+
+        let mut pack = QuizPack::new("Future");
+        pack.version = QUIZ_PACK_VERSION + 1;
+        assert!(QuizPack::from_json(&pack.to_json()).is_err());
+    }
+
+    #[test]
+    fn test_answer_hash_is_stable() {
+        assert_eq!(answer_hash(&board(&[])), "cbf29ce484222325");
+        assert_ne!(answer_hash(&board(&["A"])), answer_hash(&board(&["B"])));
+    }
+}
@@ -0,0 +1,192 @@
+//! "Top 10 ... in YEAR" questions over a single random season
+use super::sql::player_seasons;
+use super::{
+    register, year_or_random, Category, Difficulty, Era, ParamKind, Question, QuestionMeta,
+    QuestionParams, QuestionRegistry,
+};
+use crate::sql_runner::Query;
+use rand::RngCore;
+
+/// Top 10 player-seasons in one random year, ordered by a stat or rate
+pub struct SingleSeasonQuestion {
+    meta: QuestionMeta,
+    /// Question text with a `{year}` placeholder
+    text: &'static str,
+    /// Selected expressions after `p.name`; the last one is the scored stat
+    select: &'static [&'static str],
+    /// Extra WHERE conditions after the season filter
+    filters: &'static [&'static str],
+    /// ORDER BY expression (always descending)
+    order_by: &'static str,
+}
+
+impl Question for SingleSeasonQuestion {
+    fn meta(&self) -> &QuestionMeta {
+        &self.meta
+    }
+
+    fn sample_params(&self, fixed: &QuestionParams, rng: &mut dyn RngCore) -> QuestionParams {
+        QuestionParams {
+            year: Some(year_or_random(fixed, rng)),
+            ..Default::default()
+        }
+    }
+
+    fn supported_params(&self) -> Vec<ParamKind> {
+        vec![ParamKind::Year]
+    }
+
+    fn build(&self, params: &QuestionParams) -> (String, Query) {
+        let year = params.year();
+        let q = self.text.replace("{year}", &year.to_string());
+        let sql = player_seasons()
+            .columns(self.select.iter().copied())
+            .filter("s.season = :year")
+            .filter_all(self.filters.iter().copied())
+            .order_by_desc(self.order_by)
+            .limit(10)
+            .to_sql();
+        (q, Query::new(sql).bind("year", year))
+    }
+
+    fn columns(&self) -> Vec<String> {
+        let mut cols = vec!["name".to_string()];
+        cols.extend(self.select.iter().map(|expr| {
+            // "1.0 * a / b AS ypc" -> "ypc", "s.season" -> "season"
+            let name = expr.rsplit(" AS ").next().unwrap_or(expr);
+            name.trim_start_matches("s.").to_string()
+        }));
+        cols
+    }
+}
+
+/// Registers every single-season question
+pub(super) fn register_all(m: &mut QuestionRegistry) {
+    let add = |m: &mut QuestionRegistry,
+               code: &str,
+               desc: &str,
+               text: &'static str,
+               select: &'static [&'static str],
+               filters: &'static [&'static str],
+               order_by: &'static str,
+               difficulty: Difficulty| {
+        let stat = select.last().copied().unwrap_or(order_by);
+        let position = filters
+            .iter()
+            .find_map(|f| f.strip_prefix("s.position = '")?.strip_suffix('\''));
+        register(
+            m,
+            SingleSeasonQuestion {
+                meta: QuestionMeta::new(code, desc)
+                    .category(Category::for_stat(stat))
+                    .position(position)
+                    .era(Era::Season)
+                    .difficulty(difficulty),
+                text,
+                select,
+                filters,
+                order_by,
+            },
+        )
+    };
+
+    add(
+        m,
+        "top10compperc_year",
+        "Top 10 QBs in completion percentage in one season",
+        "Top 10 QBs in completion percentage in {year} (min 100 attempts).",
+        &[
+            "s.team_abbr",
+            "s.season",
+            "s.completions",
+            "s.attempts",
+            "1.0 * s.completions / s.attempts AS comp_pct",
+        ],
+        &["s.position = 'QB'", "s.attempts >= 100"],
+        "comp_pct",
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "top10passyds_year",
+        "Top 10 QBs in passing yards in one season",
+        "Top 10 QBs in passing yards in {year}.",
+        &["s.team_abbr", "s.season", "s.passing_yards"],
+        &["s.position = 'QB'"],
+        "s.passing_yards",
+        Difficulty::Easy,
+    );
+    add(
+        m,
+        "top10ypc_year",
+        "Top 10 rushers in yards per carry in one season",
+        "Top 10 players in yards per carry in {year} (min 50 rush attempts).",
+        &[
+            "s.team_abbr",
+            "s.season",
+            "s.rushing_attempts",
+            "s.rushing_yards",
+            "1.0 * s.rushing_yards / s.rushing_attempts AS ypc",
+        ],
+        &["s.rushing_attempts >= 50"],
+        "ypc",
+        Difficulty::Hard,
+    );
+    add(
+        m,
+        "top10ypr_year",
+        "Top 10 receivers in yards per reception in one season",
+        "Top 10 players in yards per reception in {year} (min 50 targets).",
+        &[
+            "s.team_abbr",
+            "s.season",
+            "s.targets",
+            "s.receptions",
+            "s.receiving_yards",
+            "1.0 * s.receiving_yards / s.receptions AS ypr",
+        ],
+        &["s.targets >= 50", "s.receptions > 0"],
+        "ypr",
+        Difficulty::Hard,
+    );
+    add(
+        m,
+        "top10rushers_year",
+        "Top 10 rushers in rushing yards in one season",
+        "Top 10 rushers in rushing yards in {year}.",
+        &["s.team_abbr", "s.season", "s.rushing_yards"],
+        &[],
+        "s.rushing_yards",
+        Difficulty::Easy,
+    );
+    add(
+        m,
+        "top10receivers_year",
+        "Top 10 receivers in receiving yards in one season",
+        "Top 10 pass catchers in receiving yards in {year}.",
+        &["s.team_abbr", "s.season", "s.receiving_yards"],
+        &[],
+        "s.receiving_yards",
+        Difficulty::Easy,
+    );
+    add(
+        m,
+        "top10rushingqb_year",
+        "Top 10 rushing QBs in one season",
+        "Top 10 QBs in rushing yards in {year}.",
+        &["s.team_abbr", "s.season", "s.rushing_yards"],
+        &["s.position = 'QB'"],
+        "s.rushing_yards",
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "top10receivingte_year",
+        "Top 10 TEs in receiving yards in one season",
+        "Top 10 TEs in receiving yards in {year}.",
+        &["s.team_abbr", "s.season", "s.receiving_yards"],
+        &["s.position = 'TE'"],
+        "s.receiving_yards",
+        Difficulty::Medium,
+    );
+}
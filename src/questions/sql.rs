@@ -0,0 +1,225 @@
+//! Small SELECT builder shared by the question families
+//!
+//! Question kinds compose a [`SelectQuery`] from the common patterns below
+//! instead of hand-writing SQL strings, so a typo in a join or filter gets
+//! fixed once instead of in every kind that copied it.
+
+/// A SELECT statement assembled clause by clause
+#[derive(Debug, Clone, Default)]
+pub struct SelectQuery {
+    ctes: Vec<(String, SelectQuery)>,
+    columns: Vec<String>,
+    from: String,
+    joins: Vec<String>,
+    filters: Vec<String>,
+    group_by: Option<String>,
+    order_by: Vec<String>,
+    limit: Option<u32>,
+}
+
+impl SelectQuery {
+    /// Starts a query over a table, e.g. `"seasons s"`
+    pub fn from(table: &str) -> Self {
+        Self {
+            from: table.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a common table expression, rendered in the order added
+    pub fn with(mut self, name: &str, query: SelectQuery) -> Self {
+        self.ctes.push((name.to_string(), query));
+        self
+    }
+
+    /// Adds a selected expression
+    pub fn column(mut self, expr: impl Into<String>) -> Self {
+        self.columns.push(expr.into());
+        self
+    }
+
+    /// Adds several selected expressions
+    pub fn columns<I, S>(mut self, exprs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.columns.extend(exprs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds a full join clause, e.g. `"JOIN players p ON p.player_id = s.player_id"`
+    pub fn join(mut self, clause: impl Into<String>) -> Self {
+        self.joins.push(clause.into());
+        self
+    }
+
+    /// Adds a WHERE condition; conditions are ANDed together
+    pub fn filter(mut self, condition: impl Into<String>) -> Self {
+        self.filters.push(condition.into());
+        self
+    }
+
+    /// Adds several WHERE conditions
+    pub fn filter_all<I, S>(mut self, conditions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.filters.extend(conditions.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn group_by(mut self, expr: &str) -> Self {
+        self.group_by = Some(expr.to_string());
+        self
+    }
+
+    /// Adds a descending sort key
+    pub fn order_by_desc(mut self, expr: &str) -> Self {
+        self.order_by.push(format!("{expr} DESC"));
+        self
+    }
+
+    pub fn limit(mut self, n: u32) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    /// Renders the query as SQL text
+    pub fn to_sql(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+
+        if !self.ctes.is_empty() {
+            let ctes: Vec<String> = self
+                .ctes
+                .iter()
+                .map(|(name, query)| format!("{name} AS (\n{}\n)", query.to_sql()))
+                .collect();
+            lines.push(format!("WITH {}", ctes.join(",\n")));
+        }
+
+        lines.push(format!("SELECT {}", self.columns.join(", ")));
+        lines.push(format!("FROM {}", self.from));
+        lines.extend(self.joins.iter().cloned());
+        if !self.filters.is_empty() {
+            lines.push(format!("WHERE {}", self.filters.join(" AND ")));
+        }
+        if let Some(group_by) = &self.group_by {
+            lines.push(format!("GROUP BY {group_by}"));
+        }
+        if !self.order_by.is_empty() {
+            lines.push(format!("ORDER BY {}", self.order_by.join(", ")));
+        }
+        if let Some(limit) = self.limit {
+            lines.push(format!("LIMIT {limit}"));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Joins `players p` onto `seasons s` and selects the player name first
+pub fn player_seasons() -> SelectQuery {
+    SelectQuery::from("seasons s")
+        .column("p.name")
+        .join("JOIN players p ON p.player_id = s.player_id")
+}
+
+/// Career total of one stat per player, for use as a CTE
+pub fn career_totals(stat: &str, alias: &str) -> SelectQuery {
+    SelectQuery::from("seasons")
+        .columns(["player_id".to_string(), format!("SUM({stat}) AS {alias}")])
+        .group_by("player_id")
+}
+
+/// The player's most recent team between the `:start` and `:end` seasons,
+/// optionally only counting seasons at the `:position` position
+pub fn last_team_in_range(by_position: bool) -> String {
+    let mut sub = SelectQuery::from("seasons s2")
+        .column("s2.team_abbr")
+        .filter("s2.player_id = s.player_id")
+        .filter("s2.season BETWEEN :start AND :end");
+    if by_position {
+        sub = sub.filter("s2.position = :position");
+    }
+    let sub = sub.order_by_desc("s2.season").limit(1);
+    format!("({}) AS last_team", sub.to_sql())
+}
+
+/// Builds the "last 10 players to ..." board.
+///
+/// `eligible` selects every qualifying season (it must select `s.player_id`,
+/// `s.team_abbr`, `s.season`, and each of `stat_columns`). Each player's most
+/// recent qualifying season is kept, and the ten most recent are returned.
+/// Any CTEs declared on `eligible` are hoisted in front of it.
+pub fn last10(mut eligible: SelectQuery, stat_columns: &[&str]) -> SelectQuery {
+    let hoisted = std::mem::take(&mut eligible.ctes);
+
+    let latest = SelectQuery::from("eligible e").column("e.*").join(
+        "JOIN (SELECT player_id, MAX(season) AS max_season FROM eligible GROUP BY player_id) m \
+             ON m.player_id = e.player_id AND m.max_season = e.season",
+    );
+
+    let mut query = SelectQuery::from("latest");
+    query.ctes = hoisted;
+    query
+        .with("eligible", eligible)
+        .with("latest", latest)
+        .column("p.name")
+        .columns(["latest.team_abbr", "latest.season"])
+        .columns(stat_columns.iter().map(|c| format!("latest.{c}")))
+        .join("JOIN players p ON p.player_id = latest.player_id")
+        .order_by_desc("latest.season")
+        .limit(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_select() {
+        let sql = player_seasons()
+            .column("s.passing_yards")
+            .filter("s.season = 2010")
+            .filter("s.position = 'QB'")
+            .order_by_desc("s.passing_yards")
+            .limit(10)
+            .to_sql();
+        assert_eq!(
+            sql,
+            "SELECT p.name, s.passing_yards\n\
+             FROM seasons s\n\
+             JOIN players p ON p.player_id = s.player_id\n\
+             WHERE s.season = 2010 AND s.position = 'QB'\n\
+             ORDER BY s.passing_yards DESC\n\
+             LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_ctes_render_in_order() {
+        let sql = SelectQuery::from("b")
+            .with("a", SelectQuery::from("seasons").column("1"))
+            .with("b", SelectQuery::from("a").column("2"))
+            .column("*")
+            .to_sql();
+        assert!(sql.starts_with("WITH a AS (\nSELECT 1\nFROM seasons\n),\nb AS ("));
+    }
+
+    #[test]
+    fn test_last10_hoists_ctes() {
+        let eligible = SelectQuery::from("seasons s")
+            .with("career", career_totals("rushing_yards", "career_rush_yds"))
+            .columns(["s.player_id", "s.team_abbr", "s.season", "s.rushing_tds"])
+            .join("JOIN career ON career.player_id = s.player_id");
+        let sql = last10(eligible, &["rushing_tds"]).to_sql();
+
+        let career = sql.find("career AS").unwrap();
+        let eligible = sql.find("eligible AS").unwrap();
+        let latest = sql.find("latest AS").unwrap();
+        assert!(career < eligible && eligible < latest);
+        assert!(sql.ends_with("ORDER BY latest.season DESC\nLIMIT 10"));
+    }
+}
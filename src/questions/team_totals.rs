@@ -0,0 +1,158 @@
+//! Career totals for one team: "Top 10 players in X for TEAM ..."
+use super::sql::player_seasons;
+use super::{
+    register, team_or_random, year_range_or_random, Category, Difficulty, Era, ParamKind, Question,
+    QuestionMeta, QuestionParams, QuestionRegistry, Scope, START_YEAR,
+};
+use crate::sql_runner::Query;
+use rand::RngCore;
+
+/// Top 10 players by a summed stat for one team, over a random year range or
+/// every season since the start year
+pub struct TeamTotalQuestion {
+    meta: QuestionMeta,
+    /// Human-readable stat name, e.g. "receiving yards"
+    label: &'static str,
+    /// Column in `seasons` to sum
+    stat: &'static str,
+    /// Alias for the summed column
+    alias: &'static str,
+    /// Use every season since START_YEAR instead of a random range
+    since_start: bool,
+}
+
+impl Question for TeamTotalQuestion {
+    fn meta(&self) -> &QuestionMeta {
+        &self.meta
+    }
+
+    fn sample_params(&self, fixed: &QuestionParams, rng: &mut dyn RngCore) -> QuestionParams {
+        let team = team_or_random(fixed, rng);
+        let year_range = if self.since_start {
+            None
+        } else {
+            Some(year_range_or_random(fixed, rng))
+        };
+        QuestionParams {
+            team: Some(team),
+            year_range,
+            ..Default::default()
+        }
+    }
+
+    fn supported_params(&self) -> Vec<ParamKind> {
+        if self.since_start {
+            vec![ParamKind::Team]
+        } else {
+            vec![ParamKind::Team, ParamKind::YearRange]
+        }
+    }
+
+    fn build(&self, params: &QuestionParams) -> (String, Query) {
+        let team = params.team();
+        let (q, season_filter, range) = if self.since_start {
+            (
+                format!(
+                    "Top 10 players in {label} for {team} since {start} (inclusive).",
+                    label = self.label,
+                    start = START_YEAR
+                ),
+                format!("s.season >= {START_YEAR}"),
+                None,
+            )
+        } else {
+            let (s, e) = params.year_range();
+            (
+                format!(
+                    "Top 10 players in {label} for {team} between {s}–{e}.",
+                    label = self.label
+                ),
+                "s.season BETWEEN :start AND :end".to_string(),
+                Some((s, e)),
+            )
+        };
+        let sql = player_seasons()
+            .columns([
+                "s.team_abbr".to_string(),
+                format!("SUM(s.{}) AS {}", self.stat, self.alias),
+            ])
+            .filter("s.team_abbr = :team")
+            .filter(season_filter)
+            .group_by("s.player_id")
+            .order_by_desc(self.alias)
+            .limit(10)
+            .to_sql();
+        let mut query = Query::new(sql).bind("team", team);
+        if let Some((s, e)) = range {
+            query = query.bind("start", s).bind("end", e);
+        }
+        (q, query)
+    }
+
+    fn columns(&self) -> Vec<String> {
+        vec!["name".into(), "team_abbr".into(), self.alias.into()]
+    }
+}
+
+/// Registers every team-total question
+pub(super) fn register_all(m: &mut QuestionRegistry) {
+    let add = |m: &mut QuestionRegistry,
+               code: &str,
+               desc: &str,
+               label: &'static str,
+               stat: &'static str,
+               alias: &'static str,
+               since_start: bool,
+               difficulty: Difficulty| {
+        let era = if since_start {
+            Era::AllTime
+        } else {
+            Era::YearRange
+        };
+        register(
+            m,
+            TeamTotalQuestion {
+                meta: QuestionMeta::new(code, desc)
+                    .category(Category::for_stat(stat))
+                    .scope(Scope::Team)
+                    .era(era)
+                    .difficulty(difficulty),
+                label,
+                stat,
+                alias,
+                since_start,
+            },
+        )
+    };
+
+    add(
+        m,
+        "recyds_yearrange_TEAM",
+        "Top 10 receiving yards for a team in a year range",
+        "receiving yards",
+        "receiving_yards",
+        "rec_yards",
+        false,
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "rushyds_yearrange_TEAM",
+        "Top 10 rushing yards for a team in a year range",
+        "rushing yards",
+        "rushing_yards",
+        "rush_yards",
+        false,
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "passyds_TEAM",
+        "Top 10 passing yards for a team since the start year",
+        "passing yards",
+        "passing_yards",
+        "pass_yards",
+        true,
+        Difficulty::Easy,
+    );
+}
@@ -0,0 +1,205 @@
+//! League-wide "Top 10 ... between START–END" questions
+use super::sql::{last_team_in_range, player_seasons};
+use super::{
+    register, year_range_or_random, Category, Difficulty, Era, ParamKind, Question, QuestionMeta,
+    QuestionParams, QuestionRegistry,
+};
+use crate::sql_runner::Query;
+use rand::RngCore;
+
+/// Top 10 players league-wide by a summed stat over a random year range,
+/// optionally restricted to one position
+pub struct YearRangeQuestion {
+    meta: QuestionMeta,
+    /// Question text before " between START–END.", e.g. "Top 10 QBs in rushing yards".
+    /// A caller-chosen position replaces the word "players".
+    text: &'static str,
+    /// Column in `seasons` to sum
+    stat: &'static str,
+    /// Alias for the summed column
+    alias: &'static str,
+    /// Position the question is always limited to; others can be narrowed by
+    /// the caller
+    position: Option<&'static str>,
+}
+
+impl Question for YearRangeQuestion {
+    fn meta(&self) -> &QuestionMeta {
+        &self.meta
+    }
+
+    fn sample_params(&self, fixed: &QuestionParams, rng: &mut dyn RngCore) -> QuestionParams {
+        QuestionParams {
+            year_range: Some(year_range_or_random(fixed, rng)),
+            position: fixed.position.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn supported_params(&self) -> Vec<ParamKind> {
+        if self.position.is_some() {
+            vec![ParamKind::YearRange]
+        } else {
+            vec![ParamKind::YearRange, ParamKind::Position]
+        }
+    }
+
+    fn build(&self, params: &QuestionParams) -> (String, Query) {
+        let (s, e) = params.year_range();
+        let position = self.position.or(params.position.as_deref());
+        let text = match (self.position, &params.position) {
+            (None, Some(pos)) => self.text.replacen("players", &format!("{pos}s"), 1),
+            _ => self.text.to_string(),
+        };
+        let q = format!("{text} between {s}–{e}.");
+        let mut select = player_seasons()
+            .column(last_team_in_range(position.is_some()))
+            .column(format!("SUM(s.{}) AS {}", self.stat, self.alias))
+            .filter("s.season BETWEEN :start AND :end");
+        if position.is_some() {
+            select = select.filter("s.position = :position");
+        }
+        let sql = select
+            .group_by("s.player_id")
+            .order_by_desc(self.alias)
+            .limit(10)
+            .to_sql();
+        let mut query = Query::new(sql).bind("start", s).bind("end", e);
+        if let Some(pos) = position {
+            query = query.bind("position", pos);
+        }
+        (q, query)
+    }
+
+    fn columns(&self) -> Vec<String> {
+        vec!["name".into(), "last_team".into(), self.alias.into()]
+    }
+}
+
+/// Registers every league-wide year-range question
+pub(super) fn register_all(m: &mut QuestionRegistry) {
+    let add = |m: &mut QuestionRegistry,
+               code: &str,
+               desc: &str,
+               text: &'static str,
+               stat: &'static str,
+               alias: &'static str,
+               position: Option<&'static str>,
+               difficulty: Difficulty| {
+        register(
+            m,
+            YearRangeQuestion {
+                meta: QuestionMeta::new(code, desc)
+                    .category(Category::for_stat(stat))
+                    .position(position)
+                    .era(Era::YearRange)
+                    .difficulty(difficulty),
+                text,
+                stat,
+                alias,
+                position,
+            },
+        )
+    };
+
+    add(
+        m,
+        "top10fumlost_yearrange",
+        "Top 10 players with most fumbles lost in a year range",
+        "Top 10 players with most fumbles lost",
+        "fumbles_lost",
+        "fum_lost",
+        None,
+        Difficulty::Hard,
+    );
+    add(
+        m,
+        "top10rushtd_yearrange",
+        "Top 10 players with most rushing TDs in a year range",
+        "Top 10 players with most rushing TDs",
+        "rushing_tds",
+        "rush_tds",
+        None,
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "top10rectd_yearrange",
+        "Top 10 players with most receiving TDs in a year range",
+        "Top 10 players with most receiving TDs",
+        "receiving_tds",
+        "rec_tds",
+        None,
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "top10passtd_yearrange",
+        "Top 10 players with most passing TDs in a year range",
+        "Top 10 players with most passing TDs",
+        "passing_tds",
+        "pass_tds",
+        None,
+        Difficulty::Easy,
+    );
+    add(
+        m,
+        "top10intthrown_yearrange",
+        "Top 10 players with most interceptions thrown in a year range",
+        "Top 10 players with most interceptions thrown",
+        "interceptions",
+        "ints",
+        None,
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "top10rushingqb_yearrange",
+        "Top 10 QBs in rushing yards in a year range",
+        "Top 10 QBs in rushing yards",
+        "rushing_yards",
+        "rush_yards",
+        Some("QB"),
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "top10receivingte_yearrange",
+        "Top 10 TEs in receiving yards in a year range",
+        "Top 10 TEs in receiving yards",
+        "receiving_yards",
+        "rec_yards",
+        Some("TE"),
+        Difficulty::Medium,
+    );
+    add(
+        m,
+        "top10receivingrb_yearrange",
+        "Top 10 RBs in receiving yards in a year range",
+        "Top 10 RBs in receiving yards",
+        "receiving_yards",
+        "rec_yards",
+        Some("RB"),
+        Difficulty::Hard,
+    );
+    add(
+        m,
+        "top10rushingwr_yearrange",
+        "Top 10 WRs in rushing yards in a year range",
+        "Top 10 WRs in rushing yards",
+        "rushing_yards",
+        "rush_yards",
+        Some("WR"),
+        Difficulty::Hard,
+    );
+    add(
+        m,
+        "top10receptions_yearrange",
+        "Top 10 players in receptions in a year range",
+        "Top 10 players in total receptions",
+        "receptions",
+        "recs",
+        None,
+        Difficulty::Medium,
+    );
+}
@@ -0,0 +1,396 @@
+//! Quizzes on top of a [`GameSession`]
+//!
+//! A quiz picks its questions up front, optionally from one category or for
+//! one team, and plays them in order. Each board is scored as usual, and the
+//! quiz adds them up into one scorecard at the end. A team marathon is a quiz
+//! of every question that takes a team, all for one franchise, ending in a
+//! fan score.
+use crate::error::{KnowBallError, Result};
+use crate::game::{TriviaGame, TriviaResult};
+use crate::questions::{Category, ParamKind, Question, QuestionParams, QuestionSpec};
+use crate::session::GameSession;
+use rand::seq::SliceRandom;
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+/// Boards in a quiz when no count is given
+pub const DEFAULT_QUIZ_LENGTH: usize = 10;
+
+/// Most boards one quiz can have
+pub const MAX_QUIZ_LENGTH: usize = 100;
+
+/// One board of the quiz, once played
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuizBoard {
+    pub code: String,
+    pub score: u32,
+    pub correct: usize,
+    pub total: usize,
+    pub strikes: usize,
+    /// Points for naming every row
+    pub possible: u32,
+}
+
+/// A fixed run of questions and the boards played from them so far
+#[derive(Clone)]
+pub struct Quiz {
+    category: Option<Category>,
+    team: Option<String>,
+    questions: Vec<Arc<dyn Question>>,
+    /// Questions asked so far, including any whose board was empty
+    asked: usize,
+    boards: Vec<QuizBoard>,
+}
+
+impl Quiz {
+    /// Picks `count` questions from the session's registry, from `category`
+    /// and for `team` when given. Each question is used once before any
+    /// repeats, and a seeded session always picks the same ones. `count` has
+    /// to be from 1 to [`MAX_QUIZ_LENGTH`].
+    pub fn new(
+        session: &mut GameSession,
+        count: usize,
+        category: Option<Category>,
+        team: Option<String>,
+    ) -> Result<Self> {
+        if !(1..=MAX_QUIZ_LENGTH).contains(&count) {
+            return Err(KnowBallError::Parse(format!(
+                "a quiz has 1 to {MAX_QUIZ_LENGTH} questions, not {count}"
+            )));
+        }
+        let team = team.map(|team| team.to_ascii_uppercase());
+        let engine = session.engine_mut();
+        let pool: Vec<Arc<dyn Question>> = engine
+            .registry()
+            .values()
+            .filter(|question| fits(question.as_ref(), category, team.as_deref()))
+            .map(Arc::clone)
+            .collect();
+        if pool.is_empty() {
+            return Err(KnowBallError::Parse(format!(
+                "no questions {}",
+                describe(category, team.as_deref())
+            )));
+        }
+        let mut questions = Vec::with_capacity(count);
+        while questions.len() < count {
+            let mut round = pool.clone();
+            round.shuffle(engine.rng_mut());
+            round.truncate(count - questions.len());
+            questions.extend(round);
+        }
+        Ok(Self {
+            category,
+            team,
+            questions,
+            asked: 0,
+            boards: Vec::new(),
+        })
+    }
+
+    /// Every question that takes a team, in code order, all for `team`
+    pub fn marathon(session: &GameSession, team: &str) -> Result<Self> {
+        let team = team.to_ascii_uppercase();
+        let questions: Vec<Arc<dyn Question>> = session
+            .engine()
+            .registry()
+            .values()
+            .filter(|question| fits(question.as_ref(), None, Some(&team)))
+            .map(Arc::clone)
+            .collect();
+        if questions.is_empty() {
+            return Err(KnowBallError::Parse(format!("no questions for {team}")));
+        }
+        Ok(Self {
+            category: None,
+            team: Some(team),
+            questions,
+            asked: 0,
+            boards: Vec::new(),
+        })
+    }
+
+    /// How many questions the quiz asks
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.questions.is_empty()
+    }
+
+    pub fn boards(&self) -> &[QuizBoard] {
+        &self.boards
+    }
+
+    /// The next question's place in the quiz, from 1
+    pub fn number(&self) -> usize {
+        self.asked + 1
+    }
+
+    /// Over once every question has been asked
+    pub fn is_over(&self) -> bool {
+        self.asked >= self.questions.len()
+    }
+
+    /// Plays the next question, with the quiz's team if it has one. The board
+    /// is recorded in the session like any other; an empty board is asked but
+    /// not scored.
+    pub fn play_board<F>(&mut self, session: &mut GameSession, play: F) -> Result<TriviaResult>
+    where
+        F: FnOnce(&QuestionSpec, &mut TriviaGame),
+    {
+        let question = self
+            .questions
+            .get(self.asked)
+            .cloned()
+            .ok_or_else(|| KnowBallError::Parse("the quiz is over".into()))?;
+        self.asked += 1;
+        let params = QuestionParams {
+            team: self.team.clone(),
+            ..QuestionParams::default()
+        };
+        let result = session.play_question(question.as_ref(), &params, play)?;
+        if result.total > 0 {
+            self.record(&result);
+        }
+        Ok(result)
+    }
+
+    /// Counts a finished board
+    pub fn record(&mut self, result: &TriviaResult) {
+        self.boards.push(QuizBoard {
+            code: result
+                .spec
+                .as_ref()
+                .map(|spec| spec.code.clone())
+                .unwrap_or_default(),
+            score: result.score,
+            correct: result.correct(),
+            total: result.total,
+            strikes: result.strikes,
+            possible: result.rows.iter().map(|row| row.points).sum(),
+        });
+    }
+
+    /// Every point scored in the quiz
+    pub fn score(&self) -> u32 {
+        self.boards.iter().map(|board| board.score).sum()
+    }
+
+    /// Points scored as a share of every point on the boards played, from 0
+    /// to 100
+    pub fn fan_score(&self) -> u32 {
+        let possible: u32 = self.boards.iter().map(|board| board.possible).sum();
+        (self.score() * 100).checked_div(possible).unwrap_or(0)
+    }
+
+    /// The team's fan score and what it makes the player
+    pub fn fan_line(&self) -> String {
+        let fan = match self.fan_score() {
+            0..=19 => "Bandwagon fan",
+            20..=39 => "Casual fan",
+            40..=59 => "Season-ticket holder",
+            60..=79 => "Diehard",
+            _ => "Franchise historian",
+        };
+        format!(
+            "{} fan score: {}/100. {fan}!",
+            self.team.as_deref().unwrap_or("Team"),
+            self.fan_score()
+        )
+    }
+
+    /// What the quiz is, before the first board
+    pub fn intro_line(&self) -> String {
+        let described = match (self.category, &self.team) {
+            (None, None) => String::new(),
+            (category, team) => format!(" {}", describe(category, team.as_deref())),
+        };
+        format!("Quiz: {} questions{described}.", self.len())
+    }
+
+    /// Every board on one line each, then the totals
+    pub fn scorecard(&self) -> String {
+        let mut out = String::from("Quiz scorecard\n");
+        let width = self
+            .boards
+            .iter()
+            .map(|board| board.code.len())
+            .max()
+            .unwrap_or(0);
+        for (i, board) in self.boards.iter().enumerate() {
+            let named = format!("{}/{}", board.correct, board.total);
+            let _ = writeln!(
+                out,
+                "{:>3}. {:<width$}  {named:>5} named  {:>5} pts  {} {}",
+                i + 1,
+                board.code,
+                board.score,
+                board.strikes,
+                if board.strikes == 1 {
+                    "strike"
+                } else {
+                    "strikes"
+                }
+            );
+        }
+        let correct: usize = self.boards.iter().map(|board| board.correct).sum();
+        let total: usize = self.boards.iter().map(|board| board.total).sum();
+        let strikes: usize = self.boards.iter().map(|board| board.strikes).sum();
+        let percent = (correct * 100).checked_div(total).unwrap_or(0);
+        let _ = write!(
+            out,
+            "Total: {} points over {} boards, {correct} of {total} named ({percent}%), {strikes} {}.",
+            self.score(),
+            self.boards.len(),
+            if strikes == 1 { "strike" } else { "strikes" }
+        );
+        out
+    }
+}
+
+/// Whether `question` can be asked in a quiz of `category` for `team`
+fn fits(question: &dyn Question, category: Option<Category>, team: Option<&str>) -> bool {
+    let meta = question.meta();
+    category.is_none_or(|category| meta.categories.contains(&category))
+        && team.is_none_or(|team| {
+            question.supported_params().contains(&ParamKind::Team)
+                && question.league().teams().contains(&team)
+        })
+}
+
+/// e.g. "on rushing for PIT"
+fn describe(category: Option<Category>, team: Option<&str>) -> String {
+    let mut out = Vec::new();
+    if let Some(category) = category {
+        out.push(format!("on {}", category.as_str().replace('_', " ")));
+    }
+    if let Some(team) = team {
+        out.push(format!("for {team}"));
+    }
+    if out.is_empty() {
+        "registered".to_string()
+    } else {
+        out.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::TriviaEngine;
+    use crate::game::RowResult;
+    use crate::sql_runner::Query;
+
+    fn result(code: &str, score: u32, correct: usize, strikes: usize) -> TriviaResult {
+        TriviaResult {
+            spec: Some(QuestionSpec {
+                code: code.into(),
+                params: QuestionParams::default(),
+                text: String::new(),
+                query: Query::default(),
+            }),
+            score,
+            total: 10,
+            rows: (0..10)
+                .map(|i| RowResult {
+                    name: format!("Player {i}"),
+                    stat: "1".into(),
+                    points: 100,
+                    guessed: i < correct,
+                    passed: false,
+                    cells: Vec::new(),
+                    found_at: None,
+                })
+                .collect(),
+            strikes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_picks_a_fixed_run() {
+        let mut session = GameSession::new(TriviaEngine::with_seed(7));
+        let quiz = Quiz::new(
+            &mut session,
+            12,
+            Some(Category::Rushing),
+            Some("pit".into()),
+        )
+        .unwrap();
+        assert_eq!(quiz.len(), 12);
+        assert_eq!(quiz.number(), 1);
+        for question in &quiz.questions {
+            assert!(fits(
+                question.as_ref(),
+                Some(Category::Rushing),
+                Some("PIT")
+            ));
+        }
+        assert_eq!(quiz.intro_line(), "Quiz: 12 questions on rushing for PIT.");
+
+        let mut again = GameSession::new(TriviaEngine::with_seed(7));
+        let codes = |quiz: &Quiz| -> Vec<String> {
+            quiz.questions
+                .iter()
+                .map(|q| q.meta().code.clone())
+                .collect()
+        };
+        let same = Quiz::new(&mut again, 12, Some(Category::Rushing), Some("PIT".into())).unwrap();
+        assert_eq!(codes(&quiz), codes(&same));
+    }
+
+    #[test]
+    fn test_no_questions_is_an_error() {
+        let mut session = GameSession::new(TriviaEngine::with_seed(7));
+        let err = Quiz::new(&mut session, 5, None, Some("XYZ".into()))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("no questions for XYZ"));
+
+        for count in [0, MAX_QUIZ_LENGTH + 1, usize::MAX] {
+            assert!(Quiz::new(&mut session, count, None, None).is_err());
+        }
+        assert!(Quiz::new(&mut session, MAX_QUIZ_LENGTH, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_marathon_plays_every_team_question() {
+        let session = GameSession::new(TriviaEngine::with_seed(7));
+        let mut quiz = Quiz::marathon(&session, "pit").unwrap();
+        let codes: Vec<&str> = quiz
+            .questions
+            .iter()
+            .map(|q| q.meta().code.as_str())
+            .collect();
+        assert!(codes.contains(&"last10rushers_TEAM"));
+        assert!(codes.is_sorted());
+        assert_eq!(
+            quiz.intro_line(),
+            format!("Quiz: {} questions for PIT.", codes.len())
+        );
+
+        quiz.record(&result("last10rushers_TEAM", 450, 5, 1));
+        quiz.record(&result("recyds_yearrange_TEAM", 250, 3, 3));
+        assert_eq!(quiz.fan_score(), 35);
+        assert_eq!(quiz.fan_line(), "PIT fan score: 35/100. Casual fan!");
+        assert!(Quiz::marathon(&session, "XYZ").is_err());
+    }
+
+    #[test]
+    fn test_scorecard_adds_up_the_boards() {
+        let mut session = GameSession::new(TriviaEngine::with_seed(7));
+        let mut quiz = Quiz::new(&mut session, 2, None, None).unwrap();
+        quiz.record(&result("last10rushers_PIT", 450, 7, 1));
+        quiz.record(&result("passyds_year", 120, 2, 3));
+        assert_eq!(quiz.score(), 570);
+        assert_eq!(
+            quiz.scorecard(),
+            "Quiz scorecard\n\
+             \x20 1. last10rushers_PIT   7/10 named    450 pts  1 strike\n\
+             \x20 2. passyds_year        2/10 named    120 pts  3 strikes\n\
+             Total: 570 points over 2 boards, 9 of 20 named (45%), 4 strikes."
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Markdown scoreboards to paste into Discord or a forum
+//!
+//! A `Scoreboard` is the standings plus every finished round, taken from one
+//! player's session history or a multiplayer room. `to_markdown` renders it as
+//! a standings table, a per-question breakdown, and the most valuable answers
+//! that were never found.
+use crate::export::markdown_table;
+use crate::game::RowResult;
+use crate::session::{GameSession, RoundRecord, MAX_BOARD_SCORE};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fmt::Write;
+
+/// Missed answers listed under "Hardest misses"
+pub const HARDEST_MISSES: usize = 5;
+
+/// A player's total in the standings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Standing {
+    pub name: String,
+    pub score: u32,
+}
+
+/// Everything a report shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scoreboard {
+    pub title: String,
+    pub standings: Vec<Standing>,
+    /// Finished rounds, oldest first
+    pub rounds: Vec<RoundRecord>,
+}
+
+impl Scoreboard {
+    pub fn new(
+        title: impl Into<String>,
+        standings: Vec<Standing>,
+        rounds: Vec<RoundRecord>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            standings,
+            rounds,
+        }
+    }
+
+    /// One player's session so far
+    pub fn for_session(player: &str, session: &GameSession) -> Self {
+        let standing = Standing {
+            name: player.to_string(),
+            score: session.score(),
+        };
+        Self::new(
+            "Know Ball session",
+            vec![standing],
+            session.history().to_vec(),
+        )
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.title);
+        let total: u32 = self.rounds.iter().map(|r| r.score).sum();
+        let _ = writeln!(
+            out,
+            "{} board(s), {total} of {} points\n",
+            self.rounds.len(),
+            self.rounds.len() as u32 * MAX_BOARD_SCORE
+        );
+
+        out.push_str("## Standings\n\n");
+        let header = ["Rank", "Player", "Points"].map(String::from);
+        markdown_table(&mut out, &header, &self.standing_rows());
+
+        out.push_str("## Questions\n\n");
+        if self.rounds.is_empty() {
+            out.push_str("No boards finished yet.\n");
+            return out;
+        }
+        let header = ["#", "Question", "Found", "Points"].map(String::from);
+        let rows: Vec<Vec<String>> = self
+            .rounds
+            .iter()
+            .enumerate()
+            .map(|(i, round)| {
+                vec![
+                    (i + 1).to_string(),
+                    round.text.clone(),
+                    format!("{}/{}", round.correct(), round.total),
+                    round.score.to_string(),
+                ]
+            })
+            .collect();
+        markdown_table(&mut out, &header, &rows);
+
+        let misses = self.hardest_misses();
+        if !misses.is_empty() {
+            out.push_str("## Hardest misses\n\n");
+            let header = ["Answer", "Stat", "Points", "Question"].map(String::from);
+            markdown_table(&mut out, &header, &misses);
+        }
+        out
+    }
+
+    /// Standings by score, with tied players sharing a rank
+    fn standing_rows(&self) -> Vec<Vec<String>> {
+        let mut standings: Vec<&Standing> = self.standings.iter().collect();
+        standings.sort_by_key(|s| Reverse(s.score));
+        let mut rank = 0;
+        standings
+            .iter()
+            .enumerate()
+            .map(|(i, standing)| {
+                if i == 0 || standings[i - 1].score != standing.score {
+                    rank = i + 1;
+                }
+                vec![
+                    rank.to_string(),
+                    standing.name.clone(),
+                    standing.score.to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    /// The highest-value rows nobody found, with the question they were on
+    fn hardest_misses(&self) -> Vec<Vec<String>> {
+        let mut misses: Vec<(usize, &RowResult)> = self
+            .rounds
+            .iter()
+            .enumerate()
+            .flat_map(|(i, round)| round.rows.iter().map(move |row| (i, row)))
+            .filter(|(_, row)| !row.guessed)
+            .collect();
+        misses.sort_by_key(|(_, row)| Reverse(row.points));
+        misses
+            .into_iter()
+            .take(HARDEST_MISSES)
+            .map(|(i, row)| {
+                vec![
+                    row.name.clone(),
+                    row.stat.clone(),
+                    row.points.to_string(),
+                    (i + 1).to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(name: &str, points: u32, guessed: bool) -> RowResult {
+        RowResult {
+            passed: false,
+            name: name.into(),
+            stat: "100".into(),
+            points,
+            guessed,
+            cells: Vec::new(),
+            found_at: None,
+        }
+    }
+
+    fn round(text: &str, rows: Vec<RowResult>) -> RoundRecord {
+        RoundRecord {
+            code: "passyds_TEAM".into(),
+            text: text.into(),
+            score: rows.iter().filter(|r| r.guessed).map(|r| r.points).sum(),
+            total: rows.len(),
+            column_names: vec![],
+            rows,
+            best_streak: 0,
+            bonus: 0,
+            skipped: false,
+        }
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let standings = vec![
+            Standing {
+                name: "Bo".into(),
+                score: 300,
+            },
+            Standing {
+                name: "Ann".into(),
+                score: 700,
+            },
+            Standing {
+                name: "Cy".into(),
+                score: 300,
+            },
+        ];
+        let rounds = vec![
+            round(
+                "Top passers for PIT",
+                vec![
+                    row("Tom Brady", 200, true),
+                    row("Mason Rudolph", 800, false),
+                ],
+            ),
+            round("Top rushers for GB", vec![row("A|B", 900, false)]),
+        ];
+        let md = Scoreboard::new("Room bar", standings, rounds).to_markdown();
+
+        assert!(md.starts_with("# Room bar\n\n2 board(s), 200 of 2000 points\n"));
+        assert!(md.contains("| 1 | Ann | 700 |\n| 2 | Bo | 300 |\n| 2 | Cy | 300 |"));
+        assert!(md.contains("| 1 | Top passers for PIT | 1/2 | 200 |"));
+        let (_, misses) = md.split_once("## Hardest misses").unwrap();
+        assert!(misses.contains("| A\\|B | 100 | 900 | 2 |\n| Mason Rudolph | 100 | 800 | 1 |"));
+        assert!(!misses.contains("Brady"));
+    }
+
+    #[test]
+    fn test_empty_session() {
+        let session = GameSession::new(crate::TriviaEngine::with_seed(1));
+        let md = Scoreboard::for_session("You", &session).to_markdown();
+        assert!(md.contains("| 1 | You | 0 |"));
+        assert!(md.ends_with("No boards finished yet.\n"));
+    }
+}
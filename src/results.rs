@@ -0,0 +1,436 @@
+//! Every finished board, kept for good
+//!
+//! The REPL writes each board with rows to `know_ball_results.sqlite` in the
+//! working directory: its code, the parameters it was played with, the score,
+//! and when. High scores and each code's past attempts are read back from
+//! there, for one profile or for everyone who has played on this machine.
+use crate::error::Result;
+use crate::game::{GameEnd, TriviaResult};
+use crate::questions::QuestionParams;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::fmt::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where results are kept when no other file is given
+pub const DEFAULT_RESULTS_DB: &str = "know_ball_results.sqlite";
+
+/// Environment variable naming the results file instead; `:memory:` keeps
+/// them for one session only
+pub const RESULTS_ENV: &str = "KNOWBALL_RESULTS";
+
+/// The results file: `KNOWBALL_RESULTS` if set, else [`DEFAULT_RESULTS_DB`]
+pub fn default_path() -> PathBuf {
+    std::env::var_os(RESULTS_ENV)
+        .filter(|path| !path.is_empty())
+        .map_or_else(|| PathBuf::from(DEFAULT_RESULTS_DB), PathBuf::from)
+}
+
+/// One finished board as it was saved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardRecord {
+    pub code: String,
+    pub params: QuestionParams,
+    pub score: u32,
+    pub correct: u32,
+    pub total: u32,
+    /// Whether the player skipped it, banking what they had
+    pub skipped: bool,
+    /// The day it was played, as YYYY-MM-DD in UTC
+    pub date: String,
+}
+
+/// Columns read by [`BoardRecord::from_row`], in order
+const RECORD_COLUMNS: &str = "code, team, year, start_year, end_year, threshold, position, \
+     score, correct, total, skipped, date(played_at, 'unixepoch')";
+
+impl BoardRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let start: Option<i32> = row.get(3)?;
+        let end: Option<i32> = row.get(4)?;
+        Ok(Self {
+            code: row.get(0)?,
+            params: QuestionParams {
+                team: row.get(1)?,
+                year: row.get(2)?,
+                year_range: start.zip(end),
+                threshold: row.get(5)?,
+                position: row.get(6)?,
+            },
+            score: row.get(7)?,
+            correct: row.get(8)?,
+            total: row.get(9)?,
+            skipped: row.get(10)?,
+            date: row.get(11)?,
+        })
+    }
+}
+
+/// How one question code has gone before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeStats {
+    pub plays: u32,
+    pub best: u32,
+    /// Rounded down
+    pub average: u32,
+}
+
+/// e.g. "You've played this 4 times, best 720, average 510"
+impl fmt::Display for CodeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.plays {
+            1 => write!(f, "You've played this once, scoring {}", self.best),
+            plays => write!(
+                f,
+                "You've played this {plays} times, best {}, average {}",
+                self.best, self.average
+            ),
+        }
+    }
+}
+
+/// The results file, open for reading and writing
+#[derive(Debug)]
+pub struct ResultsDb {
+    conn: Connection,
+}
+
+impl ResultsDb {
+    /// Opens the results file at `path`, creating it if it doesn't exist
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Results kept in memory, gone when dropped
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS results (
+                id INTEGER PRIMARY KEY,
+                profile TEXT,
+                code TEXT NOT NULL,
+                team TEXT,
+                year INTEGER,
+                start_year INTEGER,
+                end_year INTEGER,
+                threshold INTEGER,
+                position TEXT,
+                score INTEGER NOT NULL,
+                correct INTEGER NOT NULL,
+                total INTEGER NOT NULL,
+                played_at INTEGER NOT NULL,
+                skipped INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE INDEX IF NOT EXISTS results_code ON results (code)",
+        )?;
+        // Files from before skipping was added don't have the column yet
+        let has_skipped: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('results') WHERE name = 'skipped'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_skipped {
+            conn.execute_batch(
+                "ALTER TABLE results ADD COLUMN skipped INTEGER NOT NULL DEFAULT 0",
+            )?;
+        }
+        Ok(Self { conn })
+    }
+
+    /// Saves a finished board, under `profile` when one is given. Boards
+    /// without rows or without a question code aren't saved.
+    pub fn record(
+        &self,
+        profile: Option<&str>,
+        result: &TriviaResult,
+        played_at: SystemTime,
+    ) -> Result<()> {
+        let Some(spec) = result.spec.as_ref().filter(|_| result.total > 0) else {
+            return Ok(());
+        };
+        let params = &spec.params;
+        let secs = played_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs() as i64);
+        self.conn.execute(
+            "INSERT INTO results (profile, code, team, year, start_year, end_year, threshold,
+                position, score, correct, total, played_at, skipped)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                profile,
+                spec.code,
+                params.team,
+                params.year,
+                params.year_range.map(|(start, _)| start),
+                params.year_range.map(|(_, end)| end),
+                params.threshold,
+                params.position,
+                result.score,
+                result.correct() as u32,
+                result.total as u32,
+                secs,
+                result.end == Some(GameEnd::Skipped),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The best board for each question code, highest score first. Ties go
+    /// to the earlier board.
+    pub fn best_per_code(&self, profile: Option<&str>) -> Result<Vec<BoardRecord>> {
+        let sql = format!(
+            "SELECT {RECORD_COLUMNS} FROM (
+                SELECT *, ROW_NUMBER() OVER (PARTITION BY code ORDER BY score DESC, id) AS place
+                FROM results WHERE ?1 IS NULL OR profile = ?1
+            )
+            WHERE place = 1
+            ORDER BY score DESC, code"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let records = stmt
+            .query_map([profile], BoardRecord::from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(records)
+    }
+
+    /// How often `code` has been played through and how well, or None if
+    /// never. Skipped boards don't count.
+    pub fn code_stats(&self, profile: Option<&str>, code: &str) -> Result<Option<CodeStats>> {
+        let stats = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*), MAX(score), AVG(score) FROM results
+                 WHERE code = ?1 AND (?2 IS NULL OR profile = ?2) AND NOT skipped
+                 HAVING COUNT(*) > 0",
+                params![code, profile],
+                |row| {
+                    Ok(CodeStats {
+                        plays: row.get(0)?,
+                        best: row.get(1)?,
+                        average: row.get::<_, f64>(2)? as u32,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(stats)
+    }
+
+    /// Every board of `code`, newest first, only those for `team` when given
+    pub fn history(
+        &self,
+        profile: Option<&str>,
+        code: &str,
+        team: Option<&str>,
+    ) -> Result<Vec<BoardRecord>> {
+        let sql = format!(
+            "SELECT {RECORD_COLUMNS} FROM results
+             WHERE code = ?1 AND (?2 IS NULL OR profile = ?2) AND (?3 IS NULL OR team = ?3)
+             ORDER BY played_at DESC, id DESC"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let records = stmt
+            .query_map(params![code, profile, team], BoardRecord::from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(records)
+    }
+
+    /// The past boards of `code` as a table, newest first, with skipped ones
+    /// marked
+    pub fn history_text(
+        &self,
+        profile: Option<&str>,
+        code: &str,
+        team: Option<&str>,
+    ) -> Result<String> {
+        let history = self.history(profile, code, team)?;
+        let what = match team {
+            Some(team) => format!("{code} for {team}"),
+            None => code.to_string(),
+        };
+        if history.is_empty() {
+            return Ok(format!("No boards of {what} played yet."));
+        }
+        let mut out = format!(
+            "{what}: {} {}\n",
+            history.len(),
+            if history.len() == 1 {
+                "board"
+            } else {
+                "boards"
+            }
+        );
+        let params: Vec<String> = history.iter().map(|r| r.params.to_string()).collect();
+        let params_width = params.iter().map(String::len).max().unwrap_or(0);
+        for (record, params) in history.iter().zip(&params) {
+            let named = format!("{}/{}", record.correct, record.total);
+            let _ = write!(
+                out,
+                "  {}  {params:<params_width$}  {:>5} pts  {named:>5} named",
+                record.date, record.score
+            );
+            out.push_str(if record.skipped { "  skipped\n" } else { "\n" });
+        }
+        out.pop();
+        Ok(out)
+    }
+
+    /// The personal bests as a table, with the best board of all last
+    pub fn highscores_text(&self, profile: Option<&str>) -> Result<String> {
+        let best = self.best_per_code(profile)?;
+        let mut out = match profile {
+            Some(name) => format!("High scores for {name}\n"),
+            None => String::from("High scores\n"),
+        };
+        let Some(top) = best.first() else {
+            out.push_str("No boards played yet.");
+            return Ok(out);
+        };
+        let code_width = best.iter().map(|r| r.code.len()).max().unwrap_or(0);
+        let params: Vec<String> = best.iter().map(|r| r.params.to_string()).collect();
+        let params_width = params.iter().map(String::len).max().unwrap_or(0);
+        for (record, params) in best.iter().zip(&params) {
+            let named = format!("{}/{}", record.correct, record.total);
+            let _ = writeln!(
+                out,
+                "  {:<code_width$}  {params:<params_width$}  {:>5} pts  {named:>5} named  {}",
+                record.code, record.score, record.date
+            );
+        }
+        let _ = write!(
+            out,
+            "Best overall: {} points on {}, {}",
+            top.score,
+            describe(top),
+            top.date
+        );
+        Ok(out)
+    }
+}
+
+/// e.g. "last10passers_TEAM (PIT)"
+fn describe(record: &BoardRecord) -> String {
+    let params = record.params.to_string();
+    if params.is_empty() {
+        record.code.clone()
+    } else {
+        format!("{} ({params})", record.code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::QuestionSpec;
+    use crate::sql_runner::Query;
+    use std::time::Duration;
+
+    fn result(code: &str, team: &str, score: u32) -> TriviaResult {
+        TriviaResult {
+            spec: Some(QuestionSpec {
+                code: code.into(),
+                params: QuestionParams::for_team(Some(team)),
+                text: String::new(),
+                query: Query::default(),
+            }),
+            score,
+            total: 10,
+            ..Default::default()
+        }
+    }
+
+    fn day(days: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(days * 86_400)
+    }
+
+    #[test]
+    fn test_best_per_code_keeps_the_top_board() {
+        let db = ResultsDb::in_memory().unwrap();
+        db.record(None, &result("passyds_TEAM", "PIT", 300), day(0))
+            .unwrap();
+        db.record(None, &result("passyds_TEAM", "GB", 700), day(1))
+            .unwrap();
+        db.record(Some("ann"), &result("rushyds_TEAM", "DAL", 500), day(2))
+            .unwrap();
+        db.record(None, &TriviaResult::default(), day(3)).unwrap();
+
+        let best = db.best_per_code(None).unwrap();
+        assert_eq!(best.len(), 2);
+        assert_eq!(best[0].code, "passyds_TEAM");
+        assert_eq!(best[0].params.team.as_deref(), Some("GB"));
+        assert_eq!(best[0].date, "1970-01-02");
+        assert_eq!(best[1].score, 500);
+
+        let ann = db.best_per_code(Some("ann")).unwrap();
+        assert_eq!(ann.len(), 1);
+        assert_eq!(ann[0].code, "rushyds_TEAM");
+    }
+
+    #[test]
+    fn test_code_stats_and_history() {
+        let db = ResultsDb::in_memory().unwrap();
+        assert_eq!(db.code_stats(None, "passyds_TEAM").unwrap(), None);
+        db.record(None, &result("passyds_TEAM", "PIT", 300), day(0))
+            .unwrap();
+        let once = db.code_stats(None, "passyds_TEAM").unwrap().unwrap();
+        assert_eq!(once.to_string(), "You've played this once, scoring 300");
+        db.record(None, &result("passyds_TEAM", "GB", 720), day(1))
+            .unwrap();
+        db.record(None, &result("passyds_TEAM", "PIT", 511), day(2))
+            .unwrap();
+        let skipped = TriviaResult {
+            end: Some(GameEnd::Skipped),
+            ..result("passyds_TEAM", "PIT", 100)
+        };
+        db.record(None, &skipped, day(3)).unwrap();
+        let stats = db.code_stats(None, "passyds_TEAM").unwrap().unwrap();
+        assert_eq!(
+            stats,
+            CodeStats {
+                plays: 3,
+                best: 720,
+                average: 510
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "You've played this 3 times, best 720, average 510"
+        );
+        assert_eq!(db.code_stats(Some("ann"), "passyds_TEAM").unwrap(), None);
+
+        assert_eq!(
+            db.history_text(None, "passyds_TEAM", Some("PIT")).unwrap(),
+            "passyds_TEAM for PIT: 3 boards\n\
+             \x20 1970-01-04  PIT    100 pts   0/10 named  skipped\n\
+             \x20 1970-01-03  PIT    511 pts   0/10 named\n\
+             \x20 1970-01-01  PIT    300 pts   0/10 named"
+        );
+        assert_eq!(db.history(None, "passyds_TEAM", None).unwrap().len(), 4);
+        assert_eq!(
+            db.history_text(None, "rushyds_TEAM", None).unwrap(),
+            "No boards of rushyds_TEAM played yet."
+        );
+    }
+
+    #[test]
+    fn test_highscores_text() {
+        let db = ResultsDb::in_memory().unwrap();
+        assert_eq!(
+            db.highscores_text(Some("ann")).unwrap(),
+            "High scores for ann\nNo boards played yet."
+        );
+        db.record(None, &result("passyds_TEAM", "GB", 700), day(1))
+            .unwrap();
+        db.record(None, &result("last10rushers_TEAM", "PIT", 450), day(2))
+            .unwrap();
+        assert_eq!(
+            db.highscores_text(None).unwrap(),
+            "High scores\n\
+             \x20 passyds_TEAM        GB     700 pts   0/10 named  1970-01-02\n\
+             \x20 last10rushers_TEAM  PIT    450 pts   0/10 named  1970-01-03\n\
+             Best overall: 700 points on passyds_TEAM (GB), 1970-01-02"
+        );
+    }
+}
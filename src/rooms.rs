@@ -0,0 +1,725 @@
+//! Multiplayer rooms where connected players race on one board (`rooms` feature)
+//!
+//! `Rooms` turns each client's message into the messages every affected client
+//! should receive, so a transport like the WebSocket server only has to
+//! deliver them. Messages are applied one at a time in arrival order, which
+//! settles simultaneous guesses: the first guess to name a player reveals the
+//! row and scores the points, and anyone naming the same player afterwards is
+//! told they were too late without taking a strike. Strikes are shared by the
+//! room, as the board only has three.
+//!
+//! Players can also chat and send canned [`Emote`]s, which go to everyone in
+//! the room, board or no board.
+use crate::engine::TriviaEngine;
+use crate::game::{GameEnd, GuessOutcome, TriviaGame};
+use crate::questions::QuestionParams;
+use crate::report::{Scoreboard, Standing};
+use crate::session::RoundRecord;
+use crate::sql_runner::Board;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Longest chat message a room passes on, in characters
+pub const MAX_CHAT_LEN: usize = 200;
+
+/// A canned reaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Emote {
+    Gg,
+    Nice,
+    Wow,
+    Ouch,
+    Lol,
+    Hurry,
+}
+
+impl Emote {
+    pub const ALL: [Emote; 6] = [
+        Emote::Gg,
+        Emote::Nice,
+        Emote::Wow,
+        Emote::Ouch,
+        Emote::Lol,
+        Emote::Hurry,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Emote::Gg => "gg",
+            Emote::Nice => "nice",
+            Emote::Wow => "wow",
+            Emote::Ouch => "ouch",
+            Emote::Lol => "lol",
+            Emote::Hurry => "hurry",
+        }
+    }
+
+    /// How the reaction reads in a line of text
+    pub fn text(self) -> &'static str {
+        match self {
+            Emote::Gg => "👏 good game",
+            Emote::Nice => "👍 nice one",
+            Emote::Wow => "😮 wow",
+            Emote::Ouch => "😬 ouch",
+            Emote::Lol => "😂 lol",
+            Emote::Hurry => "⏱ hurry up",
+        }
+    }
+}
+
+impl FromStr for Emote {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        Emote::ALL
+            .into_iter()
+            .find(|e| e.as_str() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Emote::ALL.iter().map(|e| e.as_str()).collect();
+                format!("unknown emote '{s}', expected {}", names.join(", "))
+            })
+    }
+}
+
+/// What a client can send, tagged with a `type` field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Enters a room under a display name, leaving any other room
+    Join {
+        room: String,
+        name: String,
+    },
+    /// Starts a board by code, or a random one
+    Start {
+        #[serde(default)]
+        code: Option<String>,
+    },
+    Guess {
+        text: String,
+    },
+    /// Ends the board and shows every answer
+    Reveal,
+    /// Asks for a Markdown scoreboard of the room's boards so far
+    Report,
+    /// Says something to the room
+    Chat {
+        text: String,
+    },
+    /// Sends a canned reaction by name, e.g. `gg`; see [`Emote`]. Kept as
+    /// text so an unknown name gets an error back rather than a message
+    /// that doesn't parse.
+    Emote {
+        emote: String,
+    },
+    Leave,
+}
+
+/// What the server sends, tagged with a `type` field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Sent to a client that joined, with everyone already there
+    Joined {
+        room: String,
+        players: Vec<PlayerScore>,
+    },
+    /// Someone else entered the room
+    PlayerJoined {
+        name: String,
+    },
+    PlayerLeft {
+        name: String,
+    },
+    /// A new board, with the names blanked
+    Board {
+        question: String,
+        board: Board,
+    },
+    /// A player revealed a row and scored its points
+    Correct {
+        player: String,
+        row: usize,
+        name: String,
+        points: u32,
+    },
+    /// A player missed; `strikes` is the room's total
+    Strike {
+        player: String,
+        strikes: usize,
+    },
+    /// Sent only to the guesser when someone else got there first
+    TooLate {
+        guess: String,
+    },
+    /// Sent only to the guesser when the guess fits `matches` players
+    Ambiguous {
+        guess: String,
+        matches: usize,
+    },
+    /// Running scores after every change
+    Scores {
+        players: Vec<PlayerScore>,
+    },
+    /// The board is over; every answer is shown
+    Finished {
+        end: GameEnd,
+        board: Board,
+    },
+    /// Sent only to the client that asked for a report
+    Report {
+        markdown: String,
+    },
+    /// A player said something to the room
+    Chat {
+        player: String,
+        text: String,
+    },
+    /// A player reacted
+    Emote {
+        player: String,
+        emote: Emote,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// A player's name and points across every board played in the room
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerScore {
+    pub name: String,
+    pub score: u32,
+}
+
+/// A message addressed to one client
+pub type Delivery = (u64, ServerMessage);
+
+struct Player {
+    client: u64,
+    name: String,
+    score: u32,
+}
+
+#[derive(Default)]
+struct Room {
+    game: Option<TriviaGame>,
+    /// In join order
+    players: Vec<Player>,
+    /// Finished boards, oldest first
+    history: Vec<RoundRecord>,
+}
+
+impl Room {
+    fn player_mut(&mut self, client: u64) -> Option<&mut Player> {
+        self.players.iter_mut().find(|p| p.client == client)
+    }
+
+    fn scores(&self) -> Vec<PlayerScore> {
+        self.players
+            .iter()
+            .map(|p| PlayerScore {
+                name: p.name.clone(),
+                score: p.score,
+            })
+            .collect()
+    }
+
+    /// The same message for every player in the room
+    fn broadcast(&self, message: ServerMessage) -> Vec<Delivery> {
+        self.players
+            .iter()
+            .map(|p| (p.client, message.clone()))
+            .collect()
+    }
+}
+
+/// Every room and which room each client is in
+pub struct Rooms {
+    engine: TriviaEngine,
+    rooms: HashMap<String, Room>,
+    members: HashMap<u64, String>,
+}
+
+impl Rooms {
+    pub fn new(engine: TriviaEngine) -> Self {
+        Self {
+            engine,
+            rooms: HashMap::new(),
+            members: HashMap::new(),
+        }
+    }
+
+    /// Gives the engine back, e.g. to keep playing alone after a match
+    pub fn into_engine(self) -> TriviaEngine {
+        self.engine
+    }
+
+    /// Names of the rooms with anyone in them
+    pub fn room_names(&self) -> Vec<&str> {
+        self.rooms.keys().map(String::as_str).collect()
+    }
+
+    /// Applies one client message and returns what each client should receive
+    pub fn handle(&mut self, client: u64, message: ClientMessage) -> Vec<Delivery> {
+        match message {
+            ClientMessage::Join { room, name } => self.join(client, room, name),
+            ClientMessage::Leave => self.leave(client),
+            ClientMessage::Start { code } => self.start(client, code.as_deref()),
+            ClientMessage::Guess { text } => self.guess(client, &text),
+            ClientMessage::Reveal => self.reveal(client),
+            ClientMessage::Report => self.report(client),
+            ClientMessage::Chat { text } => self.chat(client, &text),
+            ClientMessage::Emote { emote } => match emote.parse() {
+                Ok(emote) => self.emote(client, emote),
+                Err(e) => error(client, &e),
+            },
+        }
+    }
+
+    /// Removes a client from its room, e.g. when its connection closes.
+    /// Empty rooms are dropped.
+    pub fn leave(&mut self, client: u64) -> Vec<Delivery> {
+        let Some(name) = self.members.remove(&client) else {
+            return Vec::new();
+        };
+        let Some(room) = self.rooms.get_mut(&name) else {
+            return Vec::new();
+        };
+        let Some(index) = room.players.iter().position(|p| p.client == client) else {
+            return Vec::new();
+        };
+        let player = room.players.remove(index);
+        if room.players.is_empty() {
+            self.rooms.remove(&name);
+            return Vec::new();
+        }
+        room.broadcast(ServerMessage::PlayerLeft { name: player.name })
+    }
+
+    fn join(&mut self, client: u64, room_name: String, name: String) -> Vec<Delivery> {
+        let name = name.trim().to_string();
+        if name.is_empty() || room_name.trim().is_empty() {
+            return error(client, "room and name can't be blank");
+        }
+        let taken = self.rooms.get(&room_name).is_some_and(|room| {
+            room.players
+                .iter()
+                .any(|p| p.client != client && p.name.eq_ignore_ascii_case(&name))
+        });
+        if taken {
+            return error(client, &format!("'{name}' is already in {room_name}"));
+        }
+        if self.members.get(&client) == Some(&room_name) {
+            return error(client, &format!("you're already in {room_name}"));
+        }
+
+        let mut out = self.leave(client);
+        let room = self.rooms.entry(room_name.clone()).or_default();
+        out.extend(room.broadcast(ServerMessage::PlayerJoined { name: name.clone() }));
+        room.players.push(Player {
+            client,
+            name,
+            score: 0,
+        });
+        self.members.insert(client, room_name.clone());
+
+        out.push((
+            client,
+            ServerMessage::Joined {
+                room: room_name,
+                players: room.scores(),
+            },
+        ));
+        if let Some(game) = &room.game {
+            out.push((client, board_message(game)));
+        }
+        out
+    }
+
+    fn start(&mut self, client: u64, code: Option<&str>) -> Vec<Delivery> {
+        let Some(room) = self.room_of(client) else {
+            return error(client, "join a room first");
+        };
+        if room.game.is_some() {
+            return error(client, "a board is already in play; reveal it first");
+        }
+
+        let (question, fixed) = match code {
+            Some(code) => match self.engine.lookup(code) {
+                Some(parsed) => (
+                    parsed.question,
+                    QuestionParams::for_team(parsed.team.as_deref()),
+                ),
+                None => return error(client, &format!("unknown question code '{code}'")),
+            },
+            None => match self.engine.random_question() {
+                Some(question) => (question, QuestionParams::default()),
+                None => return error(client, "no questions are registered"),
+            },
+        };
+        let game = match self.engine.load_question(question.as_ref(), &fixed) {
+            Ok((spec, board)) if !board.rows.is_empty() => {
+                TriviaGame::new(spec.text.clone(), board).with_spec(spec)
+            }
+            Ok(_) => return error(client, "that question came back empty; try another"),
+            Err(e) => return error(client, &format!("couldn't load a board: {e}")),
+        };
+
+        let room = self.room_of(client).expect("client is in a room");
+        let out = room.broadcast(board_message(&game));
+        room.game = Some(game);
+        out
+    }
+
+    fn guess(&mut self, client: u64, text: &str) -> Vec<Delivery> {
+        let Some(room) = self.room_of(client) else {
+            return error(client, "join a room first");
+        };
+        let Some(game) = room.game.as_mut() else {
+            return error(client, "no board in play");
+        };
+
+        let outcome = game.submit_guess(text);
+        let player = room.player_mut(client).expect("member is in the room");
+        let message = match outcome {
+            GuessOutcome::Correct { row, name, points } => {
+                player.score += points;
+                ServerMessage::Correct {
+                    player: player.name.clone(),
+                    row,
+                    name,
+                    points,
+                }
+            }
+            GuessOutcome::Strike { strikes } => ServerMessage::Strike {
+                player: player.name.clone(),
+                strikes,
+            },
+            GuessOutcome::AlreadyGuessed => {
+                return vec![(
+                    client,
+                    ServerMessage::TooLate {
+                        guess: text.trim().to_string(),
+                    },
+                )]
+            }
+            GuessOutcome::Ambiguous { rows } => {
+                return vec![(
+                    client,
+                    ServerMessage::Ambiguous {
+                        guess: text.trim().to_string(),
+                        matches: rows.len(),
+                    },
+                )]
+            }
+            GuessOutcome::Empty | GuessOutcome::GameOver | GuessOutcome::Paused => {
+                return Vec::new()
+            }
+        };
+
+        let mut out = room.broadcast(message);
+        out.extend(room.broadcast(ServerMessage::Scores {
+            players: room.scores(),
+        }));
+        out.extend(finish_if_over(room));
+        out
+    }
+
+    fn reveal(&mut self, client: u64) -> Vec<Delivery> {
+        let Some(room) = self.room_of(client) else {
+            return error(client, "join a room first");
+        };
+        let Some(game) = room.game.as_mut() else {
+            return error(client, "no board in play");
+        };
+        game.reveal();
+        finish_if_over(room)
+    }
+
+    fn report(&mut self, client: u64) -> Vec<Delivery> {
+        let Some(name) = self.members.get(&client) else {
+            return error(client, "join a room first");
+        };
+        let room = &self.rooms[name];
+        let standings = room
+            .players
+            .iter()
+            .map(|p| Standing {
+                name: p.name.clone(),
+                score: p.score,
+            })
+            .collect();
+        let scoreboard = Scoreboard::new(
+            format!("Know Ball room {name}"),
+            standings,
+            room.history.clone(),
+        );
+        vec![(
+            client,
+            ServerMessage::Report {
+                markdown: scoreboard.to_markdown(),
+            },
+        )]
+    }
+
+    fn chat(&mut self, client: u64, text: &str) -> Vec<Delivery> {
+        let Some(room) = self.room_of(client) else {
+            return error(client, "join a room first");
+        };
+        // Control characters could move another player's cursor or recolor
+        // their terminal
+        let text: String = text.trim().chars().filter(|c| !c.is_control()).collect();
+        if text.is_empty() {
+            return Vec::new();
+        }
+        if text.chars().count() > MAX_CHAT_LEN {
+            return error(
+                client,
+                &format!("chat messages are at most {MAX_CHAT_LEN} characters"),
+            );
+        }
+        let player = room.player_mut(client).expect("member is in the room");
+        let message = ServerMessage::Chat {
+            player: player.name.clone(),
+            text,
+        };
+        room.broadcast(message)
+    }
+
+    fn emote(&mut self, client: u64, emote: Emote) -> Vec<Delivery> {
+        let Some(room) = self.room_of(client) else {
+            return error(client, "join a room first");
+        };
+        let player = room.player_mut(client).expect("member is in the room");
+        let message = ServerMessage::Emote {
+            player: player.name.clone(),
+            emote,
+        };
+        room.broadcast(message)
+    }
+
+    fn room_of(&mut self, client: u64) -> Option<&mut Room> {
+        let name = self.members.get(&client)?;
+        self.rooms.get_mut(name)
+    }
+}
+
+fn board_message(game: &TriviaGame) -> ServerMessage {
+    ServerMessage::Board {
+        question: game.question().to_string(),
+        board: game.visible_board(),
+    }
+}
+
+/// Clears a finished board and tells the room how it ended
+fn finish_if_over(room: &mut Room) -> Vec<Delivery> {
+    let Some(end) = room.game.as_ref().and_then(TriviaGame::end) else {
+        return Vec::new();
+    };
+    let game = room.game.take().expect("room has a board");
+    if let Some(spec) = game.spec() {
+        room.history
+            .push(RoundRecord::from_result(spec, &game.result()));
+    }
+    room.broadcast(ServerMessage::Finished {
+        end,
+        board: game.board().clone(),
+    })
+}
+
+fn error(client: u64, message: &str) -> Vec<Delivery> {
+    vec![(
+        client,
+        ServerMessage::Error {
+            message: message.to_string(),
+        },
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MockStore;
+
+    fn rooms() -> Rooms {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Mason Rudolph".into(), "1000".into()],
+            ],
+        };
+        Rooms::new(TriviaEngine::with_seed(1).with_store(MockStore::new(board)))
+    }
+
+    fn join(rooms: &mut Rooms, client: u64, name: &str) -> Vec<Delivery> {
+        rooms.handle(
+            client,
+            ClientMessage::Join {
+                room: "bar".into(),
+                name: name.into(),
+            },
+        )
+    }
+
+    fn guess(rooms: &mut Rooms, client: u64, text: &str) -> Vec<Delivery> {
+        rooms.handle(client, ClientMessage::Guess { text: text.into() })
+    }
+
+    #[test]
+    fn test_first_correct_guess_gets_credit() {
+        let mut rooms = rooms();
+        join(&mut rooms, 1, "Ann");
+        let out = join(&mut rooms, 2, "Bo");
+        assert_eq!(
+            out[0],
+            (1, ServerMessage::PlayerJoined { name: "Bo".into() })
+        );
+
+        let out = rooms.handle(1, ClientMessage::Start { code: None });
+        assert_eq!(out.len(), 2);
+        assert!(
+            matches!(&out[1].1, ServerMessage::Board { board, .. } if board.rows[0][0].is_empty())
+        );
+
+        let out = guess(&mut rooms, 2, "brady");
+        assert!(matches!(&out[0].1, ServerMessage::Correct { player, .. } if player == "Bo"));
+        assert_eq!(out.len(), 4);
+
+        // Ann's guess for the same player lands second
+        let out = guess(&mut rooms, 1, "Tom Brady");
+        assert_eq!(
+            out,
+            [(
+                1,
+                ServerMessage::TooLate {
+                    guess: "Tom Brady".into()
+                }
+            )]
+        );
+
+        let out = guess(&mut rooms, 1, "rudolph");
+        let ServerMessage::Scores { players } = &out[2].1 else {
+            panic!("expected scores, got {:?}", out[2].1);
+        };
+        assert!(players.iter().all(|p| p.score > 0));
+        assert!(matches!(
+            out.last().unwrap().1,
+            ServerMessage::Finished {
+                end: GameEnd::Perfect,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_rooms_reject_bad_requests() {
+        let mut rooms = rooms();
+        let is_error = |out: Vec<Delivery>| matches!(&out[..], [(_, ServerMessage::Error { .. })]);
+        assert!(is_error(guess(&mut rooms, 1, "Brady")));
+        join(&mut rooms, 1, "Ann");
+        assert!(is_error(join(&mut rooms, 2, "ann")));
+        assert!(is_error(guess(&mut rooms, 1, "Brady")));
+        assert!(is_error(rooms.handle(
+            1,
+            ClientMessage::Start {
+                code: Some("nope".into())
+            }
+        )));
+    }
+
+    #[test]
+    fn test_report_covers_finished_boards() {
+        let mut rooms = rooms();
+        join(&mut rooms, 1, "Ann");
+        join(&mut rooms, 2, "Bo");
+        rooms.handle(1, ClientMessage::Start { code: None });
+        guess(&mut rooms, 2, "Rudolph");
+        rooms.handle(1, ClientMessage::Reveal);
+
+        let out = rooms.handle(1, ClientMessage::Report);
+        let [(1, ServerMessage::Report { markdown })] = &out[..] else {
+            panic!("expected a report for the asker, got {out:?}");
+        };
+        assert!(markdown.starts_with("# Know Ball room bar"));
+        assert!(markdown.contains("| 1 | Bo |"));
+        assert!(markdown.contains("| 1/2 |"));
+        assert!(markdown.contains("| Tom Brady | 5000 |"));
+    }
+
+    #[test]
+    fn test_chat_and_emotes_reach_the_room() {
+        let mut rooms = rooms();
+        let chat = |text: &str| ClientMessage::Chat { text: text.into() };
+        assert!(matches!(
+            &rooms.handle(1, chat("hi"))[..],
+            [(1, ServerMessage::Error { .. })]
+        ));
+        join(&mut rooms, 1, "Ann");
+        join(&mut rooms, 2, "Bo");
+
+        let out = rooms.handle(2, chat(" too \x1b[2Jslow "));
+        let expected = ServerMessage::Chat {
+            player: "Bo".into(),
+            text: "too [2Jslow".into(),
+        };
+        assert_eq!(out, [(1, expected.clone()), (2, expected)]);
+        assert!(rooms.handle(2, chat("  ")).is_empty());
+        assert!(matches!(
+            &rooms.handle(2, chat(&"x".repeat(MAX_CHAT_LEN + 1)))[..],
+            [(2, ServerMessage::Error { .. })]
+        ));
+
+        let out = rooms.handle(1, ClientMessage::Emote { emote: "GG".into() });
+        assert_eq!(
+            out[1],
+            (
+                2,
+                ServerMessage::Emote {
+                    player: "Ann".into(),
+                    emote: Emote::Gg
+                }
+            )
+        );
+        let out = rooms.handle(
+            1,
+            ClientMessage::Emote {
+                emote: "boo".into(),
+            },
+        );
+        assert!(
+            matches!(&out[..], [(1, ServerMessage::Error { message })] if message.contains("gg, nice"))
+        );
+    }
+
+    #[test]
+    fn test_empty_rooms_are_dropped() {
+        let mut rooms = rooms();
+        join(&mut rooms, 1, "Ann");
+        join(&mut rooms, 2, "Bo");
+        assert_eq!(
+            rooms.leave(1),
+            [(2, ServerMessage::PlayerLeft { name: "Ann".into() })]
+        );
+        assert!(rooms.leave(2).is_empty());
+        assert!(rooms.room_names().is_empty());
+    }
+
+    #[test]
+    fn test_client_messages_parse_from_json() {
+        let start: ClientMessage = serde_json::from_str(r#"{"type":"start"}"#).unwrap();
+        assert_eq!(start, ClientMessage::Start { code: None });
+        let guess: ClientMessage =
+            serde_json::from_str(r#"{"type":"guess","text":"Brady"}"#).unwrap();
+        assert_eq!(
+            guess,
+            ClientMessage::Guess {
+                text: "Brady".into()
+            }
+        );
+    }
+}
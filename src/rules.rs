@@ -0,0 +1,234 @@
+//! The rules a board is played under
+//!
+//! Classic play gives three strikes, lets the player reveal the board or pass
+//! on a row, and has no clock. [`RulesPreset`] names the common variants, and
+//! [`RuleSettings`] layers individual changes on top of one, from the config
+//! file's `[rules]` table or the command line.
+use crate::game::MAX_STRIKES;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// What a round allows and when it ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameRules {
+    /// Misses that end the round, or None for no limit
+    pub strikes: Option<usize>,
+    /// Whether the player may give up the board or pass on a row
+    pub allow_reveal: bool,
+    /// Whether hints are counted; with them off, a frontend shouldn't offer any
+    pub allow_hints: bool,
+    /// How long the round lasts, or None for no clock
+    pub time_limit: Option<Duration>,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules::preset(RulesPreset::Classic)
+    }
+}
+
+impl GameRules {
+    /// The rules `preset` names
+    pub fn preset(preset: RulesPreset) -> Self {
+        let classic = GameRules {
+            strikes: Some(MAX_STRIKES),
+            allow_reveal: true,
+            allow_hints: true,
+            time_limit: None,
+        };
+        match preset {
+            RulesPreset::Classic => classic,
+            RulesPreset::SuddenDeath => GameRules {
+                strikes: Some(1),
+                ..classic
+            },
+            RulesPreset::Casual => GameRules {
+                strikes: None,
+                ..classic
+            },
+        }
+    }
+
+    /// "3 strikes", "1 strike", or "unlimited strikes"
+    pub fn strikes_text(&self) -> String {
+        match self.strikes {
+            Some(1) => "1 strike".to_string(),
+            Some(n) => format!("{n} strikes"),
+            None => "unlimited strikes".to_string(),
+        }
+    }
+}
+
+/// A named set of rules
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RulesPreset {
+    /// Three strikes, reveals allowed, no clock
+    #[default]
+    Classic,
+    /// One miss ends the round
+    SuddenDeath,
+    /// Misses never end the round
+    Casual,
+}
+
+impl RulesPreset {
+    pub const ALL: [RulesPreset; 3] = [
+        RulesPreset::Classic,
+        RulesPreset::SuddenDeath,
+        RulesPreset::Casual,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RulesPreset::Classic => "classic",
+            RulesPreset::SuddenDeath => "sudden-death",
+            RulesPreset::Casual => "casual",
+        }
+    }
+}
+
+impl fmt::Display for RulesPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RulesPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "classic" => Ok(RulesPreset::Classic),
+            "sudden-death" => Ok(RulesPreset::SuddenDeath),
+            "casual" => Ok(RulesPreset::Casual),
+            _ => Err(format!(
+                "unknown rules '{s}', expected classic, sudden-death, or casual"
+            )),
+        }
+    }
+}
+
+/// Changes to a preset's rules, each optional, as written in the config file:
+///
+/// ```toml
+/// [rules]
+/// preset = "casual"
+/// reveal = false
+/// time-limit = 60
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RuleSettings {
+    pub preset: Option<RulesPreset>,
+    /// Misses that end the round, 0 for no limit
+    pub strikes: Option<usize>,
+    pub reveal: Option<bool>,
+    pub hints: Option<bool>,
+    /// Seconds per board, at least one
+    #[serde(default, deserialize_with = "positive_secs")]
+    pub time_limit: Option<u64>,
+}
+
+/// A `time-limit` of 0 would end every board the moment it starts
+fn positive_secs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    match u64::deserialize(deserializer)? {
+        0 => Err(D::Error::custom(
+            "invalid time-limit 0, expected a whole number of seconds",
+        )),
+        secs => Ok(Some(secs)),
+    }
+}
+
+impl RuleSettings {
+    /// `base`'s settings with these on top. A preset given here starts over
+    /// from that preset, dropping whatever `base` changed.
+    pub fn over(self, base: RuleSettings) -> RuleSettings {
+        let base = if self.preset.is_some() {
+            RuleSettings::default()
+        } else {
+            base
+        };
+        RuleSettings {
+            preset: self.preset.or(base.preset),
+            strikes: self.strikes.or(base.strikes),
+            reveal: self.reveal.or(base.reveal),
+            hints: self.hints.or(base.hints),
+            time_limit: self.time_limit.or(base.time_limit),
+        }
+    }
+
+    /// The rules these settings describe
+    pub fn rules(&self) -> GameRules {
+        let preset = GameRules::preset(self.preset.unwrap_or_default());
+        GameRules {
+            strikes: match self.strikes {
+                Some(0) => None,
+                Some(n) => Some(n),
+                None => preset.strikes,
+            },
+            allow_reveal: self.reveal.unwrap_or(preset.allow_reveal),
+            allow_hints: self.hints.unwrap_or(preset.allow_hints),
+            time_limit: self
+                .time_limit
+                .map(Duration::from_secs)
+                .or(preset.time_limit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_names_round_trip() {
+        for preset in RulesPreset::ALL {
+            assert_eq!(preset.as_str().parse::<RulesPreset>(), Ok(preset));
+        }
+        assert!("hardcore".parse::<RulesPreset>().is_err());
+    }
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(GameRules::default().strikes, Some(MAX_STRIKES));
+        assert_eq!(
+            GameRules::preset(RulesPreset::SuddenDeath).strikes_text(),
+            "1 strike"
+        );
+        assert_eq!(GameRules::preset(RulesPreset::Casual).strikes, None);
+    }
+
+    #[test]
+    fn test_settings_layer_over_the_file() {
+        let file = RuleSettings {
+            preset: Some(RulesPreset::Casual),
+            reveal: Some(false),
+            ..RuleSettings::default()
+        };
+        let flags = RuleSettings {
+            time_limit: Some(60),
+            ..RuleSettings::default()
+        };
+        let rules = flags.over(file).rules();
+        assert_eq!(rules.strikes, None);
+        assert!(!rules.allow_reveal);
+        assert_eq!(rules.time_limit, Some(Duration::from_secs(60)));
+        let unlimited = RuleSettings {
+            strikes: Some(0),
+            ..RuleSettings::default()
+        };
+        assert_eq!(unlimited.rules().strikes, None);
+
+        // A preset on top starts over
+        let flags = RuleSettings {
+            preset: Some(RulesPreset::SuddenDeath),
+            ..RuleSettings::default()
+        };
+        let rules = flags.over(file).rules();
+        assert_eq!(rules, GameRules::preset(RulesPreset::SuddenDeath));
+    }
+}
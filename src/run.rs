@@ -0,0 +1,48 @@
+//! What survival and blitz runs share
+//!
+//! Both deal random boards one after another on top of a [`GameSession`],
+//! each played under the session's rules with one of them changed for the
+//! run, and both end early when the player gives up a board.
+use crate::error::{KnowBallError, Result};
+use crate::game::{GameEnd, TriviaGame, TriviaResult};
+use crate::questions::{QuestionParams, QuestionSpec};
+use crate::rules::GameRules;
+use crate::session::GameSession;
+
+/// Loads a random board and plays it under the session's rules as changed
+/// by `run_rules`. The session's rules are put back afterwards.
+pub(crate) fn play_random_board<R, F>(
+    session: &mut GameSession,
+    run_rules: R,
+    play: F,
+) -> Result<TriviaResult>
+where
+    R: FnOnce(GameRules) -> GameRules,
+    F: FnOnce(&QuestionSpec, &mut TriviaGame),
+{
+    let question = session
+        .engine_mut()
+        .random_question()
+        .ok_or_else(|| KnowBallError::Parse("no questions registered".into()))?;
+    let rules = *session.rules();
+    session.set_rules(run_rules(rules));
+    let played = session.play_question(question.as_ref(), &QuestionParams::default(), play);
+    session.set_rules(rules);
+    played
+}
+
+/// Whether the player gave up `result` with rows still hidden. Passing on
+/// the last hidden row ends a board the same way but doesn't count.
+pub(crate) fn gave_up(result: &TriviaResult) -> bool {
+    let shown = result.rows.iter().all(|row| row.guessed || row.passed);
+    result.end == Some(GameEnd::GaveUp) && !shown
+}
+
+/// The question code `result` was played from, empty for a custom board
+pub(crate) fn code(result: &TriviaResult) -> String {
+    result
+        .spec
+        .as_ref()
+        .map(|spec| spec.code.clone())
+        .unwrap_or_default()
+}
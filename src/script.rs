@@ -0,0 +1,356 @@
+//! Plays a session from a script of typed lines, for tests and tools
+//!
+//! [`run_script`] feeds a [`GameSession`] the lines a player would type at the
+//! REPL: commands pick boards, and the lines after a board starts are guesses
+//! until it ends. `reveal` gives up, and so does running out of lines. The
+//! [`Transcript`] records the session's [`GameEvent`]s and each guess's
+//! outcome, so a test can check what happened instead of matching the text
+//! the CLI happened to print.
+//!
+//! Only commands that play or score boards are understood; the rest (packs,
+//! exports, party and LAN play, ...) are recorded as errors.
+use crate::command::{parse_command, Command};
+use crate::deal::{deal, Frontend};
+use crate::events::GameEvent;
+use crate::game::{GuessOutcome, TriviaGame, TriviaResult};
+use crate::questions::QuestionSpec;
+use crate::session::{GameSession, SessionSummary};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+
+/// One step of a scripted session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+// One event per entry, so the size of `BoardFinished` doesn't matter
+#[allow(clippy::large_enum_variant)]
+pub enum Entry {
+    /// Something the session's observers heard
+    Event { event: GameEvent },
+    /// A line typed during a board and what it did
+    Guess {
+        guess: String,
+        outcome: GuessOutcome,
+    },
+    /// A heads-up the REPL would print, such as a renamed code
+    Notice { message: String },
+    /// A line the script couldn't act on
+    Error { line: String, message: String },
+    /// Every question code, from `list`
+    Codes { codes: Vec<String> },
+    /// Session totals, from `score`
+    Summary { summary: SessionSummary },
+}
+
+/// Everything that happened while a script ran
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Transcript {
+    pub entries: Vec<Entry>,
+    /// Totals when the script ended
+    pub summary: SessionSummary,
+}
+
+impl Transcript {
+    /// Every game event, in order
+    pub fn events(&self) -> impl Iterator<Item = &GameEvent> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Event { event } => Some(event),
+            _ => None,
+        })
+    }
+
+    /// Code of each board started, in order
+    pub fn codes(&self) -> Vec<&str> {
+        self.events()
+            .filter_map(|event| match event {
+                GameEvent::QuestionStarted { code, .. } => Some(code.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Text of each board started, in order
+    pub fn questions(&self) -> Vec<&str> {
+        self.events()
+            .filter_map(|event| match event {
+                GameEvent::QuestionStarted { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Result of each finished board, in order
+    pub fn results(&self) -> Vec<&TriviaResult> {
+        self.events()
+            .filter_map(|event| match event {
+                GameEvent::BoardFinished { result, .. } => Some(result),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every guess and its outcome, in order
+    pub fn guesses(&self) -> Vec<(&str, &GuessOutcome)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Guess { guess, outcome } => Some((guess.as_str(), outcome)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn notices(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Notice { message } => Some(message.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Message for each line that couldn't be acted on
+    pub fn errors(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Error { message, .. } => Some(message.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+type Log = Arc<Mutex<Vec<Entry>>>;
+
+/// Plays `lines` in `session` until they run out or one is `quit`
+pub fn run_script<I, S>(mut session: GameSession, lines: I) -> Transcript
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let log: Log = Arc::default();
+    let events = Arc::clone(&log);
+    session.subscribe(move |event: &GameEvent| {
+        events.lock().unwrap().push(Entry::Event {
+            event: event.clone(),
+        });
+    });
+
+    let mut lines = lines.into_iter().map(|line| line.as_ref().to_string());
+    while let Some(line) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match parse_command(&line) {
+            Ok(command) => command,
+            Err(e) => {
+                push_error(&log, &line, e.to_string());
+                continue;
+            }
+        };
+        match command {
+            Command::Quit => break,
+            Command::Score => push(
+                &log,
+                Entry::Summary {
+                    summary: session.summary(),
+                },
+            ),
+            Command::List => {
+                let codes = session.engine().registry().keys().cloned().collect();
+                push(&log, Entry::Codes { codes });
+            }
+            Command::LockTeam(team) => session.set_locked_team(team),
+            Command::Play(_)
+            | Command::Custom { .. }
+            | Command::Start { .. }
+            | Command::Daily
+            | Command::Replay(_) => {
+                let mut frontend = Scripted {
+                    log: &log,
+                    line: &line,
+                    lines: &mut lines,
+                };
+                deal(&mut session, &command, &mut frontend);
+            }
+            _ => push_error(&log, &line, "isn't supported in scripts".into()),
+        }
+    }
+
+    let entries = std::mem::take(&mut *log.lock().unwrap());
+    Transcript {
+        entries,
+        summary: session.summary(),
+    }
+}
+
+/// A script line that deals a board, guessed from the lines after it
+struct Scripted<'a, L> {
+    log: &'a Log,
+    line: &'a str,
+    lines: &'a mut L,
+}
+
+impl<L: Iterator<Item = String>> Frontend for Scripted<'_, L> {
+    fn notice(&mut self, message: String) {
+        push(self.log, Entry::Notice { message });
+    }
+
+    fn error(&mut self, message: String) {
+        push_error(self.log, self.line, message);
+    }
+
+    fn play(&mut self, _: &QuestionSpec, game: &mut TriviaGame) {
+        guess_until_over(game, self.log, self.lines);
+    }
+}
+
+fn guess_until_over(game: &mut TriviaGame, log: &Log, lines: &mut impl Iterator<Item = String>) {
+    while !game.is_over() {
+        let Some(guess) = lines.next() else {
+            game.reveal();
+            return;
+        };
+        if guess.trim().eq_ignore_ascii_case("reveal") {
+            game.reveal();
+            return;
+        }
+        if guess.trim().eq_ignore_ascii_case("skip") {
+            game.skip();
+            return;
+        }
+        let outcome = game.submit_guess(&guess);
+        push(log, Entry::Guess { guess, outcome });
+    }
+}
+
+fn push(log: &Log, entry: Entry) {
+    log.lock().unwrap().push(entry);
+}
+
+fn push_error(log: &Log, line: &str, message: String) {
+    push(
+        log,
+        Entry::Error {
+            line: line.to_string(),
+            message,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameEnd;
+    use crate::sql_runner::Board;
+    use crate::store::MockStore;
+    use crate::TriviaEngine;
+
+    fn session() -> GameSession {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Drew Brees".into(), "4000".into()],
+            ],
+        };
+        let engine = TriviaEngine::with_seed(490).with_store(MockStore::new(board));
+        GameSession::new(engine)
+    }
+
+    #[test]
+    fn test_guesses_play_the_board() {
+        let transcript = run_script(
+            session(),
+            [
+                "last10passers_PIT",
+                "brady",
+                "brady",
+                "Manning",
+                "Brees",
+                "score",
+            ],
+        );
+        assert!(transcript.errors().is_empty(), "{:?}", transcript.errors());
+        assert_eq!(transcript.codes().len(), 1);
+        let outcomes: Vec<&GuessOutcome> =
+            transcript.guesses().into_iter().map(|(_, o)| o).collect();
+        assert!(matches!(outcomes[0], GuessOutcome::Correct { row: 0, .. }));
+        assert_eq!(*outcomes[1], GuessOutcome::AlreadyGuessed);
+        assert_eq!(*outcomes[2], GuessOutcome::Strike { strikes: 1 });
+        assert!(matches!(outcomes[3], GuessOutcome::Correct { row: 1, .. }));
+        assert!(transcript.events().any(|e| matches!(
+            e,
+            GameEvent::BoardFinished {
+                end: GameEnd::Perfect,
+                ..
+            }
+        )));
+        assert!(matches!(
+            transcript.entries.last(),
+            Some(Entry::Summary { summary }) if summary.questions_played == 1
+        ));
+        assert_eq!(transcript.summary.questions_played, 1);
+    }
+
+    #[test]
+    fn test_reveal_and_running_out_give_up() {
+        let transcript = run_script(session(), ["start", "reveal", "start", "brady"]);
+        let results = transcript.results();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].correct(), 0);
+        assert_eq!(results[1].correct(), 1);
+        assert!(transcript.events().all(|e| !matches!(
+            e,
+            GameEvent::BoardFinished {
+                end: GameEnd::Perfect,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn test_quit_stops_the_script() {
+        let transcript = run_script(session(), ["quit", "start"]);
+        assert!(transcript.entries.is_empty());
+        assert_eq!(transcript.summary.questions_played, 0);
+    }
+
+    #[test]
+    fn test_unknown_and_unsupported_lines_are_errors() {
+        let transcript = run_script(session(), ["last10passers_XYZ", "party Ann Ben"]);
+        assert_eq!(
+            transcript.errors(),
+            [
+                "unknown command or code 'last10passers_xyz'; did you mean 'last10passers_TEAM'?",
+                "isn't supported in scripts",
+            ]
+        );
+        assert!(transcript.codes().is_empty());
+    }
+
+    #[test]
+    fn test_failed_board_is_an_error_and_play_goes_on() {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![vec!["Tom Brady".into(), "5000".into()]],
+        };
+        let store = MockStore::new(board).then_fail("disk I/O error");
+        let engine = TriviaEngine::with_seed(495).with_store(store);
+        let transcript = run_script(GameSession::new(engine), ["start", "start", "brady"]);
+        assert_eq!(transcript.errors(), ["store error: disk I/O error"]);
+        assert_eq!(transcript.results()[0].score, 1000);
+        assert_eq!(transcript.summary.questions_played, 1);
+    }
+
+    #[test]
+    fn test_renamed_code_leaves_a_notice() {
+        let transcript = run_script(session(), ["recyds_PIT_yearrange", "reveal"]);
+        assert_eq!(
+            transcript.notices(),
+            ["'recyds_TEAM_yearrange' has been renamed to 'recyds_yearrange_TEAM'; the old code will keep working for now."]
+        );
+        assert!(transcript.questions()[0].contains("PIT"));
+    }
+}
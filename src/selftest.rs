@@ -0,0 +1,311 @@
+//! Runs every registered question across a sweep of parameters
+//!
+//! Each question is generated for every team in its league, the first, middle, and last
+//! season, two-season ranges in the middle and at the end and the whole span,
+//! and each position it can be narrowed to, and every board is checked for the shape the game needs: the columns
+//! the question declares, at least one row, a name in every row, and a number
+//! in the stat column. `selftest` in the REPL runs it against the real
+//! database; the tests run it against the fixture, so broken SQL shows up as
+//! soon as a question (or a pack) is added rather than when someone draws it.
+use crate::questions::{
+    generate_question, League, ParamKind, Question, QuestionParams, QuestionRegistry, END_YEAR,
+    POSITIONS, START_YEAR,
+};
+use crate::sql_runner::Board;
+use crate::store::StatsStore;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+use std::fmt;
+
+/// Why a generated board isn't playable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Problem {
+    /// The query didn't run
+    Query {
+        message: String,
+    },
+    Columns {
+        expected: Vec<String>,
+        actual: Vec<String>,
+    },
+    Empty,
+    RowWidth {
+        row: usize,
+        width: usize,
+    },
+    MissingName {
+        row: usize,
+    },
+    NotANumber {
+        row: usize,
+        value: String,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Query { message } => write!(f, "query failed: {message}"),
+            Problem::Columns { expected, actual } => write!(
+                f,
+                "columns are [{}], expected [{}]",
+                actual.join(", "),
+                expected.join(", ")
+            ),
+            Problem::Empty => write!(f, "no rows returned"),
+            Problem::RowWidth { row, width } => {
+                write!(f, "row {} has {width} value(s)", row + 1)
+            }
+            Problem::MissingName { row } => write!(f, "row {} has no player name", row + 1),
+            Problem::NotANumber { row, value } => {
+                write!(f, "row {} has stat '{value}', not a number", row + 1)
+            }
+        }
+    }
+}
+
+/// One board that failed, and the question text it was generated with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Failure {
+    pub code: String,
+    pub text: String,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.code, self.text, self.problem)
+    }
+}
+
+/// What a run checked and what failed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SelfTestReport {
+    pub questions: usize,
+    pub boards: usize,
+    pub failures: Vec<Failure>,
+}
+
+impl SelfTestReport {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// e.g. `Checked 1204 boards from 29 question(s): all OK`
+    pub fn summary(&self) -> String {
+        let outcome = match self.failures.len() {
+            0 => "all OK".to_string(),
+            n => format!("{n} failed"),
+        };
+        format!(
+            "Checked {} boards from {} question(s): {outcome}",
+            self.boards, self.questions
+        )
+    }
+}
+
+/// Every combination of the parameters in `supported` the sweep tries, with
+/// teams from `league`. Thresholds stay at each question's own minimum.
+pub fn param_sweep(league: League, supported: &[ParamKind]) -> Vec<QuestionParams> {
+    let mut sweep = vec![QuestionParams::default()];
+    let middle = (START_YEAR + END_YEAR) / 2;
+    for kind in supported {
+        sweep = sweep
+            .into_iter()
+            .flat_map(|params| -> Vec<QuestionParams> {
+                match kind {
+                    ParamKind::Team => league
+                        .teams()
+                        .iter()
+                        .map(|team| QuestionParams {
+                            team: Some(team.to_string()),
+                            ..params.clone()
+                        })
+                        .collect(),
+                    ParamKind::Year => [START_YEAR, middle, END_YEAR]
+                        .into_iter()
+                        .map(|year| QuestionParams {
+                            year: Some(year),
+                            ..params.clone()
+                        })
+                        .collect(),
+                    // Not the first two seasons: HOU only joined in 2002
+                    ParamKind::YearRange => [
+                        (middle, middle + 1),
+                        (START_YEAR, END_YEAR),
+                        (END_YEAR - 1, END_YEAR),
+                    ]
+                    .into_iter()
+                    .map(|range| QuestionParams {
+                        year_range: Some(range),
+                        ..params.clone()
+                    })
+                    .collect(),
+                    ParamKind::Position => std::iter::once(None)
+                        .chain(POSITIONS.iter().map(|p| Some(p.to_string())))
+                        .map(|position| QuestionParams {
+                            position,
+                            ..params.clone()
+                        })
+                        .collect(),
+                    ParamKind::Threshold => vec![params],
+                }
+            })
+            .collect();
+    }
+    sweep
+}
+
+/// Checks that `board` is playable as `question`'s board
+pub fn check_board(question: &dyn Question, board: &Board) -> Result<(), Problem> {
+    let expected = question.columns();
+    if board.column_names != expected {
+        return Err(Problem::Columns {
+            expected,
+            actual: board.column_names.clone(),
+        });
+    }
+    if board.rows.is_empty() {
+        return Err(Problem::Empty);
+    }
+    for (row, values) in board.rows.iter().enumerate() {
+        if values.len() != expected.len() {
+            return Err(Problem::RowWidth {
+                row,
+                width: values.len(),
+            });
+        }
+        if values[0].trim().is_empty() || values[0] == "NULL" {
+            return Err(Problem::MissingName { row });
+        }
+        let stat = &values[values.len() - 1];
+        if !stat.parse::<f64>().is_ok_and(f64::is_finite) {
+            return Err(Problem::NotANumber {
+                row,
+                value: stat.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Generates and checks every question in `registry` across its sweep,
+/// loading boards from `store`
+pub fn run(registry: &QuestionRegistry, store: &dyn StatsStore) -> SelfTestReport {
+    let mut codes: Vec<&String> = registry.keys().collect();
+    codes.sort();
+
+    let mut report = SelfTestReport {
+        questions: codes.len(),
+        ..Default::default()
+    };
+    // Only parameters the sweep leaves open are sampled, so any seed will do
+    let mut rng = StdRng::seed_from_u64(0);
+    for code in codes {
+        let question = registry[code.as_str()].as_ref();
+        for params in param_sweep(question.league(), &question.supported_params()) {
+            let spec = generate_question(question, &params, &mut rng);
+            report.boards += 1;
+            let checked = store
+                .query_board(&spec.query)
+                .map_err(|e| Problem::Query {
+                    message: e.to_string(),
+                })
+                .and_then(|board| check_board(question, &board));
+            if let Err(problem) = checked {
+                report.failures.push(Failure {
+                    code: code.clone(),
+                    text: spec.text,
+                    problem,
+                });
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::questions::build_registry;
+    use crate::store::MockStore;
+
+    fn board(columns: &[&str], rows: &[&[&str]]) -> Board {
+        Board {
+            column_names: columns.iter().map(|c| c.to_string()).collect(),
+            rows: rows
+                .iter()
+                .map(|row| row.iter().map(|v| v.to_string()).collect())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_sweep_covers_every_combination() {
+        let nfl = League::Nfl;
+        assert_eq!(param_sweep(nfl, &[]), [QuestionParams::default()]);
+        let sweep = param_sweep(nfl, &[ParamKind::Team, ParamKind::YearRange]);
+        assert_eq!(sweep.len(), 32 * 3);
+        assert!(sweep
+            .iter()
+            .any(|p| p.team.as_deref() == Some("HOU") && p.year_range == Some((2012, 2013))));
+        let sweep = param_sweep(nfl, &[ParamKind::YearRange, ParamKind::Position]);
+        assert_eq!(sweep.len(), 3 * 5);
+        assert_eq!(param_sweep(nfl, &[ParamKind::Threshold]).len(), 1);
+        let sweep = param_sweep(League::Nba, &[ParamKind::Team]);
+        assert_eq!(sweep.len(), 30);
+        assert!(sweep.iter().any(|p| p.team.as_deref() == Some("BOS")));
+    }
+
+    #[test]
+    fn test_check_board_finds_each_problem() {
+        let registry = build_registry();
+        let question = registry["top10passyds_year"].as_ref();
+        let columns = ["name", "team_abbr", "season", "passing_yards"];
+        let good = board(&columns, &[&["Tom Brady", "NE", "2007", "4806"]]);
+        assert_eq!(check_board(question, &good), Ok(()));
+
+        let cases = [
+            (
+                board(&["name"], &[&["Tom Brady"]]),
+                "columns are [name], expected [name, team_abbr, season, passing_yards]",
+            ),
+            (board(&columns, &[]), "no rows returned"),
+            (
+                board(&columns, &[&["Tom Brady", "NE"]]),
+                "row 1 has 2 value(s)",
+            ),
+            (
+                board(&columns, &[&["NULL", "NE", "2007", "4806"]]),
+                "row 1 has no player name",
+            ),
+            (
+                board(&columns, &[&["Tom Brady", "NE", "2007", "NULL"]]),
+                "row 1 has stat 'NULL', not a number",
+            ),
+        ];
+        for (board, message) in cases {
+            assert_eq!(
+                check_board(question, &board).unwrap_err().to_string(),
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_reports_failed_queries() {
+        let mut registry = QuestionRegistry::new();
+        registry.insert(build_registry()["top10passyds_year"].clone());
+        let report = run(&registry, &MockStore::default());
+        assert_eq!(report.questions, 1);
+        assert_eq!(report.boards, 3);
+        assert!(!report.is_ok());
+        assert!(matches!(report.failures[0].problem, Problem::Query { .. }));
+        assert_eq!(
+            report.summary(),
+            "Checked 3 boards from 1 question(s): 3 failed"
+        );
+    }
+}
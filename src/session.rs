@@ -0,0 +1,657 @@
+//! One player's session: the engine plus everything played so far
+//!
+//! `GameSession` keeps the running score, round history, locked team, and
+//! event observers in one place so frontends, saved sessions, and tests all
+//! see the same state instead of loose counters in `main`.
+use crate::answer_match::{Matcher, MatcherKind};
+use crate::engine::TriviaEngine;
+use crate::error::{KnowBallError, Result};
+use crate::events::{EventBus, GameEvent, GameObserver};
+use crate::game::{
+    streak_bonus, GameEnd, RowResult, TriviaGame, TriviaResult, ANSWER_COL, PERFECT_STREAK_LENGTH,
+};
+use crate::ghost::GhostStore;
+use crate::questions::{
+    validate_params, BoardId, ParamKind, Question, QuestionParams, QuestionSpec,
+};
+use crate::rules::GameRules;
+use crate::sql_runner::Board;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Points available on a single board
+pub const MAX_BOARD_SCORE: u32 = 1000;
+
+/// A finished round in the session history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundRecord {
+    pub code: String,
+    pub text: String,
+    pub score: u32,
+    pub total: usize,
+    /// The board's column names, matching each row's `cells`
+    #[serde(default)]
+    pub column_names: Vec<String>,
+    /// Every board row, with whether it was found
+    #[serde(default)]
+    pub rows: Vec<RowResult>,
+    /// Most correct guesses in a row
+    #[serde(default)]
+    pub best_streak: usize,
+    /// Extra points from streaks, in the board and across perfect boards,
+    /// on top of `score`
+    #[serde(default)]
+    pub bonus: u32,
+    /// Whether the player skipped the board, banking what they had
+    #[serde(default)]
+    pub skipped: bool,
+}
+
+impl RoundRecord {
+    pub fn from_result(spec: &QuestionSpec, result: &TriviaResult) -> Self {
+        Self {
+            code: spec.code.clone(),
+            text: spec.text.clone(),
+            score: result.score,
+            total: result.total,
+            column_names: result.column_names.clone(),
+            rows: result.rows.clone(),
+            best_streak: result.best_streak,
+            bonus: result.streak_bonus,
+            skipped: result.end == Some(GameEnd::Skipped),
+        }
+    }
+
+    /// Number of rows found
+    pub fn correct(&self) -> usize {
+        self.rows.iter().filter(|r| r.guessed).count()
+    }
+
+    /// Whether every row was found
+    pub fn is_perfect(&self) -> bool {
+        self.total > 0 && self.correct() == self.total
+    }
+}
+
+/// Totals for everything played so far
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    /// Boards played through, not counting skipped ones
+    pub questions_played: u32,
+    /// Boards skipped, whose points still count
+    #[serde(default)]
+    pub questions_skipped: u32,
+    /// Points from every board, skipped ones included
+    pub total_score: u32,
+    /// Points available on every board, skipped ones included
+    pub max_score: u32,
+    /// Average score per board, skipped ones included, or None before the
+    /// first board
+    pub average: Option<f64>,
+    /// Streak bonuses earned, on top of `total_score`
+    #[serde(default)]
+    pub streak_bonus: u32,
+    /// Perfect boards in a row, up to the last one
+    #[serde(default)]
+    pub perfect_streak: usize,
+}
+
+impl SessionSummary {
+    /// Every board the totals cover, played through or skipped
+    pub fn boards(&self) -> u32 {
+        self.questions_played + self.questions_skipped
+    }
+}
+
+/// Session state shared by every frontend
+pub struct GameSession {
+    engine: TriviaEngine,
+    locked_team: Option<String>,
+    history: Vec<RoundRecord>,
+    events: EventBus,
+    matcher: Arc<dyn Matcher>,
+    ghosts: Option<Arc<Mutex<GhostStore>>>,
+    rules: GameRules,
+}
+
+impl GameSession {
+    pub fn new(engine: TriviaEngine) -> Self {
+        Self {
+            engine,
+            locked_team: None,
+            history: Vec::new(),
+            events: EventBus::new(),
+            matcher: MatcherKind::default().matcher(),
+            ghosts: None,
+            rules: GameRules::default(),
+        }
+    }
+
+    /// Adds an observer that hears about every board played in this session
+    pub fn subscribe(&mut self, observer: impl GameObserver + 'static) {
+        self.events.subscribe(observer);
+    }
+
+    /// Sets how guesses are matched on every board from now on
+    pub fn set_matcher(&mut self, matcher: Arc<dyn Matcher>) {
+        self.matcher = matcher;
+    }
+
+    /// Plays every board from now on under `rules`
+    pub fn set_rules(&mut self, rules: GameRules) {
+        self.rules = rules;
+    }
+
+    pub fn rules(&self) -> &GameRules {
+        &self.rules
+    }
+
+    /// Races each board against the previous attempt at it in `ghosts`.
+    /// The session only reads the store; whoever owns it records new ghosts,
+    /// e.g. from `BoardFinished` events.
+    pub fn set_ghosts(&mut self, ghosts: Arc<Mutex<GhostStore>>) {
+        self.ghosts = Some(ghosts);
+    }
+
+    pub fn engine(&self) -> &TriviaEngine {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut TriviaEngine {
+        &mut self.engine
+    }
+
+    /// Team used for team questions when the request doesn't name one
+    pub fn locked_team(&self) -> Option<&str> {
+        self.locked_team.as_deref()
+    }
+
+    pub fn set_locked_team(&mut self, team: Option<String>) {
+        self.locked_team = team;
+    }
+
+    /// Loads a question's board and hands a game over it to `play`, recording
+    /// the result.
+    ///
+    /// `fixed` pins any parameters the player chose; the locked team fills in
+    /// when no team was given and the question takes one. `play` is the frontend's game loop; the session
+    /// only picks parameters, wires up observers, and keeps score. Empty boards
+    /// are not counted.
+    pub fn play_question<F>(
+        &mut self,
+        question: &dyn Question,
+        fixed: &QuestionParams,
+        play: F,
+    ) -> Result<TriviaResult>
+    where
+        F: FnOnce(&QuestionSpec, &mut TriviaGame),
+    {
+        let mut fixed = fixed.clone();
+        if fixed.team.is_none() && question.supported_params().contains(&ParamKind::Team) {
+            fixed.team = self.locked_team.clone();
+        }
+        let (spec, board) = self.engine.load_question(question, &fixed)?;
+        Ok(self.play_board(spec, board, play))
+    }
+
+    /// Deals the board `id` names again: its question with exactly the same
+    /// parameters, whatever team is locked. Fails if no question has that
+    /// code or the question can't take those parameters.
+    pub fn replay<F>(&mut self, id: &BoardId, play: F) -> Result<TriviaResult>
+    where
+        F: FnOnce(&QuestionSpec, &mut TriviaGame),
+    {
+        let question = self
+            .engine
+            .registry()
+            .get_ignore_case(&id.code)
+            .cloned()
+            .ok_or_else(|| KnowBallError::Parse(format!("no question '{}' to replay", id.code)))?;
+        validate_params(question.as_ref(), &id.params)?;
+        let (spec, board) = self.engine.load_question(question.as_ref(), &id.params)?;
+        Ok(self.play_board(spec, board, play))
+    }
+
+    /// Plays an already-generated question, such as one from a quiz pack,
+    /// running its SQL as-is instead of generating new parameters
+    pub fn play_spec<F>(&mut self, spec: QuestionSpec, play: F) -> Result<TriviaResult>
+    where
+        F: FnOnce(&QuestionSpec, &mut TriviaGame),
+    {
+        let board = self.engine.load_board(&spec)?;
+        Ok(self.play_board(spec, board, play))
+    }
+
+    /// Plays the board everyone gets for `seed`; see
+    /// [`TriviaEngine::load_seeded`]
+    pub fn play_seeded<F>(&mut self, seed: u64, play: F) -> Result<TriviaResult>
+    where
+        F: FnOnce(&QuestionSpec, &mut TriviaGame),
+    {
+        let (spec, board) = self.engine.load_seeded(seed)?;
+        Ok(self.play_board(spec, board, play))
+    }
+
+    /// Plays the `count` boards everyone gets for `seed` in order; see
+    /// [`TriviaEngine::load_seeded_set`]. Every board is loaded before the
+    /// first is played, so a failure doesn't leave a set half played.
+    /// `play` is also told each board's place in the set.
+    pub fn play_seeded_set<F>(
+        &mut self,
+        seed: u64,
+        count: usize,
+        mut play: F,
+    ) -> Result<Vec<TriviaResult>>
+    where
+        F: FnMut(usize, &QuestionSpec, &mut TriviaGame),
+    {
+        let boards = self.engine.load_seeded_set(seed, count)?;
+        Ok(boards
+            .into_iter()
+            .enumerate()
+            .map(|(i, (spec, board))| {
+                self.play_board(spec, board, |spec, game| play(i, spec, game))
+            })
+            .collect())
+    }
+
+    /// Wires a game over the board to the session's observers and matcher,
+    /// hands it to `play`, and records the result
+    fn play_board<F>(&mut self, spec: QuestionSpec, board: Board, play: F) -> TriviaResult
+    where
+        F: FnOnce(&QuestionSpec, &mut TriviaGame),
+    {
+        let mut hidden = board.clone();
+        for row in &mut hidden.rows {
+            row[ANSWER_COL].clear();
+        }
+        self.events.emit(GameEvent::QuestionStarted {
+            code: spec.code.clone(),
+            text: spec.text.clone(),
+            total: board.rows.len(),
+            board: hidden,
+            max_strikes: self.rules.strikes,
+        });
+        let events = std::mem::take(&mut self.events);
+        let mut game = TriviaGame::with_events(spec.text.clone(), board, events)
+            .with_spec(spec.clone())
+            .with_matcher(Arc::clone(&self.matcher))
+            .with_rules(self.rules);
+        let ghost = self.ghosts.as_ref().and_then(|ghosts| {
+            let ghosts = ghosts.lock().unwrap();
+            ghosts.get(&spec, game.total()).cloned()
+        });
+        if let Some(ghost) = ghost {
+            game = game.with_ghost(ghost);
+        }
+        play(&spec, &mut game);
+        self.events = game.take_events();
+
+        let result = game.result();
+        self.record(&spec, &result);
+        result
+    }
+
+    /// Adds a finished round to the history
+    pub fn record(&mut self, spec: &QuestionSpec, result: &TriviaResult) {
+        if result.total == 0 {
+            return;
+        }
+        let mut record = RoundRecord::from_result(spec, result);
+        if record.is_perfect() && self.perfect_streak() + 1 >= PERFECT_STREAK_LENGTH {
+            record.bonus += streak_bonus(record.score);
+        }
+        self.history.push(record);
+    }
+
+    /// Perfect boards in a row, up to the last one played
+    pub fn perfect_streak(&self) -> usize {
+        self.history
+            .iter()
+            .rev()
+            .take_while(|round| round.is_perfect())
+            .count()
+    }
+
+    /// Streak bonuses earned this session, on top of the score
+    pub fn bonus(&self) -> u32 {
+        self.history.iter().map(|r| r.bonus).sum()
+    }
+
+    /// Every counted round, oldest first
+    pub fn history(&self) -> &[RoundRecord] {
+        &self.history
+    }
+
+    /// Boards played through, not counting skipped ones
+    pub fn questions_played(&self) -> u32 {
+        self.history.iter().filter(|r| !r.skipped).count() as u32
+    }
+
+    pub fn questions_skipped(&self) -> u32 {
+        self.history.iter().filter(|r| r.skipped).count() as u32
+    }
+
+    /// Total points earned this session
+    pub fn score(&self) -> u32 {
+        self.history.iter().map(|r| r.score).sum()
+    }
+
+    pub fn summary(&self) -> SessionSummary {
+        let boards = self.history.len() as u32;
+        let total_score = self.score();
+        SessionSummary {
+            questions_played: self.questions_played(),
+            questions_skipped: self.questions_skipped(),
+            total_score,
+            max_score: boards * MAX_BOARD_SCORE,
+            average: (boards > 0).then(|| total_score as f64 / boards as f64),
+            streak_bonus: self.bonus(),
+            perfect_streak: self.perfect_streak(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEnd, GuessOutcome};
+    use crate::questions::QuestionParams;
+    use crate::sql_runner::Query;
+    use crate::store::MockStore;
+
+    fn spec(code: &str) -> QuestionSpec {
+        QuestionSpec {
+            code: code.into(),
+            params: QuestionParams::default(),
+            text: format!("{code} text"),
+            query: Query::default(),
+        }
+    }
+
+    #[test]
+    fn test_summary_tracks_rounds() {
+        let mut session = GameSession::new(TriviaEngine::with_seed(1));
+        assert_eq!(session.summary().average, None);
+
+        session.record(
+            &spec("a"),
+            &TriviaResult {
+                score: 600,
+                total: 10,
+                ..Default::default()
+            },
+        );
+        session.record(
+            &spec("b"),
+            &TriviaResult {
+                score: 300,
+                total: 10,
+                ..Default::default()
+            },
+        );
+
+        let summary = session.summary();
+        assert_eq!(summary.questions_played, 2);
+        assert_eq!(summary.total_score, 900);
+        assert_eq!(summary.max_score, 2000);
+        assert_eq!(summary.average, Some(450.0));
+        assert_eq!(session.history()[1].code, "b");
+
+        session.record(
+            &spec("c"),
+            &TriviaResult {
+                score: 300,
+                total: 10,
+                end: Some(GameEnd::Skipped),
+                ..Default::default()
+            },
+        );
+        let summary = session.summary();
+        assert_eq!(
+            (summary.questions_played, summary.questions_skipped),
+            (2, 1)
+        );
+        assert_eq!(summary.boards(), 3);
+        assert_eq!((summary.total_score, summary.max_score), (1200, 3000));
+        assert_eq!(summary.average, Some(400.0));
+        assert!(session.history()[2].skipped);
+    }
+
+    #[test]
+    fn test_perfect_boards_in_a_row_earn_a_bonus() {
+        let mut session = GameSession::new(TriviaEngine::with_seed(1));
+        let perfect = |score: u32| TriviaResult {
+            score,
+            total: 1,
+            rows: vec![RowResult {
+                name: "Tom Brady".into(),
+                stat: "5000".into(),
+                points: score,
+                guessed: true,
+                passed: false,
+                cells: Vec::new(),
+                found_at: None,
+            }],
+            streak_bonus: 5,
+            ..Default::default()
+        };
+        session.record(&spec("a"), &perfect(1000));
+        assert_eq!((session.perfect_streak(), session.bonus()), (1, 5));
+        session.record(&spec("b"), &perfect(900));
+        assert_eq!(session.perfect_streak(), 2);
+        assert_eq!(session.history()[1].bonus, 5 + streak_bonus(900));
+
+        let summary = session.summary();
+        assert_eq!(summary.total_score, 1900);
+        assert_eq!(summary.streak_bonus, 10 + streak_bonus(900));
+        session.record(
+            &spec("c"),
+            &TriviaResult {
+                total: 10,
+                ..Default::default()
+            },
+        );
+        assert_eq!(session.perfect_streak(), 0);
+    }
+
+    #[test]
+    fn test_empty_board_not_counted() {
+        let mut session = GameSession::new(TriviaEngine::with_seed(1));
+        session.record(&spec("a"), &TriviaResult::default());
+        assert_eq!(session.questions_played(), 0);
+    }
+
+    #[test]
+    fn test_play_spec_runs_the_saved_sql() {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![vec!["Tom Brady".into(), "5000".into()]],
+        };
+        let store = MockStore::new(board);
+        let engine = TriviaEngine::with_seed(1).with_store(store);
+        let mut session = GameSession::new(engine);
+
+        let mut saved = spec("a");
+        saved.query = "SELECT saved".into();
+        let result = session
+            .play_spec(saved, |_, game| {
+                game.submit_guess("Brady");
+            })
+            .unwrap();
+        assert_eq!(result.correct(), 1);
+        assert_eq!(session.questions_played(), 1);
+    }
+    #[test]
+    fn test_locked_team_only_fills_team_questions() {
+        let store = MockStore::new(named(&["Tom Brady"]));
+        let engine = TriviaEngine::with_seed(3).with_store(store);
+        let mut session = GameSession::new(engine);
+        session.set_locked_team(Some("PIT".into()));
+        let mut play = |code: &str| {
+            let question = session.engine().registry()[code].clone();
+            session
+                .play_question(question.as_ref(), &QuestionParams::default(), |_, _| {})
+                .unwrap()
+                .spec
+                .unwrap()
+        };
+        let team = play("last10passers_TEAM");
+        assert_eq!(team.params.team.as_deref(), Some("PIT"));
+        let league = play("top10passyds_year");
+        assert_eq!(league.params.team, None);
+    }
+
+    #[test]
+    fn test_replay_deals_the_same_board() {
+        let store = MockStore::new(named(&["Tom Brady"]));
+        let engine = TriviaEngine::with_seed(3).with_store(store);
+        let mut session = GameSession::new(engine);
+        let question = session.engine().registry()["recyds_yearrange_TEAM"].clone();
+        let first = session
+            .play_question(question.as_ref(), &QuestionParams::default(), |_, _| {})
+            .unwrap()
+            .spec
+            .unwrap();
+        let id = BoardId::of(&first);
+        let again = session.replay(&id, |_, _| {}).unwrap().spec.unwrap();
+        assert_eq!(again, first);
+
+        let unknown: BoardId = "nope:PIT".parse().unwrap();
+        assert!(session.replay(&unknown, |_, _| {}).is_err());
+        let wrong: BoardId = "recyds_yearrange_TEAM:1990-2005".parse().unwrap();
+        assert!(session.replay(&wrong, |_, _| {}).is_err());
+    }
+
+    fn named(names: &[&str]) -> Board {
+        Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| vec![name.to_string(), (1000 * (i + 1)).to_string()])
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_failed_query_mid_session_keeps_the_rest() {
+        let store = MockStore::default()
+            .then_board(named(&["Tom Brady"]))
+            .then_fail("database is locked")
+            .then_board(named(&["Drew Brees"]));
+        let mut session = GameSession::new(TriviaEngine::with_seed(1).with_store(store));
+
+        let played: Vec<_> = ["Brady", "Manning", "Brees"]
+            .into_iter()
+            .map(|guess| {
+                session.play_spec(spec("a"), |_, game| {
+                    game.submit_guess(guess);
+                })
+            })
+            .collect();
+        assert_eq!(played[0].as_ref().unwrap().score, 1000);
+        assert_eq!(
+            played[1].as_ref().unwrap_err().to_string(),
+            "store error: database is locked"
+        );
+        assert_eq!(played[2].as_ref().unwrap().correct(), 1);
+        assert_eq!(session.questions_played(), 2);
+        assert_eq!(session.score(), 2000);
+    }
+
+    /// One thing a player can do on a board
+    #[derive(Debug, Clone, Copy)]
+    enum Move {
+        Guess(&'static str),
+        Hint,
+        Reveal,
+    }
+
+    // Every sequence of up to six moves on a three-row board, checking the
+    // score, strikes, and end against a tally kept by hand
+    #[test]
+    fn test_every_short_game_adds_up() {
+        const MOVES: [Move; 6] = [
+            Move::Guess("Brady"),
+            Move::Guess("Brees"),
+            Move::Guess("Manning"),
+            Move::Guess("Nobody"),
+            Move::Hint,
+            Move::Reveal,
+        ];
+        let board = named(&["Tom Brady", "Drew Brees", "Peyton Manning"]);
+        let points = board.point_values();
+        let mut session =
+            GameSession::new(TriviaEngine::with_seed(1).with_store(MockStore::new(board)));
+
+        let mut games = 0;
+        for len in 0..=6u32 {
+            for n in 0..MOVES.len().pow(len) {
+                let moves: Vec<Move> = (0..len)
+                    .map(|i| MOVES[n / MOVES.len().pow(i) % MOVES.len()])
+                    .collect();
+                let (mut found, mut strikes, mut hints, mut gave_up) = ([false; 3], 0, 0, false);
+                let result = session
+                    .play_spec(spec("a"), |_, game| {
+                        for &m in &moves {
+                            let over = found.iter().all(|&f| f) || strikes == 3 || gave_up;
+                            match m {
+                                Move::Guess(name) => {
+                                    let outcome = game.submit_guess(name);
+                                    let row = MOVES
+                                        .iter()
+                                        .position(|&m| matches!(m, Move::Guess(n) if n == name));
+                                    let expected = match row {
+                                        _ if over => GuessOutcome::GameOver,
+                                        Some(row) if row < 3 && found[row] => {
+                                            GuessOutcome::AlreadyGuessed
+                                        }
+                                        Some(row) if row < 3 => {
+                                            found[row] = true;
+                                            GuessOutcome::Correct {
+                                                row,
+                                                name: game.board().rows[row][0].clone(),
+                                                points: points[row],
+                                            }
+                                        }
+                                        _ => {
+                                            strikes += 1;
+                                            GuessOutcome::Strike { strikes }
+                                        }
+                                    };
+                                    assert_eq!(outcome, expected, "{moves:?}");
+                                }
+                                Move::Hint => {
+                                    game.record_hint();
+                                    hints += 1;
+                                }
+                                Move::Reveal => {
+                                    game.reveal();
+                                    gave_up |= !over;
+                                }
+                            }
+                        }
+                    })
+                    .unwrap();
+
+                let score: u32 = (0..3).filter(|&i| found[i]).map(|i| points[i]).sum();
+                let end = if found.iter().all(|&f| f) {
+                    Some(GameEnd::Perfect)
+                } else if strikes == 3 {
+                    Some(GameEnd::StruckOut)
+                } else if gave_up {
+                    Some(GameEnd::GaveUp)
+                } else {
+                    None
+                };
+                assert_eq!(result.score, score, "{moves:?}");
+                assert_eq!(result.strikes, strikes, "{moves:?}");
+                assert_eq!(result.hints, hints, "{moves:?}");
+                assert_eq!(result.end, end, "{moves:?}");
+                games += 1;
+            }
+        }
+        assert_eq!(session.questions_played(), games);
+    }
+}
@@ -0,0 +1,237 @@
+//! Live game state for overlays and companion screens (`--spectate`)
+//!
+//! A [`Spectator`] listens to the session's events and, after each one,
+//! writes the whole state of the board as one line of JSON: the board with
+//! the names found so far, the score, the strikes, and the guess that changed
+//! it. A stream overlay only has to render the latest line, and one that
+//! joins mid-round has everything it needs from the next.
+//!
+//! ```json
+//! {"code":"passyds_TEAM","question":"...","board":{...},"score":120,"strikes":1,"max_strikes":3,"correct":1,"total":10,"last":{"type":"strike","strikes":1},"end":null}
+//! ```
+use crate::events::GameEvent;
+use crate::game::{GameEnd, ANSWER_COL};
+use crate::sql_runner::Board;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Where the stream goes: `tcp:HOST:PORT`, `unix:PATH` (Unix only), or a
+/// file path, which can also be a named pipe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpectateTarget {
+    File(PathBuf),
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for SpectateTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(addr) = s.strip_prefix("tcp:") {
+            return Ok(SpectateTarget::Tcp(addr.into()));
+        }
+        if let Some(path) = s.strip_prefix("unix:") {
+            #[cfg(unix)]
+            return Ok(SpectateTarget::Unix(path.into()));
+            #[cfg(not(unix))]
+            return Err(format!("Unix sockets aren't available here: '{path}'"));
+        }
+        if s.is_empty() {
+            return Err("--spectate needs a file, tcp:HOST:PORT, or unix:PATH".into());
+        }
+        Ok(SpectateTarget::File(s.into()))
+    }
+}
+
+impl SpectateTarget {
+    /// Creates the file or connects to the socket
+    pub fn open(&self) -> io::Result<Box<dyn Write + Send>> {
+        Ok(match self {
+            SpectateTarget::File(path) => Box::new(File::create(path)?),
+            SpectateTarget::Tcp(addr) => Box::new(TcpStream::connect(addr)?),
+            #[cfg(unix)]
+            SpectateTarget::Unix(path) => Box::new(std::os::unix::net::UnixStream::connect(path)?),
+        })
+    }
+}
+
+/// Everything an overlay shows, as of the latest event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpectatorState {
+    pub code: String,
+    pub question: String,
+    /// Unfound names are blank until the round ends
+    pub board: Board,
+    pub score: u32,
+    pub strikes: usize,
+    /// None when misses never end the round
+    pub max_strikes: Option<usize>,
+    pub correct: usize,
+    pub total: usize,
+    /// The guess that produced this state, if any
+    pub last: Option<GameEvent>,
+    /// Set once the round is over
+    pub end: Option<GameEnd>,
+}
+
+/// Mirrors each board's state to `out` as newline-delimited JSON
+#[derive(Debug)]
+pub struct Spectator<W> {
+    out: W,
+    state: Option<SpectatorState>,
+}
+
+impl<W: Write> Spectator<W> {
+    pub fn new(out: W) -> Self {
+        Self { out, state: None }
+    }
+
+    /// The state as of the last event, or None before the first board
+    pub fn state(&self) -> Option<&SpectatorState> {
+        self.state.as_ref()
+    }
+
+    /// Applies `event` and writes the new state as one line
+    pub fn write(&mut self, event: &GameEvent) -> io::Result<()> {
+        self.apply(event);
+        let Some(state) = &self.state else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_vec(state).map_err(io::Error::other)?;
+        line.push(b'\n');
+        self.out.write_all(&line)?;
+        self.out.flush()
+    }
+
+    fn apply(&mut self, event: &GameEvent) {
+        if let GameEvent::QuestionStarted {
+            code,
+            text,
+            total,
+            board,
+            max_strikes,
+        } = event
+        {
+            self.state = Some(SpectatorState {
+                code: code.clone(),
+                question: text.clone(),
+                board: board.clone(),
+                score: 0,
+                strikes: 0,
+                max_strikes: *max_strikes,
+                correct: 0,
+                total: *total,
+                last: None,
+                end: None,
+            });
+            return;
+        }
+        let Some(state) = &mut self.state else {
+            return;
+        };
+        match event {
+            GameEvent::QuestionStarted { .. } => {}
+            GameEvent::CorrectGuess { row, name, points } => {
+                if let Some(cells) = state.board.rows.get_mut(*row) {
+                    cells[ANSWER_COL] = name.clone();
+                }
+                state.score += points;
+                state.correct += 1;
+                state.last = Some(event.clone());
+            }
+            GameEvent::RowPassed { row, name } => {
+                if let Some(cells) = state.board.rows.get_mut(*row) {
+                    cells[ANSWER_COL] = name.clone();
+                }
+                state.last = Some(event.clone());
+            }
+            GameEvent::Strike { strikes } => {
+                state.strikes = *strikes;
+                state.last = Some(event.clone());
+            }
+            GameEvent::BoardFinished { end, result } => {
+                for (cells, row) in state.board.rows.iter_mut().zip(&result.rows) {
+                    cells[ANSWER_COL] = row.name.clone();
+                }
+                state.score = result.score;
+                state.end = Some(*end);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+    use crate::game::{TriviaGame, MAX_STRIKES};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            "tcp:127.0.0.1:9000".parse(),
+            Ok(SpectateTarget::Tcp("127.0.0.1:9000".into()))
+        );
+        assert_eq!(
+            "game.ndjson".parse(),
+            Ok(SpectateTarget::File("game.ndjson".into()))
+        );
+        assert!("".parse::<SpectateTarget>().is_err());
+    }
+
+    #[test]
+    fn test_mirrors_a_round() {
+        let board = Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![
+                vec!["Tom Brady".into(), "5000".into()],
+                vec!["Drew Brees".into(), "4000".into()],
+            ],
+        };
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&lines);
+        let mut spectator = Spectator::new(Vec::new());
+        let mut events = EventBus::new();
+        events.subscribe(move |event: &GameEvent| {
+            spectator.write(event).unwrap();
+            *sink.lock().unwrap() = spectator.out.clone();
+        });
+
+        let mut hidden = board.clone();
+        for row in &mut hidden.rows {
+            row[ANSWER_COL].clear();
+        }
+        events.emit(GameEvent::QuestionStarted {
+            code: "passyds_TEAM".into(),
+            text: "Most yards".into(),
+            total: 2,
+            board: hidden,
+            max_strikes: Some(MAX_STRIKES),
+        });
+        let mut game = TriviaGame::with_events("Most yards", board, events);
+        game.submit_guess("Manning");
+        game.submit_guess("Brees");
+        game.reveal();
+
+        let lines = lines.lock().unwrap();
+        let states: Vec<SpectatorState> = String::from_utf8_lossy(&lines)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(states.len(), 4);
+        assert_eq!(states[0].board.rows[0][0], "");
+        assert_eq!(states[1].strikes, 1);
+        assert_eq!(states[2].board.rows[1][0], "Drew Brees");
+        assert_eq!(states[2].board.rows[0][0], "");
+        assert_eq!(states[2].score, game.score());
+        assert_eq!(states[3].end, Some(GameEnd::GaveUp));
+        assert_eq!(states[3].board.rows[0][0], "Tom Brady");
+    }
+}
@@ -0,0 +1,395 @@
+//! Plain-speech rendering for voice assistants and text-to-speech
+//!
+//! Everything here is a short sentence with no tables, symbols, or column
+//! abbreviations, so it can be piped straight into a speech engine: "Number 3
+//! is still hidden: 4,841 passing yards for PIT in 2018." Like the other
+//! renderers it only reads a game's state and never does any IO.
+use crate::game::{GameEnd, GuessOutcome, TriviaGame, ANSWER_COL, MAX_STRIKES};
+use crate::session::{SessionSummary, MAX_BOARD_SCORE};
+
+/// Column names that read as "for {team}"
+const TEAM_COLUMNS: [&str; 3] = ["team", "team_abbr", "last_team"];
+
+/// Column names that read as "in {season}"
+const SEASON_COLUMNS: [&str; 3] = ["season", "year", "max_season"];
+
+/// Whole column names that don't read well word by word
+const COLUMN_LABELS: [(&str, &str); 4] = [
+    ("ypr", "yards per reception"),
+    ("ypc", "yards per carry"),
+    ("comp_pct", "completion percentage"),
+    ("tgts", "targets"),
+];
+
+/// Abbreviations inside column names, spelled out
+const WORDS: [(&str, &str); 13] = [
+    ("rec", "receiving"),
+    ("recs", "receptions"),
+    ("rush", "rushing"),
+    ("pass", "passing"),
+    ("yds", "yards"),
+    ("td", "touchdowns"),
+    ("tds", "touchdowns"),
+    ("int", "interceptions"),
+    ("ints", "interceptions"),
+    ("att", "attempts"),
+    ("atts", "attempts"),
+    ("fum", "fumbles"),
+    ("pct", "percentage"),
+];
+
+/// The question, how many names are left to find, and one sentence per row
+pub fn board_sentences(game: &TriviaGame) -> Vec<String> {
+    let mut sentences = vec![sentence(game.question())];
+    if game.total() == 0 {
+        sentences.push("This question has no answers.".into());
+        return sentences;
+    }
+    sentences.push(progress_sentence(game));
+    sentences.extend((0..game.total()).map(|i| row_sentence(game, i)));
+    sentences
+}
+
+/// "Number 3 is still hidden: 4,841 passing yards for PIT in 2018.", or the
+/// name once it has been found or the round is over
+pub fn row_sentence(game: &TriviaGame, row: usize) -> String {
+    let cells = &game.board().rows[row];
+    let clues = clues(&game.board().column_names, cells);
+    let who = if game.is_guessed(row) || game.is_over() {
+        format!("Number {} is {}", row + 1, cells[ANSWER_COL])
+    } else {
+        format!("Number {} is still hidden", row + 1)
+    };
+    if clues.is_empty() {
+        format!("{who}.")
+    } else {
+        format!("{who}: {clues}.")
+    }
+}
+
+/// Where the round stands: names found and strikes left
+pub fn progress_sentence(game: &TriviaGame) -> String {
+    let found = format!(
+        "You have found {} of {} for {} points",
+        game.correct(),
+        game.total(),
+        number(&game.score().to_string())
+    );
+    match game.rules().strikes {
+        Some(max) => {
+            let left = max.saturating_sub(game.strikes());
+            format!("{found}, with {} left.", plural(left, "strike"))
+        }
+        None => format!("{found}."),
+    }
+}
+
+/// Feedback for one guess in `game`, or None when there is nothing to say
+pub fn outcome_sentence(game: &TriviaGame, outcome: &GuessOutcome) -> Option<String> {
+    match outcome {
+        GuessOutcome::Correct { row, name, points } => Some(format!(
+            "Correct! Number {} is {name}, worth {points} points.",
+            row + 1
+        )),
+        GuessOutcome::AlreadyGuessed => Some("You already got that one.".into()),
+        GuessOutcome::Ambiguous { rows } => Some(format!(
+            "That fits {} players. Say more of the name.",
+            number(&rows.len().to_string())
+        )),
+        GuessOutcome::Strike { strikes } => Some(match game.rules().strikes {
+            Some(max) => format!("Strike {strikes} of {max}."),
+            None => format!("Strike {strikes}."),
+        }),
+        GuessOutcome::Paused => Some("The clock is paused. Say resume to go on.".into()),
+        GuessOutcome::Empty | GuessOutcome::GameOver => None,
+    }
+}
+
+/// Where a timed round's clock stands, after `pause` or `resume`
+pub fn clock_sentence(game: &TriviaGame) -> String {
+    let left = game
+        .remaining()
+        .map_or(0, |left| left.as_secs_f64().ceil() as usize);
+    if game.is_paused() {
+        format!(
+            "Paused with {} left. Say resume to go on.",
+            plural(left, "second")
+        )
+    } else {
+        format!("The clock is running, {} left.", plural(left, "second"))
+    }
+}
+
+/// How the round ended, the score, and the names that were missed
+pub fn result_sentences(game: &TriviaGame) -> Vec<String> {
+    let mut sentences = Vec::new();
+    sentences.push(match game.end() {
+        Some(GameEnd::Perfect) => format!("Perfect! You found all {}.", game.total()),
+        Some(GameEnd::StruckOut) if game.strikes() == MAX_STRIKES => {
+            "Three strikes, you're out.".into()
+        }
+        Some(GameEnd::StruckOut) => format!("Strike {}, you're out.", game.strikes()),
+        Some(GameEnd::TimeUp) => "Time's up.".into(),
+        Some(GameEnd::Skipped) => "Skipping this one.".into(),
+        _ => "Stopping early.".into(),
+    });
+    sentences.push(format!(
+        "You found {} of {} for {} points out of {}.",
+        game.correct(),
+        game.total(),
+        number(&game.score().to_string()),
+        number(&MAX_BOARD_SCORE.to_string())
+    ));
+    let missed: Vec<&str> = game
+        .board()
+        .rows
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !game.is_guessed(*i))
+        .map(|(_, row)| row[ANSWER_COL].as_str())
+        .collect();
+    if !missed.is_empty() {
+        sentences.push(format!("You missed {}.", join_and(&missed)));
+    }
+    sentences
+}
+
+/// Session totals in one sentence
+pub fn summary_sentence(summary: &SessionSummary) -> String {
+    let Some(average) = summary.average else {
+        return "You haven't played any questions yet.".into();
+    };
+    let skipped = match summary.questions_skipped {
+        0 => String::new(),
+        n => format!(" and skipped {n}"),
+    };
+    format!(
+        "You have played {}{skipped} for {} of {} points, an average of {average:.0}.",
+        plural(summary.questions_played as usize, "question"),
+        number(&summary.total_score.to_string()),
+        number(&summary.max_score.to_string())
+    )
+}
+
+/// `text` as a sentence: capitalized, and ending in punctuation
+pub fn sentence(text: &str) -> String {
+    let text = text.trim();
+    let mut chars = text.chars();
+    let Some(first) = chars.next() else {
+        return String::new();
+    };
+    let mut out: String = first.to_uppercase().chain(chars).collect();
+    if !out.ends_with(['.', '!', '?']) {
+        out.push('.');
+    }
+    out
+}
+
+/// A row's cells other than the name: the scored stat, then the team and
+/// season, then any other columns
+fn clues(column_names: &[String], cells: &[String]) -> String {
+    let column = |j: usize| column_names.get(j).map_or("", String::as_str);
+    let stat = cells.len() - 1;
+    let mut clue = String::new();
+    let mut others = Vec::new();
+    for j in (1..cells.len()).filter(|&j| !cells[j].trim().is_empty()) {
+        let name = column(j).to_ascii_lowercase();
+        if j == stat {
+            continue;
+        } else if TEAM_COLUMNS.contains(&name.as_str()) {
+            clue.push_str(&format!(" for {}", cells[j]));
+        } else if SEASON_COLUMNS.contains(&name.as_str()) {
+            clue.push_str(&format!(" in {}", cells[j]));
+        } else {
+            others.push(stat_phrase(&name, &cells[j]));
+        }
+    }
+    if stat > ANSWER_COL && !cells[stat].trim().is_empty() {
+        clue.insert_str(
+            0,
+            &stat_phrase(&column(stat).to_ascii_lowercase(), &cells[stat]),
+        );
+    }
+    if !others.is_empty() {
+        let others: Vec<&str> = others.iter().map(String::as_str).collect();
+        clue.push_str(&format!(", with {}", join_and(&others)));
+    }
+    clue.trim().to_string()
+}
+
+/// "1,101 receiving yards", or "completion percentage of 67.0 percent" for a
+/// percentage column, which may hold a fraction
+fn stat_phrase(column: &str, value: &str) -> String {
+    let label = label(column);
+    if column.ends_with("pct") {
+        if let Ok(fraction) = value.trim().parse::<f64>() {
+            let percent = if fraction <= 1.0 {
+                fraction * 100.0
+            } else {
+                fraction
+            };
+            return format!("{label} of {percent:.1} percent");
+        }
+    }
+    format!("{} {label}", number(value))
+}
+
+/// A column name in words, e.g. `career_rec_yds` as "career receiving yards"
+fn label(column: &str) -> String {
+    if let Some((_, label)) = COLUMN_LABELS.iter().find(|(c, _)| *c == column) {
+        return label.to_string();
+    }
+    column
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            WORDS
+                .iter()
+                .find(|(short, _)| *short == word)
+                .map_or(word, |(_, long)| long)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whole numbers with thousands separators and decimals to one place, so
+/// "4841" is read as "4,841" and "18.04918" as "18.0"; anything else as is
+fn number(value: &str) -> String {
+    let value = value.trim();
+    if let Ok(n) = value.parse::<i64>() {
+        let digits = n.unsigned_abs().to_string();
+        let mut out = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                out.push(',');
+            }
+            out.push(digit);
+        }
+        return if n < 0 { format!("-{out}") } else { out };
+    }
+    match value.parse::<f64>() {
+        Ok(n) if n.is_finite() => format!("{n:.1}"),
+        _ => value.to_string(),
+    }
+}
+
+/// "1 strike" or "2 strikes"
+pub(crate) fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        format!("1 {word}")
+    } else {
+        format!("{n} {word}s")
+    }
+}
+
+/// "A", "A and B", or "A, B and C"
+fn join_and(items: &[&str]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.to_string(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_runner::Board;
+    use std::time::Duration;
+
+    fn game() -> TriviaGame {
+        let board = Board {
+            column_names: ["name", "team_abbr", "season", "targets", "ypr"]
+                .map(String::from)
+                .to_vec(),
+            rows: vec![
+                ["Calvin Johnson", "DET", "2011", "158", "17.46"]
+                    .map(String::from)
+                    .to_vec(),
+                ["Marvin Jones", "DET", "2017", "107", "18.04918"]
+                    .map(String::from)
+                    .to_vec(),
+            ],
+        };
+        TriviaGame::new("Best yards per catch for DET", board)
+    }
+
+    #[test]
+    fn test_board_reads_as_sentences() {
+        let mut game = game();
+        game.submit_guess("Calvin");
+        let sentences = board_sentences(&game);
+        assert_eq!(sentences[0], "Best yards per catch for DET.");
+        assert!(sentences[1].starts_with("You have found 1 of 2 for "));
+        assert!(sentences[1].ends_with("with 3 strikes left."));
+        assert_eq!(
+            sentences[2],
+            "Number 1 is Calvin Johnson: 17.5 yards per reception for DET in 2011, with 158 targets."
+        );
+        assert_eq!(
+            sentences[3],
+            "Number 2 is still hidden: 18.0 yards per reception for DET in 2017, with 107 targets."
+        );
+    }
+
+    #[test]
+    fn test_outcomes_and_result() {
+        let mut game = game();
+        let outcome = game.submit_guess("Moss");
+        assert_eq!(
+            outcome_sentence(&game, &outcome).as_deref(),
+            Some("Strike 1 of 3.")
+        );
+        assert_eq!(outcome_sentence(&game, &GuessOutcome::Empty), None);
+        game.reveal();
+        let mut timed =
+            TriviaGame::new("q", game.board().clone()).with_time_limit(Duration::from_secs(30));
+        timed.pause();
+        assert_eq!(
+            clock_sentence(&timed),
+            "Paused with 30 seconds left. Say resume to go on."
+        );
+        let outcome = timed.submit_guess("Moss");
+        assert_eq!(
+            outcome_sentence(&timed, &outcome).as_deref(),
+            Some("The clock is paused. Say resume to go on.")
+        );
+        let result = result_sentences(&game);
+        assert_eq!(result[0], "Stopping early.");
+        assert_eq!(result[1], "You found 0 of 2 for 0 points out of 1,000.");
+        assert_eq!(result[2], "You missed Calvin Johnson and Marvin Jones.");
+        assert!(row_sentence(&game, 1).starts_with("Number 2 is Marvin Jones: "));
+    }
+
+    #[test]
+    fn test_summary_counts_skipped_boards() {
+        let summary = SessionSummary {
+            questions_played: 2,
+            questions_skipped: 1,
+            total_score: 1200,
+            max_score: 3000,
+            average: Some(400.0),
+            streak_bonus: 0,
+            perfect_streak: 0,
+        };
+        assert_eq!(
+            summary_sentence(&summary),
+            "You have played 2 questions and skipped 1 for 1,200 of 3,000 points, an average of 400."
+        );
+    }
+
+    #[test]
+    fn test_labels_and_numbers() {
+        assert_eq!(label("career_rec_yds"), "career receiving yards");
+        assert_eq!(label("fum_lost"), "fumbles lost");
+        assert_eq!(stat_phrase("passing_yards", "4841"), "4,841 passing yards");
+        assert_eq!(
+            stat_phrase("comp_pct", "0.6696"),
+            "completion percentage of 67.0 percent"
+        );
+        assert_eq!(number("-1234567"), "-1,234,567");
+        assert_eq!(number("N/A"), "N/A");
+        assert_eq!(join_and(&["A", "B", "C"]), "A, B and C");
+        assert_eq!(sentence("  who led PIT?"), "Who led PIT?");
+    }
+}
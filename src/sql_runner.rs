@@ -1,263 +1,228 @@
-//! SQL query execution and trivia game logic
-use rusqlite::{types::Value, Connection, Result};
-use std::io::{self, Write};
+//! SQL query execution and board loading
+//!
+//! Question SQL never has a team, year, or position written into it: those
+//! are named parameters (`:team`, `:year`) bound by the store when the
+//! [`Query`] runs.
+#[cfg(feature = "sqlite")]
+use crate::error::Result;
+#[cfg(feature = "sqlite")]
+use crate::store::{SqliteStore, StatsStore};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 
 /// Path to the SQLite database file
 pub const DB_PATH: &str = "nfl.sqlite";
 
-/// Result of a completed trivia round containing score and total answers in the questions
-pub struct TriviaResult {
-    pub score: u32,
-    pub total: usize,
+/// A value bound to one of a query's named parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SqlValue {
+    Integer(i64),
+    Text(String),
 }
 
-/// Runs an interactive trivia game where users guess hidden player names.
-///
-/// Players have 3 strikes. Scoring is out of 1000 points, with harder answers
-/// (lower stats) worth more points. The first column should be the player name,
-/// and the last column should be the numeric stat for scoring.
-pub fn run_trivia(question: &str, sql: &str) -> Result<TriviaResult> {
-    let conn = Connection::open(DB_PATH)?;
-    let mut stmt = conn.prepare(sql)?;
-
-    let column_count = stmt.column_count();
-    let column_names: Vec<String> = (0..column_count)
-        .map(|i| stmt.column_name(i).unwrap_or("").to_string())
-        .collect();
-
-    // Fetch all rows into memory
-    let rows_iter = stmt.query_map([], |row| {
-        let mut vals = Vec::with_capacity(column_count);
-        for i in 0..column_count {
-            let v: Value = row.get(i)?;
-            let s = match v {
-                Value::Null => "NULL".to_string(),
-                Value::Integer(i) => i.to_string(),
-                Value::Real(f) => f.to_string(),
-                Value::Text(t) => t,
-                Value::Blob(_) => "<blob>".to_string(),
-            };
-            vals.push(s);
-        }
-        Ok(vals)
-    })?;
-
-    let mut rows: Vec<Vec<String>> = Vec::new();
-    for row_res in rows_iter {
-        rows.push(row_res?);
+impl From<i32> for SqlValue {
+    fn from(n: i32) -> Self {
+        SqlValue::Integer(n.into())
     }
+}
 
-    if rows.is_empty() {
-        println!("(No rows returned for this question.)");
-        return Ok(TriviaResult { score: 0, total: 0 });
+impl From<u32> for SqlValue {
+    fn from(n: u32) -> Self {
+        SqlValue::Integer(n.into())
     }
+}
 
-    let answer_col: usize = 0;
-    let total = rows.len();
-    let mut guessed = vec![false; total];
-    let mut correct = 0usize;
-    let mut strikes = 0usize;
-    let mut score = 0u32;
-
-    // Calculate point values for each answer
-    let point_values = calculate_point_values(&rows, &column_names);
-
-    println!("--- TRIVIA ---");
-    println!("{}", &question);
-    println!("Guess the hidden names! You have 3 strikes.");
-    println!("(Type a player name, e.g. 'Rudolph' or 'Mason Rudolph'. Type 'reveal' to give up.)");
-    println!();
-
-    let stdin = io::stdin();
-
-    loop {
-        if correct == total || strikes >= 3 {
-            break;
-        }
+impl From<&str> for SqlValue {
+    fn from(s: &str) -> Self {
+        SqlValue::Text(s.to_string())
+    }
+}
 
-        println!("\nQuestion: {}", question);
-        println!("--- CURRENT BOARD ---");
-        if !column_names.is_empty() {
-            println!("{}", column_names.join(" | "));
-            println!("{}", "-".repeat(column_names.join(" | ").len()));
+/// The value as a SQL literal, with text quoted
+impl fmt::Display for SqlValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlValue::Integer(n) => write!(f, "{n}"),
+            SqlValue::Text(s) => write!(f, "'{}'", s.replace('\'', "''")),
         }
+    }
+}
 
-        for (i, row) in rows.iter().enumerate() {
-            let display_cols: Vec<String> = row
-                .iter()
-                .enumerate()
-                .map(|(j, val)| {
-                    if j == answer_col && !guessed[i] {
-                        "-------".to_string()
-                    } else {
-                        val.clone()
-                    }
-                })
-                .collect();
-
-            println!("{:>2}: {}", i + 1, display_cols.join(" | "));
+#[cfg(feature = "sqlite")]
+impl rusqlite::ToSql for SqlValue {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        match self {
+            SqlValue::Integer(n) => n.to_sql(),
+            SqlValue::Text(s) => s.to_sql(),
         }
+    }
+}
 
-        println!(
-            "Correct: {}/{}  Strikes: {}/3  Score: {}",
-            correct, total, strikes, score
-        );
-        println!();
-
-        print!("Enter guess: ");
-        io::stdout().flush().ok();
-
-        let mut guess = String::new();
-        if stdin.read_line(&mut guess).is_err() {
-            println!("Error reading input, try again.");
-            continue;
-        }
-        let guess = guess.trim();
-        if guess.is_empty() {
-            continue;
-        }
+/// A question's SQL and the values of its named parameters.
+///
+/// Keeping the values out of the text means a team code or year can never
+/// change the statement, and the same statement can be prepared once for
+/// every team and year. Queries saved before parameters existed have their
+/// values inline and no bindings, and still run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Query {
+    pub sql: String,
+    /// Parameter name, with its leading colon, to value
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bindings: BTreeMap<String, SqlValue>,
+}
 
-        if guess.eq_ignore_ascii_case("reveal") {
-            break;
+impl Query {
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            bindings: BTreeMap::new(),
         }
+    }
 
-        let guess_lc = guess.to_lowercase();
-
-        // Check if already guessed
-        let mut already_got = false;
-        for (i, row) in rows.iter().enumerate() {
-            let ans_lc = row[answer_col].to_lowercase();
-            if ans_lc.contains(&guess_lc) || guess_lc.contains(&ans_lc) {
-                if guessed[i] {
-                    already_got = true;
-                    break;
-                }
-            }
-        }
-        if already_got {
-            println!("You already got that one!");
-            println!();
-            continue;
-        }
+    /// Binds `:name` to `value`
+    pub fn bind(mut self, name: &str, value: impl Into<SqlValue>) -> Self {
+        self.bindings.insert(format!(":{name}"), value.into());
+        self
+    }
 
-        // Try to match
-        let mut found_idx: Option<usize> = None;
-        for (i, row) in rows.iter().enumerate() {
-            if guessed[i] {
-                continue;
+    /// The SQL with every bound parameter replaced by its literal, for logs,
+    /// error messages, and tools that want one self-contained statement
+    pub fn inline(&self) -> String {
+        let mut out = String::with_capacity(self.sql.len());
+        let mut rest = self.sql.as_str();
+        while let Some(colon) = rest.find(':') {
+            out.push_str(&rest[..colon]);
+            let after = &rest[colon + 1..];
+            let len = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            let name = &rest[colon..colon + 1 + len];
+            match self.bindings.get(name) {
+                Some(value) => out.push_str(&value.to_string()),
+                None => out.push_str(name),
             }
-            let ans_lc = row[answer_col].to_lowercase();
-            if ans_lc.contains(&guess_lc) || guess_lc.contains(&ans_lc) {
-                found_idx = Some(i);
-                break;
-            }
-        }
-
-        if let Some(i) = found_idx {
-            guessed[i] = true;
-            correct += 1;
-            let points = point_values[i];
-            score += points;
-            println!("Correct! {} (+{} points)", rows[i][answer_col], points);
-        } else {
-            strikes += 1;
-            println!("Strike {}!", strikes);
+            rest = &after[len..];
         }
-        println!();
+        out.push_str(rest);
+        out
     }
+}
 
-    // Print full board
-    println!("--- FINAL ANSWERS ---");
-    if !column_names.is_empty() {
-        println!("{}", column_names.join(" | "));
-        println!("{}", "-".repeat(column_names.join(" | ").len()));
+impl From<&str> for Query {
+    fn from(sql: &str) -> Self {
+        Query::new(sql)
     }
-    for (i, row) in rows.iter().enumerate() {
-        let status = if guessed[i] { "✓" } else { "✗" };
-        println!(
-            "{:>2} {}: {} ({}pts)",
-            i + 1,
-            status,
-            row.join(" | "),
-            point_values[i]
-        );
+}
+
+impl From<String> for Query {
+    fn from(sql: String) -> Self {
+        Query::new(sql)
     }
-    if correct == total {
-        println!("Perfect! You got all {} answers!", total);
-    } else if strikes >= 3 {
-        println!("Three strikes, you're out!");
-    } else {
-        println!("Stopping early. Here are the full answers:");
+}
+
+/// Column names and stringified rows returned by a question's SQL
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Board {
+    pub column_names: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Board {
+    /// Point value of each row, using the same inverse weighting as the game
+    pub fn point_values(&self) -> Vec<u32> {
+        calculate_point_values(&self.rows, &self.column_names)
     }
-    println!("Final Score: {}/1000", score);
-    println!("--- END ---\n");
+}
 
-    Ok(TriviaResult { score, total })
+/// Runs a question's SQL against `nfl.sqlite` and loads the resulting board.
+///
+/// Shorthand for querying a default [`SqliteStore`], so it opens the database
+/// on every call; the engine goes through its own [`StatsStore`], which keeps
+/// one connection for the session.
+#[cfg(feature = "sqlite")]
+pub fn fetch_board(query: &Query) -> Result<Board> {
+    SqliteStore::default().query_board(query)
 }
 
 /// Calculates point values for each answer based on inverse stat weighting.
 ///
-/// Lower stats = higher points. Equal stats = equal points.
-fn calculate_point_values(rows: &[Vec<String>], _column_names: &[String]) -> Vec<u32> {
+/// Lower stats = higher points. Equal stats = equal points. Every row is worth
+/// at least one point, and the values add up to 1000 give or take a point per
+/// row. A board whose stats aren't all finite numbers (NULLs come through as
+/// "NULL") falls back to equal weight.
+pub(crate) fn calculate_point_values(rows: &[Vec<String>], _column_names: &[String]) -> Vec<u32> {
     let total = rows.len();
 
     if rows.is_empty() {
         return vec![100; total];
     }
 
-    // The stat column is always in the last column
-    let stat_col_idx = rows[0].len() - 1;
-
-    // Parse stat values
+    // The stat column is always in the last column. "NaN" and "inf" parse as
+    // floats but can't be weighted.
     let stats: Vec<f64> = rows
         .iter()
-        .filter_map(|row| {
-            if row.len() > stat_col_idx {
-                row[stat_col_idx].parse::<f64>().ok()
-            } else {
-                None
-            }
-        })
+        .filter_map(|row| row.last()?.trim().parse::<f64>().ok())
+        .filter(|stat| stat.is_finite())
         .collect();
 
-    if stats.is_empty() || stats.len() != total {
-        // Fallback to equal weight
-        let points_each = 1000 / total as u32;
-        return vec![points_each; total];
+    // Fallback to equal weight
+    let equal = vec![1000 / total as u32; total];
+    if stats.len() != total {
+        return equal;
     }
 
     // Check if all stats are the same (e.g., all have 1 TD)
     let all_same = stats.iter().all(|&s| (s - stats[0]).abs() < 0.01);
     if all_same {
-        let points_each = 1000 / total as u32;
-        return vec![points_each; total];
+        return equal;
     }
 
     // Inverse scoring: lower stats = higher points
     let max_stat = stats.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
     let min_stat = stats.iter().cloned().fold(f64::INFINITY, f64::min);
 
-    let inverses: Vec<f64> = if (max_stat - min_stat).abs() < 0.01 {
-        // If all same, equal weight
-        vec![1.0; total]
+    // The top stat's inverse is the smallest stat, which would be zero or
+    // negative for stats like rushing yards that can be; spread those boards
+    // from a floor above zero instead
+    let floor = if min_stat > 0.0 {
+        min_stat
     } else {
-        stats.iter().map(|&s| max_stat - s + min_stat).collect()
+        (max_stat - min_stat) / total as f64
     };
+    let inverses: Vec<f64> = stats.iter().map(|&s| max_stat - s + floor).collect();
 
     // Normalize to sum to 1000
     let sum: f64 = inverses.iter().sum();
-    let point_values: Vec<u32> = inverses
+    if !sum.is_finite() {
+        return equal;
+    }
+    inverses
         .iter()
-        .map(|&inv| ((inv / sum) * 1000.0).round() as u32)
-        .collect();
-
-    point_values
+        .map(|&inv| (((inv / sum) * 1000.0).round() as u32).max(1))
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_inline_fills_bound_parameters() {
+        let query =
+            Query::new("SELECT :team AS t WHERE s.season BETWEEN :start AND :end AND x = :other")
+                .bind("team", "N'O")
+                .bind("start", 2010)
+                .bind("end", 2012u32);
+        assert_eq!(
+            query.inline(),
+            "SELECT 'N''O' AS t WHERE s.season BETWEEN 2010 AND 2012 AND x = :other"
+        );
+        assert_eq!(Query::from("SELECT 1").inline(), "SELECT 1");
+    }
+
     #[test]
     fn test_equal_point_distribution() {
         // Test with equal stats (all should get equal points)
@@ -307,4 +272,111 @@ mod tests {
         // Should sum to approximately 1000 (within rounding)
         assert!((sum as i32 - 1000).abs() <= 2);
     }
+
+    fn board(stats: &[&str]) -> Vec<Vec<String>> {
+        stats
+            .iter()
+            .enumerate()
+            .map(|(i, stat)| vec![format!("Player{i}"), stat.to_string()])
+            .collect()
+    }
+
+    #[test]
+    fn test_negative_and_zero_stats_keep_every_row_positive() {
+        let points = calculate_point_values(&board(&["40", "0", "-12"]), &[]);
+        assert!(points.iter().all(|&p| p > 0), "{points:?}");
+        assert!(points[0] < points[1] && points[1] < points[2]);
+
+        let points = calculate_point_values(&board(&["100000", "1"]), &[]);
+        assert_eq!(points, [1, 1000]);
+    }
+
+    #[test]
+    fn test_unusable_stats_fall_back_to_equal_weight() {
+        for weird in ["", "NULL", "NaN", "inf", "-inf"] {
+            let points = calculate_point_values(&board(&["300", weird, "100"]), &[]);
+            assert_eq!(points, [333, 333, 333], "{weird:?}");
+        }
+        let points = calculate_point_values(&[vec![], vec!["x".into()]], &[]);
+        assert_eq!(points, [500, 500]);
+        // The spread between these overflows to infinity
+        let huge = f64::MAX.to_string();
+        let points = calculate_point_values(&board(&[&huge, &format!("-{huge}")]), &[]);
+        assert_eq!(points, [500, 500]);
+    }
+
+    mod props {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn stat() -> impl Strategy<Value = String> {
+            prop_oneof![
+                (0u32..20_000).prop_map(|n| n.to_string()),
+                (-500i32..500).prop_map(|n| n.to_string()),
+                (0.0f64..200.0).prop_map(|f| format!("{f:.1}")),
+                Just(String::new()),
+                Just("NaN".to_string()),
+                Just("inf".to_string()),
+                any::<f64>().prop_map(|f| f.to_string()),
+            ]
+        }
+
+        fn numbers() -> impl Strategy<Value = Vec<i64>> {
+            prop::collection::vec(-5_000i64..100_000, 1..=12)
+        }
+
+        fn rows(stats: &[String]) -> Vec<Vec<String>> {
+            stats.iter().map(|s| vec!["P".into(), s.clone()]).collect()
+        }
+
+        proptest! {
+            #[test]
+            fn total_stays_near_1000(stats in prop::collection::vec(stat(), 1..=12)) {
+                let points = calculate_point_values(&rows(&stats), &[]);
+                prop_assert_eq!(points.len(), stats.len());
+                let sum: u32 = points.iter().sum();
+                prop_assert!(sum.abs_diff(1000) <= stats.len() as u32, "{:?} -> {:?}", stats, points);
+                prop_assert!(points.iter().all(|&p| p <= 1000));
+            }
+
+            #[test]
+            fn no_row_is_worth_zero(stats in prop::collection::vec(stat(), 1..=12)) {
+                let points = calculate_point_values(&rows(&stats), &[]);
+                prop_assert!(points.iter().all(|&p| p > 0), "{:?} -> {:?}", stats, points);
+            }
+
+            #[test]
+            fn lower_stats_are_worth_at_least_as_much(values in numbers()) {
+                let stats: Vec<String> = values.iter().map(i64::to_string).collect();
+                let points = calculate_point_values(&rows(&stats), &[]);
+                for i in 0..values.len() {
+                    for j in 0..values.len() {
+                        if values[i] < values[j] {
+                            prop_assert!(points[i] >= points[j], "{:?} -> {:?}", values, points);
+                        }
+                        if values[i] == values[j] {
+                            prop_assert_eq!(points[i], points[j]);
+                        }
+                    }
+                }
+            }
+
+            #[test]
+            fn row_order_doesnt_change_points(mut values in numbers(), seed in any::<u64>()) {
+                use rand::seq::SliceRandom;
+                use rand::SeedableRng;
+
+                let points_of = |values: &[i64]| {
+                    let stats: Vec<String> = values.iter().map(i64::to_string).collect();
+                    let points = calculate_point_values(&rows(&stats), &[]);
+                    let mut pairs: Vec<(i64, u32)> = values.iter().copied().zip(points).collect();
+                    pairs.sort();
+                    pairs
+                };
+                let before = points_of(&values);
+                values.shuffle(&mut rand::rngs::StdRng::seed_from_u64(seed));
+                prop_assert_eq!(before, points_of(&values));
+            }
+        }
+    }
 }
@@ -0,0 +1,591 @@
+//! Where boards come from
+//!
+//! The engine asks a `StatsStore` to run each question's [`Query`] instead of
+//! opening the database itself. `SqliteStore` reads `nfl.sqlite` (`sqlite`
+//! feature), and `MockStore` serves canned boards from memory so game logic
+//! can be tested, or run in a browser, without the database file.
+#[cfg(feature = "sqlite")]
+use crate::demo;
+use crate::error::{KnowBallError, Result};
+#[cfg(feature = "sqlite")]
+use crate::questions::League;
+#[cfg(feature = "sqlite")]
+use crate::sql_runner::DB_PATH;
+use crate::sql_runner::{Board, Query};
+#[cfg(feature = "sqlite")]
+use rusqlite::{backup::Backup, types::Value, Connection, OpenFlags, Statement, ToSql};
+use std::collections::VecDeque;
+#[cfg(feature = "sqlite")]
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "sqlite")]
+use std::time::Duration;
+
+/// Runs a question's query and returns its board
+pub trait StatsStore: Send + Sync {
+    fn query_board(&self, query: &Query) -> Result<Board>;
+
+    /// Whether `query` returns at least `min` rows. Stores that can count
+    /// without reading the rows should, since this runs before every fetch.
+    fn has_rows(&self, query: &Query, min: usize) -> Result<bool> {
+        Ok(self.query_board(query)?.rows.len() >= min)
+    }
+
+    /// A store with its own connection for a worker thread, or None when
+    /// threads should share this one
+    fn for_thread(&self) -> Option<Box<dyn StatsStore>> {
+        None
+    }
+}
+
+/// A shared store, so the caller can keep a handle on the store it gave the
+/// engine
+impl<S: StatsStore + ?Sized> StatsStore for Arc<S> {
+    fn query_board(&self, query: &Query) -> Result<Board> {
+        (**self).query_board(query)
+    }
+
+    fn has_rows(&self, query: &Query, min: usize) -> Result<bool> {
+        (**self).has_rows(query, min)
+    }
+
+    fn for_thread(&self) -> Option<Box<dyn StatsStore>> {
+        (**self).for_thread()
+    }
+}
+
+/// How many prepared statements a connection keeps, enough for every
+/// question's query and its row count
+#[cfg(feature = "sqlite")]
+const STATEMENT_CACHE: usize = 64;
+
+/// Boards read from a SQLite database file.
+///
+/// The file is opened on the first query and the connection is kept for every
+/// query after it, so a session pays for the open once. Statements are
+/// prepared through the connection's cache, so a question asked again with new
+/// bindings skips the parse. Clones share the connection, and queries from
+/// several threads take turns on it.
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    path: PathBuf,
+    conn: Arc<Mutex<Option<Connection>>>,
+    /// Whether `conn` holds an in-memory copy rather than the file
+    preloaded: bool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            conn: Arc::default(),
+            preloaded: false,
+        }
+    }
+
+    /// Copies the whole database at `path` into memory with SQLite's backup
+    /// API, so every query afterwards runs without touching the disk. The
+    /// copy is read-only, like the file.
+    pub fn preload(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if !path.exists() {
+            return Err(KnowBallError::DbMissing { path });
+        }
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let file = Connection::open_with_flags(&path, flags)?;
+        let mut memory = Connection::open_in_memory()?;
+        Backup::new(&file, &mut memory)?.run_to_completion(1024, Duration::ZERO, None)?;
+        memory.pragma_update(None, "query_only", true)?;
+        memory.set_prepared_statement_cache_capacity(STATEMENT_CACHE);
+        Ok(Self {
+            path,
+            conn: Arc::new(Mutex::new(Some(memory))),
+            preloaded: true,
+        })
+    }
+
+    /// The made-up `league` from [`crate::demo`], built in memory. Like a
+    /// preloaded copy it is read-only, and [`Self::path`] is `:memory:`.
+    pub fn demo(league: League) -> Result<Self> {
+        let mut memory = Connection::open_in_memory()?;
+        demo::fill(&mut memory, league)?;
+        memory.pragma_update(None, "query_only", true)?;
+        memory.set_prepared_statement_cache_capacity(STATEMENT_CACHE);
+        Ok(Self {
+            path: PathBuf::from(":memory:"),
+            conn: Arc::new(Mutex::new(Some(memory))),
+            preloaded: true,
+        })
+    }
+
+    /// The database file, which a preloaded store no longer reads
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The detail line of each step in SQLite's plan for `query`, from
+    /// `EXPLAIN QUERY PLAN`
+    pub fn query_plan(&self, query: &Query) -> Result<Vec<String>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {}", query.sql))?;
+            let params = bound_params(&stmt, query)?;
+            let steps = stmt.query_map(params.as_slice(), |row| row.get::<_, String>("detail"))?;
+            Ok(steps.collect::<rusqlite::Result<_>>()?)
+        })
+    }
+
+    /// Runs `f` on the store's connection, opening it first if needed.
+    ///
+    /// Fails with `DbMissing` instead of letting SQLite create an empty file
+    /// when the database isn't there. The database is opened read-only, so
+    /// SQL from a shared pack can't change it.
+    fn with_connection<T>(&self, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        if conn.is_none() {
+            if !self.path.exists() {
+                return Err(KnowBallError::DbMissing {
+                    path: self.path.clone(),
+                });
+            }
+            let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+            let opened = Connection::open_with_flags(&self.path, flags)?;
+            opened.set_prepared_statement_cache_capacity(STATEMENT_CACHE);
+            *conn = Some(opened);
+        }
+        f(conn.as_ref().expect("connection was just opened")).map_err(|e| self.missing_table(e))
+    }
+
+    /// Turns SQLite's "no such table" into [`KnowBallError::MissingTable`]
+    fn missing_table(&self, e: KnowBallError) -> KnowBallError {
+        if let KnowBallError::Sql(rusqlite::Error::SqliteFailure(_, Some(message))) = &e {
+            if let Some(table) = message.strip_prefix("no such table: ") {
+                return KnowBallError::MissingTable {
+                    path: self.path.display().to_string(),
+                    table: table.to_string(),
+                };
+            }
+        }
+        e
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Default for SqliteStore {
+    /// The `nfl.sqlite` file in the working directory
+    fn default() -> Self {
+        Self::new(DB_PATH)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl StatsStore for SqliteStore {
+    fn query_board(&self, query: &Query) -> Result<Board> {
+        self.with_connection(|conn| run(conn, query))
+    }
+
+    /// Counts at most `min` rows in SQLite instead of reading the board
+    fn has_rows(&self, query: &Query, min: usize) -> Result<bool> {
+        // Newlines keep a trailing `--` comment from swallowing the wrapper
+        let sql = query.sql.trim().trim_end_matches(';');
+        let count = format!("SELECT COUNT(*) FROM (SELECT 1 FROM (\n{sql}\n) LIMIT {min})");
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare_cached(&count)?;
+            let params = bound_params(&stmt, query)?;
+            let found: i64 = stmt.query_row(params.as_slice(), |row| row.get(0))?;
+            Ok(found as usize >= min)
+        })
+    }
+
+    /// A second connection to the same file. A preloaded copy is shared
+    /// instead, since another copy would cost as much as the queries it saves.
+    fn for_thread(&self) -> Option<Box<dyn StatsStore>> {
+        (!self.preloaded).then(|| Box::new(Self::new(&self.path)) as Box<dyn StatsStore>)
+    }
+}
+
+/// The values `stmt` takes from `query`'s bindings, by name. A parameter
+/// left unbound would quietly run as NULL, so it is an error instead.
+#[cfg(feature = "sqlite")]
+fn bound_params<'q>(stmt: &Statement, query: &'q Query) -> Result<Vec<(&'q str, &'q dyn ToSql)>> {
+    let mut params = Vec::with_capacity(stmt.parameter_count());
+    for index in 1..=stmt.parameter_count() {
+        let name = stmt.parameter_name(index).unwrap_or("?");
+        match query.bindings.get_key_value(name) {
+            Some((name, value)) => params.push((name.as_str(), value as &dyn ToSql)),
+            None => return Err(KnowBallError::UnboundParameter(name.to_string())),
+        }
+    }
+    Ok(params)
+}
+
+/// Runs `query` and reads every row as text
+#[cfg(feature = "sqlite")]
+fn run(conn: &Connection, query: &Query) -> Result<Board> {
+    let mut stmt = conn.prepare_cached(&query.sql)?;
+    let params = bound_params(&stmt, query)?;
+
+    let column_count = stmt.column_count();
+    let column_names: Vec<String> = (0..column_count)
+        .map(|i| stmt.column_name(i).unwrap_or("").to_string())
+        .collect();
+
+    // Fetch all rows into memory
+    let rows_iter = stmt.query_map(params.as_slice(), |row| {
+        let mut vals = Vec::with_capacity(column_count);
+        for i in 0..column_count {
+            let v: Value = row.get(i)?;
+            let s = match v {
+                Value::Null => "NULL".to_string(),
+                Value::Integer(i) => i.to_string(),
+                Value::Real(f) => f.to_string(),
+                Value::Text(t) => t,
+                Value::Blob(_) => "<blob>".to_string(),
+            };
+            vals.push(s);
+        }
+        Ok(vals)
+    })?;
+
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for row_res in rows_iter {
+        rows.push(row_res?);
+    }
+
+    Ok(Board { column_names, rows })
+}
+
+/// In-memory store for tests: serves the first board whose pattern appears in
+/// the SQL (with its parameters [inlined](Query::inline)), or the fallback
+/// board, and records every query it was asked.
+///
+/// Scripted steps from [`MockStore::then_board`] and [`MockStore::then_fail`]
+/// come first, one per fetch in the order they were added, so a test can
+/// line up a session's boards or make a query fail partway through.
+#[derive(Debug, Default)]
+pub struct MockStore {
+    boards: Vec<(String, Board)>,
+    fallback: Option<Board>,
+    script: Mutex<VecDeque<std::result::Result<Board, String>>>,
+    queries: Mutex<Vec<String>>,
+}
+
+impl MockStore {
+    /// A store that answers every query with `board`
+    pub fn new(board: Board) -> Self {
+        Self {
+            fallback: Some(board),
+            ..Default::default()
+        }
+    }
+
+    /// Serves `board` for any SQL containing `pattern`
+    pub fn with_board(mut self, pattern: &str, board: Board) -> Self {
+        self.boards.push((pattern.to_string(), board));
+        self
+    }
+
+    /// Serves `board` for the next fetch not already scripted, whatever its SQL
+    pub fn then_board(self, board: Board) -> Self {
+        self.script.lock().unwrap().push_back(Ok(board));
+        self
+    }
+
+    /// Fails the next fetch not already scripted with [`KnowBallError::Store`]
+    pub fn then_fail(self, message: &str) -> Self {
+        self.script
+            .lock()
+            .unwrap()
+            .push_back(Err(message.to_string()));
+        self
+    }
+
+    /// Every query so far as inlined SQL, oldest first. Row counts from
+    /// `has_rows` aren't recorded.
+    pub fn queries(&self) -> Vec<String> {
+        self.queries.lock().unwrap().clone()
+    }
+
+    fn find(&self, sql: &str) -> Result<&Board> {
+        self.boards
+            .iter()
+            .find(|(pattern, _)| sql.contains(pattern.as_str()))
+            .map(|(_, board)| board)
+            .or(self.fallback.as_ref())
+            .ok_or_else(|| KnowBallError::NoBoard(sql.to_string()))
+    }
+}
+
+impl StatsStore for MockStore {
+    /// Fails with `NoBoard` when no board matches
+    fn query_board(&self, query: &Query) -> Result<Board> {
+        let sql = query.inline();
+        self.queries.lock().unwrap().push(sql.clone());
+        match self.script.lock().unwrap().pop_front() {
+            Some(step) => step.map_err(KnowBallError::Store),
+            None => self.find(&sql).cloned(),
+        }
+    }
+
+    /// Counts the next scripted board without using it up. A scripted
+    /// failure counts as enough rows, so it is the fetch that fails.
+    fn has_rows(&self, query: &Query, min: usize) -> Result<bool> {
+        match self.script.lock().unwrap().front() {
+            Some(Ok(board)) => Ok(board.rows.len() >= min),
+            Some(Err(_)) => Ok(true),
+            None => Ok(self.find(&query.inline())?.rows.len() >= min),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(name: &str) -> Board {
+        Board {
+            column_names: vec!["name".into(), "yards".into()],
+            rows: vec![vec![name.into(), "100".into()]],
+        }
+    }
+
+    #[test]
+    fn test_mock_matches_patterns_in_order() {
+        let store = MockStore::new(board("Fallback"))
+            .with_board("'PIT'", board("Steeler"))
+            .with_board("SELECT", board("Anyone"));
+
+        let pit = store
+            .query_board(&Query::from("SELECT ... WHERE team_abbr = 'PIT'"))
+            .unwrap();
+        assert_eq!(pit.rows[0][0], "Steeler");
+        assert_eq!(
+            store.query_board(&Query::from("SELECT 1")).unwrap().rows[0][0],
+            "Anyone"
+        );
+        assert_eq!(
+            store.query_board(&Query::from("PRAGMA")).unwrap().rows[0][0],
+            "Fallback"
+        );
+        assert_eq!(store.queries().len(), 3);
+    }
+
+    #[test]
+    fn test_mock_counts_without_recording() {
+        let store = MockStore::default().with_board("'PIT'", board("Steeler"));
+        assert!(store
+            .has_rows(&Query::from("WHERE team_abbr = 'PIT'"), 1)
+            .unwrap());
+        assert!(!store
+            .has_rows(&Query::from("WHERE team_abbr = 'PIT'"), 2)
+            .unwrap());
+        assert!(store.has_rows(&Query::from("SELECT 1"), 1).is_err());
+        assert!(store.queries().is_empty());
+    }
+
+    #[test]
+    fn test_mock_without_fallback_errors() {
+        let store = MockStore::default();
+        assert!(matches!(
+            store.query_board(&Query::from("SELECT 1")),
+            Err(KnowBallError::NoBoard(sql)) if sql == "SELECT 1"
+        ));
+    }
+
+    #[test]
+    fn test_mock_script_comes_first() {
+        let store = MockStore::new(board("Fallback"))
+            .then_board(board("First"))
+            .then_fail("connection reset")
+            .then_board(board("Third"));
+
+        assert!(store.has_rows(&Query::from("SELECT 1"), 1).unwrap());
+        assert!(!store.has_rows(&Query::from("SELECT 1"), 2).unwrap());
+        assert_eq!(
+            store.query_board(&Query::from("SELECT 1")).unwrap().rows[0][0],
+            "First"
+        );
+        assert!(store.has_rows(&Query::from("SELECT 1"), 100).unwrap());
+        assert!(matches!(
+            store.query_board(&Query::from("SELECT 2")),
+            Err(KnowBallError::Store(message)) if message == "connection reset"
+        ));
+        assert_eq!(
+            store.query_board(&Query::from("SELECT 3")).unwrap().rows[0][0],
+            "Third"
+        );
+        assert_eq!(
+            store.query_board(&Query::from("SELECT 4")).unwrap().rows[0][0],
+            "Fallback"
+        );
+        assert_eq!(store.queries().len(), 4);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_missing_file() {
+        let store = SqliteStore::new("does/not/exist.sqlite");
+        assert!(matches!(
+            store.query_board(&Query::from("SELECT 1")),
+            Err(KnowBallError::DbMissing { .. })
+        ));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_preload_copies_into_memory() {
+        let path =
+            std::env::temp_dir().join(format!("know_ball_preload_{}.sqlite", std::process::id()));
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE players (name TEXT); INSERT INTO players VALUES ('Tom Brady');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = SqliteStore::preload(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let board = store
+            .query_board(&Query::from("SELECT name FROM players"))
+            .unwrap();
+        assert_eq!(board.rows, [["Tom Brady"]]);
+        assert!(store
+            .query_board(&Query::from("DELETE FROM players"))
+            .is_err());
+        assert!(matches!(
+            SqliteStore::preload(&path),
+            Err(KnowBallError::DbMissing { .. })
+        ));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_demo_store_is_filled_and_read_only() {
+        let store = SqliteStore::demo(League::Nfl).unwrap();
+        let query = Query::new("SELECT name FROM players WHERE player_id = :id")
+            .bind("id", demo::player_id("PIT", "QB1", 2024).as_str());
+        assert_eq!(store.query_board(&query).unwrap().rows.len(), 1);
+        assert!(store
+            .query_board(&Query::from("DELETE FROM players"))
+            .is_err());
+        assert!(store.for_thread().is_none());
+
+        let nba = SqliteStore::demo(League::Nba).unwrap();
+        assert!(nba
+            .has_rows(&Query::from("SELECT points FROM games"), 10)
+            .unwrap());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_reuses_its_connection() {
+        let path =
+            std::env::temp_dir().join(format!("know_ball_store_{}.sqlite", std::process::id()));
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE players (name TEXT); INSERT INTO players VALUES ('Tom Brady');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = SqliteStore::new(&path);
+        let clone = store.clone();
+        assert_eq!(
+            store
+                .query_board(&Query::from("SELECT name FROM players"))
+                .unwrap()
+                .rows[0][0],
+            "Tom Brady"
+        );
+        // The open connection still reads the file after it is unlinked
+        std::fs::remove_file(&path).unwrap();
+        let board = clone
+            .query_board(&Query::from("SELECT COUNT(*) AS n FROM players"))
+            .unwrap();
+        assert_eq!(board.column_names, ["n"]);
+        assert_eq!(board.rows, [["1"]]);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_counts_rows() {
+        let path =
+            std::env::temp_dir().join(format!("know_ball_count_{}.sqlite", std::process::id()));
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE players (name TEXT); INSERT INTO players VALUES ('A'), ('B');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = SqliteStore::new(&path);
+        let sql = "SELECT name FROM players ORDER BY name -- everyone";
+        assert!(store.has_rows(&Query::from(sql), 2).unwrap());
+        assert!(!store.has_rows(&Query::from(sql), 3).unwrap());
+        assert!(store
+            .has_rows(&Query::from("SELECT name FROM players;\n"), 1)
+            .unwrap());
+        assert!(store
+            .has_rows(&Query::from("SELECT nope FROM players"), 1)
+            .is_err());
+        assert!(matches!(
+            store.query_board(&Query::from("SELECT name FROM seasons")),
+            Err(KnowBallError::MissingTable { table, .. }) if table == "seasons"
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_binds_named_parameters() {
+        let path =
+            std::env::temp_dir().join(format!("know_ball_bind_{}.sqlite", std::process::id()));
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE players (name TEXT, year INTEGER);
+             INSERT INTO players VALUES ('O''Brien', 2020), ('Brady', 2021);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = SqliteStore::new(&path);
+        let sql = "SELECT name FROM players WHERE name = :name OR year = :year";
+        let query = Query::new(sql).bind("name", "O'Brien").bind("year", 2021);
+        let board = store.query_board(&query).unwrap();
+        assert_eq!(board.rows, [["O'Brien"], ["Brady"]]);
+        assert!(store.has_rows(&query, 2).unwrap());
+        assert!(matches!(
+            store.query_board(&Query::new(sql).bind("name", "Brady")),
+            Err(KnowBallError::UnboundParameter(name)) if name == ":year"
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    // The cached statement must take the new bindings, not the last ones
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_cached_statement_rebinds() {
+        let path =
+            std::env::temp_dir().join(format!("know_ball_cache_{}.sqlite", std::process::id()));
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE players (name TEXT, year INTEGER);
+             INSERT INTO players VALUES ('Manning', 2020), ('Brady', 2021);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = SqliteStore::new(&path);
+        let sql = "SELECT name FROM players WHERE year = :year";
+        for (year, name) in [(2020, "Manning"), (2021, "Brady"), (2020, "Manning")] {
+            let query = Query::new(sql).bind("year", year);
+            assert!(store.has_rows(&query, 1).unwrap());
+            assert_eq!(store.query_board(&query).unwrap().rows, [[name]]);
+        }
+        assert!(!store
+            .has_rows(&Query::new(sql).bind("year", 1999), 1)
+            .unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! Survival runs on top of a [`GameSession`]
+//!
+//! Random boards keep coming, and strikes carry over from one board to the
+//! next: a board is played with only the strikes the run has left, and the
+//! run ends once they're all used. Its score is every point earned before
+//! then. Giving up a board ends the run too; passing on rows or skipping a
+//! board doesn't.
+use crate::error::Result;
+use crate::game::{TriviaGame, TriviaResult, MAX_STRIKES};
+use crate::questions::QuestionSpec;
+use crate::rules::GameRules;
+use crate::run;
+use crate::session::GameSession;
+use crate::speech::plural;
+
+/// One board of the run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurvivalBoard {
+    pub code: String,
+    pub score: u32,
+    pub correct: usize,
+    pub total: usize,
+    pub strikes: usize,
+}
+
+/// A run of boards sharing one set of strikes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Survival {
+    max_strikes: usize,
+    boards: Vec<SurvivalBoard>,
+    gave_up: bool,
+}
+
+impl Default for Survival {
+    fn default() -> Self {
+        Self::new(MAX_STRIKES)
+    }
+}
+
+impl Survival {
+    /// A run that ends after `max_strikes` misses in all
+    pub fn new(max_strikes: usize) -> Self {
+        Self {
+            max_strikes: max_strikes.max(1),
+            boards: Vec::new(),
+            gave_up: false,
+        }
+    }
+
+    pub fn boards(&self) -> &[SurvivalBoard] {
+        &self.boards
+    }
+
+    /// Misses so far, across every board
+    pub fn strikes(&self) -> usize {
+        self.boards.iter().map(|board| board.strikes).sum()
+    }
+
+    pub fn strikes_left(&self) -> usize {
+        self.max_strikes.saturating_sub(self.strikes())
+    }
+
+    /// Every point earned in the run
+    pub fn score(&self) -> u32 {
+        self.boards.iter().map(|board| board.score).sum()
+    }
+
+    /// Over once the strikes are used up or the player gives up a board
+    pub fn is_over(&self) -> bool {
+        self.gave_up || self.strikes_left() == 0
+    }
+
+    /// Loads a random board, plays it with the strikes the run has left, and
+    /// counts it. The session's other rules still apply, and its strike
+    /// count is put back afterwards. An empty board doesn't count.
+    pub fn play_board<F>(&mut self, session: &mut GameSession, play: F) -> Result<TriviaResult>
+    where
+        F: FnOnce(&QuestionSpec, &mut TriviaGame),
+    {
+        let strikes = Some(self.strikes_left());
+        let result = run::play_random_board(session, |rules| GameRules { strikes, ..rules }, play)?;
+        if result.total > 0 {
+            self.record(&result);
+        }
+        Ok(result)
+    }
+
+    /// Counts a finished board
+    pub fn record(&mut self, result: &TriviaResult) {
+        self.gave_up = run::gave_up(result);
+        self.boards.push(SurvivalBoard {
+            code: run::code(result),
+            score: result.score,
+            correct: result.correct(),
+            total: result.total,
+            strikes: result.strikes,
+        });
+    }
+
+    /// The rules of the run, before the first board
+    pub fn intro_line(&self) -> String {
+        format!(
+            "Survival: boards keep coming, and strikes carry over from one to the next. You have {}.",
+            plural(self.max_strikes, "strike")
+        )
+    }
+
+    /// Where the run stands between boards
+    pub fn status_line(&self) -> String {
+        format!(
+            "Survival: {} after {}, {} left",
+            points(self.score()),
+            plural(self.boards.len(), "board"),
+            plural(self.strikes_left(), "strike")
+        )
+    }
+
+    /// How the run went, once it's over
+    pub fn final_line(&self) -> String {
+        let ending = if self.gave_up {
+            "You stopped"
+        } else {
+            "Out of strikes"
+        };
+        format!(
+            "{ending} after {}. Survival score: {}.",
+            plural(self.boards.len(), "board"),
+            points(self.score())
+        )
+    }
+}
+
+fn points(score: u32) -> String {
+    plural(score as usize, "point")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameEnd, RowResult};
+    use std::time::Duration;
+
+    fn result(score: u32, strikes: usize, end: GameEnd) -> TriviaResult {
+        let row = |guessed: bool| RowResult {
+            name: "Tom Brady".into(),
+            stat: "5000".into(),
+            points: score,
+            guessed,
+            passed: false,
+            cells: vec!["Tom Brady".into(), "5000".into()],
+            found_at: None,
+        };
+        TriviaResult {
+            spec: None,
+            question: "q".into(),
+            column_names: vec!["name".into(), "yards".into()],
+            score,
+            total: 2,
+            rows: vec![row(score > 0), row(false)],
+            strikes,
+            hints: 0,
+            best_streak: 0,
+            streak_bonus: 0,
+            elapsed: Duration::ZERO,
+            end: Some(end),
+        }
+    }
+
+    #[test]
+    fn test_strikes_carry_over() {
+        let mut run = Survival::default();
+        run.record(&result(400, 1, GameEnd::Perfect));
+        assert_eq!((run.strikes_left(), run.is_over()), (2, false));
+        assert_eq!(
+            run.status_line(),
+            "Survival: 400 points after 1 board, 2 strikes left"
+        );
+        run.record(&result(250, 2, GameEnd::StruckOut));
+        assert!(run.is_over());
+        assert_eq!(run.score(), 650);
+        assert_eq!(
+            run.final_line(),
+            "Out of strikes after 2 boards. Survival score: 650 points."
+        );
+    }
+
+    #[test]
+    fn test_giving_up_ends_the_run() {
+        let mut run = Survival::new(3);
+        run.record(&result(0, 0, GameEnd::GaveUp));
+        assert!(run.is_over());
+        assert_eq!(run.strikes_left(), 3);
+        assert!(run.final_line().starts_with("You stopped after 1 board."));
+    }
+}
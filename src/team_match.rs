@@ -0,0 +1,337 @@
+//! Team-vs-team matches on top of a [`GameSession`]
+//!
+//! Two teams of local players take turns: each round, the first team plays a
+//! board and then the second team plays one, each from a category its
+//! captain picks. The higher score takes the round, and the first team to
+//! win a majority of the best-of-N rounds takes the match. If the rounds end
+//! level, total points decide it. `report` renders the whole match as
+//! Markdown.
+use crate::error::{KnowBallError, Result};
+use crate::export::markdown_table;
+use crate::game::{TriviaGame, TriviaResult};
+use crate::questions::{by_category, Category, QuestionParams, QuestionSpec};
+use crate::session::GameSession;
+use rand::seq::SliceRandom;
+use std::fmt::Write as _;
+use thiserror::Error;
+
+/// Rounds in a match when none is given
+pub const DEFAULT_BEST_OF: u32 = 3;
+
+/// A team and its players; the first player is the captain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Team {
+    pub name: String,
+    pub players: Vec<String>,
+}
+
+impl Team {
+    pub fn captain(&self) -> &str {
+        &self.players[0]
+    }
+}
+
+/// Why a match couldn't be set up
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MatchError {
+    #[error("team '{0}' has no players")]
+    NoPlayers(String),
+    #[error("both teams are called '{0}'")]
+    SameName(String),
+    #[error("a match is best of an odd number of rounds, not {0}")]
+    BestOf(u32),
+}
+
+/// One board a team played
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchBoard {
+    /// 0 for the first team, 1 for the second
+    pub team: usize,
+    /// The captain's pick, or None for any category
+    pub category: Option<Category>,
+    pub code: String,
+    pub text: String,
+    pub score: u32,
+    pub correct: usize,
+    pub total: usize,
+}
+
+/// A best-of-N match between two teams
+#[derive(Debug, Clone)]
+pub struct TeamMatch {
+    teams: [Team; 2],
+    best_of: u32,
+    /// In play order: round 1's boards first, the first team's before the
+    /// second's
+    boards: Vec<MatchBoard>,
+}
+
+impl TeamMatch {
+    pub fn new(first: Team, second: Team, best_of: u32) -> std::result::Result<Self, MatchError> {
+        for team in [&first, &second] {
+            if team.players.is_empty() {
+                return Err(MatchError::NoPlayers(team.name.clone()));
+            }
+        }
+        if first.name.eq_ignore_ascii_case(&second.name) {
+            return Err(MatchError::SameName(second.name));
+        }
+        if best_of.is_multiple_of(2) {
+            return Err(MatchError::BestOf(best_of));
+        }
+        Ok(Self {
+            teams: [first, second],
+            best_of,
+            boards: Vec::new(),
+        })
+    }
+
+    pub fn teams(&self) -> &[Team; 2] {
+        &self.teams
+    }
+
+    pub fn boards(&self) -> &[MatchBoard] {
+        &self.boards
+    }
+
+    /// The round being played, from 1
+    pub fn round(&self) -> u32 {
+        self.boards.len() as u32 / 2 + 1
+    }
+
+    /// The team whose turn it is
+    pub fn up(&self) -> usize {
+        self.boards.len() % 2
+    }
+
+    /// Rounds each team has taken; a level round goes to neither
+    pub fn rounds_won(&self) -> [u32; 2] {
+        let mut won = [0, 0];
+        for round in self.boards.chunks_exact(2) {
+            match round[0].score.cmp(&round[1].score) {
+                std::cmp::Ordering::Greater => won[0] += 1,
+                std::cmp::Ordering::Less => won[1] += 1,
+                std::cmp::Ordering::Equal => {}
+            }
+        }
+        won
+    }
+
+    /// Every point each team has scored
+    pub fn points(&self) -> [u32; 2] {
+        let mut points = [0, 0];
+        for board in &self.boards {
+            points[board.team] += board.score;
+        }
+        points
+    }
+
+    /// Over once a team has a majority of the rounds or every round is
+    /// played
+    pub fn is_over(&self) -> bool {
+        let majority = self.best_of / 2 + 1;
+        let played = self.boards.len() as u32 / 2;
+        self.up() == 0
+            && (played >= self.best_of || self.rounds_won().iter().any(|&won| won >= majority))
+    }
+
+    /// The winning team once the match is over: most rounds, then most
+    /// points. None while it's on, or for a draw.
+    pub fn winner(&self) -> Option<usize> {
+        if !self.is_over() {
+            return None;
+        }
+        let (won, points) = (self.rounds_won(), self.points());
+        match won[0].cmp(&won[1]).then(points[0].cmp(&points[1])) {
+            std::cmp::Ordering::Greater => Some(0),
+            std::cmp::Ordering::Less => Some(1),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Loads a board for the team that's up, from the captain's category if
+    /// one was picked, and hands it to `play`. The board counts for the team
+    /// and is recorded in the session like any other; an empty board counts
+    /// for neither, so the same team goes again.
+    pub fn play_board<F>(
+        &mut self,
+        session: &mut GameSession,
+        category: Option<Category>,
+        play: F,
+    ) -> Result<TriviaResult>
+    where
+        F: FnOnce(&QuestionSpec, &mut TriviaGame),
+    {
+        let engine = session.engine_mut();
+        let question = match category {
+            Some(category) => {
+                let questions = by_category(engine.registry(), category);
+                questions.choose(engine.rng_mut()).cloned()
+            }
+            None => engine.random_question(),
+        };
+        let question =
+            question.ok_or_else(|| KnowBallError::Parse("no questions in that category".into()))?;
+        let result = session.play_question(question.as_ref(), &QuestionParams::default(), play)?;
+        if result.total > 0 {
+            self.record(category, &result);
+        }
+        Ok(result)
+    }
+
+    /// Counts a finished board for the team that's up
+    pub fn record(&mut self, category: Option<Category>, result: &TriviaResult) {
+        let (code, text) = match &result.spec {
+            Some(spec) => (spec.code.clone(), spec.text.clone()),
+            None => (String::new(), result.question.clone()),
+        };
+        self.boards.push(MatchBoard {
+            team: self.up(),
+            category,
+            code,
+            text,
+            score: result.score,
+            correct: result.correct(),
+            total: result.total,
+        });
+    }
+
+    /// One line with the rounds and points so far
+    pub fn score_line(&self) -> String {
+        let ([a, b], won, points) = (&self.teams, self.rounds_won(), self.points());
+        format!(
+            "{} {}, {} {} in rounds ({} to {} in points)",
+            a.name, won[0], b.name, won[1], points[0], points[1]
+        )
+    }
+
+    /// The match as Markdown: the result, each round, and the teams
+    pub fn report(&self) -> String {
+        let [a, b] = &self.teams;
+        let mut out = format!("# {} vs {}\n\n", a.name, b.name);
+        let won = self.rounds_won();
+        let verdict = match self.winner() {
+            Some(team) => format!("{} wins", self.teams[team].name),
+            None if self.is_over() => "A draw".to_string(),
+            None => "In progress".to_string(),
+        };
+        let points = self.points();
+        let _ = writeln!(
+            out,
+            "{verdict}, {} rounds to {} (best of {}). Points: {} {}, {} {}.\n",
+            won[0], won[1], self.best_of, a.name, points[0], b.name, points[1]
+        );
+
+        out.push_str("## Rounds\n\n");
+        let header = ["Round", &a.name, &b.name, "Winner"].map(String::from);
+        let board = |board: &MatchBoard| {
+            let category = board.category.map_or("any", |c| c.as_str());
+            format!(
+                "{} ({category}, {}/{})",
+                board.score, board.correct, board.total
+            )
+        };
+        let rows: Vec<Vec<String>> = self
+            .boards
+            .chunks(2)
+            .enumerate()
+            .map(|(i, round)| {
+                let second = round.get(1);
+                let winner = match second.map(|s| round[0].score.cmp(&s.score)) {
+                    Some(std::cmp::Ordering::Greater) => a.name.clone(),
+                    Some(std::cmp::Ordering::Less) => b.name.clone(),
+                    Some(std::cmp::Ordering::Equal) => "Level".to_string(),
+                    None => String::new(),
+                };
+                vec![
+                    (i + 1).to_string(),
+                    board(&round[0]),
+                    second.map(board).unwrap_or_default(),
+                    winner,
+                ]
+            })
+            .collect();
+        markdown_table(&mut out, &header, &rows);
+
+        out.push_str("## Teams\n\n");
+        for team in &self.teams {
+            let _ = writeln!(
+                out,
+                "- **{}**: {} (captain {})",
+                team.name,
+                team.players.join(", "),
+                team.captain()
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team(name: &str, players: &[&str]) -> Team {
+        Team {
+            name: name.into(),
+            players: players.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    fn result(score: u32) -> TriviaResult {
+        TriviaResult {
+            score,
+            total: 10,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_new_checks_setup() {
+        let red = team("Red", &["Ann", "Ben"]);
+        assert_eq!(
+            TeamMatch::new(red.clone(), team("Blue", &[]), 3).unwrap_err(),
+            MatchError::NoPlayers("Blue".into())
+        );
+        assert_eq!(
+            TeamMatch::new(red.clone(), team("red", &["Cam"]), 3).unwrap_err(),
+            MatchError::SameName("red".into())
+        );
+        assert_eq!(
+            TeamMatch::new(red, team("Blue", &["Cam"]), 4).unwrap_err(),
+            MatchError::BestOf(4)
+        );
+    }
+
+    #[test]
+    fn test_majority_ends_the_match() {
+        let mut m = TeamMatch::new(team("Red", &["Ann"]), team("Blue", &["Cam"]), 3).unwrap();
+        for (score, up) in [(500, 0), (300, 1), (400, 0)] {
+            assert_eq!(m.up(), up);
+            m.record(None, &result(score));
+        }
+        assert!(!m.is_over());
+        m.record(Some(Category::Rushing), &result(200));
+        assert_eq!(m.rounds_won(), [2, 0]);
+        assert_eq!(m.round(), 3);
+        assert!(m.is_over());
+        assert_eq!(m.winner(), Some(0));
+
+        let report = m.report();
+        assert!(report.starts_with(
+            "# Red vs Blue\n\nRed wins, 2 rounds to 0 (best of 3). Points: Red 900, Blue 500.\n"
+        ));
+        assert!(report.contains("| 2 | 400 (any, 0/10) | 200 (rushing, 0/10) | Red |"));
+        assert!(report.contains("- **Red**: Ann (captain Ann)"));
+    }
+
+    #[test]
+    fn test_level_rounds_go_to_points() {
+        let mut m = TeamMatch::new(team("Red", &["Ann"]), team("Blue", &["Cam"]), 1).unwrap();
+        m.record(None, &result(300));
+        m.record(None, &result(300));
+        assert_eq!(m.rounds_won(), [0, 0]);
+        assert!(m.is_over());
+        assert_eq!(m.winner(), None);
+    }
+}
@@ -0,0 +1,248 @@
+//! Colors and symbols for the terminal board
+//!
+//! Everything the text frontend draws in color or marks with a symbol goes
+//! through a [`Theme`], so a palette can be swapped without touching the
+//! renderers. Colors are only written to a terminal that hasn't set
+//! `NO_COLOR`; piped output keeps the theme's symbols but drops the escapes.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::IsTerminal;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// An ANSI text style, or none
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    /// The SGR parameters, e.g. "1;32" for bold green; empty for plain text
+    code: &'static str,
+}
+
+impl Style {
+    pub const PLAIN: Style = Style { code: "" };
+
+    const fn ansi(code: &'static str) -> Self {
+        Style { code }
+    }
+
+    /// Appends `text` to `out` in this style
+    pub fn push(&self, out: &mut String, text: &str) {
+        if self.code.is_empty() {
+            out.push_str(text);
+        } else {
+            out.push_str("\x1b[");
+            out.push_str(self.code);
+            out.push('m');
+            out.push_str(text);
+            out.push_str("\x1b[0m");
+        }
+    }
+
+    /// `text` in this style
+    pub fn paint(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len() + 12);
+        self.push(&mut out, text);
+        out
+    }
+}
+
+/// The built-in themes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Default,
+    /// Bold, bright colors and heavier marks
+    HighContrast,
+    /// Blue and orange instead of green and red
+    #[serde(alias = "deuteranopia-safe")]
+    Deuteranopia,
+    /// No color at all, with ASCII marks
+    Mono,
+}
+
+impl ThemeName {
+    pub const ALL: [ThemeName; 4] = [
+        ThemeName::Default,
+        ThemeName::HighContrast,
+        ThemeName::Deuteranopia,
+        ThemeName::Mono,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThemeName::Default => "default",
+            ThemeName::HighContrast => "high-contrast",
+            ThemeName::Deuteranopia => "deuteranopia",
+            ThemeName::Mono => "mono",
+        }
+    }
+}
+
+impl fmt::Display for ThemeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ThemeName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "default" => Ok(ThemeName::Default),
+            "high-contrast" => Ok(ThemeName::HighContrast),
+            "deuteranopia" | "deuteranopia-safe" => Ok(ThemeName::Deuteranopia),
+            "mono" => Ok(ThemeName::Mono),
+            _ => Err(format!(
+                "unknown theme '{s}', expected default, high-contrast, deuteranopia, or mono"
+            )),
+        }
+    }
+}
+
+/// The styles and symbols one theme draws a board with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: ThemeName,
+    /// Column names and section rules
+    pub heading: Style,
+    /// The placeholder for a name that hasn't been guessed
+    pub hidden: Style,
+    /// Names the player found, and "Correct!"
+    pub found: Style,
+    /// Names the player missed, and strikes
+    pub missed: Style,
+    pub found_mark: &'static str,
+    pub missed_mark: &'static str,
+    pub hidden_name: &'static str,
+}
+
+impl Theme {
+    /// `name`'s theme with its colors
+    pub fn new(name: ThemeName) -> Self {
+        let base = Theme {
+            name,
+            heading: Style::ansi("1"),
+            hidden: Style::ansi("2"),
+            found: Style::ansi("32"),
+            missed: Style::ansi("31"),
+            found_mark: "✓",
+            missed_mark: "✗",
+            hidden_name: "-------",
+        };
+        match name {
+            ThemeName::Default => base,
+            ThemeName::HighContrast => Theme {
+                heading: Style::ansi("1;4"),
+                hidden: Style::ansi("1"),
+                found: Style::ansi("1;92"),
+                missed: Style::ansi("1;91"),
+                found_mark: "✔",
+                missed_mark: "✘",
+                ..base
+            },
+            ThemeName::Deuteranopia => Theme {
+                found: Style::ansi("1;34"),
+                missed: Style::ansi("38;5;208"),
+                ..base
+            },
+            ThemeName::Mono => Theme {
+                found_mark: "[x]",
+                missed_mark: "[ ]",
+                ..base
+            }
+            .plain(),
+        }
+    }
+
+    /// The same symbols without any color
+    pub fn plain(self) -> Self {
+        Theme {
+            heading: Style::PLAIN,
+            hidden: Style::PLAIN,
+            found: Style::PLAIN,
+            missed: Style::PLAIN,
+            ..self
+        }
+    }
+
+    /// `name`'s theme for standard output: in color only on a terminal
+    /// without `NO_COLOR` set
+    pub fn for_stdout(name: ThemeName) -> Self {
+        let theme = Theme::new(name);
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        if std::io::stdout().is_terminal() && !no_color {
+            theme
+        } else {
+            theme.plain()
+        }
+    }
+}
+
+/// The default theme without color, which is how boards are drawn until
+/// [`set`] picks another
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::new(ThemeName::Default).plain()
+    }
+}
+
+static CURRENT: OnceLock<Theme> = OnceLock::new();
+
+/// Chooses the theme the terminal frontend draws with, once at startup.
+/// Returns false if one was already chosen.
+pub fn set(theme: Theme) -> bool {
+    CURRENT.set(theme).is_ok()
+}
+
+/// The theme chosen with [`set`], or the plain default
+pub fn current() -> Theme {
+    CURRENT.get().copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_names_round_trip() {
+        for name in ThemeName::ALL {
+            assert_eq!(name.as_str().parse::<ThemeName>(), Ok(name));
+        }
+        assert_eq!(
+            "Deuteranopia-Safe".parse::<ThemeName>(),
+            Ok(ThemeName::Deuteranopia)
+        );
+        assert_eq!(
+            "neon".parse::<ThemeName>().unwrap_err(),
+            "unknown theme 'neon', expected default, high-contrast, deuteranopia, or mono"
+        );
+    }
+
+    #[test]
+    fn test_styles_wrap_and_reset() {
+        let theme = Theme::new(ThemeName::Default);
+        assert_eq!(theme.found.paint("Brady"), "\x1b[32mBrady\x1b[0m");
+        assert_eq!(theme.plain().found.paint("Brady"), "Brady");
+        assert_eq!(Theme::default(), theme.plain());
+    }
+
+    // Found and missed must differ in more than red against green
+    #[test]
+    fn test_colorblind_themes_avoid_red_green() {
+        let theme = Theme::new(ThemeName::Deuteranopia);
+        for style in [theme.found, theme.missed] {
+            assert!(!["31", "32", "91", "92"].contains(&style.code.rsplit(';').next().unwrap()));
+        }
+        assert_ne!(theme.found_mark, theme.missed_mark);
+    }
+
+    #[test]
+    fn test_mono_has_no_escapes() {
+        let theme = Theme::new(ThemeName::Mono);
+        for style in [theme.heading, theme.hidden, theme.found, theme.missed] {
+            assert_eq!(style, Style::PLAIN);
+        }
+        assert!(theme.found_mark.is_ascii() && theme.missed_mark.is_ascii());
+    }
+}
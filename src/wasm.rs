@@ -0,0 +1,163 @@
+//! Browser bindings (`wasm` feature)
+//!
+//! A page has no `nfl.sqlite` file to open, so the split is: `WasmEngine`
+//! generates a question and hands back its SQL, the page runs that SQL itself
+//! (sql.js over a downloaded copy of the database, or a bundled dataset), and
+//! `WasmGame` plays the resulting board. Specs, boards, outcomes, and results
+//! cross the boundary as JSON strings in the same shapes serde gives them.
+//!
+//! Build with `--no-default-features --features wasm` for
+//! `wasm32-unknown-unknown`, since the bundled SQLite doesn't target it.
+use crate::engine::TriviaEngine;
+use crate::error::{KnowBallError, Result};
+use crate::game::TriviaGame;
+use crate::questions::{QuestionParams, QuestionSpec};
+use crate::sql_runner::Board;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// Question generation for a browser front-end
+#[wasm_bindgen]
+pub struct WasmEngine {
+    engine: TriviaEngine,
+}
+
+#[wasm_bindgen]
+impl WasmEngine {
+    /// An engine with the given seed, or a random one
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: Option<u64>) -> WasmEngine {
+        let engine = match seed {
+            Some(seed) => TriviaEngine::with_seed(seed),
+            None => TriviaEngine::new(),
+        };
+        Self { engine }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.engine.seed()
+    }
+
+    /// Every registered question code, sorted
+    pub fn codes(&self) -> Vec<String> {
+        self.engine.registry().keys().cloned().collect()
+    }
+
+    /// Generates a question for `code` (e.g. "last10passers_PIT"), or a random
+    /// one, and returns its spec as JSON. Run the spec's `sql` to get the board.
+    pub fn generate(&mut self, code: Option<String>) -> std::result::Result<String, JsError> {
+        let spec = self.spec(code.as_deref()).map_err(js_error)?;
+        Ok(to_json(&spec))
+    }
+}
+
+impl WasmEngine {
+    fn spec(&mut self, code: Option<&str>) -> Result<QuestionSpec> {
+        let (question, fixed) = match code {
+            Some(code) => {
+                let parsed = self
+                    .engine
+                    .lookup(code)
+                    .ok_or_else(|| KnowBallError::Parse(format!("unknown code '{code}'")))?;
+                let fixed = QuestionParams::for_team(parsed.team.as_deref());
+                (parsed.question, fixed)
+            }
+            None => {
+                let question = self
+                    .engine
+                    .random_question()
+                    .ok_or_else(|| KnowBallError::Parse("no questions registered".into()))?;
+                (question, QuestionParams::default())
+            }
+        };
+        Ok(self.engine.generate(question.as_ref(), &fixed))
+    }
+}
+
+/// One board being played in the page
+#[wasm_bindgen]
+pub struct WasmGame {
+    game: TriviaGame,
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    /// Starts a round from a spec returned by [`WasmEngine::generate`] and the
+    /// board its SQL produced (`{"column_names": [...], "rows": [[...]]}`)
+    #[wasm_bindgen(constructor)]
+    pub fn new(spec_json: &str, board_json: &str) -> std::result::Result<WasmGame, JsError> {
+        start_game(spec_json, board_json)
+            .map(|game| Self { game })
+            .map_err(js_error)
+    }
+
+    /// Submits a guess and returns the outcome as JSON
+    pub fn guess(&mut self, guess: &str) -> String {
+        to_json(&self.game.submit_guess(guess))
+    }
+
+    pub fn reveal(&mut self) {
+        self.game.reveal();
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    /// The board as JSON, with unguessed names blanked until the round ends
+    pub fn board(&self) -> String {
+        to_json(&self.game.visible_board())
+    }
+
+    /// The round's result as JSON
+    pub fn result(&self) -> String {
+        to_json(&self.game.result())
+    }
+}
+
+fn start_game(spec_json: &str, board_json: &str) -> Result<TriviaGame> {
+    let parse = |what: &str, e: serde_json::Error| KnowBallError::Parse(format!("bad {what}: {e}"));
+    let spec: QuestionSpec = serde_json::from_str(spec_json).map_err(|e| parse("spec", e))?;
+    let board: Board = serde_json::from_str(board_json).map_err(|e| parse("board", e))?;
+    Ok(TriviaGame::new(spec.text.clone(), board).with_spec(spec))
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("game types serialize to JSON")
+}
+
+fn js_error(e: KnowBallError) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_then_play_from_json() {
+        let mut engine = WasmEngine::new(Some(3));
+        let spec = engine.spec(Some("last10passers_PIT")).unwrap();
+        assert_eq!(spec.params.team.as_deref(), Some("PIT"));
+        assert!(engine.spec(Some("nope")).is_err());
+
+        let board = r#"{"column_names":["name","yards"],
+            "rows":[["Tom Brady","5000"],["Mason Rudolph","1000"]]}"#;
+        let mut game = WasmGame::new(&to_json(&spec), board).ok().unwrap();
+        assert!(game.board().contains(r#"["","5000"]"#));
+
+        assert!(game.guess("rudolph").contains(r#""type":"correct""#));
+        assert!(game.guess("Manning").contains(r#""type":"strike""#));
+        game.reveal();
+        assert!(game.is_over());
+        assert!(game.board().contains("Tom Brady"));
+        assert!(game.result().contains(r#""score":"#));
+    }
+
+    #[test]
+    fn test_bad_board_json() {
+        let spec = to_json(&WasmEngine::new(Some(1)).spec(None).unwrap());
+        let err = start_game(&spec, "[]").err().unwrap();
+        assert!(err.to_string().starts_with("bad board"));
+    }
+}
@@ -0,0 +1,389 @@
+//! The weekly challenge: five boards per ISO week, the same for everyone
+//!
+//! The week's set comes from [`IsoWeek::seed`] through
+//! [`TriviaEngine::load_seeded_set`](crate::TriviaEngine::load_seeded_set),
+//! so anyone with the same database plays the same boards. A finished set is
+//! summed up as a result code like `2026-W42-120.450.0.900.300-9c1e27d4`: the
+//! week, each board's score, and a check over both and the boards' answers.
+//! A friend's code is checked against the boards the week loads here before
+//! it goes on the local [`WeeklyBoard`], which catches typos, edited scores
+//! that didn't update the check, and codes from a different database. It
+//! can't stop someone who recomputes the check, since there's no server.
+use crate::questions::quiz_pack::answer_hash;
+use crate::session::MAX_BOARD_SCORE;
+use crate::sql_runner::Board;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Boards in a week's set
+pub const WEEKLY_BOARDS: usize = 5;
+
+/// Where the local weekly leaderboard is kept
+pub const DEFAULT_WEEKLY_FILE: &str = "know_ball_weekly.json";
+
+/// Why a result code or the leaderboard file couldn't be used
+#[derive(Debug, Error)]
+pub enum WeeklyError {
+    #[error("'{0}' isn't a result code, which looks like 2026-W42-120.450.0.900.300-9c1e27d4")]
+    Malformed(String),
+    #[error("'{0}' isn't a week, which looks like 2026-W42")]
+    Week(String),
+    /// The check doesn't match the scores and this database's boards
+    #[error("the code doesn't match {0}'s boards here; it was changed, or was played on another database")]
+    Mismatch(IsoWeek),
+    #[error("could not read or write the weekly leaderboard: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the weekly leaderboard is damaged: {0}")]
+    Parse(String),
+}
+
+/// An ISO 8601 week: weeks start on Monday, and week 1 is the one with the
+/// year's first Thursday
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct IsoWeek {
+    pub year: i32,
+    pub week: u32,
+}
+
+impl IsoWeek {
+    /// The week `time` falls in, in UTC
+    pub fn of(time: SystemTime) -> Self {
+        let days = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() / (24 * 60 * 60)) as i64;
+        // The epoch was a Thursday; a week belongs to its Thursday's year
+        let weekday = (days + 3).rem_euclid(7);
+        let thursday = days - weekday + 3;
+        let year = year_of(thursday);
+        let week = (thursday - days_from_civil(year, 1, 1)) / 7 + 1;
+        Self {
+            year,
+            week: week as u32,
+        }
+    }
+
+    /// The seed of the week's set, which no daily board's seed reaches for
+    /// centuries
+    pub fn seed(self) -> u64 {
+        self.year as u64 * 100 + u64::from(self.week)
+    }
+}
+
+impl fmt::Display for IsoWeek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-W{:02}", self.year, self.week)
+    }
+}
+
+impl FromStr for IsoWeek {
+    type Err = WeeklyError;
+
+    /// Parses `2026-W42`, case-insensitively
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || WeeklyError::Week(s.to_string());
+        let upper = s.trim().to_uppercase();
+        let (year, week) = upper.split_once("-W").ok_or_else(bad)?;
+        let year = year.parse().map_err(|_| bad())?;
+        let week = week.parse().map_err(|_| bad())?;
+        if !(1..=53).contains(&week) {
+            return Err(bad());
+        }
+        Ok(Self { year, week })
+    }
+}
+
+/// Days since the Unix epoch of a civil date, from Howard Hinnant's
+/// `days_from_civil`
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// The civil year of a day since the Unix epoch
+fn year_of(days: i64) -> i32 {
+    let guess = (1970 + days.div_euclid(365)) as i32;
+    (guess - 1..=guess + 1)
+        .rev()
+        .find(|&year| days_from_civil(year, 1, 1) <= days)
+        .expect("one of three years starts before the day")
+}
+
+/// One player's scores on a week's set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeeklyResult {
+    pub week: IsoWeek,
+    /// Each board's score, in set order
+    pub scores: Vec<u32>,
+}
+
+impl WeeklyResult {
+    pub fn total(&self) -> u32 {
+        self.scores.iter().sum()
+    }
+
+    /// The code to share, checked against the boards it was played on
+    pub fn code(&self, boards: &[Board]) -> String {
+        format!(
+            "{}-{}-{}",
+            self.week,
+            self.scores_text(),
+            self.check(boards)
+        )
+    }
+
+    /// Reads a friend's code and checks it against the week's `boards` as
+    /// loaded here
+    pub fn verify(code: &str, boards: &[Board]) -> Result<Self, WeeklyError> {
+        let (result, check) = Self::parse(code)?;
+        if result.check(boards) != check {
+            return Err(WeeklyError::Mismatch(result.week));
+        }
+        Ok(result)
+    }
+
+    /// The result in a code and its check, without checking it
+    pub fn parse(code: &str) -> Result<(Self, String), WeeklyError> {
+        let malformed = || WeeklyError::Malformed(code.to_string());
+        let mut parts = code.trim().rsplitn(3, '-');
+        let (Some(check), Some(scores), Some(week)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(malformed());
+        };
+        let week = week.parse().map_err(|_| malformed())?;
+        let scores: Vec<u32> = scores
+            .split('.')
+            .map(|score| score.parse().ok().filter(|&s| s <= MAX_BOARD_SCORE))
+            .collect::<Option<_>>()
+            .ok_or_else(malformed)?;
+        if scores.len() != WEEKLY_BOARDS || check.is_empty() {
+            return Err(malformed());
+        }
+        Ok((Self { week, scores }, check.to_lowercase()))
+    }
+
+    fn scores_text(&self) -> String {
+        let scores: Vec<String> = self.scores.iter().map(u32::to_string).collect();
+        scores.join(".")
+    }
+
+    /// FNV-1a over the week, the scores, and each board's answer hash, as
+    /// 8 hex digits
+    fn check(&self, boards: &[Board]) -> String {
+        let hashes: Vec<String> = boards.iter().map(answer_hash).collect();
+        let text = format!("{}|{}|{}", self.week, self.scores_text(), hashes.join(","));
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in text.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        format!("{:08x}", hash >> 32)
+    }
+}
+
+/// One player's week on the local leaderboard
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeeklyEntry {
+    pub player: String,
+    pub result: WeeklyResult,
+    pub code: String,
+}
+
+/// Weekly results kept on this machine: your own and friends' verified codes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WeeklyBoard {
+    entries: Vec<WeeklyEntry>,
+}
+
+impl WeeklyBoard {
+    /// The saved leaderboard, or an empty one if there's no file yet
+    pub fn load(path: &Path) -> Result<Self, WeeklyError> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                serde_json::from_str(&contents).map_err(|e| WeeklyError::Parse(e.to_string()))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), WeeklyError> {
+        let json = serde_json::to_string_pretty(self).expect("entries serialize to JSON");
+        Ok(std::fs::write(path, json + "\n")?)
+    }
+
+    /// Adds a result, keeping only each player's best total per week.
+    /// Returns whether it was kept.
+    pub fn add(&mut self, entry: WeeklyEntry) -> bool {
+        let same = |e: &WeeklyEntry| {
+            e.result.week == entry.result.week && e.player.eq_ignore_ascii_case(&entry.player)
+        };
+        if let Some(existing) = self.entries.iter_mut().find(|e| same(e)) {
+            if existing.result.total() >= entry.result.total() {
+                return false;
+            }
+            *existing = entry;
+            return true;
+        }
+        self.entries.push(entry);
+        true
+    }
+
+    /// The week's entries, best total first
+    pub fn standings(&self, week: IsoWeek) -> Vec<&WeeklyEntry> {
+        let mut standings: Vec<&WeeklyEntry> = self
+            .entries
+            .iter()
+            .filter(|e| e.result.week == week)
+            .collect();
+        standings.sort_by_key(|e| std::cmp::Reverse(e.result.total()));
+        standings
+    }
+
+    /// Every week with a result, latest first
+    pub fn weeks(&self) -> Vec<IsoWeek> {
+        let mut weeks: Vec<IsoWeek> = self.entries.iter().map(|e| e.result.week).collect();
+        weeks.sort_by(|a, b| b.cmp(a));
+        weeks.dedup();
+        weeks
+    }
+
+    /// The week's standings as numbered lines with each board's score
+    pub fn standings_text(&self, week: IsoWeek) -> String {
+        self.standings(week)
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                format!(
+                    "{:>3}. {} {} ({})\n",
+                    i + 1,
+                    e.player,
+                    e.result.total(),
+                    e.result.scores_text().replace('.', " + ")
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn day(days: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(days * 24 * 60 * 60 + 3600)
+    }
+
+    fn boards() -> Vec<Board> {
+        (0..WEEKLY_BOARDS)
+            .map(|i| Board {
+                column_names: vec!["name".into(), "yards".into()],
+                rows: vec![vec![format!("Player {i}"), "100".into()]],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_iso_weeks() {
+        let week = |days| IsoWeek::of(day(days));
+        assert_eq!(
+            week(20_742),
+            IsoWeek {
+                year: 2026,
+                week: 42
+            }
+        );
+        assert_eq!(
+            week(18_628),
+            IsoWeek {
+                year: 2020,
+                week: 53
+            }
+        );
+        assert_eq!(
+            week(20_087),
+            IsoWeek {
+                year: 2025,
+                week: 1
+            }
+        );
+        assert_eq!(
+            week(20_454),
+            IsoWeek {
+                year: 2026,
+                week: 1
+            }
+        );
+        assert_eq!(week(20_742).to_string(), "2026-W42");
+        assert_eq!("2026-w42".parse::<IsoWeek>().unwrap(), week(20_742));
+        assert!("2026-W54".parse::<IsoWeek>().is_err());
+        assert_ne!(week(20_742).seed(), week(20_735).seed());
+    }
+
+    #[test]
+    fn test_codes_verify_against_the_boards() {
+        let result = WeeklyResult {
+            week: IsoWeek {
+                year: 2026,
+                week: 42,
+            },
+            scores: vec![120, 450, 0, 900, 300],
+        };
+        let code = result.code(&boards());
+        assert!(code.starts_with("2026-W42-120.450.0.900.300-"));
+        assert_eq!(WeeklyResult::verify(&code, &boards()).unwrap(), result);
+
+        let edited = code.replace("900", "990");
+        assert!(matches!(
+            WeeklyResult::verify(&edited, &boards()),
+            Err(WeeklyError::Mismatch(_))
+        ));
+        let mut other = boards();
+        other[0].rows[0][0] = "Someone Else".into();
+        assert!(WeeklyResult::verify(&code, &other).is_err());
+        assert!(matches!(
+            WeeklyResult::parse("2026-W42-120.450-abc"),
+            Err(WeeklyError::Malformed(_))
+        ));
+        assert!(WeeklyResult::parse("2026-W42-1200.0.0.0.0-abc").is_err());
+    }
+
+    #[test]
+    fn test_board_keeps_best_per_week() {
+        let week = IsoWeek {
+            year: 2026,
+            week: 42,
+        };
+        let entry = |player: &str, scores: Vec<u32>| WeeklyEntry {
+            player: player.into(),
+            result: WeeklyResult { week, scores },
+            code: String::new(),
+        };
+        let mut board = WeeklyBoard::default();
+        assert!(board.add(entry("You", vec![100, 0, 0, 0, 0])));
+        assert!(board.add(entry("Ann", vec![300, 0, 0, 0, 0])));
+        assert!(!board.add(entry("you", vec![50, 0, 0, 0, 0])));
+        assert!(board.add(entry("You", vec![400, 0, 0, 0, 0])));
+        assert_eq!(board.weeks(), [week]);
+        assert_eq!(
+            board.standings_text(week),
+            "  1. You 400 (400 + 0 + 0 + 0 + 0)\n  2. Ann 300 (300 + 0 + 0 + 0 + 0)\n"
+        );
+        assert!(board
+            .standings(IsoWeek {
+                year: 2026,
+                week: 41
+            })
+            .is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+use know_ball::cli::{render_final, BoardRenderer};
+use know_ball::sql_runner::Board;
+use know_ball::TriviaGame;
+
+/// One board per shape of question, with names that stress the layout:
+/// punctuation, suffixes, long names, negative and tied stats
+fn shapes() -> Vec<(&'static str, &'static str, Board)> {
+    let board = |columns: &[&str], rows: &[&[&str]]| Board {
+        column_names: columns.iter().map(|c| c.to_string()).collect(),
+        rows: rows
+            .iter()
+            .map(|row| row.iter().map(|v| v.to_string()).collect())
+            .collect(),
+    };
+    vec![
+        (
+            "last10",
+            "Last 10 PIT quarterbacks with at least 10 attempts in a season",
+            board(
+                &["name", "team_abbr", "season", "attempts"],
+                &[
+                    &["Russell Wilson", "PIT", "2024", "336"],
+                    &["Justin Fields", "PIT", "2024", "161"],
+                    &["Kenny Pickett", "PIT", "2023", "324"],
+                    &["Mason Rudolph", "PIT", "2023", "74"],
+                ],
+            ),
+        ),
+        (
+            "team_total",
+            "Top passing yards for TB since 2000",
+            board(
+                &["name", "last_team", "pass_yards"],
+                &[
+                    &["Tom Brady", "TB", "14643"],
+                    &["Jameis Winston", "TB", "19737"],
+                    &["Josh Freeman", "TB", "13534"],
+                ],
+            ),
+        ),
+        (
+            "year_range",
+            "Top 10 in receiving yards between 2021–2023",
+            board(
+                &["name", "team_abbr", "receiving_yards"],
+                &[
+                    &["Ja'Marr Chase", "CIN", "3717"],
+                    &["Amon-Ra St. Brown", "DET", "4161"],
+                    &["Marvin Harrison Jr.", "ARI", "885"],
+                    &["Equanimeous St. Brown", "CHI", "-4"],
+                ],
+            ),
+        ),
+        (
+            "tied",
+            "Most rushing touchdowns by a WR in 2012",
+            board(
+                &["name", "team_abbr", "season", "rushing_tds"],
+                &[
+                    &["Percy Harvin", "MIN", "2012", "1"],
+                    &["Randall Cobb", "GB", "2012", "1"],
+                    &["Golden Tate", "SEA", "2012", "1"],
+                ],
+            ),
+        ),
+    ]
+}
+
+/// Snapshots the board in this state through the terminal renderers, and
+/// the chat bot's when it is built
+fn check(name: &str, game: &TriviaGame) {
+    let mut terminal = BoardRenderer::new(game).render(game).to_string();
+    terminal.push_str(&render_final(game));
+    insta::assert_snapshot!(name, terminal);
+
+    #[cfg(feature = "bot")]
+    {
+        use know_ball::bot::{render_board, render_final};
+        let chat = format!("{}\n\n{}", render_board(game), render_final(game));
+        insta::assert_snapshot!(format!("chat_{name}"), chat);
+    }
+}
+
+/// The first name on the board and a guess that matches nothing
+fn first_and_miss(board: &Board) -> (String, &'static str) {
+    (board.rows[0][0].clone(), "Nobody Atall")
+}
+
+#[test]
+fn test_masked_boards() {
+    for (shape, question, board) in shapes() {
+        let game = TriviaGame::new(question, board);
+        check(&format!("{shape}_masked"), &game);
+    }
+}
+
+// One name found and one strike
+#[test]
+fn test_partly_guessed_boards() {
+    for (shape, question, board) in shapes() {
+        let (hit, miss) = first_and_miss(&board);
+        let mut game = TriviaGame::new(question, board);
+        game.submit_guess(&hit);
+        game.submit_guess(miss);
+        check(&format!("{shape}_partial"), &game);
+    }
+}
+
+// Each way a round can end
+#[test]
+fn test_final_boards() {
+    for (shape, question, board) in shapes() {
+        let names: Vec<String> = board.rows.iter().map(|row| row[0].clone()).collect();
+        let mut game = TriviaGame::new(question, board.clone());
+        for name in &names {
+            game.submit_guess(name);
+        }
+        check(&format!("{shape}_perfect"), &game);
+
+        let (hit, miss) = first_and_miss(&board);
+        let mut game = TriviaGame::new(question, board.clone());
+        game.submit_guess(&hit);
+        for _ in 0..3 {
+            game.submit_guess(miss);
+        }
+        check(&format!("{shape}_struck_out"), &game);
+
+        let mut game = TriviaGame::new(question, board.clone());
+        game.reveal();
+        check(&format!("{shape}_revealed"), &game);
+
+        let mut game = TriviaGame::new(question, board);
+        game.submit_guess(&hit);
+        game.skip();
+        check(&format!("{shape}_skipped"), &game);
+    }
+}
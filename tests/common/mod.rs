@@ -0,0 +1,89 @@
+//! Test support: the demo league as a stand-in for `nfl.sqlite`
+//!
+//! The real database isn't in the repository, so the tests play the
+//! made-up league from `know_ball::demo`, which has the same schema and is
+//! built the same way every time.
+#![allow(dead_code)]
+
+use know_ball::demo;
+use know_ball::questions::League;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The name of whoever held `role` for `team` in `season`
+pub fn player_name(team: &str, role: &str, season: i32) -> String {
+    fixture_db()
+        .query_row(
+            "SELECT name FROM players WHERE player_id = ?1",
+            [demo::player_id(team, role, season)],
+            |row| row.get(0),
+        )
+        .unwrap()
+}
+
+/// Creates the NFL tables in `conn` and fills them
+pub fn fill(conn: &mut Connection) {
+    demo::fill(conn, League::Nfl).unwrap();
+}
+
+/// A fresh in-memory copy of the fixture database
+pub fn fixture_db() -> Connection {
+    let mut conn = Connection::open_in_memory().unwrap();
+    fill(&mut conn);
+    conn
+}
+
+/// A directory under `target/tmp` holding the fixture as `nfl.sqlite` and
+/// the NBA one as `nba.sqlite`, built once per test binary. Files the CLI writes to its working
+/// directory land here too.
+pub fn fixture_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+            .join(format!("{}_fixture", env!("CARGO_CRATE_NAME")));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = dir.join("nfl.sqlite");
+        std::fs::remove_file(&db).ok();
+        fill(&mut Connection::open(&db).unwrap());
+        let nba = dir.join("nba.sqlite");
+        std::fs::remove_file(&nba).ok();
+        demo::fill(&mut Connection::open(&nba).unwrap(), League::Nba).unwrap();
+        std::fs::remove_file(dir.join("know_ball_profiles.sqlite")).ok();
+        dir
+    })
+}
+
+/// A file in the repository, for tests that now run from the fixture directory
+pub fn repo_path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+/// The `know_ball` binary, run against the fixture database. Its config
+/// file is looked up under the fixture directory and `KNOWBALL_DB` is
+/// cleared, so a setup of the developer's own can't change the output.
+/// Finished boards are kept in memory, so one test's boards don't show up in
+/// another's.
+#[cfg(feature = "cli")]
+#[allow(deprecated)]
+pub fn know_ball() -> assert_cmd::Command {
+    let mut cmd = assert_cmd::Command::cargo_bin("know_ball").unwrap();
+    cmd.current_dir(fixture_dir())
+        .env("XDG_CONFIG_HOME", fixture_dir())
+        .env_remove("KNOWBALL_DB")
+        .env("KNOWBALL_RESULTS", ":memory:");
+    cmd
+}
+
+/// Plays `lines` through the engine against the fixture database, seeded
+/// like `--seed`
+#[cfg(feature = "cli")]
+pub fn play_script(seed: u64, lines: &[&str]) -> know_ball::script::Transcript {
+    use know_ball::session::GameSession;
+    use know_ball::store::SqliteStore;
+    use know_ball::TriviaEngine;
+
+    let store = SqliteStore::new(fixture_dir().join("nfl.sqlite"));
+    let engine = TriviaEngine::with_seed(seed).with_store(store);
+    know_ball::script::run_script(GameSession::new(engine), lines)
+}
@@ -1,12 +1,13 @@
-#![allow(deprecated)]
+mod common;
 
-use assert_cmd::Command;
+use common::{fixture_dir, know_ball, play_script, player_name};
+use know_ball::game::GuessOutcome;
 use predicates::prelude::*;
 
 // Test that the program starts and shows welcome message
 #[test]
 fn test_program_starts() {
-    let mut cmd = Command::cargo_bin("know_ball").unwrap();
+    let mut cmd = know_ball();
 
     cmd.write_stdin("quit\n")
         .assert()
@@ -17,19 +18,21 @@ fn test_program_starts() {
 // Test that list command shows available questions
 #[test]
 fn test_list_command() {
-    let mut cmd = Command::cargo_bin("know_ball").unwrap();
+    let mut cmd = know_ball();
 
     cmd.write_stdin("list\nquit\n")
         .assert()
         .success()
         .stdout(predicate::str::contains("Available question codes:"))
-        .stdout(predicate::str::contains("last10passers_TEAM"));
+        .stdout(predicate::str::contains(
+            "passyds_TEAM: Top 10 passing yards for a team since the start year [passing · easy]",
+        ));
 }
 
 // Test that quit command exits gracefully
 #[test]
 fn test_quit_command() {
-    let mut cmd = Command::cargo_bin("know_ball").unwrap();
+    let mut cmd = know_ball();
 
     cmd.write_stdin("quit\n")
         .assert()
@@ -40,7 +43,7 @@ fn test_quit_command() {
 // Test exit command also works
 #[test]
 fn test_exit_command() {
-    let mut cmd = Command::cargo_bin("know_ball").unwrap();
+    let mut cmd = know_ball();
 
     cmd.write_stdin("exit\n")
         .assert()
@@ -51,33 +54,70 @@ fn test_exit_command() {
 // Test invalid command shows error message
 #[test]
 fn test_invalid_command() {
-    let mut cmd = Command::cargo_bin("know_ball").unwrap();
+    let mut cmd = know_ball();
 
     cmd.write_stdin("notacommand\nquit\n")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Unknown command or code"));
+        .stdout(predicate::str::contains("Error: unknown command or code"));
 }
 
 // Test that a valid team-specific question is recognized
 #[test]
 fn test_valid_team_question() {
-    let mut cmd = Command::cargo_bin("know_ball").unwrap();
+    let starter = player_name("PIT", "QB1", 2024);
+    let transcript = play_script(1, &["last10passers_PIT", &starter, "Nobody Atall"]);
 
-    // Type the command then immediately reveal to end the trivia
-    cmd.write_stdin("last10passers_PIT\nreveal\nquit\n")
+    assert!(transcript.errors().is_empty(), "{:?}", transcript.errors());
+    assert_eq!(transcript.codes(), ["last10passers_TEAM"]);
+    assert!(transcript.questions()[0].contains("PIT"));
+    let guesses = transcript.guesses();
+    assert!(matches!(guesses[0].1, GuessOutcome::Correct { row: 0, .. }));
+    assert_eq!(*guesses[1].1, GuessOutcome::Strike { strikes: 1 });
+    let result = transcript.results()[0];
+    assert_eq!((result.total, result.correct()), (10, 1));
+    assert_eq!(transcript.summary.questions_played, 1);
+
+    know_ball()
+        .write_stdin("last10passers_PIT\nreveal\nquit\n")
         .assert()
         .success()
         .stdout(predicate::str::contains("Team: PIT"))
         .stdout(predicate::str::contains("TRIVIA"));
 }
 
+// Test that a renamed code still works and says what it is called now
+#[test]
+fn test_renamed_code_alias() {
+    let transcript = play_script(1, &["recyds_PIT_yearrange", "reveal"]);
+
+    assert!(transcript.notices()[0]
+        .starts_with("'recyds_TEAM_yearrange' has been renamed to 'recyds_yearrange_TEAM'"));
+    assert!(transcript.questions()[0].contains("for PIT"));
+    assert_eq!(transcript.results()[0].correct(), 0);
+
+    know_ball()
+        .write_stdin("recyds_PIT_yearrange\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "'recyds_TEAM_yearrange' has been renamed to 'recyds_yearrange_TEAM'",
+        ))
+        .stdout(predicate::str::contains("Team: PIT"));
+}
+
 // Test that start command generates a random question
 #[test]
 fn test_start_command() {
-    let mut cmd = Command::cargo_bin("know_ball").unwrap();
+    let transcript = play_script(1, &["start", "reveal", "start", "reveal"]);
+
+    assert!(transcript.errors().is_empty(), "{:?}", transcript.errors());
+    assert_eq!(transcript.results().len(), 2);
+    assert!(transcript.results().iter().all(|r| r.total == 10));
+    assert_eq!(transcript.summary.questions_played, 2);
 
-    cmd.write_stdin("start\nreveal\nquit\n")
+    know_ball()
+        .write_stdin("start\nreveal\nquit\n")
         .assert()
         .success()
         .stdout(predicate::str::contains("Random code:"))
@@ -87,7 +127,7 @@ fn test_start_command() {
 // Test case insensitivity for commands
 #[test]
 fn test_case_insensitive_commands() {
-    let mut cmd = Command::cargo_bin("know_ball").unwrap();
+    let mut cmd = know_ball();
 
     cmd.write_stdin("LIST\nQUIT\n")
         .assert()
@@ -98,10 +138,1295 @@ fn test_case_insensitive_commands() {
 // Test invalid team code
 #[test]
 fn test_invalid_team_code() {
-    let mut cmd = Command::cargo_bin("know_ball").unwrap();
+    let transcript = play_script(1, &["last10passers_XYZ"]);
+
+    assert!(transcript.errors()[0].starts_with("unknown command or code 'last10passers_xyz'"));
+    assert!(transcript.codes().is_empty());
+
+    know_ball()
+        .write_stdin("last10passers_XYZ\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Error: unknown command or code 'last10passers_xyz'",
+        ));
+}
+
+// Test that the same seed replays the same random question
+#[test]
+fn test_seed_flag_is_reproducible() {
+    let run = || {
+        let output = know_ball()
+            .args(["--seed", "4217"])
+            .write_stdin("start\nreveal\nquit\n")
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let first = run();
+    assert!(first.contains("Seed: 4217"));
+    assert_eq!(first, run());
+
+    let boards = || play_script(4217, &["start", "reveal", "start", "reveal"]);
+    assert_eq!(boards().questions(), boards().questions());
+}
+
+// Test that --spectate writes one JSON state per step of the round
+#[test]
+fn test_spectate_file() {
+    let path =
+        std::env::temp_dir().join(format!("know_ball_spectate_{}.ndjson", std::process::id()));
+    let mut cmd = know_ball();
+    cmd.arg(format!("--spectate={}", path.display()))
+        .write_stdin("passyds_PIT\nzzzz\nreveal\nquit\n")
+        .assert()
+        .success();
+
+    let stream = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    let states: Vec<serde_json::Value> = stream
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(states.len(), 3);
+    assert_eq!(states[0]["board"]["rows"][0][0], "");
+    assert_eq!(states[1]["strikes"], 1);
+    assert_eq!(states[2]["end"], "gave_up");
+    assert_ne!(states[2]["board"]["rows"][0][0], "");
+}
+
+// Test that --ghost races the previous attempt at the same board
+#[test]
+fn test_ghost_race() {
+    let run = |input: &str| {
+        let output = know_ball()
+            .arg("--ghost")
+            .write_stdin(input)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let starter = player_name("PIT", "QB1", 2024);
+    let first = run(&format!("last10passers_PIT\n{starter}\nreveal\nquit\n"));
+    let second = run("last10passers_PIT\nreveal\nquit\n");
+    std::fs::remove_file(fixture_dir().join("know_ball_ghosts.json")).ok();
+    assert!(!first.contains("Ghost:"));
+    assert!(second.contains("Ghost: "));
+    assert!(second.contains("Your ghost wins by"));
+}
+
+// Test that --profile keeps lifetime stats from one session to the next
+#[test]
+fn test_profile_keeps_lifetime_stats() {
+    know_ball()
+        .args(["--profile", "lifetime"])
+        .write_stdin("last10passers_PIT\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Profile lifetime: no games yet"));
+    know_ball()
+        .args(["--profile", "lifetime"])
+        .write_stdin("profile\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Profile lifetime: 1 game, average 0, 0 perfect boards",
+        ));
+    know_ball()
+        .args(["--profile", "two words"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("bad profile name"));
+}
+
+// Test that finished boards are saved and read back as high scores
+#[test]
+fn test_highscores_keep_the_best_board() {
+    let starter = player_name("PIT", "QB1", 2024);
+    std::fs::remove_file(fixture_dir().join("highscores.sqlite")).ok();
+    know_ball()
+        .args(["--profile", "scorer"])
+        .env("KNOWBALL_RESULTS", "highscores.sqlite")
+        .write_stdin(format!(
+            "last10passers_PIT\nreveal\nlast10passers_PIT\n{starter}\nreveal\nquit\n"
+        ))
+        .assert()
+        .success();
+    know_ball()
+        .args(["--profile", "scorer"])
+        .env("KNOWBALL_RESULTS", "highscores.sqlite")
+        .write_stdin("highscores\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("High scores for scorer"))
+        .stdout(
+            predicate::str::is_match(r"  last10passers_TEAM  PIT min=10\s+[1-9]\d* pts").unwrap(),
+        )
+        .stdout(predicate::str::contains("Best overall: "));
+}
+
+// Test that a code's past boards are shown before it's played again and
+// listed by `history`
+#[test]
+fn test_history_of_a_code() {
+    let starter = player_name("PIT", "QB1", 2024);
+    know_ball()
+        .write_stdin(format!(
+            "last10passers_PIT\n{starter}\nreveal\nlast10passers_PIT\nreveal\n\
+             history last10passers_PIT\nhistory last10passers_GB\nhistory nope\nquit\n"
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Note: You've played this once, scoring [1-9]").unwrap())
+        .stdout(predicate::str::contains(
+            "last10passers_TEAM for PIT: 2 boards",
+        ))
+        .stdout(predicate::str::is_match(r"PIT min=10\s+0 pts\s+0/10 named").unwrap())
+        .stdout(predicate::str::contains(
+            "No boards of last10passers_TEAM for GB played yet.",
+        ))
+        .stdout(predicate::str::contains("unknown question code 'nope'"));
+}
+
+// Test that skip ends a board, keeps its points, and counts it apart
+#[test]
+fn test_skip_banks_the_points() {
+    let starter = player_name("PIT", "QB1", 2024);
+    know_ball()
+        .write_stdin(format!(
+            "last10passers_PIT\n{starter}\nskip\nhistory last10passers_PIT\nquit\n"
+        ))
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                r"Skipped with 1 of 10 named, banking [1-9]\d* points \(\d+% of the board\)",
+            )
+            .unwrap(),
+        )
+        .stdout(predicate::str::is_match(r"1/10 named  skipped").unwrap())
+        .stdout(predicate::str::contains("Questions played: 0"))
+        .stdout(predicate::str::contains("Questions skipped: 1"))
+        .stdout(
+            predicate::str::is_match(r"Total score: [1-9]\d*/1000, skipped boards included")
+                .unwrap(),
+        );
+}
+
+// Test that the Board ID shown at the start deals the same board again
+#[test]
+fn test_replay_by_board_id() {
+    let run = |args: &[&str], input: &str| {
+        let output = know_ball().args(args).write_stdin(input).output().unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let line = |stdout: &str, prefix: &str| {
+        stdout
+            .lines()
+            .find_map(|l| l.strip_prefix(prefix))
+            .unwrap()
+            .to_string()
+    };
+
+    let first = run(&["--seed", "11"], "start\nreveal\nquit\n");
+    let id = line(&first, "Board ID: ");
+    let again = run(&["--seed", "12", "replay", &id], "reveal\n");
+    assert_eq!(line(&first, "Question: "), line(&again, "Question: "));
+    assert_eq!(line(&again, "Board ID: "), id);
+
+    know_ball()
+        .write_stdin("replay nope:PIT\nreplay passyds_TEAM:PIT:GB\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no question 'nope' to replay"))
+        .stdout(predicate::str::contains(
+            "bad board ID 'passyds_TEAM:PIT:GB'",
+        ));
+}
+
+// Test that party mode credits each player's guesses
+#[test]
+fn test_party_mode() {
+    let mut cmd = know_ball();
+    cmd.write_stdin("party Ann Ben\n1:zzzz\nmahomes\nreveal\ndone\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--- PARTY ---"))
+        .stdout(predicate::str::contains("Strike 1 for Ann!"))
+        .stdout(predicate::str::contains(
+            "start each guess with your number",
+        ))
+        .stdout(predicate::str::contains("[1] Ann 0 ✗ | [2] Ben 0"))
+        .stdout(predicate::str::contains("--- PARTY STANDINGS ---"))
+        .stdout(predicate::str::contains("Tie between Ann and Ben at 0."));
+}
+
+// Test that only 'done' ends a party; other text between boards asks again
+#[test]
+fn test_party_ends_on_done() {
+    let assert = know_ball()
+        .write_stdin("party Ann Ben\nreveal\nmahomes\nDone\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--- PARTY STANDINGS ---"));
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    assert_eq!(stdout.matches("type 'done' to end the party").count(), 2);
+    assert_eq!(stdout.matches("--- PARTY ---").count(), 1);
+}
+
+// Test a one-round team match from the captains' picks to the report
+#[test]
+fn test_team_match() {
+    let mut cmd = know_ball();
+    cmd.args(["--seed", "3"])
+        .write_stdin("match Red=Ann,Ben Blue=Cam 1\nrushing\nreveal\nfootball\n\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Round 1: Red is up. Ann, pick a category",
+        ))
+        .stdout(predicate::str::contains("Round 1: Blue is up. Cam"))
+        .stdout(predicate::str::contains("unknown category 'football'"))
+        .stdout(predicate::str::contains("# Red vs Blue"))
+        .stdout(predicate::str::contains(
+            "A draw, 0 rounds to 0 (best of 1).",
+        ))
+        .stdout(predicate::str::contains(
+            "| 1 | 0 (rushing, 0/10) | 0 (any, 0/10) | Level |",
+        ))
+        .stdout(predicate::str::contains(
+            "- **Red**: Ann, Ben (captain Ann)",
+        ));
+}
+
+// Test that the daily board doesn't depend on the session seed
+#[test]
+fn test_daily_board_ignores_seed() {
+    let question = |seed: &str| {
+        let output = know_ball()
+            .args(["--seed", seed])
+            .write_stdin("daily\nreveal\nquit\n")
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let line = stdout.lines().find(|l| l.starts_with("Question: "));
+        line.unwrap().to_string()
+    };
+
+    assert_eq!(question("1"), question("2"));
+}
+
+// Test that a weekly result code checks out on another run and tampering
+// is caught
+#[test]
+fn test_weekly_result_codes() {
+    let run = |input: &str| {
+        let output = know_ball().write_stdin(input).output().unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    let played = run("weekly\nreveal\nreveal\nreveal\nreveal\nreveal\nquit\n");
+    let code = played
+        .split("Your result code is ")
+        .nth(1)
+        .and_then(|rest| rest.split(';').next())
+        .unwrap()
+        .to_string();
+    let edited = code.replacen("-0.", "-900.", 1);
+    let checked = run(&format!(
+        "weekly add Ann {code}\nweekly add Ben {edited}\nweekly board\nquit\n"
+    ));
+    std::fs::remove_file(fixture_dir().join("know_ball_weekly.json")).ok();
+    assert!(played.contains("Board 5 of 5"));
+    assert!(checked.contains("Added Ann's 0 for "));
+    assert!(checked.contains("the code doesn't match"));
+    assert!(checked.contains("1. You 0 (0 + 0 + 0 + 0 + 0)"));
+    assert!(checked.contains("2. Ann 0"));
+    assert!(!checked.contains("Ben 900"));
+}
+
+// Test that selftest checks every question against the database
+#[test]
+fn test_selftest_command() {
+    let mut cmd = know_ball();
+
+    cmd.write_stdin("selftest\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checked "))
+        .stdout(predicate::str::contains(" question(s): all OK"))
+        .stdout(predicate::str::contains("Error").not());
+}
+
+// Test that a malformed seed is rejected
+#[test]
+fn test_invalid_seed() {
+    let mut cmd = know_ball();
+
+    cmd.args(["--seed", "abc"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid seed"));
+}
+
+// Test that the REPL ends like `quit` when stdin closes, in every mode
+#[test]
+fn test_repl_ends_at_eof() {
+    for args in [&[][..], &["--json"], &["--speech"]] {
+        know_ball()
+            .args(args)
+            .write_stdin("score\n")
+            .timeout(std::time::Duration::from_secs(10))
+            .assert()
+            .success();
+    }
+    know_ball()
+        .write_stdin("")
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== SESSION SUMMARY ==="))
+        .stdout(predicate::str::ends_with("Goodbye!\n"));
+}
+
+// Test that --json prints one tagged JSON object per line
+#[test]
+fn test_json_output() {
+    let output = know_ball()
+        .args(["--json", "--seed", "7"])
+        .write_stdin("list\nlast10passers_PIT\nreveal\nnotacommand\nquit\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let types: Vec<String> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            value["type"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(
+        types,
+        ["ready", "codes", "question", "result", "error", "summary"]
+    );
+}
+
+// Test that --json and --speech can pause a timed board, and that a guess
+// while paused isn't counted
+#[test]
+fn test_json_and_speech_pause() {
+    let output = know_ball()
+        .args(["--json", "--timed=60"])
+        .write_stdin("last10passers_PIT\npause\nNobody\nresume\nresume\nreveal\nquit\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let messages: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let types: Vec<&str> = messages
+        .iter()
+        .map(|m| m["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        types,
+        ["ready", "question", "paused", "guess", "resumed", "error", "result", "summary"]
+    );
+    assert_eq!(messages[3]["outcome"]["type"], "paused");
+    assert_eq!(messages[3]["strikes"], 0);
+
+    let output = know_ball()
+        .args(["--speech", "--timed=60"])
+        .write_stdin("last10passers_PIT\npause\nrepeat\nNobody\nresume\nreveal\nquit\n")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("left. Say resume to go on.").count(), 2);
+    assert!(stdout.contains("The clock is paused. Say resume to go on."));
+    assert!(stdout.contains("The clock is running, "));
+    assert!(!stdout.contains("Strike 1"));
+}
+
+// Test that --speech reads the board and replies as plain sentences
+#[test]
+fn test_speech_output() {
+    let output = know_ball()
+        .args(["--speech", "--seed", "7"])
+        .write_stdin("last10passers_PIT\nnobody\nreveal\nquit\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Number 1 is still hidden: "));
+    assert!(stdout.contains(" for PIT "));
+    assert!(stdout.contains("\nStrike 1 of 3.\n"));
+    assert!(stdout.contains("You found 0 of "));
+    assert!(stdout.contains("You have played 1 question for 0 of 1,000 points"));
+    assert!(!stdout.contains(" | "));
+    assert!(!stdout.contains("Code: "));
+}
+
+// Test that --preload plays from an in-memory copy of the database
+#[test]
+fn test_preload() {
+    let output = know_ball()
+        .args(["--preload", "--seed", "7"])
+        .write_stdin("last10passers_PIT\nreveal\nquit\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Code: last10passers_PIT"));
+    assert!(!stdout.contains("Error"));
+}
+
+// Test that `pass N` shows one row without a strike
+#[test]
+fn test_pass_one_row() {
+    let starter = common::player_name("PIT", "QB1", 2024);
+    know_ball()
+        .write_stdin("last10passers_PIT\npass 1\npass 1\npass 11\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Row 1 was {starter}. No points, no strike."
+        )))
+        .stdout(predicate::str::contains(
+            "There's no hidden row 1 to pass on.",
+        ))
+        .stdout(predicate::str::contains(
+            "There's no hidden row 11 to pass on.",
+        ))
+        .stdout(predicate::str::contains("Strikes: 0/3"));
+}
+
+// Test that `hint N` shows a row's initials, unless --no-hints is given
+#[test]
+fn test_hint_shows_initials() {
+    let starter = common::player_name("PIT", "QB1", 2024);
+    let initials: String = starter
+        .split_whitespace()
+        .map(|word| format!("{}.", &word[..1]))
+        .collect();
+    know_ball()
+        .write_stdin("last10passers_PIT\nhint 1\nhint 11\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Row 1's initials are {initials}"
+        )))
+        .stdout(predicate::str::contains(
+            "There's no hidden row 11 to give a hint for.",
+        ))
+        .stdout(predicate::str::contains("Hints used: 1"));
+
+    know_ball()
+        .arg("--no-hints")
+        .write_stdin("last10passers_PIT\nhint 1\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No hints under these rules."))
+        .stdout(predicate::str::contains("Hints used").not());
+}
+
+// Test that --rules and --no-reveal change what ends a board
+#[test]
+fn test_rules_flags() {
+    know_ball()
+        .args(["--rules", "sudden-death"])
+        .write_stdin("last10passers_PIT\nNobody Atall\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("You have 1 strike."))
+        .stdout(predicate::str::contains("Strike 1, you're out!"));
+
+    know_ball()
+        .args(["--strikes", "2", "--no-reveal"])
+        .write_stdin("last10passers_PIT\nreveal\npass 1\nNobody Atall\nNobody Else\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("You have 2 strikes."))
+        .stdout(
+            predicate::str::contains("No reveals, skips, or passes under these rules.").count(2),
+        )
+        .stdout(predicate::str::contains("Strikes: 1/2  Score: 0"))
+        .stdout(predicate::str::contains("Strike 2, you're out!"));
+
+    know_ball()
+        .args(["--rules", "hardcore"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown rules 'hardcore'"));
+}
+
+// Test that survival carries strikes across boards and ends the run
+#[test]
+fn test_survival_mode() {
+    know_ball()
+        .args(["survival", "--rules", "sudden-death", "--seed", "7"])
+        .write_stdin("Nobody Atall\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("You have 1 strike."))
+        .stdout(predicate::str::contains(
+            "Out of strikes after 1 board. Survival score: 0 points.",
+        ));
+
+    know_ball()
+        .args(["--seed", "7"])
+        .write_stdin("survival\nNobody Atall\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Strikes: 1/3"))
+        .stdout(predicate::str::contains("You stopped after 1 board."));
+}
+
+// Test that start with a category or difficulty only deals questions that fit
+#[test]
+fn test_start_with_category_and_difficulty() {
+    know_ball()
+        .args(["--seed", "3"])
+        .write_stdin("start turnovers\nreveal\nstart bowling\nstart turnovers easy\nquit\n")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                "Random code: (last10intthrowers_TEAM|top10fumlost_yearrange|top10intthrown_yearrange)\n",
+            )
+            .unwrap(),
+        )
+        .stdout(predicate::str::contains("unknown category 'bowling'"))
+        .stdout(predicate::str::contains(
+            "no questions match that category and difficulty",
+        ));
+}
+
+// Test that a quiz plays its boards in order and adds them up at the end
+#[test]
+fn test_quiz_scorecard() {
+    know_ball()
+        .args([
+            "quiz",
+            "--count",
+            "2",
+            "--category",
+            "rushing",
+            "--team",
+            "PIT",
+        ])
+        .write_stdin("reveal\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Quiz: 2 questions on rushing for PIT.",
+        ))
+        .stdout(predicate::str::contains("Board 2 of 2"))
+        .stdout(predicate::str::contains("Quiz scorecard"))
+        .stdout(predicate::str::contains(
+            "Total: 0 points over 2 boards, 0 of 20 named (0%), 0 strikes.",
+        ));
+
+    know_ball()
+        .args(["quiz", "--team", "XYZ"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("no questions for XYZ"));
+}
+
+// Test that a team marathon deals every team question for the team and ends
+// with a fan score
+#[test]
+fn test_team_marathon() {
+    let reveals = "reveal\n".repeat(20);
+    know_ball()
+        .args(["team", "pit"])
+        .write_stdin(reveals + "quit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("PIT marathon:"))
+        .stdout(predicate::str::contains("last10rushers_TEAM"))
+        .stdout(predicate::str::contains(
+            "PIT fan score: 0/100. Bandwagon fan!",
+        ));
+}
+
+// Test that a locked team fills in team questions while league questions
+// still play, until the team is unlocked
+#[test]
+fn test_team_lock() {
+    know_ball()
+        .write_stdin(
+            "team lock pit\nlast10passers_TEAM\nreveal\ntop10passyds_year\nreveal\nteam unlock\nquit\n",
+        )
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Questions that take a team are now for PIT until 'team unlock'.",
+        ))
+        .stdout(predicate::str::contains("Board ID: last10passers_TEAM:PIT"))
+        .stdout(predicate::str::contains("Top 10 QBs in passing yards in"))
+        .stdout(predicate::str::contains(
+            "Questions that take a team are open again.",
+        ))
+        .stderr(predicate::str::contains("Error").not());
+}
+
+// Test that --timed ends the board when the clock runs out, even while the
+// prompt is waiting on a guess
+#[test]
+fn test_timed_board_runs_out() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_know_ball"))
+        .current_dir(fixture_dir())
+        .env("XDG_CONFIG_HOME", fixture_dir())
+        .env_remove("KNOWBALL_DB")
+        .arg("--timed=1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"last10passers_PIT\n").unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    // The Enter that the time's-up message asks for, then a REPL command
+    stdin.write_all(b"\nquit\n").unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("--- CURRENT BOARD --- 0:01 left"));
+    assert!(stdout.contains("Time's up! Press Enter to see the answers."));
+    assert!(stdout.contains("Time's up! Here are the full answers:"));
+    assert!(stdout.contains("Goodbye!"));
+}
+
+// Test that pause holds the clock and the board until resume
+#[test]
+fn test_pause_holds_the_clock() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_know_ball"))
+        .current_dir(fixture_dir())
+        .env("XDG_CONFIG_HOME", fixture_dir())
+        .env_remove("KNOWBALL_DB")
+        .env("KNOWBALL_RESULTS", ":memory:")
+        .arg("--timed=1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin
+        .write_all(b"last10passers_PIT\npause\nNobody Atall\n")
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    stdin.write_all(b"resume\nreveal\nquit\n").unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Clock stopped with 0:01 left"));
+    assert_eq!(
+        stdout
+            .matches("Paused. Type 'resume' to continue: ")
+            .count(),
+        2
+    );
+    assert_eq!(stdout.matches("--- CURRENT BOARD ---").count(), 2);
+    assert!(stdout.contains("Stopping early. Here are the full answers:"));
+    assert!(!stdout.contains("Time's up!"));
+    assert!(!stdout.contains("Strike 1!"));
+}
+
+// Test that blitz deals a new board as soon as one ends and stops every
+// board when its one clock runs out
+#[test]
+fn test_blitz_runs_on_one_clock() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_know_ball"))
+        .current_dir(fixture_dir())
+        .env("XDG_CONFIG_HOME", fixture_dir())
+        .env_remove("KNOWBALL_DB")
+        .args(["blitz", "--secs", "2", "--rules", "sudden-death"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"Nobody Atall\n").unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(2500));
+    // The Enter that the time's-up message asks for
+    stdin.write_all(b"\n").unwrap();
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Blitz: 0:02 on one clock."));
+    assert!(stdout.contains("Blitz: 0 points after 1 board, 0:01 left"));
+    assert!(stdout.contains("Time's up! Here are the full answers:"));
+    assert!(stdout.contains("Time's up after 2 boards. Blitz score: 0 points."));
+}
+
+// Test that --demo plays the made-up league without any database on disk
+#[test]
+fn test_demo_needs_no_database() {
+    let output = know_ball()
+        .current_dir(std::env::temp_dir())
+        .args(["--demo", "--seed", "7"])
+        .write_stdin("last10passers_PIT\nreveal\nquit\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Demo NFL: every player and stat is made up"));
+    assert!(stdout.contains("Code: last10passers_PIT"));
+    assert!(stdout.contains(&common::player_name("PIT", "QB1", 2024)));
+    assert!(String::from_utf8(output.stderr).unwrap().is_empty());
+
+    know_ball()
+        .args(["--demo", "--db", "nfl.sqlite", "list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'--demo' cannot be used with"));
+}
+
+// Test that the hidden --explain mode logs each query's plan
+#[test]
+fn test_explain_log() {
+    let log =
+        std::env::temp_dir().join(format!("know_ball_explain_cli_{}.log", std::process::id()));
+    know_ball()
+        .args(["--seed", "7"])
+        .arg(format!("--explain={}", log.display()))
+        .write_stdin("last10passers_PIT\nreveal\nquit\n")
+        .assert()
+        .success();
+
+    let written = std::fs::read_to_string(&log).unwrap();
+    std::fs::remove_file(&log).unwrap();
+    assert!(written.contains(" ms, "));
+    assert!(written.contains("'PIT'"));
+    assert!(written.contains("\n  | "));
+}
+
+// Test that subcommands run one command and exit, with options before or
+// after the subcommand
+#[test]
+fn test_subcommands() {
+    know_ball()
+        .args(["play", "passyds_PIT", "--seed", "3"])
+        .write_stdin("reveal\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Team: PIT"))
+        .stdout(predicate::str::contains("Welcome").not());
+    know_ball()
+        .args(["--json", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("{\"type\":\"codes\""));
+    know_ball()
+        .args(["export", "passyds_PIT", "--format", "md"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# Know Ball Quiz"));
+    know_ball()
+        .args(["play", "nope"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "Error: unknown command or code 'nope'",
+        ));
+    know_ball()
+        .arg("repl")
+        .write_stdin("quit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Welcome"));
+}
+
+// Test that --json and --speech can't be combined
+#[test]
+fn test_json_and_speech_conflict() {
+    let mut cmd = know_ball();
+
+    cmd.args(["--json", "--speech"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'--json' cannot be used with '--speech'",
+        ));
+}
+
+// Test that an unknown matcher name is rejected
+#[test]
+fn test_invalid_matcher() {
+    let mut cmd = know_ball();
+
+    cmd.args(["--match", "loose"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown matcher 'loose'"));
+}
+
+// Test that a question pack adds playable codes
+#[cfg(feature = "packs")]
+#[test]
+fn test_pack_flag_adds_questions() {
+    let mut cmd = know_ball();
+
+    cmd.arg("--pack")
+        .arg(common::repo_path("packs/example.toml"))
+        .write_stdin("list\ntargets_yearrange_PIT\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Loaded 2 question(s)"))
+        .stdout(predicate::str::contains("top10sacked_year"))
+        .stdout(predicate::str::contains(
+            "Top 10 players in targets for PIT",
+        ))
+        .stdout(predicate::str::contains("TRIVIA"));
+}
+
+// Test that --league nba plays the NBA pack from nba.sqlite
+#[cfg(feature = "packs")]
+#[test]
+fn test_league_flag_plays_nba() {
+    let mut cmd = know_ball();
+
+    cmd.args(["--league", "nba"])
+        .write_stdin("points_BOS\nreveal\npassyds_PIT\ncustom rushing_yards\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "League: NBA (boards from nba.sqlite)",
+        ))
+        .stdout(predicate::str::contains(
+            "Top 10 scorers for BOS since 2000",
+        ))
+        .stdout(predicate::str::contains(
+            "Error: unknown command or code 'passyds_pit'",
+        ))
+        .stdout(predicate::str::contains(
+            "custom stats like rushing_yards are NFL only",
+        ));
+}
+
+// Test that an unknown league is rejected
+#[cfg(feature = "packs")]
+#[test]
+fn test_unknown_league() {
+    let mut cmd = know_ball();
+
+    cmd.args(["--league", "mlb"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown league 'mlb', expected nfl or nba",
+        ));
+}
+
+// Test that --theme changes the final answers' marks, and the config
+// file's theme applies when no flag is given
+#[test]
+fn test_theme_from_flag_and_config() {
+    know_ball()
+        .args(["--seed", "7", "--theme", "mono"])
+        .write_stdin("last10passers_PIT\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(" 1 [ ]: "))
+        .stdout(predicate::str::contains("✗").not());
+
+    let home = std::env::temp_dir().join(format!("know_ball_config_{}", std::process::id()));
+    std::fs::create_dir_all(home.join("knowball")).unwrap();
+    std::fs::write(home.join("knowball/config.toml"), "theme = \"mono\"\n").unwrap();
+    know_ball()
+        .env("XDG_CONFIG_HOME", &home)
+        .write_stdin("last10passers_PIT\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(" 1 [ ]: "));
+    // The flag wins over the file
+    know_ball()
+        .env("XDG_CONFIG_HOME", &home)
+        .args(["--theme", "default"])
+        .write_stdin("last10passers_PIT\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(" 1 ✗: "));
+
+    std::fs::write(home.join("knowball/config.toml"), "theme = \"neon\"\n").unwrap();
+    know_ball()
+        .env("XDG_CONFIG_HOME", &home)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("couldn't read the config file"))
+        .stderr(predicate::str::contains("unknown variant `neon`"));
+    std::fs::remove_dir_all(&home).ok();
+}
+
+// Test that a nickname from the config file counts as the player's name
+#[test]
+fn test_nicknames_from_config() {
+    let starter = common::player_name("PIT", "QB1", 2024);
+    let home = std::env::temp_dir().join(format!("know_ball_nicknames_{}", std::process::id()));
+    std::fs::create_dir_all(home.join("knowball")).unwrap();
+    std::fs::write(
+        home.join("knowball/config.toml"),
+        format!("[nicknames]\n\"The Franchise\" = \"{starter}\"\n"),
+    )
+    .unwrap();
+    know_ball()
+        .env("XDG_CONFIG_HOME", &home)
+        .write_stdin("last10passers_PIT\nthe franchise\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Correct!"));
+    std::fs::remove_dir_all(&home).ok();
+}
+
+// Test that an unknown theme is rejected
+#[test]
+fn test_unknown_theme() {
+    know_ball()
+        .args(["--theme", "neon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown theme 'neon', expected default, high-contrast, deuteranopia, or mono",
+        ));
+}
+
+// Test that packs in a questions/ directory next to the database load at
+// startup, YAML included
+#[cfg(feature = "packs")]
+#[test]
+fn test_questions_dir_loads_at_startup() {
+    let dir = std::env::temp_dir().join(format!("know_ball_qdir_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("questions")).unwrap();
+    std::fs::copy(fixture_dir().join("nfl.sqlite"), dir.join("nfl.sqlite")).unwrap();
+    std::fs::write(
+        dir.join("questions/sacks.yaml"),
+        "question:
+  - code: sacked_year
+    description: Most-sacked QBs in one season
+    params: [year]
+    text: Most-sacked QBs in {year}.
+    sql: >
+      SELECT p.name, s.sacks FROM seasons s
+      JOIN players p ON p.player_id = s.player_id
+      WHERE s.season = {year} AND s.position = 'QB'
+      ORDER BY s.sacks DESC LIMIT 10
+    columns: [name, sacks]
+",
+    )
+    .unwrap();
+
+    let assert = know_ball()
+        .current_dir(&dir)
+        .write_stdin(
+            "sacked_year
+reveal
+quit
+",
+        )
+        .assert();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert
+        .success()
+        .stdout(predicate::str::contains(
+            "Loaded 1 question(s) from questions",
+        ))
+        .stdout(predicate::str::contains("Most-sacked QBs in"));
+}
+
+// Test that --db and KNOWBALL_DB read boards from outside the working
+// directory, with the flag winning
+#[test]
+fn test_db_flag_and_env() {
+    let db = fixture_dir().join("nfl.sqlite");
+    let elsewhere = std::env::temp_dir();
+    know_ball()
+        .current_dir(&elsewhere)
+        .arg("--db")
+        .arg(&db)
+        .write_stdin("passyds_PIT\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("TRIVIA"));
+    know_ball()
+        .current_dir(&elsewhere)
+        .env("KNOWBALL_DB", &db)
+        .write_stdin("passyds_PIT\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("TRIVIA"));
+    know_ball()
+        .env("KNOWBALL_DB", &db)
+        .args(["--db", "missing.sqlite"])
+        .write_stdin("passyds_PIT\nquit\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("missing.sqlite not found"));
+}
+
+// Test that a missing pack file is reported
+#[cfg(feature = "packs")]
+#[test]
+fn test_missing_pack_file() {
+    let mut cmd = know_ball();
+
+    cmd.arg("--pack")
+        .arg(common::repo_path("packs/does_not_exist.toml"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("could not read pack"));
+}
+
+// Test that a quiz pack plays back the questions it was made with
+#[cfg(feature = "packs")]
+#[test]
+fn test_quiz_pack_create_and_play() {
+    let path = std::env::temp_dir().join(format!("know_ball_pack_{}.kbq", std::process::id()));
+    know_ball()
+        .write_stdin(format!(
+            "pack create {} recyds_yearrange_PIT --title Steelers night\nquit\n",
+            path.display()
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Saved 1 question(s)"));
+
+    let pack = std::fs::read_to_string(&path).unwrap();
+    let text = pack
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("\"text\": \""))
+        .unwrap()
+        .trim_end_matches("\",")
+        .to_string();
+
+    let mut cmd = know_ball();
+    cmd.write_stdin(format!("pack play {}\nreveal\nquit\n", path.display()))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Quiz pack: Steelers night"))
+        .stdout(predicate::str::contains(text))
+        .stdout(predicate::str::contains("differs").not());
+    std::fs::remove_file(&path).ok();
+}
+
+// Test that a saved challenge replays the same board and compares results
+#[cfg(feature = "packs")]
+#[test]
+fn test_challenge_save_and_play() {
+    let path = std::env::temp_dir().join(format!("know_ball_challenge_{}.kbc", std::process::id()));
+    know_ball()
+        .write_stdin(format!(
+            "challenge save {0}\nlast10passers_PIT\nreveal\nchallenge save {0} Ann\nquit\n",
+            path.display()
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no finished board yet"))
+        .stdout(predicate::str::contains(
+            "Saved your 0 points on last10passers_TEAM",
+        ));
+
+    let mut cmd = know_ball();
+    cmd.write_stdin(format!("challenge play {}\nreveal\nquit\n", path.display()))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Challenge from Ann: 0 points to beat",
+        ))
+        .stdout(predicate::str::contains("HEAD TO HEAD"))
+        .stdout(predicate::str::contains("It's a tie at 0."))
+        .stdout(predicate::str::contains("differs").not());
+    std::fs::remove_file(&path).ok();
+}
+
+// Test that custom pins the team and year range
+#[test]
+fn test_custom_command() {
+    let transcript = play_script(1, &["custom recyds PIT 2005 2012", "reveal"]);
+
+    assert_eq!(transcript.codes(), ["recyds_yearrange_TEAM"]);
+    assert!(transcript.questions()[0].contains("for PIT between 2005–2012"));
+
+    know_ball()
+        .write_stdin("custom recyds PIT 2005 2012\nreveal\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Code: recyds_yearrange_TEAM"))
+        .stdout(predicate::str::contains("for PIT between 2005–2012"));
+}
+
+// Test that naming a stat builds a one-off custom question
+#[test]
+fn test_custom_stat_command() {
+    let transcript = play_script(
+        1,
+        &[
+            "custom receiving_yards GB 2010 2015 WR",
+            "reveal",
+            "custom passing_yards WR",
+        ],
+    );
+
+    assert_eq!(
+        transcript.questions(),
+        ["Top 10 WRs in receiving yards for GB between 2010–2015."]
+    );
+    assert!(transcript.errors()[0].contains("passing_yards only applies to QBs"));
+
+    know_ball()
+        .write_stdin(
+            "custom receiving_yards GB 2010 2015 WR\nreveal\ncustom passing_yards WR\nquit\n",
+        )
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Top 10 WRs in receiving yards for GB between 2010–2015.",
+        ))
+        .stdout(predicate::str::contains(
+            "passing_yards only applies to QBs",
+        ));
+}
+
+// Test that export-quiz writes questions, an answer sheet, and a key
+#[test]
+fn test_export_quiz_command() {
+    let path = std::env::temp_dir().join(format!("know_ball_quiz_{}.html", std::process::id()));
+    let mut cmd = know_ball();
+
+    cmd.write_stdin(format!(
+        "export-quiz last10passers_PIT recyds_yearrange_GB --format html --out {}\nquit\n",
+        path.display()
+    ))
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Wrote a 2-question quiz"));
+
+    let html = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert!(html.contains("<h2>Answer sheet</h2>"));
+    assert!(html.contains("<h2>Answer key</h2>"));
+    assert!(html.contains("for GB between"));
+}
+
+// Test that --export-csv writes each finished board to the directory
+#[test]
+fn test_export_csv_option() {
+    let dir = std::env::temp_dir().join(format!("know_ball_csv_{}", std::process::id()));
+    let mut cmd = know_ball();
+
+    cmd.arg("--export-csv")
+        .arg(&dir)
+        .write_stdin("last10passers_PIT\nreveal\nquit\n")
+        .assert()
+        .success();
+
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    let csv = std::fs::read_to_string(&files[0]).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(files.len(), 1);
+    let name = files[0].file_name().unwrap().to_string_lossy();
+    assert!(name.starts_with("last10passers_TEAM_"), "{name}");
+    assert!(csv.lines().next().unwrap().ends_with(",points,guessed"));
+    assert!(csv.lines().nth(1).unwrap().ends_with(",false"));
+}
+
+// Test that report prints a Markdown scoreboard of the boards played
+#[test]
+fn test_report_command() {
+    let mut cmd = know_ball();
+
+    cmd.write_stdin("last10passers_PIT\nreveal\nreport\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("# Know Ball session"))
+        .stdout(predicate::str::contains("| 1 | You | 0 |"))
+        .stdout(predicate::str::contains("## Hardest misses"));
+}
+
+// Test that export-html writes a playable page without the names in it
+#[test]
+fn test_export_html_command() {
+    let path = std::env::temp_dir().join(format!("know_ball_page_{}.html", std::process::id()));
+    let mut cmd = know_ball();
+
+    cmd.write_stdin(format!(
+        "export-html last10passers_PIT --out {}\nquit\n",
+        path.display()
+    ))
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Wrote a playable quiz page"));
+
+    let html = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert!(html.contains("const QUIZ = {\"columns\":"));
+    assert!(html.contains("\"PIT\""));
+    assert!(!html.contains(&player_name("PIT", "QB1", 2024)));
+}
+
+// Test that export-anki turns a revealed board's misses into a deck, and
+// says so when there is nothing to study
+#[test]
+fn test_export_anki_command() {
+    let path = std::env::temp_dir().join(format!("know_ball_deck_{}.txt", std::process::id()));
+    let mut cmd = know_ball();
+
+    cmd.write_stdin(format!(
+        "export-anki\nlast10passers_PIT\nreveal\nexport-anki --out {}\nquit\n",
+        path.display()
+    ))
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("no missed answers to study yet"))
+    .stdout(predicate::str::contains("Wrote 10 flashcard(s)"));
+
+    let deck = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert!(deck.starts_with("#separator:tab\n#html:false\n#tags column:3\n"));
+    assert_eq!(deck.lines().count(), 13);
+    let first = deck.lines().nth(3).unwrap();
+    let fields: Vec<&str> = first.split('\t').collect();
+    assert!(fields[0].contains("team_abbr: PIT, season: 2024"));
+    assert_eq!(fields[1], player_name("PIT", "QB1", 2024));
+    assert_eq!(fields[2], "last10passers_TEAM");
+}
+
+// Test that a mistyped command gets a suggestion
+#[test]
+fn test_typo_suggestion() {
+    let mut cmd = know_ball();
 
-    cmd.write_stdin("last10passers_XYZ\nquit\n")
+    cmd.write_stdin("lsit\nquit\n")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Unknown command or code"));
+        .stdout(predicate::str::contains(
+            "Error: unknown command or code 'lsit'; did you mean 'list'?",
+        ));
 }
@@ -0,0 +1,76 @@
+use know_ball::questions::{
+    build_registry, generate_question, CustomQuestion, ParamKind, QuestionParams, QuestionSpec,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// The same parameters for every question, limited to the ones it takes
+fn fixed_params(supported: &[ParamKind]) -> QuestionParams {
+    let mut params = QuestionParams::default();
+    for kind in supported {
+        match kind {
+            ParamKind::Team => params.team = Some("PIT".into()),
+            ParamKind::Year => params.year = Some(2012),
+            ParamKind::YearRange => params.year_range = Some((2008, 2012)),
+            // Each question's own minimum and every position
+            ParamKind::Threshold | ParamKind::Position => {}
+        }
+    }
+    params
+}
+
+/// The question text, its SQL, and the value bound to each parameter
+fn render(spec: &QuestionSpec) -> String {
+    let mut out = format!("{}\n\n{}\n", spec.text, spec.query.sql);
+    for (name, value) in &spec.query.bindings {
+        out.push_str(&format!("\n{name} = {value}"));
+    }
+    out
+}
+
+// Every built-in question's SQL, so a change to the query builder or the
+// question families shows up as a snapshot diff
+#[test]
+fn test_builtin_question_sql() {
+    let registry = build_registry();
+    let mut codes: Vec<&String> = registry.keys().collect();
+    codes.sort();
+
+    for code in codes {
+        let question = &registry[code.as_str()];
+        let params = fixed_params(&question.supported_params());
+        let mut rng = StdRng::seed_from_u64(487);
+        let spec = generate_question(question.as_ref(), &params, &mut rng);
+        insta::assert_snapshot!(code.as_str(), render(&spec));
+    }
+}
+
+// The position filter changes both the text and the SQL of a league-wide
+// question
+#[test]
+fn test_position_question_sql() {
+    let registry = build_registry();
+    let params = QuestionParams {
+        year_range: Some((2008, 2012)),
+        position: Some("RB".into()),
+        ..Default::default()
+    };
+    let mut rng = StdRng::seed_from_u64(487);
+    let question = &registry["top10receptions_yearrange"];
+    let spec = generate_question(question.as_ref(), &params, &mut rng);
+    insta::assert_snapshot!(render(&spec));
+}
+
+#[test]
+fn test_custom_question_sql() {
+    let league = CustomQuestion::new()
+        .stat("receiving_yards")
+        .years(2010..=2015)
+        .position("WR");
+    let team = CustomQuestion::new()
+        .stat("passing_yards")
+        .team("GB")
+        .season(2012);
+    insta::assert_snapshot!("custom_league", render(&league.spec().unwrap()));
+    insta::assert_snapshot!("custom_team", render(&team.spec().unwrap()));
+}
@@ -0,0 +1,82 @@
+use know_ball::questions::{
+    build_registry, generate_question, CustomQuestion, Question, CUSTOM_STATS,
+};
+use know_ball::selftest::param_sweep;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sqlparser::ast::Statement;
+use sqlparser::dialect::SQLiteDialect;
+use sqlparser::parser::Parser;
+
+/// Parses `sql` as SQLite and checks it is a single read-only query
+fn check_sql(sql: &str) -> Result<(), String> {
+    let statements = Parser::parse_sql(&SQLiteDialect {}, sql).map_err(|e| e.to_string())?;
+    match &statements[..] {
+        [Statement::Query(_)] => Ok(()),
+        [other] => Err(format!("not a SELECT: {other}")),
+        _ => Err(format!("{} statements, expected one", statements.len())),
+    }
+}
+
+/// Checks the SQL `question` generates for every parameter combination the
+/// selftest tries
+fn check_question(question: &dyn Question) {
+    let mut rng = StdRng::seed_from_u64(493);
+    for params in param_sweep(question.league(), &question.supported_params()) {
+        let spec = generate_question(question, &params, &mut rng);
+        if let Err(e) = check_sql(&spec.query.sql) {
+            panic!("{} ({}): {e}\n{}", spec.code, spec.text, spec.query.sql);
+        }
+    }
+}
+
+#[test]
+fn test_check_sql_rejects_bad_statements() {
+    assert_eq!(check_sql("SELECT name FROM players"), Ok(()));
+    assert!(check_sql("SELECT name FROM players WHERE").is_err());
+    assert!(check_sql("DELETE FROM players")
+        .unwrap_err()
+        .starts_with("not a SELECT"));
+    assert_eq!(
+        check_sql("SELECT 1; DROP TABLE players"),
+        Err("2 statements, expected one".into())
+    );
+}
+
+// Every built-in question, across the selftest's parameter sweep
+#[test]
+fn test_builtin_sql_parses() {
+    for question in build_registry().values() {
+        check_question(question.as_ref());
+    }
+}
+
+#[test]
+fn test_custom_sql_parses() {
+    for stat in CUSTOM_STATS {
+        let league = CustomQuestion::new().stat(stat.column).years(2010..=2015);
+        let team = CustomQuestion::new()
+            .stat(stat.column)
+            .team("GB")
+            .season(2012);
+        for question in [league, team] {
+            let spec = question.spec().unwrap();
+            if let Err(e) = check_sql(&spec.query.sql) {
+                panic!("{}: {e}\n{}", stat.column, spec.query.sql);
+            }
+        }
+    }
+}
+
+// Pack templates are written by hand, so a typo only shows up once the
+// placeholders are filled in
+#[cfg(feature = "packs")]
+#[test]
+fn test_pack_sql_parses() {
+    use know_ball::questions::pack::load_pack;
+
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("packs/example.toml");
+    for question in load_pack(&path).unwrap() {
+        check_question(&question);
+    }
+}